@@ -2,39 +2,55 @@ use anyhow::Result;
 use chrono::Utc;
 use dashmap::DashMap;
 use log::{error, info, warn};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    signature::Keypair,
 };
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
 use tokio::sync::RwLock;
 use tokio_cron_scheduler::{Job, JobScheduler};
 
+use saros_dlmm_sdk::{
+    EndpointHealth, LedgerExport, LedgerJournal, RpcConnection, RpcConnectionConfig, RpcMethodMetrics, SendConfig,
+    SharedSigner, TransactionSender, WalletSecrets,
+};
+
 use crate::{
+    circuit_breaker::{CircuitBreaker, TripReason},
     compound_strategy::CompoundStrategy,
+    gas_budget::{BudgetExceeded, BudgetPeriod, BudgetScope, BudgetUsage, GasBudget, GasReconciliation},
     gas_optimizer::GasOptimizer,
     notification_service::NotificationService,
     position_monitor::PositionMonitor,
-    statistics::StatisticsManager,
+    statistics::{IncidentRecord, StatisticsManager, WalletStatistics},
     types::*,
 };
 
 pub struct ActiveStrategy {
     pub config: CompoundStrategyConfig,
     pub strategy: Arc<CompoundStrategy>,
+    pub circuit_breaker: Arc<CircuitBreaker>,
     pub job_id: Option<uuid::Uuid>,
     pub statistics: PoolStatistics,
 }
 
-/// Main auto-compounder that manages multiple compound strategies
+/// A configured wallet together with its loaded signer
+struct WalletState {
+    config: WalletConfig,
+    signer: SharedSigner,
+}
+
+/// Main auto-compounder that manages compound strategies across one or more wallets
 pub struct AutoCompounder {
-    rpc_client: Arc<RpcClient>,
-    wallet: Arc<Keypair>,
+    rpc_client: Arc<RpcConnection>,
+    wallets: HashMap<String, Arc<WalletState>>,
     config: AutoCompoundConfig,
-    active_strategies: Arc<DashMap<String, ActiveStrategy>>,
+    gas_budget: Arc<GasBudget>,
+    ledger_journal: Option<Arc<LedgerJournal>>,
+    active_strategies: Arc<DashMap<StrategyKey, ActiveStrategy>>,
     scheduler: Arc<JobScheduler>,
     gas_optimizer: Arc<GasOptimizer>,
+    transaction_sender: Arc<TransactionSender>,
     notification_service: Arc<NotificationService>,
     position_monitor: Arc<PositionMonitor>,
     statistics_manager: Arc<RwLock<StatisticsManager>>,
@@ -42,35 +58,116 @@ pub struct AutoCompounder {
 }
 
 impl AutoCompounder {
-    /// Create a new AutoCompounder instance
-    pub async fn new(config: AutoCompoundConfig) -> Result<Self> {
-        // Initialize RPC client
-        let rpc_client = Arc::new(RpcClient::new(&config.rpc_url));
+    /// Create a new AutoCompounder instance, loading wallet signers with `secrets`
+    pub async fn new(config: AutoCompoundConfig, secrets: &WalletSecrets) -> Result<Self> {
+        // Initialize the shared non-blocking RPC connection
+        let rpc_client = Arc::new(RpcConnection::new(RpcConnectionConfig {
+            timeout: Duration::from_secs(config.rpc_timeout_secs),
+            max_retries: config.rpc_max_retries,
+            ..RpcConnectionConfig::with_endpoints(config.rpc_endpoints.clone())
+        }));
+        if rpc_client.endpoint_count() > 1 {
+            rpc_client.spawn_health_checks();
+        }
+
+        // Load each wallet's signer (dry runs can use throwaway keypairs)
+        let mut wallet_configs = config.wallets.clone();
+        if wallet_configs.is_empty() && config.dry_run {
+            wallet_configs.push(WalletConfig::new(DEFAULT_WALLET, None));
+        }
+        if wallet_configs.is_empty() {
+            return Err(anyhow::anyhow!("At least one wallet must be configured"));
+        }
+
+        let mut wallets = HashMap::new();
+        for wallet_config in wallet_configs {
+            let signer: SharedSigner = match &wallet_config.signer {
+                Some(source) => {
+                    info!("🔐 Loading wallet '{}' from {}", wallet_config.name, source);
+                    source.load(secrets)?
+                }
+                None if config.dry_run => {
+                    warn!("🧪 No signer configured for wallet '{}', using an ephemeral keypair for the dry run", wallet_config.name);
+                    Arc::new(Keypair::new())
+                }
+                None => return Err(anyhow::anyhow!("No signer configured for wallet '{}'", wallet_config.name)),
+            };
+
+            info!("🔑 Wallet '{}': {}", wallet_config.name, signer.pubkey());
+            if let Some(budget) = wallet_config.gas_budget.daily {
+                info!("   Daily gas budget: {:.6} SOL", budget);
+            }
+            if let Some(budget) = wallet_config.gas_budget.weekly {
+                info!("   Weekly gas budget: {:.6} SOL", budget);
+            }
+            if let Some(max_strategies) = wallet_config.max_strategies {
+                info!("   Max strategies: {}", max_strategies);
+            }
+
+            let name = wallet_config.name.clone();
+            if wallets.insert(name.clone(), Arc::new(WalletState { config: wallet_config, signer })).is_some() {
+                return Err(anyhow::anyhow!("Wallet '{}' is configured more than once", name));
+            }
+        }
 
-        // Initialize wallet from private key
-        let wallet = Arc::new(Self::parse_private_key(&config.private_key)?);
+        // Fee budgets; simulated spend is tracked in memory only so it never counts against real budgets
+        let wallet_limits = wallets
+            .iter()
+            .map(|(name, wallet)| (name.clone(), wallet.config.gas_budget))
+            .collect();
+        let mut budget_config = config.gas_budget.clone();
+        if config.dry_run {
+            budget_config.state_file = None;
+        }
+        let gas_budget = Arc::new(GasBudget::load(budget_config, wallet_limits)?);
+
+        // Realised events are journaled as they are recorded; dry runs keep a separate paper journal
+        let ledger_journal = config.ledger_export.as_ref().map(|export| {
+            let path = match export.journal.file_name() {
+                Some(name) if config.dry_run => export.journal.with_file_name(format!("paper_{}", name.to_string_lossy())),
+                _ => export.journal.clone(),
+            };
+            info!("🧾 Ledger journal: {}", path.display());
+            Arc::new(LedgerJournal::new(path))
+        });
 
         // Initialize scheduler
         let scheduler = Arc::new(JobScheduler::new().await?);
         scheduler.start().await?;
 
         // Initialize components
-        let gas_optimizer = Arc::new(GasOptimizer::new(rpc_client.clone()));
+        let gas_optimizer = Arc::new(GasOptimizer::new(
+            rpc_client.clone(),
+            config.max_gas_price,
+            config.priority_fee_level,
+            config.gas_budget.max_priority_fee_lamports,
+        ));
+        let transaction_sender = Arc::new(TransactionSender::new(rpc_client.clone(), SendConfig::default()));
         let notification_service = Arc::new(NotificationService::new(&config));
+        notification_service.spawn_tasks();
         let position_monitor = Arc::new(PositionMonitor::new(rpc_client.clone()));
         let statistics_manager = Arc::new(RwLock::new(StatisticsManager::new()));
 
-        info!("🔑 Wallet address: {}", wallet.pubkey());
         info!("🌐 Network: {}", config.network);
-        info!("📡 RPC URL: {}", config.rpc_url);
+        for endpoint in &config.rpc_endpoints {
+            info!("📡 RPC endpoint: {} (weight {})", endpoint.url, endpoint.weight);
+        }
+        info!("⛽ Max gas price: {:.6} SOL", config.max_gas_price);
+        info!("⛽ Priority fee level: {}", config.priority_fee_level);
+        if config.dry_run {
+            info!("🧪 Dry-run mode: transactions are simulated and recorded in the paper ledger");
+        }
 
         Ok(Self {
             rpc_client,
-            wallet,
+            wallets,
             config,
+            gas_budget,
+            ledger_journal,
             active_strategies: Arc::new(DashMap::new()),
             scheduler,
             gas_optimizer,
+            transaction_sender,
             notification_service,
             position_monitor,
             statistics_manager,
@@ -80,81 +177,132 @@ impl AutoCompounder {
 
     /// Start a compound strategy
     pub async fn start_strategy(&mut self, config: CompoundStrategyConfig) -> Result<StartResult> {
+        let key = config.key();
         let pool_key = config.pool_address.to_string();
+        let rejected = |error: String| StartResult {
+            success: false,
+            wallet: config.wallet.clone(),
+            pool_address: pool_key.clone(),
+            strategy_type: config.strategy_type.to_string(),
+            interval_ms: config.interval_ms,
+            min_threshold: config.min_reward_threshold,
+            next_compound_time: "".to_string(),
+            error: Some(error),
+        };
+
+        let wallet = match self.wallets.get(&config.wallet) {
+            Some(wallet) => wallet.clone(),
+            None => {
+                warn!("Unknown wallet '{}' for pool {}", config.wallet, pool_key);
+                return Ok(rejected(format!("Unknown wallet '{}'", config.wallet)));
+            }
+        };
 
         // Check if strategy is already active
-        if self.active_strategies.contains_key(&pool_key) {
-            warn!("Strategy for pool {} is already active", pool_key);
-            return Ok(StartResult {
-                success: false,
-                pool_address: pool_key,
-                strategy_type: config.strategy_type.to_string(),
-                interval_ms: config.interval_ms,
-                min_threshold: config.min_reward_threshold,
-                next_compound_time: "".to_string(),
-                error: Some("Strategy already active".to_string()),
-            });
+        if self.active_strategies.contains_key(&key) {
+            warn!("Strategy for {} is already active", key);
+            return Ok(rejected("Strategy already active".to_string()));
+        }
+
+        // Enforce the wallet's strategy limit
+        if let Some(max_strategies) = wallet.config.max_strategies {
+            let running = self.active_strategies
+                .iter()
+                .filter(|entry| entry.key().wallet == config.wallet)
+                .count();
+            if running >= max_strategies {
+                warn!("Wallet '{}' already runs {} of {} allowed strategies", config.wallet, running, max_strategies);
+                return Ok(rejected(format!("Wallet '{}' strategy limit reached ({})", config.wallet, max_strategies)));
+            }
         }
 
         // Validate pool exists and get initial position
-        let position = match self.position_monitor.get_position(config.pool_address).await {
+        let position = match self.position_monitor.get_position(config.pool_address, wallet.signer.pubkey()).await {
             Ok(pos) => pos,
             Err(e) => {
-                error!("Failed to get position for pool {}: {}", pool_key, e);
-                return Ok(StartResult {
-                    success: false,
-                    pool_address: pool_key,
-                    strategy_type: config.strategy_type.to_string(),
-                    interval_ms: config.interval_ms,
-                    min_threshold: config.min_reward_threshold,
-                    next_compound_time: "".to_string(),
-                    error: Some(format!("Failed to validate pool: {}", e)),
-                });
+                error!("Failed to get position for {}: {}", key, e);
+                return Ok(rejected(format!("Failed to validate pool: {}", e)));
             }
         };
 
-        info!("📊 Initial position for {}: {:.6} LP tokens", pool_key, position.lp_token_amount);
+        info!("📊 Initial position for {}: {:.6} LP tokens", key, position.lp_token_amount);
 
         // Create compound strategy
         let strategy = Arc::new(CompoundStrategy::new(
             config.clone(),
             self.rpc_client.clone(),
-            self.wallet.clone(),
+            wallet.signer.clone(),
             self.gas_optimizer.clone(),
+            self.transaction_sender.clone(),
             self.notification_service.clone(),
+            self.config.dry_run,
         ));
 
         // Create cron schedule based on interval
         let cron_expression = Self::interval_to_cron(config.interval_ms);
         info!("⏰ Scheduling with cron expression: {}", cron_expression);
 
+        // Circuit breaker guarding this strategy
+        let circuit_breaker = Arc::new(CircuitBreaker::new(self.config.circuit_breaker.clone()));
+
         // Clone necessary values for the job
         let strategy_clone = strategy.clone();
         let statistics_manager = self.statistics_manager.clone();
-        let pool_address = config.pool_address;
+        let breaker_clone = circuit_breaker.clone();
+        let position_monitor = self.position_monitor.clone();
+        let notification_service = self.notification_service.clone();
+        let safe_address = self.config.circuit_breaker.safe_address;
+        let dry_run = self.config.dry_run;
+        let gas_budget = self.gas_budget.clone();
+        let ledger_journal = self.ledger_journal.clone();
+        let job_config = config.clone();
 
         // Create scheduled job
         let job = Job::new_async(cron_expression.as_str(), move |_uuid, _l| {
             let strategy = strategy_clone.clone();
             let stats_manager = statistics_manager.clone();
-            
+            let circuit_breaker = breaker_clone.clone();
+            let position_monitor = position_monitor.clone();
+            let notification_service = notification_service.clone();
+            let gas_budget = gas_budget.clone();
+            let ledger_journal = ledger_journal.clone();
+            let config = job_config.clone();
+            let key = config.key();
+
             Box::pin(async move {
-                info!("🔄 Executing scheduled compound for pool: {}", pool_address);
-                
-                match strategy.execute_compound().await {
+                if let Some((reason, tripped_at)) = circuit_breaker.trip_reason().await {
+                    warn!("⏸️ Strategy for {} is paused since {}: {}", key, tripped_at, reason);
+                    return;
+                }
+
+                // Defer while the compound would overrun a fee budget
+                match Self::check_gas_budget(&config, &strategy, &gas_budget, &notification_service).await {
+                    Ok(Some(_)) => return,
+                    Ok(None) => {}
+                    Err(e) => warn!("Could not check gas budget for {}: {}", key, e),
+                }
+
+                // Check pool health before touching the position
+                match Self::check_pool_health(&strategy, &position_monitor, &circuit_breaker).await {
+                    Ok(Some(reason)) => {
+                        Self::handle_circuit_trip(&strategy, reason, safe_address, &notification_service, &stats_manager, &gas_budget, ledger_journal.as_deref()).await;
+                        return;
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Could not check pool health for {}: {}", key, e),
+                }
+
+                info!("🔄 Executing scheduled compound for {}", key);
+
+                let result = match strategy.execute_compound().await {
                     Ok(result) => {
-                        info!("✅ Compound successful for pool {}", pool_address);
-                        
-                        // Update statistics
-                        let mut stats = stats_manager.write().await;
-                        stats.record_compound_result(&pool_address.to_string(), &result).await;
+                        info!("✅ Compound successful for {}", key);
+                        result
                     }
                     Err(e) => {
-                        error!("❌ Compound failed for pool {}: {}", pool_address, e);
-                        
-                        // Record failed compound
-                        let mut stats = stats_manager.write().await;
-                        let failed_result = CompoundResult {
+                        error!("❌ Compound failed for {}: {}", key, e);
+
+                        CompoundResult {
                             success: false,
                             rewards_harvested: 0.0,
                             amount_reinvested: 0.0,
@@ -163,9 +311,19 @@ impl AutoCompounder {
                             transaction_signature: "".to_string(),
                             timestamp: Utc::now(),
                             error: Some(e.to_string()),
-                        };
-                        stats.record_compound_result(&pool_address.to_string(), &failed_result).await;
+                            skipped: false,
+                            simulated: dry_run,
+                            zap_cost: None,
+                            ledger: Vec::new(),
+                        }
                     }
+                };
+
+                // Update statistics, the fee budget ledger and the accounting journal
+                Self::record_result(&key, &result, &stats_manager, &gas_budget, ledger_journal.as_deref()).await;
+
+                if let Some(reason) = circuit_breaker.record_compound(&result).await {
+                    Self::handle_circuit_trip(&strategy, reason, safe_address, &notification_service, &stats_manager, &gas_budget, ledger_journal.as_deref()).await;
                 }
             })
         })?;
@@ -180,6 +338,7 @@ impl AutoCompounder {
         let active_strategy = ActiveStrategy {
             config: config.clone(),
             strategy,
+            circuit_breaker,
             job_id: Some(job_id),
             statistics: PoolStatistics {
                 pool_address: config.pool_address,
@@ -187,11 +346,12 @@ impl AutoCompounder {
             },
         };
 
-        self.active_strategies.insert(pool_key.clone(), active_strategy);
+        self.active_strategies.insert(key.clone(), active_strategy);
 
         // Send notification
         self.notification_service.send_notification(NotificationEvent {
             event_type: NotificationEventType::CompoundStarted,
+            wallet: Some(config.wallet.clone()),
             pool_address: pool_key.clone(),
             message: format!("Auto-compound started for {} strategy", config.strategy_type),
             data: serde_json::json!({
@@ -202,13 +362,14 @@ impl AutoCompounder {
             timestamp: Utc::now(),
         }).await;
 
-        info!("✅ Auto-compound started for pool {}", pool_key);
+        info!("✅ Auto-compound started for {}", key);
         info!("   Strategy: {}", config.strategy_type);
         info!("   Interval: {}ms", config.interval_ms);
         info!("   Next compound: {}", next_compound.format("%Y-%m-%d %H:%M:%S UTC"));
 
         Ok(StartResult {
             success: true,
+            wallet: config.wallet.clone(),
             pool_address: pool_key,
             strategy_type: config.strategy_type.to_string(),
             interval_ms: config.interval_ms,
@@ -219,10 +380,8 @@ impl AutoCompounder {
     }
 
     /// Stop a compound strategy
-    pub async fn stop_strategy(&mut self, pool_address: Pubkey) -> Result<bool> {
-        let pool_key = pool_address.to_string();
-
-        match self.active_strategies.remove(&pool_key) {
+    pub async fn stop_strategy(&mut self, key: &StrategyKey) -> Result<bool> {
+        match self.active_strategies.remove(key) {
             Some((_, active_strategy)) => {
                 // Remove job from scheduler
                 if let Some(job_id) = active_strategy.job_id {
@@ -232,7 +391,8 @@ impl AutoCompounder {
                 // Send notification
                 self.notification_service.send_notification(NotificationEvent {
                     event_type: NotificationEventType::CompoundStopped,
-                    pool_address: pool_key.clone(),
+                    wallet: Some(key.wallet.clone()),
+                    pool_address: key.pool_address.to_string(),
                     message: "Auto-compound stopped".to_string(),
                     data: serde_json::json!({
                         "statistics": active_strategy.statistics
@@ -240,48 +400,102 @@ impl AutoCompounder {
                     timestamp: Utc::now(),
                 }).await;
 
-                info!("🛑 Auto-compound stopped for pool {}", pool_key);
+                info!("🛑 Auto-compound stopped for {}", key);
+                Ok(true)
+            }
+            None => {
+                warn!("No active strategy found for {}", key);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Resume a strategy paused by its circuit breaker
+    pub async fn resume_strategy(&self, key: &StrategyKey) -> Result<bool> {
+        match self.active_strategies.get(key) {
+            Some(active_strategy) => {
+                if !active_strategy.circuit_breaker.is_tripped().await {
+                    return Ok(false);
+                }
+
+                active_strategy.circuit_breaker.reset().await;
+                info!("▶️ Strategy resumed for {}", key);
                 Ok(true)
             }
             None => {
-                warn!("No active strategy found for pool {}", pool_key);
+                warn!("No active strategy found for {}", key);
                 Ok(false)
             }
         }
     }
 
-    /// Manually trigger compound for a specific pool
-    pub async fn compound_now(&self, pool_address: Pubkey) -> Result<CompoundResult> {
-        let pool_key = pool_address.to_string();
+    /// Manually trigger compound for a wallet's position in a pool; an active strategy's circuit
+    /// breaker is checked first and fed the result, as for scheduled compounds
+    pub async fn compound_now(&self, key: &StrategyKey) -> Result<CompoundResult> {
+        let wallet = self.wallets
+            .get(&key.wallet)
+            .ok_or_else(|| anyhow::anyhow!("Unknown wallet '{}'", key.wallet))?;
 
-        match self.active_strategies.get(&pool_key) {
+        let (strategy, config, circuit_breaker) = match self.active_strategies.get(key) {
             Some(active_strategy) => {
-                info!("🔄 Manual compound triggered for pool {}", pool_key);
-                active_strategy.strategy.execute_compound().await
+                info!("🔄 Manual compound triggered for {}", key);
+                (active_strategy.strategy.clone(), active_strategy.config.clone(), Some(active_strategy.circuit_breaker.clone()))
             }
             None => {
                 // Create temporary strategy for one-time compound
                 let temp_config = CompoundStrategyConfig {
-                    pool_address,
+                    wallet: key.wallet.clone(),
+                    pool_address: key.pool_address,
                     strategy_type: StrategyType::LP,
                     interval_ms: 0,
                     min_reward_threshold: 0.0,
                     reinvest_percentage: 100,
                     max_slippage: Some(1.0),
                     emergency_withdraw: false,
+                    gas_budget: BudgetLimits::default(),
                 };
 
-                let temp_strategy = CompoundStrategy::new(
-                    temp_config,
+                (Arc::new(CompoundStrategy::new(
+                    temp_config.clone(),
                     self.rpc_client.clone(),
-                    self.wallet.clone(),
+                    wallet.signer.clone(),
                     self.gas_optimizer.clone(),
+                    self.transaction_sender.clone(),
                     self.notification_service.clone(),
-                );
+                    self.config.dry_run,
+                )), temp_config, None)
+            }
+        };
 
-                temp_strategy.execute_compound().await
+        if let Some(circuit_breaker) = &circuit_breaker {
+            if let Some((reason, tripped_at)) = circuit_breaker.trip_reason().await {
+                return Err(anyhow::anyhow!("Strategy for {} is paused since {}: {}; resume it first", key, tripped_at, reason));
             }
+
+            if let Some(reason) = Self::check_pool_health(&strategy, &self.position_monitor, circuit_breaker).await? {
+                let safe_address = self.config.circuit_breaker.safe_address;
+                Self::handle_circuit_trip(&strategy, reason.clone(), safe_address, &self.notification_service, &self.statistics_manager, &self.gas_budget, self.ledger_journal.as_deref()).await;
+                return Err(anyhow::anyhow!("Compound for {} aborted, circuit breaker tripped: {}", key, reason));
+            }
+        }
+
+        // Budgets are hard limits, so manual compounds respect them too
+        if let Some(exceeded) = Self::check_gas_budget(&config, &strategy, &self.gas_budget, &self.notification_service).await? {
+            return Err(anyhow::anyhow!("Compound for {} deferred: {}", key, exceeded));
+        }
+
+        let result = strategy.execute_compound().await?;
+        Self::record_result(key, &result, &self.statistics_manager, &self.gas_budget, self.ledger_journal.as_deref()).await;
+
+        if let Some(reason) = match &circuit_breaker {
+            Some(circuit_breaker) => circuit_breaker.record_compound(&result).await,
+            None => None,
+        } {
+            let safe_address = self.config.circuit_breaker.safe_address;
+            Self::handle_circuit_trip(&strategy, reason, safe_address, &self.notification_service, &self.statistics_manager, &self.gas_budget, self.ledger_journal.as_deref()).await;
         }
+
+        Ok(result)
     }
 
     /// Get global statistics
@@ -292,21 +506,109 @@ impl AutoCompounder {
         // Calculate uptime
         let uptime = Utc::now() - self.start_time;
         stats.uptime_hours = uptime.num_minutes() as f64 / 60.0;
+
+        if !self.config.dry_run {
+            self.fill_budget_spend(&mut stats).await;
+        }
         
         Ok(stats)
     }
 
-    /// Get statistics for a specific pool
+    /// Spend against each budget window of a strategy, wallet or the global cap
+    pub async fn get_budget_usage(&self, scope: &BudgetScope) -> Vec<BudgetUsage> {
+        let strategy_limits = match scope {
+            BudgetScope::Strategy(key) => self.active_strategies
+                .get(key)
+                .map(|active_strategy| active_strategy.config.gas_budget)
+                .unwrap_or_default(),
+            _ => BudgetLimits::default(),
+        };
+        self.gas_budget.usage(scope, strategy_limits).await
+    }
+
+    /// Check the fees in the statistics for this run against the budget ledger
+    pub async fn reconcile_gas_spend(&self) -> GasReconciliation {
+        let statistics_total = {
+            let stats_manager = self.statistics_manager.read().await;
+            let ledger = if self.config.dry_run { stats_manager.paper_ledger() } else { Some(&*stats_manager) };
+            match ledger {
+                Some(ledger) => ledger.get_global_statistics().await.total_gas_spent,
+                None => 0.0,
+            }
+        };
+        self.gas_budget.reconcile(statistics_total).await
+    }
+
+    /// Add the ledger's rolling fee totals, which survive restarts, to a statistics snapshot
+    async fn fill_budget_spend(&self, stats: &mut GlobalStatistics) {
+        stats.gas_spent_24h = self.gas_budget.spent(&BudgetScope::Global, BudgetPeriod::Day).await;
+        stats.gas_spent_7d = self.gas_budget.spent(&BudgetScope::Global, BudgetPeriod::Week).await;
+    }
+
+    /// Per-method RPC request metrics for the shared connection
+    pub fn get_rpc_metrics(&self) -> Vec<RpcMethodMetrics> {
+        self.rpc_client.metrics()
+    }
+
+    /// Health of each RPC endpoint behind the shared connection
+    pub fn get_endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.rpc_client.endpoint_health()
+    }
+
+    /// Export the journaled accounting ledger (the paper journal when running dry) as CSV, if configured
+    pub async fn export_ledger(&self) -> Result<Option<LedgerExport>> {
+        let (Some(export), Some(journal)) = (&self.config.ledger_export, &self.ledger_journal) else {
+            return Ok(None);
+        };
+
+        Ok(Some(journal.load()?.export_csv(&export.dir, export.cost_basis_method)?))
+    }
+
+    /// Get global statistics for simulated (dry-run) compounds
+    pub async fn get_paper_statistics(&self) -> Result<Option<GlobalStatistics>> {
+        let stats_manager = self.statistics_manager.read().await;
+        match stats_manager.paper_ledger() {
+            Some(ledger) => {
+                let mut stats = ledger.get_global_statistics().await;
+                if self.config.dry_run {
+                    self.fill_budget_spend(&mut stats).await;
+                }
+                Ok(Some(stats))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get statistics for a specific pool, across all wallets
     pub async fn get_pool_statistics(&self, pool_address: Pubkey) -> Result<Option<PoolStatistics>> {
         let stats_manager = self.statistics_manager.read().await;
         Ok(stats_manager.get_pool_statistics(&pool_address.to_string()).await)
     }
 
-    /// Get list of active pools
-    pub async fn get_active_pools(&self) -> Vec<Pubkey> {
+    /// Get statistics for a specific wallet
+    pub async fn get_wallet_statistics(&self, wallet: &str) -> Result<Option<WalletStatistics>> {
+        let stats_manager = self.statistics_manager.read().await;
+        Ok(stats_manager.get_wallet_statistics(wallet).await)
+    }
+
+    /// Get statistics for every wallet with recorded compounds
+    pub async fn get_all_wallet_statistics(&self) -> Result<Vec<WalletStatistics>> {
+        let stats_manager = self.statistics_manager.read().await;
+        Ok(stats_manager.get_all_wallet_statistics().await)
+    }
+
+    /// Names of all configured wallets
+    pub fn get_wallet_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.wallets.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Get list of active strategies as (wallet, pool) keys
+    pub async fn get_active_strategies(&self) -> Vec<StrategyKey> {
         self.active_strategies
             .iter()
-            .map(|entry| entry.key().parse::<Pubkey>().unwrap())
+            .map(|entry| entry.key().clone())
             .collect()
     }
 
@@ -314,43 +616,165 @@ impl AutoCompounder {
     pub async fn stop_all(&mut self) -> Result<()> {
         info!("🛑 Stopping all active strategies...");
         
-        let pool_keys: Vec<String> = self.active_strategies
+        let keys: Vec<StrategyKey> = self.active_strategies
             .iter()
             .map(|entry| entry.key().clone())
             .collect();
 
-        for pool_key in pool_keys {
-            if let Ok(pool_address) = pool_key.parse::<Pubkey>() {
-                if let Err(e) = self.stop_strategy(pool_address).await {
-                    error!("Failed to stop strategy for pool {}: {}", pool_key, e);
-                }
+        for key in keys {
+            if let Err(e) = self.stop_strategy(&key).await {
+                error!("Failed to stop strategy for {}: {}", key, e);
             }
         }
 
+        // Deliver any batched and queued notifications before shutting down
+        self.notification_service.flush_digests().await;
+        self.notification_service.drain().await;
+
         // Stop scheduler - we'll just let it drop naturally since we can't get mutable access
 
         info!("✅ All strategies stopped");
         Ok(())
     }
 
-    /// Parse private key from various formats
-    fn parse_private_key(private_key: &str) -> Result<Keypair> {
-        // Try base58 format first
-        if let Ok(decoded) = bs58::decode(private_key).into_vec() {
-            if decoded.len() == 64 {
-                return Ok(Keypair::from_bytes(&decoded)?);
+    /// Defer the compound if it would overrun a fee budget, notifying about the exhausted budget
+    async fn check_gas_budget(
+        config: &CompoundStrategyConfig,
+        strategy: &CompoundStrategy,
+        gas_budget: &GasBudget,
+        notification_service: &NotificationService,
+    ) -> Result<Option<BudgetExceeded>> {
+        let estimated_cost = strategy.estimate_gas_cost().await?;
+        let Some(exceeded) = gas_budget.check(&config.key(), config.gas_budget, estimated_cost).await else {
+            return Ok(None);
+        };
+
+        let resumes = match exceeded.resumes_at {
+            Some(at) => format!("deferred until {}", at.format("%Y-%m-%d %H:%M:%S UTC")),
+            None => "deferred until the budget is raised".to_string(),
+        };
+        warn!("⛽ {} for {}, {}", exceeded, config.key(), resumes);
+
+        notification_service.send_notification(NotificationEvent {
+            event_type: NotificationEventType::GasBudgetExceeded,
+            wallet: Some(config.wallet.clone()),
+            pool_address: config.pool_address.to_string(),
+            message: format!("{}; compound {}", exceeded, resumes),
+            data: serde_json::json!({
+                "scope": exceeded.scope.to_string(),
+                "period": exceeded.period.to_string(),
+                "gas_spent": exceeded.spent,
+                "gas_budget": exceeded.limit,
+                "estimated_cost": exceeded.estimated_cost,
+                "resumes_at": exceeded.resumes_at,
+            }),
+            timestamp: Utc::now(),
+        }).await;
+
+        Ok(Some(exceeded))
+    }
+
+    /// Record a compound result in the statistics, the fee budget ledger and the accounting journal
+    async fn record_result(
+        key: &StrategyKey,
+        result: &CompoundResult,
+        statistics_manager: &RwLock<StatisticsManager>,
+        gas_budget: &GasBudget,
+        ledger_journal: Option<&LedgerJournal>,
+    ) {
+        if let Err(e) = gas_budget.record(key, result.gas_used, result.timestamp).await {
+            error!("Failed to persist gas budget ledger: {}", e);
+        }
+        if let Some(journal) = ledger_journal {
+            if let Err(e) = journal.append(&result.ledger) {
+                error!("Failed to append to ledger journal {}: {}", journal.path().display(), e);
             }
         }
+        statistics_manager.write().await.record_compound_result(&key.wallet, &key.pool_address.to_string(), result).await;
+    }
+
+    /// Feed the latest pool state into the circuit breaker
+    async fn check_pool_health(
+        strategy: &CompoundStrategy,
+        position_monitor: &PositionMonitor,
+        circuit_breaker: &CircuitBreaker,
+    ) -> Result<Option<TripReason>> {
+        let pool_info = position_monitor.get_pool_info(strategy.pool_address()).await?;
+        let current_price = strategy.get_current_price().await?;
+
+        Ok(circuit_breaker.check_pool(&pool_info, current_price).await)
+    }
+
+    /// Pause the strategy, withdraw if configured, notify and record the incident along with
+    /// the fees the withdrawal paid and the events it realised
+    async fn handle_circuit_trip(
+        strategy: &CompoundStrategy,
+        reason: TripReason,
+        safe_address: Option<Pubkey>,
+        notification_service: &NotificationService,
+        statistics_manager: &RwLock<StatisticsManager>,
+        gas_budget: &GasBudget,
+        ledger_journal: Option<&LedgerJournal>,
+    ) {
+        let pool_address = strategy.pool_address();
+        let wallet = strategy.wallet_name().to_string();
+        error!("🚨 Circuit breaker tripped for {}/{}: {}", wallet, pool_address, reason);
+
+        let withdrawal = if strategy.emergency_withdraw_enabled() {
+            match strategy.emergency_withdraw(safe_address).await {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    error!("❌ Emergency withdrawal failed for {}/{}: {}", wallet, pool_address, e);
+                    Some(EmergencyWithdrawResult {
+                        safe_address: safe_address.map(|address| address.to_string()),
+                        errors: vec![format!("Withdrawal failed: {}", e)],
+                        ..Default::default()
+                    })
+                }
+            }
+        } else {
+            None
+        };
 
-        // Try JSON array format
-        if private_key.starts_with('[') && private_key.ends_with(']') {
-            let key_array: Vec<u8> = serde_json::from_str(private_key)?;
-            if key_array.len() == 64 {
-                return Ok(Keypair::from_bytes(&key_array)?);
+        if let Some(result) = &withdrawal {
+            let key = StrategyKey::new(wallet.clone(), pool_address);
+            if let Err(e) = gas_budget.record(&key, result.gas_used, Utc::now()).await {
+                error!("Failed to persist gas budget ledger: {}", e);
+            }
+            if let Some(journal) = ledger_journal {
+                if let Err(e) = journal.append(&result.ledger) {
+                    error!("Failed to append to ledger journal {}: {}", journal.path().display(), e);
+                }
             }
         }
 
-        Err(anyhow::anyhow!("Invalid private key format"))
+        let message = match &withdrawal {
+            Some(result) if result.withdraw_signature.is_some() => {
+                format!("Strategy paused and position withdrawn: {}", reason)
+            }
+            Some(_) => format!("Strategy paused, emergency withdrawal FAILED: {}", reason),
+            None => format!("Strategy paused: {}", reason),
+        };
+
+        notification_service.send_notification(NotificationEvent {
+            event_type: NotificationEventType::EmergencyStop,
+            wallet: Some(wallet.clone()),
+            pool_address: pool_address.to_string(),
+            message,
+            data: serde_json::json!({
+                "reason": reason,
+                "withdrawal": withdrawal,
+            }),
+            timestamp: Utc::now(),
+        }).await;
+
+        statistics_manager.write().await.record_incident(IncidentRecord {
+            wallet,
+            pool_address: pool_address.to_string(),
+            reason,
+            withdrawal,
+            timestamp: Utc::now(),
+        }).await;
     }
 
     /// Convert interval in milliseconds to cron expression
@@ -369,4 +793,4 @@ impl AutoCompounder {
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,465 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write as _, io::Read, path::Path};
+
+use crate::{
+    compound_strategy::{price_impact_cost, split_rewards},
+    gas_optimizer::{compound_transaction_count, evaluate_compound, DEFAULT_MAX_GAS_PRICE},
+    statistics::{PerformanceReport, StatisticsManager},
+    types::{CompoundResult, CompoundStrategyConfig, StrategyType, ZapCost},
+};
+
+/// One observation of market conditions in a historical series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketSnapshot {
+    pub timestamp: DateTime<Utc>,
+    /// Trading fee APR earned by liquidity in the pool (%)
+    pub pool_apr: f64,
+    /// Reward tokens emitted to the pool's liquidity providers per day
+    pub reward_emissions_per_day: f64,
+    /// Additional reward tokens per day paid to the pool's farm stakers
+    #[serde(default)]
+    pub farm_emissions_per_day: f64,
+    /// Total value locked in the pool, used to compute the position's emission share
+    pub pool_tvl: f64,
+    /// Reward token price in the position's quote currency
+    pub reward_token_price: f64,
+    /// Network fee per transaction (SOL)
+    pub gas_price: f64,
+    /// SOL price in the position's quote currency; falls back to `BacktestConfig::sol_price`
+    #[serde(default)]
+    pub sol_price: Option<f64>,
+}
+
+/// Settings shared by every variant in a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// Starting position value in quote currency
+    pub initial_position_value: f64,
+    /// SOL price used when the series has no `sol_price` column
+    pub sol_price: f64,
+    /// Swap fee charged when zapping rewards into the pair (%)
+    pub swap_fee_percent: f64,
+    /// Price impact of zapping rewards into the pair (%)
+    pub price_impact_percent: f64,
+    /// Per-transaction fee at or above which compounds are deferred (SOL)
+    pub max_gas_price: f64,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            initial_position_value: 1_000.0,
+            sol_price: 150.0,
+            swap_fee_percent: 0.3,
+            price_impact_percent: 0.5,
+            max_gas_price: DEFAULT_MAX_GAS_PRICE,
+        }
+    }
+}
+
+/// Outcome of replaying a series against one strategy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub strategy: CompoundStrategyConfig,
+    pub period_days: f64,
+    pub initial_position_value: f64,
+    pub final_position_value: f64,
+    /// Value of harvested rewards that were kept instead of reinvested
+    pub rewards_kept_value: f64,
+    /// Value of rewards still pending at the end of the series
+    pub unclaimed_rewards_value: f64,
+    pub total_gas: f64,
+    pub total_gas_value: f64,
+    pub total_zap_cost_value: f64,
+    pub compounds_executed: u64,
+    pub compounds_skipped: u64,
+    pub compounds_failed: u64,
+    /// Final position + kept + unclaimed rewards - gas, in quote currency
+    pub net_value: f64,
+    /// Annualized return on the initial position after gas (%)
+    pub net_apy: f64,
+    pub report: PerformanceReport,
+}
+
+/// Parameter grid expanded into strategy variants around a base configuration
+#[derive(Debug, Clone, Default)]
+pub struct ParameterSweep {
+    pub strategy_types: Vec<StrategyType>,
+    pub intervals_ms: Vec<u64>,
+    pub min_reward_thresholds: Vec<f64>,
+    pub reinvest_percentages: Vec<u8>,
+}
+
+impl ParameterSweep {
+    /// Every combination of the sweep parameters; empty lists keep the base value
+    pub fn variants(&self, base: &CompoundStrategyConfig) -> Vec<CompoundStrategyConfig> {
+        let strategy_types = or_base(&self.strategy_types, base.strategy_type);
+        let intervals_ms = or_base(&self.intervals_ms, base.interval_ms);
+        let thresholds = or_base(&self.min_reward_thresholds, base.min_reward_threshold);
+        let reinvest_percentages = or_base(&self.reinvest_percentages, base.reinvest_percentage);
+
+        let mut variants = Vec::new();
+        for &strategy_type in &strategy_types {
+            for &interval_ms in &intervals_ms {
+                for &min_reward_threshold in &thresholds {
+                    for &reinvest_percentage in &reinvest_percentages {
+                        variants.push(CompoundStrategyConfig {
+                            strategy_type,
+                            interval_ms,
+                            min_reward_threshold,
+                            reinvest_percentage,
+                            ..base.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        variants
+    }
+}
+
+fn or_base<T: Copy>(values: &[T], base: T) -> Vec<T> {
+    if values.is_empty() {
+        vec![base]
+    } else {
+        values.to_vec()
+    }
+}
+
+/// Replays historical market data through the compound decision logic.
+///
+/// Each strategy type earns and reinvests differently:
+/// - `LP` earns the pool's trading fees plus LP emissions and zaps rewards into the pair
+/// - `Farming` earns the same plus farm emissions, and needs an extra stake transaction
+/// - `Staking` earns LP emissions only and restakes rewards directly without swapping
+pub struct Backtester {
+    config: BacktestConfig,
+    series: Vec<MarketSnapshot>,
+}
+
+impl Backtester {
+    pub fn new(config: BacktestConfig, mut series: Vec<MarketSnapshot>) -> Result<Self> {
+        if series.len() < 2 {
+            return Err(anyhow::anyhow!("Backtest series needs at least two snapshots"));
+        }
+
+        series.sort_by_key(|snapshot| snapshot.timestamp);
+
+        Ok(Self { config, series })
+    }
+
+    /// Load a series from a `.csv` or `.json` file
+    pub fn load_series(path: &Path) -> Result<Vec<MarketSnapshot>> {
+        let file = std::fs::File::open(path)?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => parse_csv(file),
+            Some("json") => Ok(serde_json::from_reader(file)?),
+            _ => Err(anyhow::anyhow!("Unsupported backtest data format: {}", path.display())),
+        }
+    }
+
+    /// Run every variant and return results ordered by net APY (best first)
+    pub async fn run_sweep(&self, variants: &[CompoundStrategyConfig]) -> Vec<BacktestResult> {
+        info!("🧪 Backtesting {} strategy variants over {} snapshots", variants.len(), self.series.len());
+
+        let mut results = Vec::with_capacity(variants.len());
+        for strategy in variants {
+            results.push(self.run(strategy).await);
+        }
+
+        results.sort_by(|a, b| b.net_apy.partial_cmp(&a.net_apy).unwrap_or(std::cmp::Ordering::Equal));
+        results
+    }
+
+    /// Replay the series against a single strategy configuration
+    pub async fn run(&self, strategy: &CompoundStrategyConfig) -> BacktestResult {
+        let pool_key = strategy.pool_address.to_string();
+        let max_slippage = strategy.max_slippage.unwrap_or(1.0);
+        let mut stats = StatisticsManager::new();
+
+        let mut position_value = self.config.initial_position_value;
+        let mut pending_rewards = 0.0;
+        let mut rewards_kept_value = 0.0;
+        let mut total_gas = 0.0;
+        let mut total_gas_value = 0.0;
+        let mut total_zap_cost_value = 0.0;
+        let (mut executed, mut skipped, mut failed) = (0u64, 0u64, 0u64);
+
+        let start = self.series[0].timestamp;
+        let mut last_check = start;
+
+        for window in self.series.windows(2) {
+            let (previous, current) = (&window[0], &window[1]);
+            let elapsed_days = (current.timestamp - previous.timestamp).num_seconds() as f64 / 86_400.0;
+
+            // Accrue yield over the interval at the previous snapshot's rates
+            if strategy.strategy_type != StrategyType::Staking {
+                position_value *= 1.0 + previous.pool_apr / 100.0 * elapsed_days / 365.0;
+            }
+            if previous.pool_tvl > 0.0 {
+                let mut emissions = previous.reward_emissions_per_day;
+                if strategy.strategy_type == StrategyType::Farming {
+                    emissions += previous.farm_emissions_per_day;
+                }
+                pending_rewards += position_value / previous.pool_tvl * emissions * elapsed_days;
+            }
+
+            if (current.timestamp - last_check).num_milliseconds() < strategy.interval_ms as i64 {
+                continue;
+            }
+            last_check = current.timestamp;
+
+            // Same decision the live compounder makes, valued at this snapshot's prices
+            let sol_price = current.sol_price.unwrap_or(self.config.sol_price);
+            let swaps = match strategy.strategy_type {
+                StrategyType::LP => 2, // Rewards are zapped into both sides of the pair
+                StrategyType::Staking | StrategyType::Farming => 0,
+            };
+            let estimated_gas_cost =
+                compound_transaction_count(strategy.strategy_type, swaps) as f64 * current.gas_price;
+            let pending_rewards_sol = pending_rewards * current.reward_token_price / sol_price;
+            let decision = evaluate_compound(
+                current.gas_price,
+                estimated_gas_cost,
+                pending_rewards,
+                pending_rewards_sol,
+                strategy.min_reward_threshold,
+                self.config.max_gas_price,
+            );
+
+            if !decision.should_proceed {
+                skipped += 1;
+                continue;
+            }
+
+            let (reinvest_amount, keep_amount) = split_rewards(pending_rewards, strategy.reinvest_percentage);
+            let harvested = pending_rewards;
+            pending_rewards = 0.0;
+
+            total_gas += estimated_gas_cost;
+            total_gas_value += estimated_gas_cost * sol_price;
+            rewards_kept_value += keep_amount * current.reward_token_price;
+
+            let zap_cost = match strategy.strategy_type {
+                StrategyType::Staking => None,
+                StrategyType::LP | StrategyType::Farming => {
+                    Some(self.zap_cost(reinvest_amount))
+                }
+            };
+
+            // The live strategy rejects swaps whose price impact exceeds the slippage limit
+            let rejected = zap_cost.is_some() && self.config.price_impact_percent > max_slippage;
+            let error = if rejected {
+                failed += 1;
+                rewards_kept_value += reinvest_amount * current.reward_token_price;
+                Some(format!(
+                    "Price impact too high swapping rewards: {:.2}% > {:.2}%",
+                    self.config.price_impact_percent, max_slippage
+                ))
+            } else {
+                executed += 1;
+                let conversion_cost = zap_cost.as_ref().map_or(0.0, |cost| cost.total_cost);
+                total_zap_cost_value += conversion_cost * current.reward_token_price;
+                position_value += (reinvest_amount - conversion_cost) * current.reward_token_price;
+                None
+            };
+
+            stats.apply_compound_result(&strategy.wallet, &pool_key, &CompoundResult {
+                success: error.is_none(),
+                rewards_harvested: harvested,
+                amount_reinvested: if error.is_none() { reinvest_amount } else { 0.0 },
+                new_position_value: position_value,
+                gas_used: estimated_gas_cost,
+                transaction_signature: format!("backtest_{}", executed + failed),
+                timestamp: current.timestamp,
+                error,
+                skipped: false,
+                simulated: true,
+                zap_cost,
+                ledger: Vec::new(),
+            });
+        }
+
+        let last = &self.series[self.series.len() - 1];
+        let period_days = (last.timestamp - start).num_seconds() as f64 / 86_400.0;
+        let unclaimed_rewards_value = pending_rewards * last.reward_token_price;
+        let net_value = position_value + rewards_kept_value + unclaimed_rewards_value - total_gas_value;
+
+        BacktestResult {
+            strategy: strategy.clone(),
+            period_days,
+            initial_position_value: self.config.initial_position_value,
+            final_position_value: position_value,
+            rewards_kept_value,
+            unclaimed_rewards_value,
+            total_gas,
+            total_gas_value,
+            total_zap_cost_value,
+            compounds_executed: executed,
+            compounds_skipped: skipped,
+            compounds_failed: failed,
+            net_value,
+            net_apy: annualize(self.config.initial_position_value, net_value, period_days),
+            report: stats.generate_performance_report().await,
+        }
+    }
+
+    /// Cost of converting rewards into the pair, matching the live zap accounting
+    fn zap_cost(&self, amount: f64) -> ZapCost {
+        let swap_fees = amount * self.config.swap_fee_percent / 100.0;
+        let price_impact = price_impact_cost(amount, swap_fees, self.config.price_impact_percent);
+        let amount_out = amount - swap_fees - price_impact;
+
+        ZapCost {
+            reward_to_x: amount / 2.0,
+            reward_to_y: amount / 2.0,
+            amount_x: amount_out / 2.0,
+            amount_y: amount_out / 2.0,
+            swap_fees,
+            price_impact_cost: price_impact,
+            total_cost: swap_fees + price_impact,
+            unswapped_reward: 0.0,
+        }
+    }
+}
+
+/// Parse a CSV series with a header row matching `MarketSnapshot` field names
+pub fn parse_csv<R: Read>(reader: R) -> Result<Vec<MarketSnapshot>> {
+    let mut csv_reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader);
+
+    csv_reader
+        .deserialize()
+        .map(|row| row.map_err(anyhow::Error::from))
+        .collect()
+}
+
+fn annualize(initial_value: f64, final_value: f64, period_days: f64) -> f64 {
+    if initial_value <= 0.0 || final_value <= 0.0 || period_days <= 0.0 {
+        return 0.0;
+    }
+
+    ((final_value / initial_value).powf(365.0 / period_days) - 1.0) * 100.0
+}
+
+/// Format sweep results as a fixed-width comparison table
+pub fn comparison_table(results: &[BacktestResult]) -> String {
+    let mut table = String::new();
+
+    let _ = writeln!(
+        table,
+        "{:<4} {:<8} {:>10} {:>10} {:>8} {:>14} {:>12} {:>10} {:>9} {:>9}",
+        "#", "TYPE", "INTERVAL_H", "THRESHOLD", "REINV_%", "FINAL_VALUE", "GAS_SOL", "NET_APY_%", "EXECUTED", "SKIPPED"
+    );
+
+    for (rank, result) in results.iter().enumerate() {
+        let _ = writeln!(
+            table,
+            "{:<4} {:<8} {:>10.1} {:>10.3} {:>8} {:>14.2} {:>12.6} {:>10.2} {:>9} {:>9}",
+            rank + 1,
+            result.strategy.strategy_type.to_string(),
+            result.strategy.interval_ms as f64 / 3_600_000.0,
+            result.strategy.min_reward_threshold,
+            result.strategy.reinvest_percentage,
+            result.final_position_value,
+            result.total_gas,
+            result.net_apy,
+            result.compounds_executed,
+            result.compounds_skipped,
+        );
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use solana_sdk::pubkey::Pubkey;
+
+    use crate::types::{BudgetLimits, DEFAULT_WALLET};
+
+    fn series(days: i64) -> Vec<MarketSnapshot> {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        (0..=days * 24)
+            .map(|hour| MarketSnapshot {
+                timestamp: start + Duration::hours(hour),
+                pool_apr: 20.0,
+                reward_emissions_per_day: 5_000.0,
+                farm_emissions_per_day: 2_000.0,
+                pool_tvl: 1_000_000.0,
+                reward_token_price: 0.5,
+                gas_price: 0.000005,
+                sol_price: None,
+            })
+            .collect()
+    }
+
+    fn strategy(strategy_type: StrategyType, interval_hours: u64, min_reward_threshold: f64) -> CompoundStrategyConfig {
+        CompoundStrategyConfig {
+            wallet: DEFAULT_WALLET.to_string(),
+            pool_address: Pubkey::new_unique(),
+            strategy_type,
+            interval_ms: interval_hours * 3_600_000,
+            min_reward_threshold,
+            reinvest_percentage: 100,
+            max_slippage: Some(1.0),
+            emergency_withdraw: false,
+            gas_budget: BudgetLimits::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let data = "timestamp,pool_apr,reward_emissions_per_day,pool_tvl,reward_token_price,gas_price\n\
+                    2024-01-01T00:00:00Z,25.0,1000,500000,0.8,0.000005\n\
+                    2024-01-01T06:00:00Z,24.5,1000,510000,0.79,0.000006\n";
+
+        let snapshots = parse_csv(data.as_bytes()).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[1].pool_apr, 24.5);
+        assert_eq!(snapshots[0].farm_emissions_per_day, 0.0);
+        assert!(snapshots[0].sol_price.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_threshold_controls_compounding() {
+        let backtester = Backtester::new(BacktestConfig::default(), series(30)).unwrap();
+
+        let frequent = backtester.run(&strategy(StrategyType::LP, 24, 1.0)).await;
+        let never = backtester.run(&strategy(StrategyType::LP, 24, 1_000_000.0)).await;
+
+        assert_eq!(frequent.compounds_executed, 30);
+        assert_eq!(never.compounds_executed, 0);
+        assert_eq!(never.compounds_skipped, 30);
+        assert!(frequent.final_position_value > never.final_position_value);
+        assert!(frequent.total_zap_cost_value > 0.0);
+        assert_eq!(frequent.report.global_stats.successful_compounds, 30);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_ranks_variants() {
+        let backtester = Backtester::new(BacktestConfig::default(), series(30)).unwrap();
+        let sweep = ParameterSweep {
+            strategy_types: vec![StrategyType::LP, StrategyType::Staking, StrategyType::Farming],
+            intervals_ms: vec![6 * 3_600_000, 24 * 3_600_000],
+            ..Default::default()
+        };
+
+        let variants = sweep.variants(&strategy(StrategyType::LP, 1, 1.0));
+        assert_eq!(variants.len(), 6);
+
+        let results = backtester.run_sweep(&variants).await;
+        assert!(results.windows(2).all(|pair| pair[0].net_apy >= pair[1].net_apy));
+        // Farm emissions outweigh the extra stake transaction in this series
+        assert_eq!(results[0].strategy.strategy_type, StrategyType::Farming);
+
+        let table = comparison_table(&results);
+        assert_eq!(table.lines().count(), 7);
+    }
+}
@@ -0,0 +1,266 @@
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tokio::sync::RwLock;
+
+use crate::types::{CircuitBreakerConfig, CompoundResult, PoolInfo};
+
+/// Smoothing factor for the APY and price moving averages
+const EMA_ALPHA: f64 = 0.2;
+
+/// Why a circuit breaker tripped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TripReason {
+    ConsecutiveFailures { count: u32 },
+    ApyCollapse { average_apy: f64, current_apy: f64 },
+    PoolInactive,
+    TvlDrop { previous_tvl: f64, current_tvl: f64 },
+    PriceDeviation { average_price: f64, current_price: f64 },
+}
+
+impl fmt::Display for TripReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TripReason::ConsecutiveFailures { count } => {
+                write!(f, "{} consecutive compound failures", count)
+            }
+            TripReason::ApyCollapse { average_apy, current_apy } => {
+                write!(f, "APY collapsed from {:.2}% to {:.2}%", average_apy, current_apy)
+            }
+            TripReason::PoolInactive => write!(f, "Pool is no longer active"),
+            TripReason::TvlDrop { previous_tvl, current_tvl } => {
+                write!(f, "TVL dropped from ${:.0} to ${:.0}", previous_tvl, current_tvl)
+            }
+            TripReason::PriceDeviation { average_price, current_price } => {
+                write!(f, "Price deviated from {:.6} to {:.6}", average_price, current_price)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    average_apy: Option<f64>,
+    last_tvl: Option<f64>,
+    average_price: Option<f64>,
+    tripped: Option<(TripReason, DateTime<Utc>)>,
+}
+
+/// Watches a strategy's compound results and pool health, pausing it when conditions turn unsafe
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: RwLock<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: RwLock::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether the breaker has tripped and the strategy is paused
+    pub async fn is_tripped(&self) -> bool {
+        self.state.read().await.tripped.is_some()
+    }
+
+    /// The reason and time the breaker tripped, if it has
+    pub async fn trip_reason(&self) -> Option<(TripReason, DateTime<Utc>)> {
+        self.state.read().await.tripped.clone()
+    }
+
+    /// Clear the trip and all tracked baselines so the strategy can resume
+    pub async fn reset(&self) {
+        info!("🔌 Circuit breaker reset");
+        *self.state.write().await = BreakerState::default();
+    }
+
+    /// Record a compound result, returning a trip reason if the breaker trips now
+    pub async fn record_compound(&self, result: &CompoundResult) -> Option<TripReason> {
+        if !self.config.enabled || result.skipped {
+            return None;
+        }
+
+        let mut state = self.state.write().await;
+        if state.tripped.is_some() {
+            return None;
+        }
+
+        if result.success {
+            state.consecutive_failures = 0;
+            return None;
+        }
+
+        state.consecutive_failures += 1;
+        warn!("⚠️ Consecutive compound failures: {}/{}",
+              state.consecutive_failures, self.config.max_consecutive_failures);
+
+        if state.consecutive_failures >= self.config.max_consecutive_failures {
+            let reason = TripReason::ConsecutiveFailures { count: state.consecutive_failures };
+            state.tripped = Some((reason.clone(), Utc::now()));
+            return Some(reason);
+        }
+
+        None
+    }
+
+    /// Check pool health against tracked baselines, returning a trip reason if the breaker trips now
+    pub async fn check_pool(&self, pool: &PoolInfo, current_price: f64) -> Option<TripReason> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let mut state = self.state.write().await;
+        if state.tripped.is_some() {
+            return None;
+        }
+
+        let reason = self.evaluate_pool(&state, pool, current_price);
+
+        // Only fold healthy observations into the baselines
+        match reason {
+            Some(reason) => {
+                state.tripped = Some((reason.clone(), Utc::now()));
+                Some(reason)
+            }
+            None => {
+                state.average_apy = Some(ema(state.average_apy, pool.apy));
+                state.average_price = Some(ema(state.average_price, current_price));
+                state.last_tvl = Some(pool.tvl);
+                None
+            }
+        }
+    }
+
+    fn evaluate_pool(&self, state: &BreakerState, pool: &PoolInfo, current_price: f64) -> Option<TripReason> {
+        if !pool.is_active {
+            return Some(TripReason::PoolInactive);
+        }
+
+        if let Some(average_apy) = state.average_apy {
+            if average_apy > 0.0 && percent_drop(average_apy, pool.apy) >= self.config.max_apy_drop_percent {
+                return Some(TripReason::ApyCollapse { average_apy, current_apy: pool.apy });
+            }
+        }
+
+        if let Some(previous_tvl) = state.last_tvl {
+            if previous_tvl > 0.0 && percent_drop(previous_tvl, pool.tvl) >= self.config.max_tvl_drop_percent {
+                return Some(TripReason::TvlDrop { previous_tvl, current_tvl: pool.tvl });
+            }
+        }
+
+        if let Some(average_price) = state.average_price {
+            let deviation = if average_price > 0.0 {
+                (current_price - average_price).abs() / average_price * 100.0
+            } else {
+                0.0
+            };
+            if deviation >= self.config.max_price_deviation_percent {
+                return Some(TripReason::PriceDeviation { average_price, current_price });
+            }
+        }
+
+        None
+    }
+}
+
+fn ema(previous: Option<f64>, value: f64) -> f64 {
+    match previous {
+        Some(previous) => previous * (1.0 - EMA_ALPHA) + value * EMA_ALPHA,
+        None => value,
+    }
+}
+
+fn percent_drop(from: f64, to: f64) -> f64 {
+    (from - to) / from * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn pool(apy: f64, tvl: f64, is_active: bool) -> PoolInfo {
+        PoolInfo {
+            address: Pubkey::new_unique(),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            token_a_symbol: "SOL".to_string(),
+            token_b_symbol: "USDC".to_string(),
+            tvl,
+            apy,
+            fee_rate: 0.3,
+            is_active,
+        }
+    }
+
+    fn compound(success: bool, skipped: bool) -> CompoundResult {
+        CompoundResult {
+            success,
+            rewards_harvested: 0.0,
+            amount_reinvested: 0.0,
+            new_position_value: 0.0,
+            gas_used: 0.0,
+            transaction_signature: "".to_string(),
+            timestamp: Utc::now(),
+            error: None,
+            skipped,
+            simulated: false,
+            zap_cost: None,
+            ledger: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trips_on_consecutive_failures() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::default());
+
+        assert_eq!(breaker.record_compound(&compound(false, false)).await, None);
+        assert_eq!(breaker.record_compound(&compound(false, true)).await, None); // Skips don't count
+        assert_eq!(breaker.record_compound(&compound(true, false)).await, None); // Success resets
+        assert_eq!(breaker.record_compound(&compound(false, false)).await, None);
+        assert_eq!(breaker.record_compound(&compound(false, false)).await, None);
+        assert_eq!(
+            breaker.record_compound(&compound(false, false)).await,
+            Some(TripReason::ConsecutiveFailures { count: 3 })
+        );
+        assert!(breaker.is_tripped().await);
+
+        breaker.reset().await;
+        assert!(!breaker.is_tripped().await);
+    }
+
+    #[tokio::test]
+    async fn test_trips_on_pool_health() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::default());
+        assert_eq!(breaker.check_pool(&pool(40.0, 1_000_000.0, true), 100.0).await, None);
+        assert_eq!(breaker.check_pool(&pool(38.0, 950_000.0, true), 105.0).await, None);
+        assert!(matches!(
+            breaker.check_pool(&pool(38.0, 500_000.0, true), 105.0).await,
+            Some(TripReason::TvlDrop { .. })
+        ));
+
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::default());
+        assert_eq!(breaker.check_pool(&pool(40.0, 1_000_000.0, true), 100.0).await, None);
+        assert!(matches!(
+            breaker.check_pool(&pool(10.0, 1_000_000.0, true), 100.0).await,
+            Some(TripReason::ApyCollapse { .. })
+        ));
+
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::default());
+        assert_eq!(breaker.check_pool(&pool(40.0, 1_000_000.0, true), 100.0).await, None);
+        assert!(matches!(
+            breaker.check_pool(&pool(40.0, 1_000_000.0, true), 75.0).await,
+            Some(TripReason::PriceDeviation { .. })
+        ));
+
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::default());
+        assert_eq!(
+            breaker.check_pool(&pool(40.0, 1_000_000.0, false), 100.0).await,
+            Some(TripReason::PoolInactive)
+        );
+    }
+}
@@ -1,19 +1,21 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::Utc;
 use log::{error, info, warn};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     pubkey::Pubkey,
-    signature::{Keypair, Signer, Signature},
     transaction::Transaction,
 };
 use std::{sync::Arc, time::Duration};
 use backoff::{ExponentialBackoff, backoff::Backoff};
 
-use saros_dlmm_sdk::{DLMMClient, PoolInfo, UserPosition};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use saros_dlmm_sdk::{
+    bin_math, error_category, ledger::quote_swap_entries, DLMMClient, DLMMPoolInfo, LedgerEntry, LedgerEventKind,
+    PoolInfo, RpcConnection, SharedSigner, TransactionSender, UserPosition,
+};
 
 use crate::{
-    gas_optimizer::GasOptimizer,
+    gas_optimizer::{GasOptimizer, BASE_TRANSACTION_FEE},
     notification_service::NotificationService,
     types::*,
 };
@@ -21,34 +23,55 @@ use crate::{
 /// Handles the execution of compound strategies
 pub struct CompoundStrategy {
     config: CompoundStrategyConfig,
-    rpc_client: Arc<RpcClient>,
-    wallet: Arc<Keypair>,
+    wallet: SharedSigner,
     gas_optimizer: Arc<GasOptimizer>,
+    transaction_sender: Arc<TransactionSender>,
     notification_service: Arc<NotificationService>,
     dlmm_client: DLMMClient,
+    dry_run: bool,
 }
 
 impl CompoundStrategy {
     pub fn new(
         config: CompoundStrategyConfig,
-        rpc_client: Arc<RpcClient>,
-        wallet: Arc<Keypair>,
+        rpc_client: Arc<RpcConnection>,
+        wallet: SharedSigner,
         gas_optimizer: Arc<GasOptimizer>,
+        transaction_sender: Arc<TransactionSender>,
         notification_service: Arc<NotificationService>,
+        dry_run: bool,
     ) -> Self {
         let dlmm_client = DLMMClient::new_with_rpc(rpc_client.clone());
 
         Self {
             config,
-            rpc_client,
             wallet,
             gas_optimizer,
+            transaction_sender,
             notification_service,
             dlmm_client,
+            dry_run,
         }
     }
 
-    /// Execute the compound operation with retry logic
+    /// Name of the wallet that owns the position
+    pub fn wallet_name(&self) -> &str {
+        &self.config.wallet
+    }
+
+    /// Pool this strategy compounds into
+    pub fn pool_address(&self) -> Pubkey {
+        self.config.pool_address
+    }
+
+    /// Whether the position should be withdrawn when the circuit breaker trips
+    pub fn emergency_withdraw_enabled(&self) -> bool {
+        self.config.emergency_withdraw
+    }
+
+    /// Execute the compound operation, retrying failures classified as transient. A retry
+    /// resumes at the step that failed, so rewards already harvested or swapped are reinvested
+    /// rather than left in the wallet.
     pub async fn execute_compound(&self) -> Result<CompoundResult> {
         let start_time = std::time::Instant::now();
         let pool_key = self.config.pool_address.to_string();
@@ -61,28 +84,39 @@ impl CompoundStrategy {
             ..Default::default()
         };
 
+        // Fees and events of every attempt count towards this compound, including failed ones
+        let mut sent = TransactionLog::default();
+        let mut progress = CompoundProgress::default();
+
         loop {
-            match self.try_execute_compound().await {
-                Ok(result) => {
+            match self.try_execute_compound(&mut sent, &mut progress).await {
+                Ok(mut result) => {
                     let duration = start_time.elapsed();
                     info!("✅ Compound completed in {:.2}s", duration.as_secs_f64());
+                    result.ledger = self.ledger_entries(&sent).await;
                     return Ok(result);
                 }
                 Err(e) => {
-                    if let Some(duration) = backoff.next_backoff() {
-                        warn!("⏳ Compound failed, retrying in {:.1}s: {}", duration.as_secs_f64(), e);
+                    let category = error_category(&e);
+                    // Terminal failures (slippage, insufficient funds, rejected instructions) won't recover
+                    if let Some(duration) = backoff.next_backoff().filter(|_| category.is_retryable()) {
+                        warn!("⏳ Compound failed ({}), retrying in {:.1}s: {:#}", category, duration.as_secs_f64(), e);
                         tokio::time::sleep(duration).await;
                     } else {
-                        error!("❌ Compound failed after all retries: {}", e);
+                        error!("❌ Compound failed ({}): {:#}", category, e);
                         return Ok(CompoundResult {
                             success: false,
-                            rewards_harvested: 0.0,
+                            rewards_harvested: progress.harvest.as_ref().map_or(0.0, |harvest| harvest.amount),
                             amount_reinvested: 0.0,
                             new_position_value: 0.0,
-                            gas_used: 0.0,
+                            gas_used: sent.total_fees(),
                             transaction_signature: "".to_string(),
                             timestamp: Utc::now(),
                             error: Some(e.to_string()),
+                            skipped: false,
+                            simulated: self.dry_run,
+                            zap_cost: None,
+                            ledger: self.ledger_entries(&sent).await,
                         });
                     }
                 }
@@ -90,33 +124,131 @@ impl CompoundStrategy {
         }
     }
 
-    /// Try to execute compound operation once
-    async fn try_execute_compound(&self) -> Result<CompoundResult> {
+    /// Try to execute compound operation once, recording every transaction sent in `sent` and
+    /// every step that lands in `progress`
+    async fn try_execute_compound(&self, sent: &mut TransactionLog, progress: &mut CompoundProgress) -> Result<CompoundResult> {
         let pool_key = self.config.pool_address.to_string();
 
+        let harvest = match &progress.harvest {
+            Some(harvest) => {
+                info!("↩️ Resuming compound with {:.6} rewards already harvested ({})", harvest.amount, harvest.signature);
+                harvest.clone()
+            }
+            None => match self.harvest(sent).await? {
+                HarvestOutcome::Harvested(harvest) => progress.harvest.insert(harvest).clone(),
+                HarvestOutcome::Skipped(result) => return Ok(result),
+            },
+        };
+        let pending_rewards = harvest.amount;
+        let harvest_signature = harvest.signature;
+
+        // Step 5: Calculate reinvestment amounts
+        let (mut reinvest_amount, keep_amount) = split_rewards(pending_rewards, self.config.reinvest_percentage);
+
+        info!("💰 Reinvesting: {:.6} ({:.0}%)", reinvest_amount, self.config.reinvest_percentage);
+        if keep_amount > 0.0 {
+            info!("🏦 Keeping: {:.6}", keep_amount);
+        }
+
+        // Step 6: Execute reinvestment based on strategy
+        let max_slippage = self.config.max_slippage.unwrap_or(1.0);
+        let (reinvest_signature, zap_cost) = match self.config.strategy_type {
+            StrategyType::LP => {
+                let (signature, zap_cost) = self.reinvest_lp(reinvest_amount, max_slippage, sent, &mut progress.zap).await?;
+                (signature, Some(zap_cost))
+            }
+            StrategyType::Staking => {
+                (self.reinvest_staking(reinvest_amount, sent).await?, None)
+            }
+            StrategyType::Farming => {
+                (self.reinvest_farming(reinvest_amount, max_slippage, sent).await?, None)
+            }
+        };
+        if let Some(unswapped) = zap_cost.as_ref().map(|cost| cost.unswapped_reward).filter(|amount| *amount > 0.0) {
+            reinvest_amount -= unswapped;
+        }
+        if zap_cost.is_none() {
+            self.record_event(sent, LedgerEventKind::Reinvestment, &harvest.reward_mint, -reinvest_amount, &reinvest_signature).await;
+        }
+
+        info!("✅ Reinvestment completed: {}", reinvest_signature);
+
+        // Step 7: Get updated position
+        let new_position = self.get_current_position().await?;
+        let gas_used = sent.total_fees();
+
+        // Send success notification
+        let mode_prefix = if self.dry_run { "[DRY RUN] " } else { "" };
+        self.notification_service.send_notification(NotificationEvent {
+            event_type: NotificationEventType::CompoundSuccess,
+            wallet: Some(self.config.wallet.clone()),
+            pool_address: pool_key,
+            message: format!("{}Compound successful: harvested {:.6}, reinvested {:.6}", mode_prefix, pending_rewards, reinvest_amount),
+            data: serde_json::json!({
+                "rewards_harvested": pending_rewards,
+                "amount_reinvested": reinvest_amount,
+                "new_position": new_position.lp_token_amount,
+                "gas_used": gas_used,
+                "estimated_gas_cost": harvest.estimated_gas_cost,
+                "harvest_signature": harvest_signature,
+                "reinvest_signature": reinvest_signature,
+                "zap_cost": zap_cost,
+                "dry_run": self.dry_run,
+            }),
+            timestamp: Utc::now(),
+        }).await;
+
+        Ok(CompoundResult {
+            success: true,
+            rewards_harvested: pending_rewards,
+            amount_reinvested: reinvest_amount,
+            new_position_value: new_position.lp_token_amount,
+            gas_used,
+            transaction_signature: reinvest_signature,
+            timestamp: Utc::now(),
+            error: None,
+            skipped: false,
+            simulated: self.dry_run,
+            zap_cost,
+            ledger: Vec::new(),
+        })
+    }
+
+    /// Check gas and the reward threshold, then harvest
+    async fn harvest(&self, sent: &mut TransactionLog) -> Result<HarvestOutcome> {
         // Step 1: Gas optimization check
         let gas_check = self.gas_optimizer.should_compound(
             self.config.pool_address,
+            self.wallet.pubkey(),
+            self.config.strategy_type,
             self.config.min_reward_threshold,
         ).await?;
 
         if !gas_check.should_proceed {
             info!("⏸️ Skipping compound: {}", gas_check.reason);
-            return Ok(CompoundResult {
+            return Ok(HarvestOutcome::Skipped(CompoundResult {
                 success: false,
                 rewards_harvested: 0.0,
                 amount_reinvested: 0.0,
                 new_position_value: 0.0,
-                gas_used: 0.0,
+                gas_used: sent.total_fees(),
                 transaction_signature: "".to_string(),
                 timestamp: Utc::now(),
                 error: Some(gas_check.reason),
-            });
+                skipped: true,
+                simulated: self.dry_run,
+                zap_cost: None,
+                ledger: Vec::new(),
+            }));
         }
 
         // Step 2: Get current position and pending rewards
         let position = self.get_current_position().await?;
-        let pending_rewards = self.get_pending_rewards().await?;
+        let user_position = self.dlmm_client.get_user_position(
+            &self.config.pool_address,
+            &self.wallet.pubkey(),
+        ).await?;
+        let pending_rewards = user_position.pending_rewards;
 
         info!("📊 Current position: {:.6} LP tokens", position.lp_token_amount);
         info!("🎁 Pending rewards: {:.6} tokens", pending_rewards);
@@ -128,76 +260,210 @@ impl CompoundStrategy {
                 pending_rewards, self.config.min_reward_threshold
             );
             info!("⏸️ {}", reason);
-            return Ok(CompoundResult {
+            return Ok(HarvestOutcome::Skipped(CompoundResult {
                 success: false,
                 rewards_harvested: 0.0,
                 amount_reinvested: 0.0,
                 new_position_value: position.lp_token_amount,
-                gas_used: 0.0,
+                gas_used: sent.total_fees(),
                 transaction_signature: "".to_string(),
                 timestamp: Utc::now(),
                 error: Some(reason),
-            });
+                skipped: true,
+                simulated: self.dry_run,
+                zap_cost: None,
+                ledger: Vec::new(),
+            }));
         }
 
         // Step 4: Harvest rewards
-        let harvest_signature = self.harvest_rewards().await?;
+        let harvest_signature = self.harvest_rewards(sent).await?;
         info!("✅ Rewards harvested: {}", harvest_signature);
+        self.record_event(sent, LedgerEventKind::Harvest, &user_position.reward_mint, pending_rewards, &harvest_signature).await;
+
+        Ok(HarvestOutcome::Harvested(HarvestedRewards {
+            amount: pending_rewards,
+            reward_mint: user_position.reward_mint,
+            signature: harvest_signature,
+            estimated_gas_cost: gas_check.estimated_gas_cost,
+        }))
+    }
 
-        // Step 5: Calculate reinvestment amounts
-        let reinvest_amount = (pending_rewards * self.config.reinvest_percentage as f64) / 100.0;
-        let keep_amount = pending_rewards - reinvest_amount;
+    /// Value an event at the token's current price and add it to the log; a missing price is
+    /// logged rather than failing a compound whose transactions already landed
+    async fn record_event(
+        &self,
+        sent: &mut TransactionLog,
+        kind: LedgerEventKind,
+        mint: &Pubkey,
+        amount: f64,
+        signature: &str,
+    ) {
+        match self.dlmm_client.get_token_price_usd(mint).await {
+            Ok(price) => sent.entries.push(
+                LedgerEntry::new(kind, Utc::now(), mint.to_string(), to_decimal(amount), price).with_signature(signature),
+            ),
+            Err(e) => warn!("⚠️ Could not value {} of {} for the ledger: {}", kind, mint, e),
+        }
+    }
 
-        info!("💰 Reinvesting: {:.6} ({:.0}%)", reinvest_amount, self.config.reinvest_percentage);
-        if keep_amount > 0.0 {
-            info!("🏦 Keeping: {:.6}", keep_amount);
+    /// Ledger entries for the logged transactions: the events they realised followed by the
+    /// gas each one paid, valued at the current SOL price
+    async fn ledger_entries(&self, sent: &TransactionLog) -> Vec<LedgerEntry> {
+        let mut entries = sent.entries.clone();
+
+        match self.dlmm_client.get_sol_price_usd().await {
+            Ok(sol_price) => entries.extend(sent.fees.iter().map(|(signature, fee)| {
+                LedgerEntry::new(LedgerEventKind::GasSpend, Utc::now(), "SOL", -to_decimal(*fee), sol_price)
+                    .with_signature(signature)
+            })),
+            Err(e) => warn!("⚠️ Could not value gas for the ledger: {}", e),
         }
 
-        // Step 6: Execute reinvestment based on strategy
-        let reinvest_signature = match self.config.strategy_type {
-            StrategyType::LP => {
-                self.reinvest_lp(reinvest_amount, self.config.max_slippage.unwrap_or(1.0)).await?
+        let source = format!("{}/{}", self.config.wallet, self.config.pool_address);
+        entries
+            .into_iter()
+            .filter(|entry| !entry.amount.is_zero())
+            .map(|entry| entry.with_source(&source))
+            .collect()
+    }
+
+    /// Estimate the fees of the next compound
+    pub async fn estimate_gas_cost(&self) -> Result<f64> {
+        self.gas_optimizer.estimate_compound_gas_cost(
+            self.config.pool_address,
+            self.wallet.pubkey(),
+            self.config.strategy_type,
+        ).await
+    }
+
+    /// Get the current active bin price for the pool
+    pub async fn get_current_price(&self) -> Result<f64> {
+        let active_bin = self.dlmm_client.get_active_bin(self.config.pool_address).await?;
+        Ok(active_bin.price)
+    }
+
+    /// Remove the whole position, claim outstanding rewards and optionally sweep funds to a safe wallet
+    pub async fn emergency_withdraw(&self, safe_address: Option<Pubkey>) -> Result<EmergencyWithdrawResult> {
+        let pool_address = self.config.pool_address;
+        let user_pubkey = self.wallet.pubkey();
+
+        warn!("🚨 Emergency withdrawal for pool {}", pool_address);
+
+        let mut result = EmergencyWithdrawResult {
+            safe_address: safe_address.map(|address| address.to_string()),
+            ..Default::default()
+        };
+        let mut sent = TransactionLog::default();
+
+        // What the position holds before it is emptied, so the ledger can record what comes back
+        let holdings = match self.position_holdings().await {
+            Ok(holdings) => Some(holdings),
+            Err(e) => {
+                warn!("⚠️ Could not read position {} for the ledger: {}", pool_address, e);
+                None
             }
-            StrategyType::Staking => {
-                self.reinvest_staking(reinvest_amount).await?
+        };
+
+        // Step 1: Pull all liquidity out of the position - this must succeed
+        let withdraw_transaction = match self.config.strategy_type {
+            StrategyType::LP => self.dlmm_client.remove_all_liquidity_tx(&pool_address, &user_pubkey).await?,
+            StrategyType::Staking => self.dlmm_client.unstake_all_tx(&pool_address, &user_pubkey).await?,
+            StrategyType::Farming => self.dlmm_client.withdraw_farm_tx(&pool_address, &user_pubkey).await?,
+        };
+        let signature = self.submit_transaction(&withdraw_transaction, &mut sent).await?;
+        info!("✅ Position withdrawn: {}", signature);
+        if let Some((pool_info, position)) = &holdings {
+            self.record_event(&mut sent, LedgerEventKind::Withdrawal, &pool_info.token_a_mint, position.token_a_amount, &signature).await;
+            self.record_event(&mut sent, LedgerEventKind::Withdrawal, &pool_info.token_b_mint, position.token_b_amount, &signature).await;
+        }
+        result.withdraw_signature = Some(signature);
+
+        // Step 2: Claim outstanding rewards (best effort)
+        match self.harvest_rewards(&mut sent).await {
+            Ok(signature) => {
+                info!("✅ Outstanding rewards claimed: {}", signature);
+                if let Some((_, position)) = &holdings {
+                    self.record_event(&mut sent, LedgerEventKind::Harvest, &position.reward_mint, position.pending_rewards, &signature).await;
+                }
+                result.claim_signature = Some(signature);
             }
-            StrategyType::Farming => {
-                self.reinvest_farming(reinvest_amount, self.config.max_slippage.unwrap_or(1.0)).await?
+            Err(e) => {
+                error!("❌ Failed to claim rewards during emergency withdrawal: {}", e);
+                result.errors.push(format!("Claim rewards failed: {}", e));
             }
-        };
+        }
 
-        info!("✅ Reinvestment completed: {}", reinvest_signature);
+        // Step 3: Move pool tokens and rewards to the safe address (best effort)
+        if let Some(safe_address) = safe_address {
+            match self.sweep_to(safe_address, &mut sent).await {
+                Ok(signature) => {
+                    info!("✅ Funds moved to safe address {}: {}", safe_address, signature);
+                    result.transfer_signature = Some(signature);
+                }
+                Err(e) => {
+                    error!("❌ Failed to move funds to safe address {}: {}", safe_address, e);
+                    result.errors.push(format!("Transfer to safe address failed: {}", e));
+                }
+            }
+        }
 
-        // Step 7: Get updated position
-        let new_position = self.get_current_position().await?;
-        let gas_used = gas_check.estimated_gas_cost;
+        result.gas_used = sent.total_fees();
+        result.ledger = self.ledger_entries(&sent).await;
+        Ok(result)
+    }
 
-        // Send success notification
-        self.notification_service.send_notification(NotificationEvent {
-            event_type: NotificationEventType::CompoundSuccess,
-            pool_address: pool_key,
-            message: format!("Compound successful: harvested {:.6}, reinvested {:.6}", pending_rewards, reinvest_amount),
-            data: serde_json::json!({
-                "rewards_harvested": pending_rewards,
-                "amount_reinvested": reinvest_amount,
-                "new_position": new_position.lp_token_amount,
-                "gas_used": gas_used,
-                "harvest_signature": harvest_signature,
-                "reinvest_signature": reinvest_signature,
-            }),
-            timestamp: Utc::now(),
-        }).await;
+    /// The pool and the user's position in it
+    async fn position_holdings(&self) -> Result<(PoolInfo, UserPosition)> {
+        let pool_info = self.dlmm_client.get_pool_info(&self.config.pool_address).await?;
+        let position = self.dlmm_client.get_user_position(&self.config.pool_address, &self.wallet.pubkey()).await?;
+        Ok((pool_info, position))
+    }
 
-        Ok(CompoundResult {
-            success: true,
-            rewards_harvested: pending_rewards,
-            amount_reinvested: reinvest_amount,
-            new_position_value: new_position.lp_token_amount,
-            gas_used,
-            transaction_signature: reinvest_signature,
-            timestamp: Utc::now(),
-            error: None,
-        })
+    /// Transfer the pool tokens and reward token balances to another wallet
+    async fn sweep_to(&self, destination: Pubkey, sent: &mut TransactionLog) -> Result<String> {
+        let pool_info = self.dlmm_client.get_pool_info(&self.config.pool_address).await?;
+        let user_position = self.dlmm_client.get_user_position(
+            &self.config.pool_address,
+            &self.wallet.pubkey(),
+        ).await?;
+
+        let mut mints = vec![pool_info.token_a_mint, pool_info.token_b_mint];
+        if !mints.contains(&user_position.reward_mint) {
+            mints.push(user_position.reward_mint);
+        }
+
+        let transaction = self.dlmm_client.transfer_tokens_tx(&self.wallet.pubkey(), &destination, &mints).await?;
+        self.submit_transaction(&transaction, sent).await
+    }
+
+    /// Send a transaction, or simulate it without sending in dry-run mode, logging the fee it
+    /// paid (or would pay, when simulated)
+    async fn submit_transaction(&self, transaction: &Transaction, sent: &mut TransactionLog) -> Result<String> {
+        if self.dry_run {
+            let simulation = self.dlmm_client.simulate_transaction(transaction).await?.into_result()?;
+
+            let signature = format!("dry_run_{}", uuid::Uuid::new_v4().simple());
+            info!("🧪 Dry run: simulated transaction {} ({:?} compute units)",
+                  signature, simulation.compute_units_consumed);
+            sent.record_fee(&signature, self.gas_optimizer.estimate_transaction_fee(self.config.pool_address).await?);
+            return Ok(signature);
+        }
+
+        let transaction = self.gas_optimizer
+            .prepare_transaction(transaction, &self.wallet.pubkey(), self.config.pool_address)
+            .await?;
+
+        let outcome = self.transaction_sender
+            .send_transaction(&transaction, &[self.wallet.as_ref()])
+            .await?;
+
+        // A transaction that landed pays its fee even when it failed on-chain
+        let signature = outcome.signature.to_string();
+        sent.record_fee(&signature, outcome.fee_sol().unwrap_or(BASE_TRANSACTION_FEE));
+        outcome.into_result()?;
+
+        Ok(signature)
     }
 
     /// Get current position for the pool
@@ -217,18 +483,8 @@ impl CompoundStrategy {
         })
     }
 
-    /// Get pending rewards for the position
-    async fn get_pending_rewards(&self) -> Result<f64> {
-        let user_position = self.dlmm_client.get_user_position(
-            &self.config.pool_address,
-            &self.wallet.pubkey(),
-        ).await?;
-
-        Ok(user_position.pending_rewards)
-    }
-
     /// Harvest rewards from the pool
-    async fn harvest_rewards(&self) -> Result<String> {
+    async fn harvest_rewards(&self, sent: &mut TransactionLog) -> Result<String> {
         info!("🌾 Harvesting rewards from pool {}", self.config.pool_address);
 
         let transaction = self.dlmm_client.claim_rewards(
@@ -236,29 +492,181 @@ impl CompoundStrategy {
             &self.wallet.pubkey(),
         ).await?;
 
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        
-        Ok(signature.to_string())
+        self.submit_transaction(&transaction, sent).await
     }
 
-    /// Reinvest into LP position
-    async fn reinvest_lp(&self, amount: f64, max_slippage: f64) -> Result<String> {
-        info!("🔄 Reinvesting {:.6} tokens into LP position", amount);
-
-        let transaction = self.dlmm_client.add_liquidity_tx(
+    /// Reinvest into LP position, converting reward tokens into the position's X/Y ratio.
+    /// Swaps that land are kept in `swapped`, and a retry deposits their output instead of
+    /// swapping again.
+    async fn reinvest_lp(
+        &self,
+        amount: f64,
+        max_slippage: f64,
+        sent: &mut TransactionLog,
+        swapped: &mut Option<ZapCost>,
+    ) -> Result<(String, ZapCost)> {
+        info!("🔄 Reinvesting {:.6} reward tokens into LP position", amount);
+
+        let pool = self.dlmm_client.get_pool(self.config.pool_address).await?;
+        let user_position = self.dlmm_client.get_user_position(
             &self.config.pool_address,
             &self.wallet.pubkey(),
-            amount,
+        ).await?;
+
+        let zap_cost = match swapped {
+            Some(zap_cost) => {
+                info!(
+                    "↩️ Resuming deposit of {:.6} X and {:.6} Y already swapped",
+                    zap_cost.amount_x, zap_cost.amount_y
+                );
+                zap_cost.clone()
+            }
+            None => {
+                let zap_cost = self.zap_rewards(&pool, &user_position, amount, max_slippage, sent).await?;
+                swapped.insert(zap_cost).clone()
+            }
+        };
+
+        // Swapped tokens stay in the wallet if the deposit fails; name them so they can be recovered
+        let stranded = || format!(
+            "adding liquidity failed, {:.6} {} and {:.6} {} remain in the wallet",
+            zap_cost.amount_x, pool.token_x, zap_cost.amount_y, pool.token_y
+        );
+        let transaction = self.dlmm_client.add_liquidity_to_position_tx(
+            &user_position,
+            zap_cost.amount_x,
+            zap_cost.amount_y,
             max_slippage,
+        ).await.with_context(stranded)?;
+
+        let signature = self.submit_transaction(&transaction, sent).await.with_context(stranded)?;
+
+        // The swapped tokens (or rewards already in the pair) go into the position
+        self.record_event(sent, LedgerEventKind::Reinvestment, &pool.token_x, -zap_cost.amount_x, &signature).await;
+        self.record_event(sent, LedgerEventKind::Reinvestment, &pool.token_y, -zap_cost.amount_y, &signature).await;
+
+        Ok((signature, zap_cost))
+    }
+
+    /// Swap reward tokens into X and Y in the position's composition around the active bin
+    async fn zap_rewards(
+        &self,
+        pool: &DLMMPoolInfo,
+        user_position: &UserPosition,
+        amount: f64,
+        max_slippage: f64,
+        sent: &mut TransactionLog,
+    ) -> Result<ZapCost> {
+
+        // Split the reward amount to match the position's composition around the active bin
+        let (x_share, y_share) = bin_math::get_range_composition(
+            user_position.lower_bin_id,
+            user_position.upper_bin_id,
+            pool.active_bin_id,
+        );
+        if x_share + y_share <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Invalid position bin range: [{}, {}]",
+                user_position.lower_bin_id, user_position.upper_bin_id
+            ));
+        }
+
+        let reward_to_x = amount * x_share;
+        let reward_to_y = amount * y_share;
+
+        info!(
+            "🎯 Target split for bins [{}, {}] at active bin {}: {:.1}% X / {:.1}% Y",
+            user_position.lower_bin_id, user_position.upper_bin_id, pool.active_bin_id,
+            x_share * 100.0, y_share * 100.0
+        );
+
+        let mut zap_cost = ZapCost {
+            reward_to_x,
+            reward_to_y,
+            ..Default::default()
+        };
+
+        zap_cost.amount_x = self.swap_reward_into(
+            &user_position.reward_mint, &pool.token_x, reward_to_x, max_slippage, &mut zap_cost, sent,
         ).await?;
+        zap_cost.amount_y = match self.swap_reward_into(
+            &user_position.reward_mint, &pool.token_y, reward_to_y, max_slippage, &mut zap_cost, sent,
+        ).await {
+            Ok(amount_y) => amount_y,
+            // X is already in the wallet: deposit it alone rather than leave it stranded
+            Err(e) if zap_cost.amount_x > 0.0 => {
+                warn!(
+                    "⚠️ Swapping rewards into Y failed after the X swap landed, adding {:.6} X alone and keeping {:.6} rewards: {:#}",
+                    zap_cost.amount_x, reward_to_y, e
+                );
+                zap_cost.unswapped_reward = reward_to_y;
+                0.0
+            }
+            Err(e) => return Err(e),
+        };
+        zap_cost.total_cost = zap_cost.swap_fees + zap_cost.price_impact_cost;
+
+        info!(
+            "💱 Zap cost: {:.6} fees + {:.6} price impact = {:.6} reward tokens",
+            zap_cost.swap_fees, zap_cost.price_impact_cost, zap_cost.total_cost
+        );
+
+        Ok(zap_cost)
+    }
+
+    /// Swap reward tokens into one side of the pair, returning the output amount
+    async fn swap_reward_into(
+        &self,
+        reward_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: f64,
+        max_slippage: f64,
+        zap_cost: &mut ZapCost,
+        sent: &mut TransactionLog,
+    ) -> Result<f64> {
+        if amount <= 0.0 {
+            return Ok(0.0);
+        }
+
+        // Rewards already paid in this side of the pair need no conversion
+        if reward_mint == output_mint {
+            return Ok(amount);
+        }
+
+        let quote = self.dlmm_client.get_swap_quote(reward_mint, output_mint, amount).await?;
+
+        if quote.price_impact > max_slippage {
+            return Err(anyhow::anyhow!(
+                "Price impact too high swapping rewards into {}: {:.2}% > {:.2}%",
+                output_mint, quote.price_impact, max_slippage
+            ));
+        }
 
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        
-        Ok(signature.to_string())
+        let minimum_amount_out = quote.amount_out * (1.0 - max_slippage / 100.0);
+        let transaction = self.dlmm_client.swap_tx(&quote, &self.wallet.pubkey(), minimum_amount_out).await?;
+        let signature = self.submit_transaction(&transaction, sent).await?;
+
+        info!("💱 Swapped {:.6} rewards -> {:.6} {} ({})", amount, quote.amount_out, output_mint, signature);
+
+        // The swap disposes of rewards and gives the output its cost basis
+        match tokio::try_join!(
+            self.dlmm_client.get_token_price_usd(reward_mint),
+            self.dlmm_client.get_token_price_usd(output_mint),
+        ) {
+            Ok((input_price, output_price)) => {
+                sent.entries.extend(quote_swap_entries(&quote, &signature, Utc::now(), input_price, output_price));
+            }
+            Err(e) => warn!("⚠️ Could not value swap {} for the ledger: {}", signature, e),
+        }
+
+        zap_cost.swap_fees += quote.fee;
+        zap_cost.price_impact_cost += price_impact_cost(amount, quote.fee, quote.price_impact);
+
+        Ok(quote.amount_out)
     }
 
     /// Reinvest into staking position
-    async fn reinvest_staking(&self, amount: f64) -> Result<String> {
+    async fn reinvest_staking(&self, amount: f64, sent: &mut TransactionLog) -> Result<String> {
         info!("🥩 Reinvesting {:.6} tokens into staking", amount);
 
         let transaction = self.dlmm_client.stake_tokens(
@@ -267,13 +675,11 @@ impl CompoundStrategy {
             amount,
         ).await?;
 
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        
-        Ok(signature.to_string())
+        self.submit_transaction(&transaction, sent).await
     }
 
     /// Reinvest into farming position
-    async fn reinvest_farming(&self, amount: f64, max_slippage: f64) -> Result<String> {
+    async fn reinvest_farming(&self, amount: f64, max_slippage: f64, sent: &mut TransactionLog) -> Result<String> {
         info!("🚜 Reinvesting {:.6} tokens into farming", amount);
 
         let transaction = self.dlmm_client.deposit_farm(
@@ -283,8 +689,64 @@ impl CompoundStrategy {
             max_slippage,
         ).await?;
 
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        
-        Ok(signature.to_string())
+        self.submit_transaction(&transaction, sent).await
+    }
+}
+
+/// Steps of a compound that have landed, so a retry resumes at the step that failed
+#[derive(Debug, Default)]
+struct CompoundProgress {
+    harvest: Option<HarvestedRewards>,
+    /// Rewards swapped into the pair for an LP reinvestment
+    zap: Option<ZapCost>,
+}
+
+/// Rewards claimed by a compound
+#[derive(Debug, Clone)]
+struct HarvestedRewards {
+    amount: f64,
+    reward_mint: Pubkey,
+    signature: String,
+    /// Gas estimate the compound went ahead on
+    estimated_gas_cost: f64,
+}
+
+enum HarvestOutcome {
+    Harvested(HarvestedRewards),
+    /// The gas or reward threshold check said to wait
+    Skipped(CompoundResult),
+}
+
+/// Transactions sent for one operation: the network fee each paid (SOL) and the ledger
+/// entries for the events they realised
+#[derive(Debug, Default)]
+struct TransactionLog {
+    fees: Vec<(String, f64)>,
+    entries: Vec<LedgerEntry>,
+}
+
+impl TransactionLog {
+    fn record_fee(&mut self, signature: &str, fee: f64) {
+        self.fees.push((signature.to_string(), fee));
     }
-}
\ No newline at end of file
+
+    fn total_fees(&self) -> f64 {
+        self.fees.iter().map(|(_, fee)| fee).sum()
+    }
+}
+
+/// Split harvested rewards into the amount to reinvest and the amount to keep
+pub fn split_rewards(pending_rewards: f64, reinvest_percentage: u8) -> (f64, f64) {
+    let reinvest_amount = (pending_rewards * reinvest_percentage as f64) / 100.0;
+    (reinvest_amount, pending_rewards - reinvest_amount)
+}
+
+/// Value lost to price impact when swapping `amount_in` after paying `fee`
+pub fn price_impact_cost(amount_in: f64, fee: f64, price_impact_percent: f64) -> f64 {
+    (amount_in - fee) * price_impact_percent / 100.0
+}
+
+/// Convert a token amount to a decimal for the ledger
+fn to_decimal(amount: f64) -> Decimal {
+    Decimal::from_f64(amount).unwrap_or_default()
+}
@@ -0,0 +1,438 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, fs, path::PathBuf};
+use tokio::sync::Mutex;
+
+use crate::types::{BudgetLimits, GasBudgetConfig, StrategyKey};
+
+/// Spends older than the longest budget window are dropped from the ledger
+const LEDGER_RETENTION_DAYS: i64 = 7;
+
+/// Tolerance when comparing fee totals (SOL)
+const RECONCILE_TOLERANCE: f64 = 1e-9;
+
+/// What a budget applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetScope {
+    Strategy(StrategyKey),
+    Wallet(String),
+    Global,
+}
+
+impl BudgetScope {
+    /// Whether spend by the given strategy counts against this scope
+    fn covers(&self, key: &StrategyKey) -> bool {
+        match self {
+            BudgetScope::Strategy(strategy) => strategy == key,
+            BudgetScope::Wallet(wallet) => *wallet == key.wallet,
+            BudgetScope::Global => true,
+        }
+    }
+}
+
+impl fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetScope::Strategy(key) => write!(f, "strategy {}", key),
+            BudgetScope::Wallet(wallet) => write!(f, "wallet {}", wallet),
+            BudgetScope::Global => write!(f, "global"),
+        }
+    }
+}
+
+/// Rolling window a budget limit covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetPeriod {
+    Day,
+    Week,
+}
+
+impl BudgetPeriod {
+    pub const ALL: [BudgetPeriod; 2] = [BudgetPeriod::Day, BudgetPeriod::Week];
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            BudgetPeriod::Day => Duration::days(1),
+            BudgetPeriod::Week => Duration::days(7),
+        }
+    }
+
+    fn limit(&self, limits: &BudgetLimits) -> Option<f64> {
+        match self {
+            BudgetPeriod::Day => limits.daily,
+            BudgetPeriod::Week => limits.weekly,
+        }
+    }
+}
+
+impl fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetPeriod::Day => write!(f, "daily"),
+            BudgetPeriod::Week => write!(f, "weekly"),
+        }
+    }
+}
+
+/// A budget the next compound would overrun
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetExceeded {
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    pub spent: f64,
+    pub limit: f64,
+    pub estimated_cost: f64,
+    /// When enough earlier spend leaves the window for the compound to fit
+    pub resumes_at: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} gas budget exhausted: {:.6} of {:.6} SOL spent, next compound needs {:.6} SOL",
+            self.scope, self.period, self.spent, self.limit, self.estimated_cost
+        )
+    }
+}
+
+/// Spend against one budget window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    pub spent: f64,
+    pub limit: Option<f64>,
+    pub remaining: Option<f64>,
+}
+
+/// Fees seen by the statistics compared against the budget ledger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasReconciliation {
+    /// `total_gas_spent` reported by the statistics for this run
+    pub statistics_total: f64,
+    /// Fees the ledger recorded during this run
+    pub ledger_session_total: f64,
+    /// Fees the ledger has recorded across all runs
+    pub ledger_lifetime_total: f64,
+    pub difference: f64,
+}
+
+impl GasReconciliation {
+    pub fn is_consistent(&self) -> bool {
+        self.difference.abs() < RECONCILE_TOLERANCE
+    }
+}
+
+/// A fee paid by a strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasSpend {
+    pub key: StrategyKey,
+    pub amount: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Persisted record of recent fee spend
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GasLedger {
+    spends: Vec<GasSpend>,
+    lifetime_spent: f64,
+}
+
+impl GasLedger {
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::days(LEDGER_RETENTION_DAYS);
+        self.spends.retain(|spend| spend.timestamp >= cutoff);
+    }
+
+    fn spent(&self, scope: &BudgetScope, since: DateTime<Utc>) -> f64 {
+        self.spends
+            .iter()
+            .filter(|spend| spend.timestamp >= since && scope.covers(&spend.key))
+            .map(|spend| spend.amount)
+            .sum()
+    }
+
+    /// Earliest time at which `cost` fits under `limit` again, or None if it never will
+    fn resumes_at(
+        &self,
+        scope: &BudgetScope,
+        period: BudgetPeriod,
+        limit: f64,
+        cost: f64,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if cost > limit {
+            return None;
+        }
+
+        let since = now - period.duration();
+        let mut remaining = self.spent(scope, since);
+        for spend in self.spends.iter().filter(|spend| spend.timestamp >= since && scope.covers(&spend.key)) {
+            remaining -= spend.amount;
+            if remaining + cost <= limit {
+                return Some(spend.timestamp + period.duration());
+            }
+        }
+
+        None
+    }
+}
+
+struct BudgetState {
+    ledger: GasLedger,
+    session_spent: f64,
+}
+
+/// Enforces per-strategy, per-wallet and global fee budgets over rolling windows
+pub struct GasBudget {
+    config: GasBudgetConfig,
+    wallet_limits: HashMap<String, BudgetLimits>,
+    state: Mutex<BudgetState>,
+}
+
+impl GasBudget {
+    /// Create the budget, restoring earlier spend from the state file if there is one
+    pub fn load(config: GasBudgetConfig, wallet_limits: HashMap<String, BudgetLimits>) -> Result<Self> {
+        let mut ledger = match &config.state_file {
+            Some(path) if path.exists() => {
+                let ledger: GasLedger = serde_json::from_str(&fs::read_to_string(path)?)
+                    .map_err(|e| anyhow::anyhow!("Invalid gas budget state in {}: {}", path.display(), e))?;
+                info!("⛽ Restored gas budget ledger from {} ({} recent spends)", path.display(), ledger.spends.len());
+                ledger
+            }
+            _ => GasLedger::default(),
+        };
+        ledger.spends.sort_by_key(|spend| spend.timestamp);
+        ledger.prune(Utc::now());
+
+        Ok(Self {
+            config,
+            wallet_limits,
+            state: Mutex::new(BudgetState {
+                ledger,
+                session_spent: 0.0,
+            }),
+        })
+    }
+
+    /// Limits that apply to a scope; `strategy_limits` are the strategy's own overrides
+    fn limits(&self, scope: &BudgetScope, strategy_limits: BudgetLimits) -> BudgetLimits {
+        match scope {
+            BudgetScope::Strategy(_) => strategy_limits.or(self.config.strategy),
+            BudgetScope::Wallet(wallet) => self.wallet_limits.get(wallet).copied().unwrap_or_default(),
+            BudgetScope::Global => self.config.global,
+        }
+    }
+
+    /// Scopes a strategy's spend counts against, narrowest first
+    fn scopes(key: &StrategyKey) -> [BudgetScope; 3] {
+        [
+            BudgetScope::Strategy(key.clone()),
+            BudgetScope::Wallet(key.wallet.clone()),
+            BudgetScope::Global,
+        ]
+    }
+
+    /// Check whether a compound costing `estimated_cost` fits every budget covering the strategy
+    pub async fn check(
+        &self,
+        key: &StrategyKey,
+        strategy_limits: BudgetLimits,
+        estimated_cost: f64,
+    ) -> Option<BudgetExceeded> {
+        let now = Utc::now();
+        let state = self.state.lock().await;
+
+        for scope in Self::scopes(key) {
+            let limits = self.limits(&scope, strategy_limits);
+            for period in BudgetPeriod::ALL {
+                let Some(limit) = period.limit(&limits) else {
+                    continue;
+                };
+
+                let spent = state.ledger.spent(&scope, now - period.duration());
+                if spent + estimated_cost > limit {
+                    let resumes_at = state.ledger.resumes_at(&scope, period, limit, estimated_cost, now);
+                    return Some(BudgetExceeded {
+                        scope,
+                        period,
+                        spent,
+                        limit,
+                        estimated_cost,
+                        resumes_at,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Record a fee paid by a strategy and persist the ledger
+    pub async fn record(&self, key: &StrategyKey, amount: f64, timestamp: DateTime<Utc>) -> Result<()> {
+        if amount <= 0.0 {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().await;
+        state.ledger.spends.push(GasSpend {
+            key: key.clone(),
+            amount,
+            timestamp,
+        });
+        state.ledger.lifetime_spent += amount;
+        state.session_spent += amount;
+        state.ledger.prune(Utc::now());
+
+        if let Some(path) = &self.config.state_file {
+            Self::persist(path, &state.ledger)?;
+        }
+
+        Ok(())
+    }
+
+    /// Spend against each window of a scope's budget
+    pub async fn usage(&self, scope: &BudgetScope, strategy_limits: BudgetLimits) -> Vec<BudgetUsage> {
+        let now = Utc::now();
+        let state = self.state.lock().await;
+        let limits = self.limits(scope, strategy_limits);
+
+        BudgetPeriod::ALL
+            .iter()
+            .map(|&period| {
+                let spent = state.ledger.spent(scope, now - period.duration());
+                let limit = period.limit(&limits);
+                BudgetUsage {
+                    scope: scope.clone(),
+                    period,
+                    spent,
+                    limit,
+                    remaining: limit.map(|limit| (limit - spent).max(0.0)),
+                }
+            })
+            .collect()
+    }
+
+    /// Fees recorded for a scope over a window
+    pub async fn spent(&self, scope: &BudgetScope, period: BudgetPeriod) -> f64 {
+        let state = self.state.lock().await;
+        state.ledger.spent(scope, Utc::now() - period.duration())
+    }
+
+    /// Compare the statistics' fee total for this run against the ledger
+    pub async fn reconcile(&self, statistics_total: f64) -> GasReconciliation {
+        let state = self.state.lock().await;
+        let reconciliation = GasReconciliation {
+            statistics_total,
+            ledger_session_total: state.session_spent,
+            ledger_lifetime_total: state.ledger.lifetime_spent,
+            difference: statistics_total - state.session_spent,
+        };
+
+        if !reconciliation.is_consistent() {
+            warn!("⚠️ Gas spend mismatch: statistics report {:.9} SOL, budget ledger recorded {:.9} SOL",
+                  reconciliation.statistics_total, reconciliation.ledger_session_total);
+        }
+
+        reconciliation
+    }
+
+    /// Write the ledger atomically so a crash never leaves a truncated file
+    fn persist(path: &PathBuf, ledger: &GasLedger) -> Result<()> {
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(ledger)?)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn budget(config: GasBudgetConfig, wallets: &[(&str, BudgetLimits)]) -> GasBudget {
+        let wallet_limits = wallets.iter().map(|(name, limits)| (name.to_string(), *limits)).collect();
+        GasBudget::load(config, wallet_limits).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_strategy_and_global_limits() {
+        let config = GasBudgetConfig {
+            strategy: BudgetLimits { daily: Some(0.01), weekly: None },
+            global: BudgetLimits { daily: None, weekly: Some(0.015) },
+            ..Default::default()
+        };
+        let budget = budget(config, &[]);
+        let pool_a = StrategyKey::new("default", Pubkey::new_unique());
+        let pool_b = StrategyKey::new("default", Pubkey::new_unique());
+
+        budget.record(&pool_a, 0.008, Utc::now()).await.unwrap();
+
+        // The strategy's own daily limit stops pool A
+        let exceeded = budget.check(&pool_a, BudgetLimits::default(), 0.003).await.unwrap();
+        assert_eq!(exceeded.scope, BudgetScope::Strategy(pool_a.clone()));
+        assert_eq!(exceeded.period, BudgetPeriod::Day);
+
+        // A per-strategy override takes precedence over the default
+        let generous = BudgetLimits { daily: Some(0.05), weekly: None };
+        assert!(budget.check(&pool_a, generous, 0.003).await.is_none());
+
+        // Pool B has its own strategy budget but shares the global weekly cap
+        assert!(budget.check(&pool_b, BudgetLimits::default(), 0.005).await.is_none());
+        let exceeded = budget.check(&pool_b, BudgetLimits::default(), 0.008).await.unwrap();
+        assert_eq!(exceeded.scope, BudgetScope::Global);
+        assert_eq!(exceeded.period, BudgetPeriod::Week);
+    }
+
+    #[tokio::test]
+    async fn test_wallet_budget_resumes_when_spend_ages_out() {
+        let limits = BudgetLimits { daily: Some(0.05), weekly: None };
+        let budget = budget(GasBudgetConfig::default(), &[("treasury", limits)]);
+        let key = StrategyKey::new("treasury", Pubkey::new_unique());
+        let other = StrategyKey::new("market-maker", Pubkey::new_unique());
+
+        let first = Utc::now() - Duration::hours(20);
+        budget.record(&key, 0.03, first).await.unwrap();
+        budget.record(&key, 0.02, Utc::now() - Duration::hours(2)).await.unwrap();
+        budget.record(&key, 0.5, Utc::now() - Duration::hours(30)).await.unwrap();
+
+        let exceeded = budget.check(&key, BudgetLimits::default(), 0.01).await.unwrap();
+        assert_eq!(exceeded.scope, BudgetScope::Wallet("treasury".to_string()));
+        assert!((exceeded.spent - 0.05).abs() < 1e-12);
+        assert_eq!(exceeded.resumes_at, Some(first + Duration::days(1)));
+
+        // Other wallets are unaffected
+        assert!(budget.check(&other, BudgetLimits::default(), 0.01).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ledger_persists_across_restarts() {
+        let path = std::env::temp_dir().join(format!("gas_budget_{}.json", uuid::Uuid::new_v4().simple()));
+        let config = GasBudgetConfig {
+            global: BudgetLimits { daily: Some(0.01), weekly: None },
+            state_file: Some(path.clone()),
+            ..Default::default()
+        };
+        let key = StrategyKey::new("default", Pubkey::new_unique());
+
+        let budget = budget(config.clone(), &[]);
+        budget.record(&key, 0.004, Utc::now()).await.unwrap();
+        budget.record(&key, 0.004, Utc::now()).await.unwrap();
+        assert!(budget.reconcile(0.008).await.is_consistent());
+
+        let restarted = GasBudget::load(config, HashMap::new()).unwrap();
+        assert!((restarted.spent(&BudgetScope::Global, BudgetPeriod::Day).await - 0.008).abs() < 1e-12);
+        assert!(restarted.check(&key, BudgetLimits::default(), 0.004).await.is_some());
+
+        // Spend from earlier runs is not part of this run's statistics
+        let reconciliation = restarted.reconcile(0.0).await;
+        assert!(reconciliation.is_consistent());
+        assert!((reconciliation.ledger_lifetime_total - 0.008).abs() < 1e-12);
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,143 +1,148 @@
 use anyhow::Result;
 use log::{info, warn};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
 use std::sync::Arc;
 
-use saros_dlmm_sdk::DLMMClient;
+use saros_dlmm_sdk::{
+    bin_math, DLMMClient, PriorityFeeConfig, PriorityFeeEstimate, PriorityFeeEstimator, PriorityLevel, RpcConnection,
+};
 
-use crate::types::GasOptimizationResult;
+use crate::types::{GasOptimizationResult, StrategyType};
+
+/// Base network fee per transaction that the compound cost estimate is calibrated against (SOL)
+pub const BASE_TRANSACTION_FEE: f64 = 0.000005;
+
+/// Default for `MAX_GAS_PRICE`: per-transaction fees above this defer compounding (SOL)
+pub const DEFAULT_MAX_GAS_PRICE: f64 = 0.01;
 
 /// Gas optimization component that determines optimal timing for compound operations
 pub struct GasOptimizer {
-    rpc_client: Arc<RpcClient>,
     dlmm_client: DLMMClient,
+    max_gas_price: f64,
+    fee_estimator: PriorityFeeEstimator,
+    priority_level: PriorityLevel,
 }
 
 impl GasOptimizer {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
-        let dlmm_client = DLMMClient::new_with_rpc(rpc_client.clone());
+    pub fn new(
+        rpc_client: Arc<RpcConnection>,
+        max_gas_price: f64,
+        priority_level: PriorityLevel,
+        max_priority_fee_lamports: Option<u64>,
+    ) -> Self {
+        let dlmm_client = DLMMClient::new_with_rpc(rpc_client);
+        let fee_estimator = dlmm_client.priority_fee_estimator(PriorityFeeConfig {
+            max_fee_lamports: max_priority_fee_lamports,
+            ..Default::default()
+        });
 
         Self {
-            rpc_client,
             dlmm_client,
+            max_gas_price,
+            fee_estimator,
+            priority_level,
         }
     }
 
-    /// Determine if compound operation should proceed based on gas costs and rewards
+    /// Determine if compound operation should proceed based on gas costs and the rewards
+    /// `wallet` has pending in the pool
     pub async fn should_compound(
         &self,
         pool_address: Pubkey,
+        wallet: Pubkey,
+        strategy_type: StrategyType,
         min_reward_threshold: f64,
     ) -> Result<GasOptimizationResult> {
-        // Get current gas price
-        let gas_price = self.get_current_gas_price().await?;
+        // Fee of a single transaction, priority fee included
+        let transaction_fee = self.estimate_transaction_fee(pool_address).await?;
         
         // Estimate gas cost for compound operation
-        let estimated_gas_cost = self.estimate_compound_gas_cost(pool_address).await?;
+        let estimated_gas_cost = self.estimate_compound_gas_cost(pool_address, wallet, strategy_type).await?;
         
         // Get pending rewards
-        let pending_rewards = self.get_pending_rewards(pool_address).await?;
+        let pending_rewards = self.get_pending_rewards(pool_address, wallet).await?;
 
-        info!("⛽ Current gas price: {:.6} SOL", gas_price);
+        info!("⛽ Current transaction fee: {:.6} SOL", transaction_fee);
         info!("📊 Estimated gas cost: {:.6} SOL", estimated_gas_cost);
         info!("🎁 Pending rewards: {:.6} tokens", pending_rewards);
 
-        // Check if rewards justify gas costs (assuming 1:1 token to SOL ratio for simplicity)
-        let profit_threshold = estimated_gas_cost * 2.0; // Require 2x gas cost in rewards
-        let gas_efficient = pending_rewards >= profit_threshold;
-
-        // Check minimum reward threshold
-        let meets_min_threshold = pending_rewards >= min_reward_threshold;
-
-        // Check if gas price is reasonable (under 0.1 SOL)
-        let reasonable_gas_price = gas_price < 0.1;
-
-        let should_proceed = gas_efficient && meets_min_threshold && reasonable_gas_price;
-
-        let reason = if !meets_min_threshold {
-            format!("Rewards below minimum threshold: {:.6} < {:.6}", pending_rewards, min_reward_threshold)
-        } else if !gas_efficient {
-            format!("Rewards don't justify gas costs: {:.6} < {:.6} (2x gas cost)", pending_rewards, profit_threshold)
-        } else if !reasonable_gas_price {
-            format!("Gas price too high: {:.6} SOL", gas_price)
-        } else {
-            "Optimal conditions for compounding".to_string()
-        };
+        // Assuming 1:1 token to SOL ratio for simplicity
+        let result = evaluate_compound(
+            transaction_fee,
+            estimated_gas_cost,
+            pending_rewards,
+            pending_rewards,
+            min_reward_threshold,
+            self.max_gas_price,
+        );
 
-        if should_proceed {
-            info!("✅ Gas optimization: {}", reason);
+        if result.should_proceed {
+            info!("✅ Gas optimization: {}", result.reason);
         } else {
-            warn!("⚠️ Gas optimization: {}", reason);
+            warn!("⚠️ Gas optimization: {}", result.reason);
         }
 
-        Ok(GasOptimizationResult {
-            should_proceed,
-            recommended_gas_price: gas_price,
-            estimated_gas_cost,
-            reason,
-        })
-    }
-
-    /// Get current gas price from the network
-    pub async fn get_current_gas_price(&self) -> Result<f64> {
-        // Get recent blockhash and fee calculator
-        let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
-        
-        // In Solana, fees are relatively stable, but we can get fee rate
-        match self.rpc_client.get_fee_for_message(&solana_sdk::message::Message::new_with_blockhash(
-            &[],
-            None,
-            &recent_blockhash,
-        )) {
-            Ok(fee) => Ok(fee as f64 / 1_000_000_000.0), // Convert lamports to SOL
-            Err(_) => {
-                // Fallback to average fee
-                warn!("Could not get current fee, using default");
-                Ok(0.000005) // 5000 lamports = 0.000005 SOL
-            }
-        }
+        Ok(result)
     }
 
-    /// Estimate gas cost for compound operation
-    async fn estimate_compound_gas_cost(&self, pool_address: Pubkey) -> Result<f64> {
-        // Simulate compound operation to estimate gas
-        // This is a simplified estimation - in production you'd simulate the actual transactions
-        
-        let base_fee = 0.000005; // Base transaction fee in SOL
-        let harvest_fee = 0.000010; // Fee for harvest transaction
-        let reinvest_fee = 0.000015; // Fee for reinvest transaction
-        
-        let total_estimated_fee = base_fee + harvest_fee + reinvest_fee;
+    /// Estimate gas cost for a compound of `wallet`'s position: every transaction the
+    /// strategy sends pays the network fee plus the (capped) priority fee
+    pub async fn estimate_compound_gas_cost(
+        &self,
+        pool_address: Pubkey,
+        wallet: Pubkey,
+        strategy_type: StrategyType,
+    ) -> Result<f64> {
+        let swaps = match strategy_type {
+            StrategyType::LP => self.zap_swap_count(pool_address, wallet).await?,
+            StrategyType::Staking | StrategyType::Farming => 0,
+        };
+        let transactions = compound_transaction_count(strategy_type, swaps);
+        let total_estimated_fee = transactions as f64 * self.estimate_transaction_fee(pool_address).await?;
         
-        info!("📊 Estimated compound gas cost: {:.6} SOL", total_estimated_fee);
+        info!("📊 Estimated compound gas cost: {:.6} SOL ({} transactions)", total_estimated_fee, transactions);
         
         Ok(total_estimated_fee)
     }
 
-    /// Get pending rewards for gas calculation
-    async fn get_pending_rewards(&self, pool_address: Pubkey) -> Result<f64> {
-        // This would need the actual user's pubkey - for now use a placeholder
-        // In practice, this would be passed as a parameter
-        let user_pubkey = solana_sdk::pubkey::Pubkey::new_unique();
-        
-        match self.dlmm_client.get_user_position(&pool_address, &user_pubkey).await {
-            Ok(position) => Ok(position.pending_rewards),
-            Err(_) => {
-                // Return mock pending rewards for estimation
-                Ok(5.0) // Mock value
-            }
-        }
+    /// Swaps an LP reinvestment needs: one into each side of the pair the position's range
+    /// takes, unless the rewards are already paid in that token
+    async fn zap_swap_count(&self, pool_address: Pubkey, wallet: Pubkey) -> Result<u32> {
+        let pool = self.dlmm_client.get_pool(pool_address).await?;
+        let position = self.dlmm_client.get_user_position(&pool_address, &wallet).await?;
+        let (x_share, y_share) =
+            bin_math::get_range_composition(position.lower_bin_id, position.upper_bin_id, pool.active_bin_id);
+
+        let swaps = [(x_share, pool.token_x), (y_share, pool.token_y)]
+            .into_iter()
+            .filter(|(share, mint)| *share > 0.0 && *mint != position.reward_mint)
+            .count();
+        Ok(swaps as u32)
+    }
+
+    /// Estimate the fee of a single transaction in `pool_address`: the network fee plus the
+    /// (capped) priority fee
+    pub async fn estimate_transaction_fee(&self, pool_address: Pubkey) -> Result<f64> {
+        let priority_fee = self.get_priority_fee_recommendation(pool_address).await? as f64 / 1_000_000_000.0;
+        Ok(BASE_TRANSACTION_FEE + priority_fee)
+    }
+
+    /// Get the rewards `wallet` has pending in the pool for gas calculation
+    async fn get_pending_rewards(&self, pool_address: Pubkey, wallet: Pubkey) -> Result<f64> {
+        let position = self.dlmm_client.get_user_position(&pool_address, &wallet).await?;
+        Ok(position.pending_rewards)
     }
 
     /// Calculate optimal compound frequency based on APY and gas costs
     pub async fn calculate_optimal_frequency(
         &self,
         pool_address: Pubkey,
+        wallet: Pubkey,
+        strategy_type: StrategyType,
         current_apy: f64,
         position_size: f64,
     ) -> Result<u64> {
-        let gas_cost = self.estimate_compound_gas_cost(pool_address).await?;
+        let gas_cost = self.estimate_compound_gas_cost(pool_address, wallet, strategy_type).await?;
         
         // Calculate daily rewards
         let daily_rewards = (position_size * current_apy / 365.0) / 100.0;
@@ -157,49 +162,97 @@ impl GasOptimizer {
         Ok(optimal_interval_ms)
     }
 
-    /// Check if network is congested
-    pub async fn is_network_congested(&self) -> Result<bool> {
-        // Get recent performance samples
-        match self.rpc_client.get_recent_performance_samples(Some(5)) {
-            Ok(samples) => {
-                let avg_tx_count: f64 = samples.iter()
-                    .map(|s| s.num_transactions as f64)
-                    .sum::<f64>() / samples.len() as f64;
-                
-                // Consider network congested if average TPS is high
-                // This is a simplified heuristic
-                let is_congested = avg_tx_count > 2000.0;
-                
-                if is_congested {
-                    warn!("🚨 Network appears congested (avg TPS: {:.0})", avg_tx_count);
-                } else {
-                    info!("🟢 Network conditions normal (avg TPS: {:.0})", avg_tx_count);
-                }
-                
-                Ok(is_congested)
-            }
-            Err(_) => {
-                warn!("Could not check network congestion");
-                Ok(false) // Assume not congested if we can't check
-            }
-        }
+    /// Priority fee for a transaction in `pool_address`, priced from recent fees on the
+    /// pool and its active bin array at the configured level
+    async fn priority_fee_estimate(&self, pool_address: Pubkey) -> Result<PriorityFeeEstimate> {
+        let active_bin = self.dlmm_client.get_active_bin(pool_address).await?;
+        let accounts = self.dlmm_client.priority_fee_accounts(&pool_address, &[active_bin.id]);
+        let estimate = self.fee_estimator.estimate(&accounts, self.priority_level).await;
+
+        Ok(self.fee_estimator.apply_cap(estimate))
     }
 
-    /// Get priority fee recommendations
-    pub async fn get_priority_fee_recommendation(&self) -> Result<u64> {
-        // In production, this would analyze recent transactions to recommend priority fees
-        // For now, return a conservative priority fee
-        
-        let is_congested = self.is_network_congested().await?;
-        
-        let priority_fee = if is_congested {
-            10000 // Higher priority fee during congestion (0.00001 SOL)
-        } else {
-            1000  // Lower priority fee during normal conditions (0.000001 SOL)
-        };
-        
-        info!("💰 Recommended priority fee: {} lamports", priority_fee);
-        
+    /// Get priority fee recommendation per transaction (lamports)
+    pub async fn get_priority_fee_recommendation(&self, pool_address: Pubkey) -> Result<u64> {
+        let estimate = self.priority_fee_estimate(pool_address).await?;
+        let priority_fee = estimate.priority_fee_lamports();
+
+        info!(
+            "💰 Recommended priority fee: {} lamports ({} micro-lamports/CU, {} level, {} samples)",
+            priority_fee, estimate.compute_unit_price, self.priority_level, estimate.samples
+        );
+
         Ok(priority_fee)
     }
-}
\ No newline at end of file
+
+    /// Add a compute budget to a transaction: the compute-unit limit comes from simulating
+    /// it and the price from recent fees in the pool
+    pub async fn prepare_transaction(
+        &self,
+        transaction: &Transaction,
+        payer: &Pubkey,
+        pool_address: Pubkey,
+    ) -> Result<Transaction> {
+        let estimate = self.priority_fee_estimate(pool_address).await?;
+        let (transaction, estimate) = self.fee_estimator.prepare(transaction, payer, estimate).await?;
+
+        info!(
+            "⛽ Compute budget: {} CU at {} micro-lamports/CU ({} lamports priority fee)",
+            estimate.compute_unit_limit, estimate.compute_unit_price, estimate.priority_fee_lamports()
+        );
+
+        Ok(transaction)
+    }
+}
+
+/// Transactions one compound sends: the harvest, `swaps` zap swaps for LP positions, and
+/// the reinvestment
+pub fn compound_transaction_count(strategy_type: StrategyType, swaps: u32) -> u32 {
+    match strategy_type {
+        StrategyType::LP => 2 + swaps,
+        StrategyType::Staking | StrategyType::Farming => 2,
+    }
+}
+
+/// Decide whether a compound should proceed given gas costs and pending rewards.
+///
+/// `pending_rewards` is compared against the minimum threshold in reward tokens, while
+/// `pending_rewards_sol` is the same rewards valued in SOL for the gas efficiency check.
+/// Compounds are deferred while the per-transaction fee is at or above `max_gas_price`.
+pub fn evaluate_compound(
+    transaction_fee: f64,
+    estimated_gas_cost: f64,
+    pending_rewards: f64,
+    pending_rewards_sol: f64,
+    min_reward_threshold: f64,
+    max_gas_price: f64,
+) -> GasOptimizationResult {
+    // Check if rewards justify gas costs
+    let profit_threshold = estimated_gas_cost * 2.0; // Require 2x gas cost in rewards
+    let gas_efficient = pending_rewards_sol >= profit_threshold;
+
+    // Check minimum reward threshold
+    let meets_min_threshold = pending_rewards >= min_reward_threshold;
+
+    // Check if gas price is reasonable
+    let reasonable_gas_price = transaction_fee < max_gas_price;
+
+    let should_proceed = gas_efficient && meets_min_threshold && reasonable_gas_price;
+
+    let reason = if !meets_min_threshold {
+        format!("Rewards below minimum threshold: {:.6} < {:.6}", pending_rewards, min_reward_threshold)
+    } else if !gas_efficient {
+        format!("Rewards don't justify gas costs: {:.6} < {:.6} (2x gas cost)", pending_rewards_sol, profit_threshold)
+    } else if !reasonable_gas_price {
+        format!("Gas price too high: {:.6} SOL >= {:.6} SOL", transaction_fee, max_gas_price)
+    } else {
+        "Optimal conditions for compounding".to_string()
+    };
+
+    GasOptimizationResult {
+        should_proceed,
+        recommended_gas_price: transaction_fee,
+        estimated_gas_cost,
+        reason,
+    }
+}
@@ -1,13 +1,21 @@
 use anyhow::Result;
 use dotenv::dotenv;
 use log::info;
+use saros_dlmm_sdk::{CostBasisMethod, PriorityLevel, RpcEndpoint, SignerSource, WalletSecrets};
 use solana_sdk::pubkey::Pubkey;
-use std::{env, str::FromStr, time::Duration};
-use tokio::signal;
+use std::{collections::HashMap, env, path::PathBuf, str::FromStr, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    signal,
+};
 
 mod auto_compounder;
+mod backtester;
+mod circuit_breaker;
 mod compound_strategy;
+mod gas_budget;
 mod gas_optimizer;
+mod notification_channels;
 mod notification_service;
 mod position_monitor;
 mod reward_harvester;
@@ -16,24 +24,47 @@ mod statistics;
 mod types;
 
 use auto_compounder::AutoCompounder;
+use backtester::{BacktestConfig, Backtester, ParameterSweep};
 use compound_strategy::CompoundStrategy;
-use types::{AutoCompoundConfig, CompoundStrategyConfig};
+use gas_budget::BudgetScope;
+use gas_optimizer::DEFAULT_MAX_GAS_PRICE;
+use types::{
+    AutoCompoundConfig, BudgetLimits, CircuitBreakerConfig, CompoundStrategyConfig, EmailConfig,
+    GasBudgetConfig, LedgerExportConfig, NotificationConfig, NotificationEventType, NotificationRoute,
+    NotificationSeverity, StrategyKey, TelegramConfig, WalletConfig, DEFAULT_WALLET,
+};
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     // Load environment variables
     dotenv().ok();
 
     // Initialize logging
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    // Backtest mode replays historical data instead of running the bot
+    let args: Vec<String> = env::args().collect();
+    if let Some(position) = args.iter().position(|arg| arg == "--backtest") {
+        let data_path = args.get(position + 1)
+            .ok_or_else(|| anyhow::anyhow!("--backtest requires a path to a CSV or JSON data file"))?;
+        return tokio::runtime::Runtime::new()?.block_on(run_backtest(PathBuf::from(data_path)));
+    }
+
     info!("🚀 Starting Saros Auto-Compound Yield Farming Bot");
 
     // Load configuration
     let config = load_config()?;
     info!("📝 Configuration loaded successfully");
 
+    // Take wallet secrets out of the environment while the process is still single-threaded
+    let secrets = WalletSecrets::take_from_env(config.wallets.iter().filter_map(|wallet| wallet.signer.as_ref()));
+
+    tokio::runtime::Runtime::new()?.block_on(run(config, secrets))
+}
+
+async fn run(config: AutoCompoundConfig, secrets: WalletSecrets) -> Result<()> {
     // Initialize auto-compounder
-    let mut compounder = AutoCompounder::new(config).await?;
+    let mut compounder = AutoCompounder::new(config, &secrets).await?;
+    drop(secrets);
     info!("⚡ Auto-compounder initialized");
 
     // Load strategies from environment
@@ -43,8 +74,12 @@ async fn main() -> Result<()> {
     // Start auto-compounding for each strategy
     for strategy in strategies {
         match compounder.start_strategy(strategy).await {
+            Ok(result) if !result.success => {
+                log::error!("❌ Strategy for {}/{} not started: {}", result.wallet, result.pool_address,
+                            result.error.unwrap_or_default());
+            }
             Ok(result) => {
-                info!("✅ Strategy started: {}", result.pool_address);
+                info!("✅ Strategy started: {} (wallet {})", result.pool_address, result.wallet);
                 info!("   Strategy type: {}", result.strategy_type);
                 info!("   Interval: {}ms", result.interval_ms);
                 info!("   Next compound: {}", result.next_compound_time);
@@ -57,31 +92,164 @@ async fn main() -> Result<()> {
 
     // Setup graceful shutdown
     info!("🎯 Auto-compound bot is running. Press Ctrl+C to stop.");
+    info!("⌨️ Commands: resume <wallet>/<pool>, compound <wallet>/<pool>");
     
     tokio::select! {
-        _ = signal::ctrl_c() => {
+        _ = wait_for_shutdown(&compounder) => {
             info!("📴 Shutdown signal received, stopping all strategies...");
             compounder.stop_all().await?;
             info!("✅ All strategies stopped gracefully");
+
+            info!("📡 RPC requests:");
+            for metrics in compounder.get_rpc_metrics() {
+                info!("   {}: {} requests, {} failures, {} retries, {:.0}ms avg",
+                      metrics.method, metrics.requests, metrics.failures,
+                      metrics.retries, metrics.average_latency_ms());
+            }
+            let endpoint_health = compounder.get_endpoint_health();
+            if endpoint_health.len() > 1 {
+                info!("🩺 RPC endpoint health:");
+                for health in endpoint_health {
+                    info!("   {} (weight {}): score {:.2}, {:.0}% errors, {} rate limited, lag {} slots",
+                          health.url, health.weight, health.score, health.error_rate * 100.0,
+                          health.rate_limited, health.slot_lag);
+                }
+            }
+
+            let wallet_statistics = compounder.get_all_wallet_statistics().await?;
+            if compounder.get_wallet_names().len() > 1 {
+                info!("👛 Wallet summary:");
+                for stats in wallet_statistics {
+                    info!("   {}: {} compounds ({} successful), harvested {:.6}, gas {:.6} SOL",
+                          stats.wallet, stats.totals.total_compounds, stats.totals.successful_compounds,
+                          stats.totals.total_rewards_harvested, stats.totals.total_gas_spent);
+                    for usage in compounder.get_budget_usage(&BudgetScope::Wallet(stats.wallet.clone())).await {
+                        if let (Some(limit), Some(remaining)) = (usage.limit, usage.remaining) {
+                            info!("      {} gas budget: {:.6} of {:.6} SOL left", usage.period, remaining, limit);
+                        }
+                    }
+                }
+            }
+
+            info!("⛽ Gas spend (including earlier runs):");
+            for usage in compounder.get_budget_usage(&BudgetScope::Global).await {
+                match usage.limit {
+                    Some(limit) => info!("   {}: {:.6} of {:.6} SOL", usage.period, usage.spent, limit),
+                    None => info!("   {}: {:.6} SOL", usage.period, usage.spent),
+                }
+            }
+            let reconciliation = compounder.reconcile_gas_spend().await;
+            if reconciliation.is_consistent() {
+                info!("   Statistics match the budget ledger ({:.6} SOL this run, {:.6} SOL lifetime)",
+                      reconciliation.ledger_session_total, reconciliation.ledger_lifetime_total);
+            }
+
+            if let Some(export) = compounder.export_ledger().await? {
+                info!("🧾 Ledger exported: {} events, {} realised lots ({} open), realised gain ${:.2}",
+                      export.events, export.realized_lots, export.open_lots, export.realized_gain_usd);
+                for file in &export.files {
+                    info!("   {}", file.display());
+                }
+            }
+
+            if let Some(paper) = compounder.get_paper_statistics().await? {
+                info!("🧪 Paper-trading summary:");
+                info!("   Simulated compounds: {} ({} successful)",
+                      paper.total_compounds, paper.successful_compounds);
+                info!("   Rewards harvested: {:.6}", paper.total_rewards_harvested);
+                info!("   Estimated gas: {:.6} SOL", paper.total_gas_spent);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Wait for Ctrl+C, running operator commands read from stdin in the meantime
+async fn wait_for_shutdown(compounder: &AutoCompounder) {
+    let mut commands = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdin_open = true;
+
+    loop {
+        tokio::select! {
+            _ = signal::ctrl_c() => return,
+            line = commands.next_line(), if stdin_open => match line {
+                Ok(Some(line)) => {
+                    if let Err(e) = run_command(compounder, line.trim()).await {
+                        log::error!("❌ {}", e);
+                    }
+                }
+                // Without a terminal (e.g. under a service manager) only the signal remains
+                Ok(None) | Err(_) => stdin_open = false,
+            },
+        }
+    }
+}
+
+/// Run a single operator command
+async fn run_command(compounder: &AutoCompounder, command: &str) -> Result<()> {
+    let (verb, argument) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+
+    match verb {
+        "" => {}
+        "resume" => {
+            let key = argument.parse::<StrategyKey>()?;
+            if !compounder.resume_strategy(&key).await? {
+                info!("Nothing to resume for {}", key);
+            }
+        }
+        "compound" => {
+            let key = argument.parse::<StrategyKey>()?;
+            let result = compounder.compound_now(&key).await?;
+            match (result.success, result.error) {
+                (true, _) => info!("✅ Manual compound for {}: harvested {:.6}, reinvested {:.6}",
+                                   key, result.rewards_harvested, result.amount_reinvested),
+                (false, error) => info!("Manual compound for {} did not complete: {}", key, error.unwrap_or_default()),
+            }
         }
+        _ => return Err(anyhow::anyhow!("Unknown command '{}' (expected resume or compound)", verb)),
     }
 
     Ok(())
 }
 
 fn load_config() -> Result<AutoCompoundConfig> {
-    let rpc_url = env::var("SOLANA_RPC_URL")
-        .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
+    // SOLANA_RPC_ENDPOINTS takes a weighted list (`url|weight,url`) and overrides SOLANA_RPC_URL
+    let rpc_endpoints = match env::var("SOLANA_RPC_ENDPOINTS") {
+        Ok(endpoints) => RpcEndpoint::parse_list(&endpoints)?,
+        Err(_) => vec![RpcEndpoint::new(
+            env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
+            1,
+        )],
+    };
 
-    let private_key = env::var("WALLET_PRIVATE_KEY")
-        .map_err(|_| anyhow::anyhow!("WALLET_PRIVATE_KEY environment variable is required"))?;
+    let rpc_timeout_secs = env::var("RPC_TIMEOUT_SECONDS")
+        .unwrap_or_else(|_| "30".to_string())
+        .parse::<u64>()?;
+
+    let rpc_max_retries = env::var("RPC_MAX_RETRIES")
+        .unwrap_or_else(|_| "3".to_string())
+        .parse::<u32>()?;
+
+    let dry_run = env::args().any(|arg| arg == "--dry-run")
+        || env::var("DRY_RUN")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+
+    let wallets = load_wallets(dry_run)?;
 
     let network = env::var("SOLANA_NETWORK")
         .unwrap_or_else(|_| "devnet".to_string());
 
     let max_gas_price = env::var("MAX_GAS_PRICE")
-        .unwrap_or_else(|_| "0.01".to_string())
-        .parse::<f64>()?;
+        .map(|value| value.parse::<f64>())
+        .unwrap_or(Ok(DEFAULT_MAX_GAS_PRICE))?;
+
+    let priority_fee_level = env::var("PRIORITY_FEE_LEVEL")
+        .unwrap_or_else(|_| "standard".to_string())
+        .parse::<PriorityLevel>()?;
+
+    let gas_budget = load_gas_budget_config()?;
 
     let enable_notifications = env::var("ENABLE_NOTIFICATIONS")
         .unwrap_or_else(|_| "false".to_string())
@@ -89,13 +257,366 @@ fn load_config() -> Result<AutoCompoundConfig> {
 
     let webhook_url = env::var("WEBHOOK_URL").ok();
 
+    let notifications = load_notification_config()?;
+    let circuit_breaker = load_circuit_breaker_config()?;
+
+    // LEDGER_EXPORT_DIR enables the accounting journal and its export on shutdown
+    let ledger_export = match env::var("LEDGER_EXPORT_DIR") {
+        Ok(dir) => Some(LedgerExportConfig {
+            journal: env::var("LEDGER_JOURNAL_FILE")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from(&dir).join("ledger_events.jsonl")),
+            dir: PathBuf::from(dir),
+            cost_basis_method: env::var("COST_BASIS_METHOD")
+                .unwrap_or_else(|_| "fifo".to_string())
+                .parse::<CostBasisMethod>()?,
+        }),
+        Err(_) => None,
+    };
+
     Ok(AutoCompoundConfig {
-        rpc_url,
-        private_key,
+        rpc_endpoints,
+        rpc_timeout_secs,
+        rpc_max_retries,
+        wallets,
         network,
+        dry_run,
         max_gas_price,
+        priority_fee_level,
+        gas_budget,
         enable_notifications,
         webhook_url,
+        notifications,
+        circuit_breaker,
+        ledger_export,
+    })
+}
+
+/// Load the default wallet (WALLET_*) plus any named wallets listed in WALLETS (WALLET_<NAME>_*)
+fn load_wallets(dry_run: bool) -> Result<Vec<WalletConfig>> {
+    let mut wallets = Vec::new();
+
+    // WALLET_SIGNER takes a signer spec (keypair file, keystore, external signer);
+    // WALLET_PRIVATE_KEY remains as a fallback
+    let default_signer = match env::var("WALLET_SIGNER") {
+        Ok(spec) => Some(SignerSource::parse(&spec)?),
+        Err(_) if env::var_os("WALLET_PRIVATE_KEY").is_some() => {
+            Some(SignerSource::Env("WALLET_PRIVATE_KEY".to_string()))
+        }
+        Err(_) => None,
+    };
+
+    let names: Vec<String> = parse_list("WALLETS")?;
+    if default_signer.is_some() || names.is_empty() {
+        wallets.push(load_wallet_limits(WalletConfig::new(DEFAULT_WALLET, default_signer), "WALLET")?);
+    }
+
+    for name in names {
+        let prefix = format!("WALLET_{}", name.to_uppercase().replace('-', "_"));
+        let signer = match env::var(format!("{}_SIGNER", prefix)) {
+            Ok(spec) => Some(SignerSource::parse(&spec)?),
+            Err(_) => None,
+        };
+        wallets.push(load_wallet_limits(WalletConfig::new(name, signer), &prefix)?);
+    }
+
+    // Signers are optional in dry-run mode
+    if !dry_run {
+        if let Some(wallet) = wallets.iter().find(|wallet| wallet.signer.is_none()) {
+            return Err(anyhow::anyhow!(if wallet.name == DEFAULT_WALLET {
+                "WALLET_SIGNER or WALLET_PRIVATE_KEY environment variable is required".to_string()
+            } else {
+                format!("WALLET_{}_SIGNER is required for wallet '{}'", wallet.name.to_uppercase().replace('-', "_"), wallet.name)
+            }));
+        }
+    }
+
+    Ok(wallets)
+}
+
+/// Read `<PREFIX>_DAILY_GAS_BUDGET`, `_WEEKLY_GAS_BUDGET`, `_MAX_STRATEGIES` and `_WEBHOOK_URL`
+fn load_wallet_limits(mut wallet: WalletConfig, prefix: &str) -> Result<WalletConfig> {
+    wallet.gas_budget = load_budget_limits(
+        &format!("{}_DAILY_GAS_BUDGET", prefix),
+        &format!("{}_WEEKLY_GAS_BUDGET", prefix),
+    )?;
+    wallet.max_strategies = match env::var(format!("{}_MAX_STRATEGIES", prefix)) {
+        Ok(max) => Some(max.parse::<usize>()?),
+        Err(_) => None,
+    };
+    wallet.webhook_url = env::var(format!("{}_WEBHOOK_URL", prefix)).ok();
+    Ok(wallet)
+}
+
+fn load_gas_budget_config() -> Result<GasBudgetConfig> {
+    let max_priority_fee_lamports = match env::var("MAX_PRIORITY_FEE_LAMPORTS") {
+        Ok(fee) => Some(fee.parse::<u64>()?),
+        Err(_) => None,
+    };
+
+    Ok(GasBudgetConfig {
+        strategy: load_budget_limits("GAS_BUDGET_STRATEGY_DAILY", "GAS_BUDGET_STRATEGY_WEEKLY")?,
+        global: load_budget_limits("GAS_BUDGET_GLOBAL_DAILY", "GAS_BUDGET_GLOBAL_WEEKLY")?,
+        max_priority_fee_lamports,
+        state_file: Some(PathBuf::from(
+            env::var("GAS_BUDGET_STATE_FILE").unwrap_or_else(|_| "gas_budget_state.json".to_string()),
+        )),
+    })
+}
+
+/// Read a pair of optional daily/weekly SOL limits
+fn load_budget_limits(daily_key: &str, weekly_key: &str) -> Result<BudgetLimits> {
+    let read = |key: &str| -> Result<Option<f64>> {
+        match env::var(key) {
+            Ok(value) => Ok(Some(value.parse::<f64>()?)),
+            Err(_) => Ok(None),
+        }
+    };
+
+    Ok(BudgetLimits {
+        daily: read(daily_key)?,
+        weekly: read(weekly_key)?,
+    })
+}
+
+async fn run_backtest(data_path: PathBuf) -> Result<()> {
+    info!("🧪 Running backtest on {}", data_path.display());
+
+    let config = BacktestConfig {
+        initial_position_value: env::var("BACKTEST_INITIAL_VALUE")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse::<f64>()?,
+        sol_price: env::var("BACKTEST_SOL_PRICE")
+            .unwrap_or_else(|_| "150".to_string())
+            .parse::<f64>()?,
+        max_gas_price: env::var("MAX_GAS_PRICE")
+            .map(|value| value.parse::<f64>())
+            .unwrap_or(Ok(DEFAULT_MAX_GAS_PRICE))?,
+        ..Default::default()
+    };
+
+    let base = CompoundStrategyConfig {
+        wallet: DEFAULT_WALLET.to_string(),
+        pool_address: match env::var("POOL_ADDRESS") {
+            Ok(address) => Pubkey::from_str(&address)?,
+            Err(_) => Pubkey::default(),
+        },
+        strategy_type: env::var("STRATEGY_TYPE")
+            .unwrap_or_else(|_| "LP".to_string())
+            .parse()?,
+        interval_ms: env::var("COMPOUND_INTERVAL")
+            .unwrap_or_else(|_| "3600000".to_string())
+            .parse::<u64>()?,
+        min_reward_threshold: env::var("MIN_REWARD_THRESHOLD")
+            .unwrap_or_else(|_| "1.0".to_string())
+            .parse::<f64>()?,
+        reinvest_percentage: env::var("REINVEST_PERCENTAGE")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse::<u8>()?,
+        max_slippage: Some(env::var("MAX_SLIPPAGE")
+            .unwrap_or_else(|_| "1.0".to_string())
+            .parse::<f64>()?),
+        emergency_withdraw: false,
+        gas_budget: BudgetLimits::default(),
+    };
+
+    let sweep = ParameterSweep {
+        strategy_types: parse_list("BACKTEST_STRATEGY_TYPES")?,
+        intervals_ms: parse_list("BACKTEST_INTERVALS_MS")?,
+        min_reward_thresholds: parse_list("BACKTEST_MIN_THRESHOLDS")?,
+        reinvest_percentages: parse_list("BACKTEST_REINVEST_PERCENTAGES")?,
+    };
+
+    let backtester = Backtester::new(config, Backtester::load_series(&data_path)?)?;
+    let results = backtester.run_sweep(&sweep.variants(&base)).await;
+
+    println!("{}", backtester::comparison_table(&results));
+
+    if let Ok(output_path) = env::var("BACKTEST_OUTPUT") {
+        std::fs::write(&output_path, serde_json::to_string_pretty(&results)?)?;
+        info!("💾 Backtest results written to {}", output_path);
+    }
+
+    Ok(())
+}
+
+/// Parse a comma-separated environment variable, returning an empty list when unset
+fn parse_list<T>(key: &str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    match env::var(key) {
+        Ok(values) => values
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<T>().map_err(Into::into))
+            .collect(),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn load_circuit_breaker_config() -> Result<CircuitBreakerConfig> {
+    let defaults = CircuitBreakerConfig::default();
+
+    let enabled = env::var("CIRCUIT_BREAKER_ENABLED")
+        .map(|value| value.parse::<bool>())
+        .unwrap_or(Ok(defaults.enabled))?;
+
+    let max_consecutive_failures = env::var("MAX_CONSECUTIVE_FAILURES")
+        .map(|value| value.parse::<u32>())
+        .unwrap_or(Ok(defaults.max_consecutive_failures))?;
+
+    let max_apy_drop_percent = env::var("MAX_APY_DROP_PERCENT")
+        .map(|value| value.parse::<f64>())
+        .unwrap_or(Ok(defaults.max_apy_drop_percent))?;
+
+    let max_tvl_drop_percent = env::var("MAX_TVL_DROP_PERCENT")
+        .map(|value| value.parse::<f64>())
+        .unwrap_or(Ok(defaults.max_tvl_drop_percent))?;
+
+    let max_price_deviation_percent = env::var("MAX_PRICE_DEVIATION_PERCENT")
+        .map(|value| value.parse::<f64>())
+        .unwrap_or(Ok(defaults.max_price_deviation_percent))?;
+
+    let safe_address = match env::var("EMERGENCY_SAFE_ADDRESS") {
+        Ok(address) => Some(Pubkey::from_str(&address)?),
+        Err(_) => None,
+    };
+
+    Ok(CircuitBreakerConfig {
+        enabled,
+        max_consecutive_failures,
+        max_apy_drop_percent,
+        max_tvl_drop_percent,
+        max_price_deviation_percent,
+        safe_address,
+    })
+}
+
+fn load_notification_config() -> Result<NotificationConfig> {
+    let discord_webhook_url = env::var("DISCORD_WEBHOOK_URL").ok();
+    let slack_webhook_url = env::var("SLACK_WEBHOOK_URL").ok();
+
+    let telegram = match (env::var("TELEGRAM_BOT_TOKEN"), env::var("TELEGRAM_CHAT_ID")) {
+        (Ok(bot_token), Ok(chat_id)) => Some(TelegramConfig { bot_token, chat_id }),
+        _ => None,
+    };
+
+    let email_enabled = env::var("EMAIL_NOTIFICATIONS")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse::<bool>()?;
+
+    let email = if email_enabled {
+        let username = env::var("EMAIL_USERNAME")
+            .map_err(|_| anyhow::anyhow!("EMAIL_USERNAME is required when EMAIL_NOTIFICATIONS=true"))?;
+
+        Some(EmailConfig {
+            smtp_host: env::var("EMAIL_SMTP_HOST")
+                .map_err(|_| anyhow::anyhow!("EMAIL_SMTP_HOST is required when EMAIL_NOTIFICATIONS=true"))?,
+            smtp_port: env::var("EMAIL_SMTP_PORT")
+                .unwrap_or_else(|_| "587".to_string())
+                .parse::<u16>()?,
+            password: env::var("EMAIL_PASSWORD")
+                .map_err(|_| anyhow::anyhow!("EMAIL_PASSWORD is required when EMAIL_NOTIFICATIONS=true"))?,
+            from: env::var("EMAIL_FROM").unwrap_or_else(|_| username.clone()),
+            to: env::var("EMAIL_TO")
+                .map_err(|_| anyhow::anyhow!("EMAIL_TO is required when EMAIL_NOTIFICATIONS=true"))?
+                .split(',')
+                .map(|address| address.trim().to_string())
+                .filter(|address| !address.is_empty())
+                .collect(),
+            username,
+        })
+    } else {
+        None
+    };
+
+    let log_file = env::var("NOTIFICATION_LOG_FILE").ok();
+
+    let stdout_json = env::var("NOTIFICATION_STDOUT_JSON")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse::<bool>()?;
+
+    // Per-channel routing rules: NOTIFY_<CHANNEL>_EVENTS, _MIN_SEVERITY, _RATE_LIMIT, _DIGEST, _WALLETS
+    let mut routes = HashMap::new();
+    for channel in ["webhook", "discord", "slack", "telegram", "email", "file", "stdout"] {
+        let prefix = format!("NOTIFY_{}", channel.to_uppercase());
+        let mut route = NotificationRoute::default();
+
+        if channel == "email" {
+            // Email is noisy by default, so only warnings and above unless configured
+            route.min_severity = NotificationSeverity::Warning;
+        }
+
+        if let Ok(events) = env::var(format!("{}_EVENTS", prefix)) {
+            route.event_types = events
+                .split(',')
+                .filter(|event| !event.trim().is_empty())
+                .map(|event| event.parse())
+                .collect::<Result<Vec<_>>>()?;
+        }
+
+        if let Ok(min_severity) = env::var(format!("{}_MIN_SEVERITY", prefix)) {
+            route.min_severity = min_severity.parse()?;
+        }
+
+        if let Ok(rate_limit) = env::var(format!("{}_RATE_LIMIT", prefix)) {
+            route.max_per_minute = rate_limit.parse()?;
+        }
+
+        if let Ok(digest) = env::var(format!("{}_DIGEST", prefix)) {
+            route.digest = digest.parse()?;
+        }
+
+        route.wallets = parse_list(&format!("{}_WALLETS", prefix))?;
+
+        routes.insert(channel.to_string(), route);
+    }
+
+    // Message templates: NOTIFY_TEMPLATE_<EVENT_TYPE>, e.g. NOTIFY_TEMPLATE_COMPOUND_SUCCESS
+    let mut templates = HashMap::new();
+    for (key, value) in env::vars() {
+        if let Some(event_type) = key.strip_prefix("NOTIFY_TEMPLATE_") {
+            templates.insert(event_type.parse::<NotificationEventType>()?, value);
+        }
+    }
+
+    let dedup_window_secs = env::var("NOTIFY_DEDUP_WINDOW_SECS")
+        .unwrap_or_else(|_| "1800".to_string()) // 30 minutes
+        .parse::<u64>()?;
+
+    let digest_interval_secs = env::var("NOTIFY_DIGEST_INTERVAL_SECS")
+        .unwrap_or_else(|_| "900".to_string()) // 15 minutes
+        .parse::<u64>()?;
+
+    // Digests are only flushed on an interval, so a digest route without one would never deliver
+    if digest_interval_secs == 0 {
+        if let Some((channel, _)) = routes.iter().find(|(_, route)| route.digest) {
+            return Err(anyhow::anyhow!(
+                "NOTIFY_{}_DIGEST=true requires NOTIFY_DIGEST_INTERVAL_SECS to be greater than 0",
+                channel.to_uppercase()
+            ));
+        }
+    }
+
+    let retry_max_elapsed_secs = env::var("NOTIFY_RETRY_MAX_SECS")
+        .unwrap_or_else(|_| "30".to_string())
+        .parse::<u64>()?;
+
+    Ok(NotificationConfig {
+        discord_webhook_url,
+        slack_webhook_url,
+        telegram,
+        email,
+        log_file,
+        stdout_json,
+        routes,
+        templates,
+        dedup_window_secs,
+        digest_interval_secs,
+        retry_max_elapsed_secs,
     })
 }
 
@@ -125,14 +646,25 @@ fn load_strategies_from_env() -> Result<Vec<CompoundStrategyConfig>> {
             .unwrap_or_else(|_| "1.0".to_string())
             .parse::<f64>()?;
 
+        let emergency_withdraw = env::var("EMERGENCY_WITHDRAW")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+
+        let wallet = env::var("STRATEGY_WALLET")
+            .unwrap_or_else(|_| DEFAULT_WALLET.to_string());
+
+        let gas_budget = load_budget_limits("STRATEGY_DAILY_GAS_BUDGET", "STRATEGY_WEEKLY_GAS_BUDGET")?;
+
         strategies.push(CompoundStrategyConfig {
+            wallet,
             pool_address,
             strategy_type: strategy_type.parse()?,
             interval_ms,
             min_reward_threshold,
             reinvest_percentage,
             max_slippage: Some(max_slippage),
-            emergency_withdraw: false,
+            emergency_withdraw,
+            gas_budget,
         });
     }
 
@@ -157,14 +689,28 @@ fn load_strategies_from_env() -> Result<Vec<CompoundStrategyConfig>> {
                 .unwrap_or_else(|_| "100".to_string())
                 .parse::<u8>()?;
 
+            let emergency_withdraw = env::var(format!("EMERGENCY_WITHDRAW_{}", i))
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()?;
+
+            let wallet = env::var(format!("STRATEGY_WALLET_{}", i))
+                .unwrap_or_else(|_| DEFAULT_WALLET.to_string());
+
+            let gas_budget = load_budget_limits(
+                &format!("STRATEGY_DAILY_GAS_BUDGET_{}", i),
+                &format!("STRATEGY_WEEKLY_GAS_BUDGET_{}", i),
+            )?;
+
             strategies.push(CompoundStrategyConfig {
+                wallet,
                 pool_address,
                 strategy_type: strategy_type.parse()?,
                 interval_ms,
                 min_reward_threshold,
                 reinvest_percentage,
                 max_slippage: Some(1.0),
-                emergency_withdraw: false,
+                emergency_withdraw,
+                gas_budget,
             });
         }
     }
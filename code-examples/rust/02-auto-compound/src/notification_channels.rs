@@ -0,0 +1,450 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use log::info;
+use reqwest::{Client, Response, StatusCode};
+use serde_json::json;
+use std::{path::PathBuf, time::Duration};
+use tokio::io::AsyncWriteExt;
+
+use crate::types::{
+    EmailConfig, NotificationEvent, NotificationEventType, NotificationSeverity, TelegramConfig,
+};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A rendered notification ready for delivery (a single event or a digest of several)
+#[derive(Debug, Clone)]
+pub struct OutgoingNotification {
+    pub title: String,
+    pub body: String,
+    pub severity: NotificationSeverity,
+    pub events: Vec<NotificationEvent>,
+}
+
+impl OutgoingNotification {
+    pub fn is_digest(&self) -> bool {
+        self.events.len() > 1
+    }
+
+    /// Pool address for single-event notifications, or a summary for digests
+    pub fn pool_address(&self) -> String {
+        match self.events.as_slice() {
+            [event] => event.pool_address.clone(),
+            _ => "multiple".to_string(),
+        }
+    }
+
+    /// Event type label for single-event notifications, or DIGEST
+    pub fn event_label(&self) -> String {
+        match self.events.as_slice() {
+            [event] => event.event_type.to_string(),
+            _ => "DIGEST".to_string(),
+        }
+    }
+}
+
+/// The destination rejected a notification outright (a 4xx other than 429), so retrying
+/// the same request cannot succeed
+#[derive(Debug, thiserror::Error)]
+#[error("{service} rejected the notification with status: {status}")]
+pub struct RejectedDelivery {
+    pub service: &'static str,
+    pub status: StatusCode,
+}
+
+/// Fail on an unsuccessful response; client errors other than rate limiting are permanent
+fn check_response(service: &'static str, response: &Response) -> Result<()> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+        return Err(RejectedDelivery { service, status }.into());
+    }
+    Err(anyhow::anyhow!("{} responded with status: {}", service, status))
+}
+
+/// A destination that notifications can be delivered to
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    /// Channel name used by routing rules (e.g. "discord")
+    fn name(&self) -> &str;
+
+    /// Deliver a notification, returning an error if delivery should be retried
+    async fn send(&self, notification: &OutgoingNotification) -> Result<()>;
+}
+
+/// Generic JSON webhook
+pub struct WebhookChannel {
+    client: Client,
+    url: String,
+    name: String,
+}
+
+impl WebhookChannel {
+    pub fn new(client: Client, url: String) -> Self {
+        Self::named(client, url, "webhook".to_string())
+    }
+
+    /// Webhook registered under a custom channel name (e.g. a per-wallet webhook)
+    pub fn named(client: Client, url: String, name: String) -> Self {
+        Self { client, url, name }
+    }
+
+    fn event_payload(event: &NotificationEvent, message: &str) -> serde_json::Value {
+        json!({
+            "event_type": event.event_type.to_string(),
+            "severity": event.event_type.severity().to_string(),
+            "wallet": event.wallet,
+            "pool_address": event.pool_address,
+            "message": message,
+            "data": event.data,
+            "timestamp": event.timestamp.to_rfc3339(),
+            "source": "saros-auto-compound-bot"
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &OutgoingNotification) -> Result<()> {
+        let payload = match notification.events.as_slice() {
+            [event] => Self::event_payload(event, &notification.body),
+            events => json!({
+                "event_type": "DIGEST",
+                "severity": notification.severity.to_string(),
+                "message": notification.title,
+                "events": events.iter()
+                    .map(|event| Self::event_payload(event, &event.message))
+                    .collect::<Vec<_>>(),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "source": "saros-auto-compound-bot"
+            }),
+        };
+
+        let response = self.client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "Saros-Auto-Compound-Bot/1.0")
+            .json(&payload)
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?;
+
+        check_response("Webhook", &response)?;
+
+        info!("✅ Webhook notification sent successfully");
+        Ok(())
+    }
+}
+
+/// Discord webhook with rich embeds
+pub struct DiscordChannel {
+    client: Client,
+    url: String,
+}
+
+impl DiscordChannel {
+    pub fn new(client: Client, url: String) -> Self {
+        Self { client, url }
+    }
+
+    fn color(notification: &OutgoingNotification) -> u32 {
+        match notification.events.as_slice() {
+            [event] => match event.event_type {
+                NotificationEventType::CompoundSuccess => 0x00ff00, // Green
+                NotificationEventType::CompoundFailed => 0xff0000,  // Red
+                NotificationEventType::CompoundStarted => 0x0099ff, // Blue
+                NotificationEventType::EmergencyStop => 0xff6600,   // Orange
+                _ => 0x888888, // Gray
+            },
+            _ => 0x9966ff, // Purple for digests
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for DiscordChannel {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    async fn send(&self, notification: &OutgoingNotification) -> Result<()> {
+        let timestamp = notification.events.last()
+            .map(|event| event.timestamp)
+            .unwrap_or_else(chrono::Utc::now);
+
+        let embed = json!({
+            "embeds": [{
+                "title": format!("🤖 {}", notification.title),
+                "description": notification.body,
+                "color": Self::color(notification),
+                "fields": [
+                    {
+                        "name": "Pool Address",
+                        "value": format!("`{}`", notification.pool_address()),
+                        "inline": false
+                    },
+                    {
+                        "name": "Event Type",
+                        "value": notification.event_label(),
+                        "inline": true
+                    },
+                    {
+                        "name": "Severity",
+                        "value": notification.severity.to_string(),
+                        "inline": true
+                    }
+                ],
+                "footer": {
+                    "text": "Saros Finance Auto-Compound Bot"
+                },
+                "timestamp": timestamp.to_rfc3339()
+            }]
+        });
+
+        let response = self.client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .json(&embed)
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?;
+
+        check_response("Discord", &response)?;
+
+        info!("✅ Discord notification sent successfully");
+        Ok(())
+    }
+}
+
+/// Slack incoming webhook with block formatting
+pub struct SlackChannel {
+    client: Client,
+    url: String,
+}
+
+impl SlackChannel {
+    pub fn new(client: Client, url: String) -> Self {
+        Self { client, url }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, notification: &OutgoingNotification) -> Result<()> {
+        let emoji = match notification.events.as_slice() {
+            [event] => match event.event_type {
+                NotificationEventType::CompoundSuccess => ":white_check_mark:",
+                NotificationEventType::CompoundFailed => ":x:",
+                NotificationEventType::CompoundStarted => ":rocket:",
+                NotificationEventType::EmergencyStop => ":warning:",
+                _ => ":information_source:",
+            },
+            _ => ":bookmark_tabs:",
+        };
+
+        let payload = json!({
+            "text": format!("{} Saros Auto-Compound Bot", emoji),
+            "blocks": [
+                {
+                    "type": "section",
+                    "text": {
+                        "type": "mrkdwn",
+                        "text": format!("*{}*\n{}", notification.title, notification.body)
+                    }
+                },
+                {
+                    "type": "section",
+                    "fields": [
+                        {
+                            "type": "mrkdwn",
+                            "text": format!("*Pool Address:*\n`{}`", notification.pool_address())
+                        },
+                        {
+                            "type": "mrkdwn",
+                            "text": format!("*Severity:*\n{}", notification.severity)
+                        }
+                    ]
+                }
+            ]
+        });
+
+        let response = self.client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?;
+
+        check_response("Slack", &response)?;
+
+        info!("✅ Slack notification sent successfully");
+        Ok(())
+    }
+}
+
+/// Telegram bot messages
+pub struct TelegramChannel {
+    client: Client,
+    config: TelegramConfig,
+}
+
+impl TelegramChannel {
+    pub fn new(client: Client, config: TelegramConfig) -> Self {
+        Self { client, config }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for TelegramChannel {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn send(&self, notification: &OutgoingNotification) -> Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.config.bot_token);
+        let payload = json!({
+            "chat_id": self.config.chat_id,
+            "text": format!("{} [{}]\n\n{}", notification.title, notification.severity, notification.body),
+            "disable_web_page_preview": true
+        });
+
+        let response = self.client
+            .post(&url)
+            .json(&payload)
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?;
+
+        check_response("Telegram", &response)?;
+
+        info!("✅ Telegram notification sent successfully");
+        Ok(())
+    }
+}
+
+/// SMTP email (STARTTLS)
+pub struct EmailChannel {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailChannel {
+    pub fn new(config: &EmailConfig) -> Result<Self> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?
+            .port(config.smtp_port)
+            .credentials(Credentials::new(config.username.clone(), config.password.clone()))
+            .timeout(Some(HTTP_TIMEOUT))
+            .build();
+
+        let from = config.from.parse()?;
+        let to = config.to
+            .iter()
+            .map(|address| address.parse())
+            .collect::<std::result::Result<Vec<Mailbox>, _>>()?;
+
+        if to.is_empty() {
+            return Err(anyhow::anyhow!("Email channel requires at least one recipient"));
+        }
+
+        Ok(Self { transport, from, to })
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    async fn send(&self, notification: &OutgoingNotification) -> Result<()> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(format!("[{}] {}", notification.severity, notification.title))
+            .header(ContentType::TEXT_PLAIN);
+
+        for recipient in &self.to {
+            builder = builder.to(recipient.clone());
+        }
+
+        let message = builder.body(notification.body.clone())?;
+        self.transport.send(message).await?;
+
+        info!("✅ Email notification sent to {} recipient(s)", self.to.len());
+        Ok(())
+    }
+}
+
+/// Where JSON-lines notifications are written
+#[derive(Debug, Clone)]
+pub enum JsonLinesTarget {
+    File(PathBuf),
+    Stdout,
+}
+
+/// Newline-delimited JSON output for log shippers and local monitoring
+pub struct JsonLinesChannel {
+    name: String,
+    target: JsonLinesTarget,
+}
+
+impl JsonLinesChannel {
+    pub fn new(target: JsonLinesTarget) -> Self {
+        let name = match target {
+            JsonLinesTarget::File(_) => "file",
+            JsonLinesTarget::Stdout => "stdout",
+        };
+
+        Self {
+            name: name.to_string(),
+            target,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for JsonLinesChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn send(&self, notification: &OutgoingNotification) -> Result<()> {
+        let line = serde_json::to_string(&json!({
+            "title": notification.title,
+            "body": notification.body,
+            "severity": notification.severity,
+            "digest": notification.is_digest(),
+            "events": notification.events,
+        }))?;
+
+        match &self.target {
+            JsonLinesTarget::Stdout => println!("{}", line),
+            JsonLinesTarget::File(path) => {
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await?;
+                file.write_all(format!("{}\n", line).as_bytes()).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,80 +1,375 @@
-use anyhow::Result;
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use chrono::Utc;
 use log::{error, info, warn};
 use reqwest::Client;
-use serde_json::json;
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{mpsc, oneshot, Mutex, Semaphore};
 
-use crate::types::{AutoCompoundConfig, NotificationEvent};
+use crate::notification_channels::{
+    DiscordChannel, EmailChannel, JsonLinesChannel, JsonLinesTarget, NotificationChannel,
+    OutgoingNotification, RejectedDelivery, SlackChannel, TelegramChannel, WebhookChannel,
+};
+use crate::types::{
+    AutoCompoundConfig, NotificationEvent, NotificationEventType, NotificationRoute,
+    NotificationSeverity,
+};
 
-/// Service for sending notifications about compound events
+/// Event types that tend to repeat every cycle and are de-duplicated
+const DEDUPLICATED_EVENTS: [NotificationEventType; 3] = [
+    NotificationEventType::HighGasPrice,
+    NotificationEventType::LowRewards,
+    NotificationEventType::GasBudgetExceeded,
+];
+
+/// Notifications waiting for delivery before new ones are dropped
+const DELIVERY_QUEUE_CAPACITY: usize = 256;
+
+/// Deliveries, including their retries, in flight at once
+const MAX_CONCURRENT_DELIVERIES: usize = 8;
+
+/// Work for the background delivery task
+enum Delivery {
+    Send { channel: usize, notification: OutgoingNotification },
+    /// Reply once everything queued before it has been delivered or given up on
+    Drain(oneshot::Sender<()>),
+}
+
+/// A channel together with the routing rule that feeds it
+struct RoutedChannel {
+    channel: Box<dyn NotificationChannel>,
+    route: NotificationRoute,
+}
+
+/// Mutable dispatch state shared across notifications
+#[derive(Default)]
+struct DispatchState {
+    deduplicator: Deduplicator,
+    rate_limiters: HashMap<String, RateLimiter>,
+    digests: HashMap<String, Vec<NotificationEvent>>,
+}
+
+/// Service for routing compound events to notification channels
 pub struct NotificationService {
-    client: Client,
-    webhook_url: Option<String>,
+    channels: Vec<RoutedChannel>,
     enabled: bool,
+    templates: HashMap<NotificationEventType, String>,
+    dedup_window: Duration,
+    digest_interval: Duration,
+    retry_max_elapsed: Duration,
+    state: Mutex<DispatchState>,
+    queue: mpsc::Sender<Delivery>,
+    /// Taken by the delivery task when it is spawned
+    receiver: std::sync::Mutex<Option<mpsc::Receiver<Delivery>>>,
 }
 
 impl NotificationService {
     pub fn new(config: &AutoCompoundConfig) -> Self {
+        let client = Client::new();
+        let notifications = &config.notifications;
+        let mut channels: Vec<Box<dyn NotificationChannel>> = Vec::new();
+
+        if let Some(url) = &config.webhook_url {
+            channels.push(Box::new(WebhookChannel::new(client.clone(), url.clone())));
+        }
+        if let Some(url) = &notifications.discord_webhook_url {
+            channels.push(Box::new(DiscordChannel::new(client.clone(), url.clone())));
+        }
+        if let Some(url) = &notifications.slack_webhook_url {
+            channels.push(Box::new(SlackChannel::new(client.clone(), url.clone())));
+        }
+        if let Some(telegram) = &notifications.telegram {
+            channels.push(Box::new(TelegramChannel::new(client.clone(), telegram.clone())));
+        }
+        if let Some(email) = &notifications.email {
+            match EmailChannel::new(email) {
+                Ok(channel) => channels.push(Box::new(channel)),
+                Err(e) => error!("Failed to configure email notifications: {}", e),
+            }
+        }
+        if let Some(path) = &notifications.log_file {
+            channels.push(Box::new(JsonLinesChannel::new(JsonLinesTarget::File(PathBuf::from(path)))));
+        }
+        if notifications.stdout_json {
+            channels.push(Box::new(JsonLinesChannel::new(JsonLinesTarget::Stdout)));
+        }
+
+        let mut channels: Vec<RoutedChannel> = channels
+            .into_iter()
+            .map(|channel| {
+                let route = notifications.routes
+                    .get(channel.name())
+                    .cloned()
+                    .unwrap_or_default();
+                RoutedChannel { channel, route }
+            })
+            .collect();
+
+        // Wallet webhooks share the webhook routing rule but only receive their wallet's events
+        for wallet in &config.wallets {
+            if let Some(url) = &wallet.webhook_url {
+                let name = format!("webhook:{}", wallet.name);
+                let route = NotificationRoute {
+                    wallets: vec![wallet.name.clone()],
+                    ..notifications.routes.get("webhook").cloned().unwrap_or_default()
+                };
+                channels.push(RoutedChannel {
+                    channel: Box::new(WebhookChannel::named(client.clone(), url.clone(), name)),
+                    route,
+                });
+            }
+        }
+
+        if config.enable_notifications {
+            let names: Vec<&str> = channels.iter().map(|c| c.channel.name()).collect();
+            info!("🔔 Notification channels: {}", if names.is_empty() { "log only".to_string() } else { names.join(", ") });
+        }
+
+        let (queue, receiver) = mpsc::channel(DELIVERY_QUEUE_CAPACITY);
+
         Self {
-            client: Client::new(),
-            webhook_url: config.webhook_url.clone(),
+            channels,
             enabled: config.enable_notifications,
+            templates: notifications.templates.clone(),
+            dedup_window: Duration::from_secs(notifications.dedup_window_secs),
+            digest_interval: Duration::from_secs(notifications.digest_interval_secs),
+            retry_max_elapsed: Duration::from_secs(notifications.retry_max_elapsed_secs),
+            state: Mutex::new(DispatchState::default()),
+            queue,
+            receiver: std::sync::Mutex::new(Some(receiver)),
         }
     }
 
-    /// Send a notification event
-    pub async fn send_notification(&self, event: NotificationEvent) {
+    /// Spawn the background tasks that deliver queued notifications and periodically flush digests
+    pub fn spawn_tasks(self: &Arc<Self>) {
         if !self.enabled {
             return;
         }
 
-        info!("🔔 Sending notification: {}", event.event_type);
+        if let Some(receiver) = self.receiver.lock().unwrap().take() {
+            tokio::spawn(Arc::clone(self).run_deliveries(receiver));
+        }
+
+        if self.digest_interval.is_zero() {
+            return;
+        }
+
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(service.digest_interval);
+            interval.tick().await; // First tick completes immediately
+
+            loop {
+                interval.tick().await;
+                service.flush_digests().await;
+            }
+        });
+    }
+
+    /// Deliver queued notifications, a bounded number at a time, so slow or failing channels
+    /// never hold up the caller
+    async fn run_deliveries(self: Arc<Self>, mut receiver: mpsc::Receiver<Delivery>) {
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_DELIVERIES));
 
-        // Send webhook notification
-        if let Some(webhook_url) = &self.webhook_url {
-            if let Err(e) = self.send_webhook_notification(webhook_url, &event).await {
-                error!("Failed to send webhook notification: {}", e);
+        while let Some(delivery) = receiver.recv().await {
+            match delivery {
+                Delivery::Send { channel, notification } => {
+                    let permit = Arc::clone(&permits).acquire_owned().await.expect("delivery semaphore is never closed");
+                    let service = Arc::clone(&self);
+                    tokio::spawn(async move {
+                        service.deliver_with_retry(&service.channels[channel], &notification).await;
+                        drop(permit);
+                    });
+                }
+                Delivery::Drain(done) => {
+                    // Every permit is free once the deliveries in flight have finished
+                    let all = permits.acquire_many(MAX_CONCURRENT_DELIVERIES as u32).await.expect("delivery semaphore is never closed");
+                    drop(all);
+                    let _ = done.send(());
+                }
             }
         }
+    }
+
+    /// Queue a notification for a channel, dropping it if the queue is full
+    fn enqueue(&self, channel: usize, notification: OutgoingNotification) {
+        if let Err(e) = self.queue.try_send(Delivery::Send { channel, notification }) {
+            let reason = match e {
+                mpsc::error::TrySendError::Full(_) => "delivery queue is full",
+                mpsc::error::TrySendError::Closed(_) => "delivery task has stopped",
+            };
+            warn!("📭 Dropping {} notification: {}", self.channels[channel].channel.name(), reason);
+        }
+    }
+
+    /// Wait until every queued notification has been delivered or given up on
+    pub async fn drain(&self) {
+        // Nothing is queued when disabled, and nothing would deliver if the task never started
+        if !self.enabled || self.receiver.lock().unwrap().is_some() {
+            return;
+        }
+
+        let (done, drained) = oneshot::channel();
+        if self.queue.send(Delivery::Drain(done)).await.is_ok() {
+            let _ = drained.await;
+        }
+    }
+
+    /// Route a notification event to its channels; delivery happens in the background
+    pub async fn send_notification(&self, event: NotificationEvent) {
+        if !self.enabled {
+            return;
+        }
 
         // Log notification for file-based monitoring
         self.log_notification(&event);
 
-        // Could add other notification channels here:
-        // - Discord webhook
-        // - Slack webhook  
-        // - Email notifications
-        // - SMS notifications
-        // - Push notifications
+        let (notification, deliveries) = {
+            let mut state = self.state.lock().await;
+
+            let suppressed = if DEDUPLICATED_EVENTS.contains(&event.event_type) {
+                match state.deduplicator.check(&event, self.dedup_window, Instant::now()) {
+                    Some(suppressed) => suppressed,
+                    None => {
+                        info!("🔕 Suppressed repeated {} for pool {}{}", event.event_type, event.pool_address, wallet_suffix(&event));
+                        return;
+                    }
+                }
+            } else {
+                0
+            };
+
+            let mut notification = self.render(&event);
+            if suppressed > 0 {
+                notification.body = format!("{}\n(repeated {} more times since last alert)", notification.body, suppressed);
+            }
+
+            let mut deliveries = Vec::new();
+            for (index, routed) in self.channels.iter().enumerate().filter(|(_, routed)| routed.route.matches(&event)) {
+                let name = routed.channel.name().to_string();
+                let allowed = !routed.route.digest && state.rate_limiters
+                    .entry(name.clone())
+                    .or_insert_with(|| RateLimiter::new(routed.route.max_per_minute, Duration::from_secs(60)))
+                    .try_acquire(Instant::now());
+
+                if allowed {
+                    deliveries.push(index);
+                } else if self.digest_interval.is_zero() {
+                    // Digests are never flushed, so holding the event would only grow memory
+                    warn!("⏳ Rate limit reached for {} channel and digests are disabled, dropping event", name);
+                } else {
+                    if !routed.route.digest {
+                        warn!("⏳ Rate limit reached for {} channel, deferring to digest", name);
+                    }
+                    state.digests.entry(name).or_default().push(event.clone());
+                }
+            }
+
+            (notification, deliveries)
+        };
+
+        for channel in deliveries {
+            self.enqueue(channel, notification.clone());
+        }
     }
 
-    /// Send webhook notification
-    async fn send_webhook_notification(&self, webhook_url: &str, event: &NotificationEvent) -> Result<()> {
-        let payload = json!({
-            "event_type": event.event_type.to_string(),
-            "pool_address": event.pool_address,
-            "message": event.message,
-            "data": event.data,
-            "timestamp": event.timestamp.to_rfc3339(),
-            "source": "saros-auto-compound-bot"
-        });
+    /// Queue all pending digests for delivery immediately
+    pub async fn flush_digests(&self) {
+        let pending: Vec<(String, Vec<NotificationEvent>)> = {
+            let mut state = self.state.lock().await;
+            state.digests.drain().filter(|(_, events)| !events.is_empty()).collect()
+        };
 
-        let response = self.client
-            .post(webhook_url)
-            .header("Content-Type", "application/json")
-            .header("User-Agent", "Saros-Auto-Compound-Bot/1.0")
-            .json(&payload)
-            .timeout(std::time::Duration::from_secs(10))
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            info!("✅ Webhook notification sent successfully");
-        } else {
-            warn!("⚠️ Webhook notification failed with status: {}", response.status());
+        for (name, events) in pending {
+            if let Some(channel) = self.channels.iter().position(|c| c.channel.name() == name) {
+                let digest = self.render_digest(events);
+                info!("📬 Sending digest of {} events to {} channel", digest.events.len(), name);
+                self.enqueue(channel, digest);
+            }
+        }
+    }
+
+    /// Deliver to a single channel, retrying transient failures with exponential backoff
+    async fn deliver_with_retry(&self, routed: &RoutedChannel, notification: &OutgoingNotification) {
+        let mut backoff = ExponentialBackoff {
+            initial_interval: Duration::from_millis(500),
+            max_elapsed_time: Some(self.retry_max_elapsed),
+            ..Default::default()
+        };
+
+        loop {
+            match routed.channel.send(notification).await {
+                Ok(()) => return,
+                Err(e) if e.is::<RejectedDelivery>() => {
+                    error!("Failed to send {} notification, not retrying: {}", routed.channel.name(), e);
+                    return;
+                }
+                Err(e) => match backoff.next_backoff() {
+                    Some(duration) => {
+                        warn!("⏳ {} notification failed, retrying in {:.1}s: {}",
+                              routed.channel.name(), duration.as_secs_f64(), e);
+                        tokio::time::sleep(duration).await;
+                    }
+                    None => {
+                        error!("Failed to send {} notification: {}", routed.channel.name(), e);
+                        return;
+                    }
+                },
+            }
         }
+    }
+
+    /// Render a single event using its configured template
+    fn render(&self, event: &NotificationEvent) -> OutgoingNotification {
+        let body = self.templates
+            .get(&event.event_type)
+            .map(|template| render_template(template, event))
+            .unwrap_or_else(|| event.message.clone());
 
-        Ok(())
+        let title = match &event.wallet {
+            Some(wallet) => format!("Saros Auto-Compound [{}]: {}", wallet, event.event_type),
+            None => format!("Saros Auto-Compound: {}", event.event_type),
+        };
+
+        OutgoingNotification {
+            title,
+            body,
+            severity: event.event_type.severity(),
+            events: vec![event.clone()],
+        }
+    }
+
+    /// Render a batch of events into a single digest notification
+    fn render_digest(&self, events: Vec<NotificationEvent>) -> OutgoingNotification {
+        let severity = events
+            .iter()
+            .map(|event| event.event_type.severity())
+            .max()
+            .unwrap_or(NotificationSeverity::Info);
+
+        let body = events
+            .iter()
+            .map(|event| format!(
+                "• {} [{}] {}{}: {}",
+                event.timestamp.format("%H:%M:%S"),
+                event.event_type,
+                event.pool_address,
+                wallet_suffix(event),
+                self.render(event).body
+            ))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        OutgoingNotification {
+            title: format!("Saros Auto-Compound digest: {} events ({})", events.len(), Utc::now().format("%Y-%m-%d %H:%M UTC")),
+            body,
+            severity,
+            events,
+        }
     }
 
     /// Log notification to console/file
@@ -107,118 +402,12 @@ impl NotificationService {
             crate::types::NotificationEventType::APYUpdate => {
                 info!("📈 {}: {}", event.event_type, event.message);
             }
+            crate::types::NotificationEventType::GasBudgetExceeded => {
+                warn!("⛽ {}{}: {}", event.event_type, wallet_suffix(event), event.message);
+            }
         }
     }
 
-    /// Send Discord webhook notification (optional enhancement)
-    pub async fn send_discord_notification(&self, webhook_url: &str, event: &NotificationEvent) -> Result<()> {
-        let color = match event.event_type {
-            crate::types::NotificationEventType::CompoundSuccess => 0x00ff00, // Green
-            crate::types::NotificationEventType::CompoundFailed => 0xff0000,  // Red
-            crate::types::NotificationEventType::CompoundStarted => 0x0099ff, // Blue
-            crate::types::NotificationEventType::EmergencyStop => 0xff6600,   // Orange
-            _ => 0x888888, // Gray
-        };
-
-        let embed = json!({
-            "embeds": [{
-                "title": format!("🤖 Saros Auto-Compound Bot"),
-                "description": event.message,
-                "color": color,
-                "fields": [
-                    {
-                        "name": "Pool Address",
-                        "value": format!("`{}`", event.pool_address),
-                        "inline": false
-                    },
-                    {
-                        "name": "Event Type",
-                        "value": event.event_type.to_string(),
-                        "inline": true
-                    },
-                    {
-                        "name": "Timestamp",
-                        "value": event.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
-                        "inline": true
-                    }
-                ],
-                "footer": {
-                    "text": "Saros Finance Auto-Compound Bot"
-                },
-                "timestamp": event.timestamp.to_rfc3339()
-            }]
-        });
-
-        let response = self.client
-            .post(webhook_url)
-            .header("Content-Type", "application/json")
-            .json(&embed)
-            .timeout(std::time::Duration::from_secs(10))
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            info!("✅ Discord notification sent successfully");
-        } else {
-            warn!("⚠️ Discord notification failed with status: {}", response.status());
-        }
-
-        Ok(())
-    }
-
-    /// Send Slack webhook notification (optional enhancement)
-    pub async fn send_slack_notification(&self, webhook_url: &str, event: &NotificationEvent) -> Result<()> {
-        let emoji = match event.event_type {
-            crate::types::NotificationEventType::CompoundSuccess => ":white_check_mark:",
-            crate::types::NotificationEventType::CompoundFailed => ":x:",
-            crate::types::NotificationEventType::CompoundStarted => ":rocket:",
-            crate::types::NotificationEventType::EmergencyStop => ":warning:",
-            _ => ":information_source:",
-        };
-
-        let payload = json!({
-            "text": format!("{} Saros Auto-Compound Bot", emoji),
-            "blocks": [
-                {
-                    "type": "section",
-                    "text": {
-                        "type": "mrkdwn",
-                        "text": format!("*{}*\n{}", event.event_type, event.message)
-                    }
-                },
-                {
-                    "type": "section",
-                    "fields": [
-                        {
-                            "type": "mrkdwn",
-                            "text": format!("*Pool Address:*\n`{}`", event.pool_address)
-                        },
-                        {
-                            "type": "mrkdwn",
-                            "text": format!("*Timestamp:*\n{}", event.timestamp.format("%Y-%m-%d %H:%M:%S UTC"))
-                        }
-                    ]
-                }
-            ]
-        });
-
-        let response = self.client
-            .post(webhook_url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .timeout(std::time::Duration::from_secs(10))
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            info!("✅ Slack notification sent successfully");
-        } else {
-            warn!("⚠️ Slack notification failed with status: {}", response.status());
-        }
-
-        Ok(())
-    }
-
     /// Create a formatted notification message
     pub fn format_compound_success_message(
         &self,
@@ -266,4 +455,200 @@ impl NotificationService {
             pool_address, current_gas_price, max_gas_price
         )
     }
-}
\ No newline at end of file
+}
+
+/// Sliding-window rate limiter
+#[derive(Debug)]
+struct RateLimiter {
+    max_events: u32,
+    window: Duration,
+    sent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    fn new(max_events: u32, window: Duration) -> Self {
+        Self {
+            max_events,
+            window,
+            sent: VecDeque::new(),
+        }
+    }
+
+    /// Record a send if the window has capacity
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        if self.max_events == 0 {
+            return true;
+        }
+
+        while let Some(&oldest) = self.sent.front() {
+            if now.duration_since(oldest) >= self.window {
+                self.sent.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.sent.len() < self.max_events as usize {
+            self.sent.push_back(now);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Suppresses repeats of the same event type for the same wallet and pool within a window
+#[derive(Debug, Default)]
+struct Deduplicator {
+    last_sent: HashMap<(NotificationEventType, Option<String>, String), (Instant, u64)>,
+}
+
+impl Deduplicator {
+    /// Returns the number of suppressed repeats if the event should be sent, or None to suppress it
+    fn check(&mut self, event: &NotificationEvent, window: Duration, now: Instant) -> Option<u64> {
+        let key = (event.event_type, event.wallet.clone(), event.pool_address.clone());
+
+        match self.last_sent.get_mut(&key) {
+            Some((sent_at, suppressed)) if now.duration_since(*sent_at) < window => {
+                *suppressed += 1;
+                None
+            }
+            Some((sent_at, suppressed)) => {
+                let repeated = *suppressed;
+                *sent_at = now;
+                *suppressed = 0;
+                Some(repeated)
+            }
+            None => {
+                self.last_sent.insert(key, (now, 0));
+                Some(0)
+            }
+        }
+    }
+}
+
+/// Render ` (wallet <name>)` for wallet-scoped events, or nothing
+fn wallet_suffix(event: &NotificationEvent) -> String {
+    event.wallet
+        .as_ref()
+        .map(|wallet| format!(" (wallet {})", wallet))
+        .unwrap_or_default()
+}
+
+/// Fill `{event_type}`, `{severity}`, `{wallet}`, `{pool_address}`, `{message}`, `{timestamp}` and `{data.<field>}` placeholders
+fn render_template(template: &str, event: &NotificationEvent) -> String {
+    let mut rendered = template
+        .replace("{event_type}", &event.event_type.to_string())
+        .replace("{severity}", &event.event_type.severity().to_string())
+        .replace("{wallet}", event.wallet.as_deref().unwrap_or(""))
+        .replace("{pool_address}", &event.pool_address)
+        .replace("{message}", &event.message)
+        .replace("{timestamp}", &event.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string());
+
+    if let Some(data) = event.data.as_object() {
+        for (key, value) in data {
+            let text = match value {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Number(n) => match n.as_f64() {
+                    Some(f) if n.is_f64() => format!("{:.6}", f),
+                    _ => n.to_string(),
+                },
+                other => other.to_string(),
+            };
+            rendered = rendered.replace(&format!("{{data.{}}}", key), &text);
+        }
+    }
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: NotificationEventType, pool: &str) -> NotificationEvent {
+        NotificationEvent {
+            event_type,
+            wallet: None,
+            pool_address: pool.to_string(),
+            message: "test".to_string(),
+            data: serde_json::json!({ "gas_used": 0.5, "signature": "abc", "count": 3 }),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let mut limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire(start));
+        assert!(limiter.try_acquire(start));
+        assert!(!limiter.try_acquire(start + Duration::from_secs(30)));
+        assert!(limiter.try_acquire(start + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn test_deduplicator_counts_suppressed_repeats() {
+        let mut dedup = Deduplicator::default();
+        let window = Duration::from_secs(600);
+        let start = Instant::now();
+        let gas = event(NotificationEventType::HighGasPrice, "pool1");
+
+        assert_eq!(dedup.check(&gas, window, start), Some(0));
+        assert_eq!(dedup.check(&gas, window, start + Duration::from_secs(10)), None);
+        assert_eq!(dedup.check(&gas, window, start + Duration::from_secs(20)), None);
+
+        // Other pools are tracked independently
+        let other = event(NotificationEventType::HighGasPrice, "pool2");
+        assert_eq!(dedup.check(&other, window, start + Duration::from_secs(20)), Some(0));
+
+        // As are other wallets on the same pool
+        let treasury = NotificationEvent {
+            wallet: Some("treasury".to_string()),
+            ..event(NotificationEventType::HighGasPrice, "pool1")
+        };
+        assert_eq!(dedup.check(&treasury, window, start + Duration::from_secs(20)), Some(0));
+
+        assert_eq!(dedup.check(&gas, window, start + Duration::from_secs(601)), Some(2));
+    }
+
+    #[test]
+    fn test_route_matching() {
+        let route = NotificationRoute {
+            min_severity: NotificationSeverity::Warning,
+            ..Default::default()
+        };
+        assert!(route.matches(&event(NotificationEventType::CompoundFailed, "pool")));
+        assert!(route.matches(&event(NotificationEventType::EmergencyStop, "pool")));
+        assert!(!route.matches(&event(NotificationEventType::CompoundSuccess, "pool")));
+
+        let route = NotificationRoute {
+            event_types: vec![NotificationEventType::CompoundSuccess],
+            ..Default::default()
+        };
+        assert!(route.matches(&event(NotificationEventType::CompoundSuccess, "pool")));
+        assert!(!route.matches(&event(NotificationEventType::CompoundFailed, "pool")));
+
+        let route = NotificationRoute {
+            wallets: vec!["treasury".to_string()],
+            ..Default::default()
+        };
+        let wallet_event = |wallet: Option<&str>| NotificationEvent {
+            wallet: wallet.map(str::to_string),
+            ..event(NotificationEventType::CompoundSuccess, "pool")
+        };
+        assert!(route.matches(&wallet_event(Some("treasury"))));
+        assert!(!route.matches(&wallet_event(Some("market-maker"))));
+        assert!(!route.matches(&wallet_event(None)));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let rendered = render_template(
+            "{event_type} on {pool_address}: gas {data.gas_used}, sig {data.signature}, n={data.count}",
+            &event(NotificationEventType::CompoundSuccess, "pool1"),
+        );
+        assert_eq!(rendered, "COMPOUND_SUCCESS on pool1: gas 0.500000, sig abc, n=3");
+    }
+}
@@ -1,24 +1,23 @@
 use anyhow::Result;
 use chrono::Utc;
 use log::{error, info, warn};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 
-use saros_dlmm_sdk::{DLMMClient, UserPosition};
+use saros_dlmm_sdk::{DLMMClient, RpcConnection, UserPosition};
 
 use crate::types::{Position, PoolInfo};
 
 /// Monitors positions and detects changes
 pub struct PositionMonitor {
-    rpc_client: Arc<RpcClient>,
+    rpc_client: Arc<RpcConnection>,
     dlmm_client: DLMMClient,
     position_cache: Arc<RwLock<HashMap<String, Position>>>,
 }
 
 impl PositionMonitor {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+    pub fn new(rpc_client: Arc<RpcConnection>) -> Self {
         let dlmm_client = DLMMClient::new_with_rpc(rpc_client.clone());
 
         Self {
@@ -28,15 +27,11 @@ impl PositionMonitor {
         }
     }
 
-    /// Get current position for a pool
-    pub async fn get_position(&self, pool_address: Pubkey) -> Result<Position> {
-        info!("📊 Getting position for pool: {}", pool_address);
+    /// Get the current position a wallet holds in a pool
+    pub async fn get_position(&self, pool_address: Pubkey, owner: Pubkey) -> Result<Position> {
+        info!("📊 Getting position for pool {} (owner {})", pool_address, owner);
 
-        // For demo purposes, use a placeholder user pubkey
-        // In production, this would be the actual user's wallet
-        let user_pubkey = Pubkey::new_unique();
-
-        let user_position = self.dlmm_client.get_user_position(&pool_address, &user_pubkey).await?;
+        let user_position = self.dlmm_client.get_user_position(&pool_address, &owner).await?;
 
         let position = Position {
             pool_address,
@@ -49,7 +44,7 @@ impl PositionMonitor {
 
         // Update cache
         let mut cache = self.position_cache.write().await;
-        cache.insert(pool_address.to_string(), position.clone());
+        cache.insert(cache_key(pool_address, owner), position.clone());
 
         info!("✅ Position retrieved: {:.6} LP tokens", position.lp_token_amount);
 
@@ -57,11 +52,11 @@ impl PositionMonitor {
     }
 
     /// Monitor positions for changes and return difference
-    pub async fn check_position_changes(&self, pool_address: Pubkey) -> Result<Option<PositionChange>> {
-        let current_position = self.get_position(pool_address).await?;
+    pub async fn check_position_changes(&self, pool_address: Pubkey, owner: Pubkey) -> Result<Option<PositionChange>> {
+        let current_position = self.get_position(pool_address, owner).await?;
         
         let cache = self.position_cache.read().await;
-        if let Some(previous_position) = cache.get(&pool_address.to_string()) {
+        if let Some(previous_position) = cache.get(&cache_key(pool_address, owner)) {
             let change = self.calculate_position_change(previous_position, &current_position);
             if change.has_significant_change() {
                 info!("📈 Position change detected for pool {}", pool_address);
@@ -109,12 +104,12 @@ impl PositionMonitor {
     }
 
     /// Monitor multiple positions concurrently
-    pub async fn monitor_positions(&self, pool_addresses: Vec<Pubkey>) -> Result<Vec<Position>> {
+    pub async fn monitor_positions(&self, pool_addresses: Vec<Pubkey>, owner: Pubkey) -> Result<Vec<Position>> {
         info!("👀 Monitoring {} positions", pool_addresses.len());
 
         let futures: Vec<_> = pool_addresses
             .iter()
-            .map(|&pool_address| self.get_position(pool_address))
+            .map(|&pool_address| self.get_position(pool_address, owner))
             .collect();
 
         let results = futures::future::join_all(futures).await;
@@ -134,13 +129,13 @@ impl PositionMonitor {
     }
 
     /// Calculate position performance metrics
-    pub async fn calculate_performance_metrics(&self, pool_address: Pubkey, duration_hours: f64) -> Result<PerformanceMetrics> {
-        let current_position = self.get_position(pool_address).await?;
+    pub async fn calculate_performance_metrics(&self, pool_address: Pubkey, owner: Pubkey, duration_hours: f64) -> Result<PerformanceMetrics> {
+        let current_position = self.get_position(pool_address, owner).await?;
         let pool_info = self.get_pool_info(pool_address).await?;
 
         // Get historical position (from cache or estimate)
         let cache = self.position_cache.read().await;
-        let historical_position = cache.get(&pool_address.to_string()).cloned()
+        let historical_position = cache.get(&cache_key(pool_address, owner)).cloned()
             .unwrap_or_else(|| {
                 // If no historical data, create an estimated historical position
                 Position {
@@ -185,18 +180,23 @@ impl PositionMonitor {
     }
 
     /// Get cached position if available
-    pub async fn get_cached_position(&self, pool_address: Pubkey) -> Option<Position> {
+    pub async fn get_cached_position(&self, pool_address: Pubkey, owner: Pubkey) -> Option<Position> {
         let cache = self.position_cache.read().await;
-        cache.get(&pool_address.to_string()).cloned()
+        cache.get(&cache_key(pool_address, owner)).cloned()
     }
 
     /// Update position cache manually
-    pub async fn update_cache(&self, position: Position) {
+    pub async fn update_cache(&self, owner: Pubkey, position: Position) {
         let mut cache = self.position_cache.write().await;
-        cache.insert(position.pool_address.to_string(), position);
+        cache.insert(cache_key(position.pool_address, owner), position);
     }
 }
 
+/// Positions are cached per owner so several wallets can share a pool
+fn cache_key(pool_address: Pubkey, owner: Pubkey) -> String {
+    format!("{}:{}", owner, pool_address)
+}
+
 /// Represents a change in position between two time points
 #[derive(Debug, Clone)]
 pub struct PositionChange {
@@ -1,38 +1,37 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::Utc;
-use log::{error, info, warn};
-use solana_client::rpc_client::RpcClient;
+use log::{debug, error, info, warn};
 use solana_sdk::{
     pubkey::Pubkey,
-    signature::{Keypair, Signature, Signer},
     transaction::Transaction,
 };
 use std::{sync::Arc, time::Duration};
 use backoff::{ExponentialBackoff, backoff::Backoff};
 
-use saros_dlmm_sdk::DLMMClient;
+use saros_dlmm_sdk::{error_category, DLMMClient, RpcConnection, SharedSigner, TransactionOutcome, TransactionSender};
 
 use crate::types::{CompoundResult, StrategyType};
 
 /// Specialized component for harvesting rewards efficiently
 pub struct RewardHarvester {
-    rpc_client: Arc<RpcClient>,
-    wallet: Arc<Keypair>,
+    wallet: SharedSigner,
     dlmm_client: DLMMClient,
+    transaction_sender: Arc<TransactionSender>,
 }
 
 impl RewardHarvester {
-    pub fn new(rpc_client: Arc<RpcClient>, wallet: Arc<Keypair>) -> Self {
-        let dlmm_client = DLMMClient::new_with_rpc(rpc_client.clone());
+    pub fn new(rpc_client: Arc<RpcConnection>, wallet: SharedSigner, transaction_sender: Arc<TransactionSender>) -> Self {
+        let dlmm_client = DLMMClient::new_with_rpc(rpc_client);
 
         Self {
-            rpc_client,
             wallet,
             dlmm_client,
+            transaction_sender,
         }
     }
 
-    /// Harvest rewards with retry logic and optimization
+    /// Harvest rewards, retrying only failures classified as transient (RPC outages,
+    /// rate limits, expired blockhashes); terminal errors are returned immediately
     pub async fn harvest_rewards(
         &self,
         pool_address: Pubkey,
@@ -52,11 +51,17 @@ impl RewardHarvester {
                     return Ok(result);
                 }
                 Err(e) => {
+                    let category = error_category(&e);
+                    if !category.is_retryable() {
+                        error!("❌ Harvest failed ({}), not retrying: {:#}", category, e);
+                        return Err(e);
+                    }
+
                     if let Some(duration) = backoff.next_backoff() {
-                        warn!("⏳ Harvest failed, retrying in {:.1}s: {}", duration.as_secs_f64(), e);
+                        warn!("⏳ Harvest failed ({}), retrying in {:.1}s: {:#}", category, duration.as_secs_f64(), e);
                         tokio::time::sleep(duration).await;
                     } else {
-                        error!("❌ Harvest failed after all retries: {}", e);
+                        error!("❌ Harvest failed after all retries: {:#}", e);
                         return Err(e);
                     }
                 }
@@ -93,7 +98,7 @@ impl RewardHarvester {
         };
 
         // Send and confirm transaction
-        let signature = self.send_transaction_with_retry(transaction).await?;
+        let outcome = self.send_transaction(transaction).await?;
 
         // Verify harvest was successful
         let new_pending_rewards = self.get_pending_rewards(pool_address).await?;
@@ -104,9 +109,9 @@ impl RewardHarvester {
         }
 
         Ok(HarvestResult {
-            signature: signature.to_string(),
+            signature: outcome.signature.to_string(),
             rewards_harvested: actual_harvested,
-            gas_used: self.estimate_gas_used(&signature).await?,
+            gas_used: outcome.fee_sol().unwrap_or(0.000005), // Fallback to the base fee
             timestamp: Utc::now(),
         })
     }
@@ -128,7 +133,7 @@ impl RewardHarvester {
         self.dlmm_client.claim_rewards(
             &pool_address,
             &self.wallet.pubkey(),
-        ).await.map_err(|e| anyhow::anyhow!("Claim rewards failed: {}", e))
+        ).await.context("Claim rewards failed")
     }
 
     /// Create harvest transaction for staking rewards
@@ -138,7 +143,7 @@ impl RewardHarvester {
         self.dlmm_client.claim_staking_rewards(
             &pool_address,
             &self.wallet.pubkey(),
-        ).await.map_err(|e| anyhow::anyhow!("Claim staking rewards failed: {}", e))
+        ).await.context("Claim staking rewards failed")
     }
 
     /// Create harvest transaction for farming rewards
@@ -148,53 +153,23 @@ impl RewardHarvester {
         self.dlmm_client.claim_farming_rewards(
             &pool_address,
             &self.wallet.pubkey(),
-        ).await.map_err(|e| anyhow::anyhow!("Claim farming rewards failed: {}", e))
+        ).await.context("Claim farming rewards failed")
     }
 
-    /// Send transaction with retry logic
-    async fn send_transaction_with_retry(&self, transaction: Transaction) -> Result<Signature> {
-        let mut backoff = ExponentialBackoff {
-            max_elapsed_time: Some(Duration::from_secs(30)),
-            ..Default::default()
-        };
+    /// Simulate a transaction to size its compute budget, then send it, rebuilding it
+    /// with a fresh blockhash until it lands. Fails before paying fees if simulation fails.
+    async fn send_transaction(&self, transaction: Transaction) -> Result<TransactionOutcome> {
+        let (transaction, simulation) = self.dlmm_client
+            .budget_compute_units(&transaction, &self.wallet.pubkey())
+            .await?;
+        debug!("🧮 Harvest simulation used {:?} compute units", simulation.compute_units_consumed);
 
-        loop {
-            match self.rpc_client.send_and_confirm_transaction(&transaction) {
-                Ok(signature) => return Ok(signature),
-                Err(e) => {
-                    if let Some(duration) = backoff.next_backoff() {
-                        warn!("Transaction failed, retrying in {:.1}s: {}", duration.as_secs_f64(), e);
-                        tokio::time::sleep(duration).await;
-                    } else {
-                        return Err(anyhow::anyhow!("Transaction failed after retries: {}", e));
-                    }
-                }
-            }
-        }
-    }
+        let outcome = self.transaction_sender
+            .send_transaction(&transaction, &[self.wallet.as_ref()])
+            .await?
+            .into_result()?;
 
-    /// Estimate gas used for a completed transaction
-    async fn estimate_gas_used(&self, signature: &Signature) -> Result<f64> {
-        // Get transaction details to calculate actual gas used
-        match self.rpc_client.get_transaction_with_config(signature, solana_client::rpc_config::RpcTransactionConfig {
-            encoding: Some(solana_transaction_status::UiTransactionEncoding::Json),
-            commitment: Some(solana_sdk::commitment_config::CommitmentConfig::confirmed()),
-            max_supported_transaction_version: Some(0),
-        }) {
-            Ok(transaction) => {
-                if let Some(meta) = transaction.transaction.meta {
-                    // Convert lamports to SOL
-                    Ok(meta.fee as f64 / 1_000_000_000.0)
-                } else {
-                    // Fallback to estimated fee
-                    Ok(0.000005) // 5000 lamports
-                }
-            }
-            Err(_) => {
-                // Fallback to estimated fee
-                Ok(0.000005)
-            }
-        }
+        Ok(outcome)
     }
 
     /// Batch harvest from multiple pools (gas optimization)
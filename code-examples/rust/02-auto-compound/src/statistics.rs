@@ -1,21 +1,27 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use log::info;
+use log::{debug, info};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::types::{CompoundResult, GlobalStatistics, PoolStatistics};
+use crate::circuit_breaker::TripReason;
+use crate::types::{CompoundResult, EmergencyWithdrawResult, GlobalStatistics, PoolStatistics};
 
 /// Manages statistics for all compound operations
 pub struct StatisticsManager {
     global_stats: GlobalStatistics,
     pool_stats: HashMap<String, PoolStatistics>,
+    wallet_stats: HashMap<String, WalletStatistics>,
     compound_history: Vec<CompoundHistoryEntry>,
+    incidents: Vec<IncidentRecord>,
+    /// Separate ledger for simulated (dry-run) compounds
+    paper_ledger: Option<Box<StatisticsManager>>,
     start_time: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompoundHistoryEntry {
+    pub wallet: String,
     pub pool_address: String,
     pub result: CompoundResult,
     pub apy_before: f64,
@@ -23,6 +29,24 @@ pub struct CompoundHistoryEntry {
     pub position_growth: f64,
 }
 
+/// A circuit breaker trip and the emergency action taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentRecord {
+    pub wallet: String,
+    pub pool_address: String,
+    pub reason: TripReason,
+    pub withdrawal: Option<EmergencyWithdrawResult>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Totals and per-pool statistics for a single wallet
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletStatistics {
+    pub wallet: String,
+    pub totals: GlobalStatistics,
+    pub pool_stats: HashMap<String, PoolStatistics>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceReport {
     pub global_stats: GlobalStatistics,
@@ -53,62 +77,49 @@ impl StatisticsManager {
         Self {
             global_stats: GlobalStatistics::default(),
             pool_stats: HashMap::new(),
+            wallet_stats: HashMap::new(),
             compound_history: Vec::new(),
+            incidents: Vec::new(),
+            paper_ledger: None,
             start_time: Utc::now(),
         }
     }
 
-    /// Record a compound operation result
-    pub async fn record_compound_result(&mut self, pool_address: &str, result: &CompoundResult) {
-        info!("📊 Recording compound result for pool: {}", pool_address);
-
-        // Update global statistics
-        self.global_stats.total_compounds += 1;
-        
-        if result.success {
-            self.global_stats.successful_compounds += 1;
-            self.global_stats.total_rewards_harvested += result.rewards_harvested;
-            self.global_stats.total_reinvested += result.amount_reinvested;
+    /// Record a compound operation result (simulated results go to the paper ledger)
+    pub async fn record_compound_result(&mut self, wallet: &str, pool_address: &str, result: &CompoundResult) {
+        if result.simulated {
+            info!("🧪 Recording simulated compound result in paper ledger for {}/{}", wallet, pool_address);
+            self.paper_ledger
+                .get_or_insert_with(|| Box::new(StatisticsManager::new()))
+                .apply_compound_result(wallet, pool_address, result);
         } else {
-            self.global_stats.failed_compounds += 1;
+            info!("📊 Recording compound result for {}/{}", wallet, pool_address);
+            self.apply_compound_result(wallet, pool_address, result);
         }
+    }
 
-        self.global_stats.total_gas_spent += result.gas_used;
-        self.global_stats.success_rate = if self.global_stats.total_compounds > 0 {
-            (self.global_stats.successful_compounds as f64 / self.global_stats.total_compounds as f64) * 100.0
-        } else {
-            0.0
-        };
-        
-        self.global_stats.net_profit = self.global_stats.total_rewards_harvested - self.global_stats.total_gas_spent;
-        self.global_stats.last_compound_time = Some(result.timestamp);
-
-        // Update pool-specific statistics
-        let pool_stats = self.pool_stats.entry(pool_address.to_string()).or_insert_with(|| {
-            PoolStatistics {
-                pool_address: pool_address.parse().unwrap_or_default(),
-                ..Default::default()
-            }
-        });
+    /// Paper-trading statistics recorded from dry-run compounds
+    pub fn paper_ledger(&self) -> Option<&StatisticsManager> {
+        self.paper_ledger.as_deref()
+    }
 
-        pool_stats.compounds += 1;
-        
-        if result.success {
-            pool_stats.total_harvested += result.rewards_harvested;
-            pool_stats.total_reinvested += result.amount_reinvested;
-        }
-        
-        pool_stats.total_gas += result.gas_used;
-        pool_stats.last_compound = Some(result.timestamp);
-        
-        pool_stats.average_reward = if pool_stats.compounds > 0 {
-            pool_stats.total_harvested / pool_stats.compounds as f64
-        } else {
-            0.0
-        };
+    /// Update statistics with a compound result without routing it to a ledger
+    pub fn apply_compound_result(&mut self, wallet: &str, pool_address: &str, result: &CompoundResult) {
+        // Update global and pool statistics across all wallets
+        accumulate_totals(&mut self.global_stats, result);
+        accumulate_pool(&mut self.pool_stats, pool_address, result);
+
+        // Update the wallet's own totals and pools
+        let wallet_stats = self.wallet_stats.entry(wallet.to_string()).or_insert_with(|| WalletStatistics {
+            wallet: wallet.to_string(),
+            ..Default::default()
+        });
+        accumulate_totals(&mut wallet_stats.totals, result);
+        accumulate_pool(&mut wallet_stats.pool_stats, pool_address, result);
 
         // Add to history
         let history_entry = CompoundHistoryEntry {
+            wallet: wallet.to_string(),
             pool_address: pool_address.to_string(),
             result: result.clone(),
             apy_before: 0.0, // Would be calculated from position data
@@ -123,10 +134,38 @@ impl StatisticsManager {
             self.compound_history.remove(0);
         }
 
-        info!("✅ Statistics updated - Global compounds: {}, Success rate: {:.1}%", 
+        debug!("✅ Statistics updated - Global compounds: {}, Success rate: {:.1}%", 
               self.global_stats.total_compounds, self.global_stats.success_rate);
     }
 
+    /// Record a circuit breaker incident, counting the emergency withdrawal's fees as gas spent
+    pub async fn record_incident(&mut self, incident: IncidentRecord) {
+        info!("🚨 Recording incident for {}/{}: {}", incident.wallet, incident.pool_address, incident.reason);
+
+        let gas_used = incident.withdrawal.as_ref().map_or(0.0, |withdrawal| withdrawal.gas_used);
+
+        self.global_stats.emergency_stops += 1;
+        accumulate_gas(&mut self.global_stats, &mut self.pool_stats, &incident.pool_address, gas_used);
+
+        let wallet_stats = self.wallet_stats.entry(incident.wallet.clone()).or_insert_with(|| WalletStatistics {
+            wallet: incident.wallet.clone(),
+            ..Default::default()
+        });
+        wallet_stats.totals.emergency_stops += 1;
+        accumulate_gas(&mut wallet_stats.totals, &mut wallet_stats.pool_stats, &incident.pool_address, gas_used);
+
+        self.incidents.push(incident);
+    }
+
+    /// Get recorded incidents, optionally filtered by pool
+    pub async fn get_incidents(&self, pool_address: Option<&str>) -> Vec<IncidentRecord> {
+        self.incidents
+            .iter()
+            .filter(|incident| pool_address.is_none_or(|pool| incident.pool_address == pool))
+            .cloned()
+            .collect()
+    }
+
     /// Get global statistics
     pub async fn get_global_statistics(&self) -> GlobalStatistics {
         let mut stats = self.global_stats.clone();
@@ -151,6 +190,18 @@ impl StatisticsManager {
         self.pool_stats.values().cloned().collect()
     }
 
+    /// Get statistics for a specific wallet
+    pub async fn get_wallet_statistics(&self, wallet: &str) -> Option<WalletStatistics> {
+        self.wallet_stats.get(wallet).cloned()
+    }
+
+    /// Get statistics for every wallet that has recorded activity
+    pub async fn get_all_wallet_statistics(&self) -> Vec<WalletStatistics> {
+        let mut stats: Vec<WalletStatistics> = self.wallet_stats.values().cloned().collect();
+        stats.sort_by(|a, b| a.wallet.cmp(&b.wallet));
+        stats
+    }
+
     /// Calculate average APY boost from compounding
     async fn calculate_average_apy_boost(&self) -> f64 {
         if self.compound_history.is_empty() {
@@ -363,20 +414,28 @@ impl StatisticsManager {
         
         self.global_stats = GlobalStatistics::default();
         self.pool_stats.clear();
+        self.wallet_stats.clear();
         self.compound_history.clear();
+        self.incidents.clear();
+        self.paper_ledger = None;
         self.start_time = Utc::now();
     }
 
     /// Export statistics to JSON
     pub async fn export_statistics(&self) -> Result<String> {
-        let export_data = StatisticsExport {
+        Ok(serde_json::to_string_pretty(&self.build_export())?)
+    }
+
+    fn build_export(&self) -> StatisticsExport {
+        StatisticsExport {
             global_stats: self.global_stats.clone(),
             pool_stats: self.pool_stats.clone(),
+            wallet_stats: self.wallet_stats.clone(),
             compound_history: self.compound_history.clone(),
+            incidents: self.incidents.clone(),
+            paper_ledger: self.paper_ledger.as_ref().map(|ledger| Box::new(ledger.build_export())),
             exported_at: Utc::now(),
-        };
-
-        Ok(serde_json::to_string_pretty(&export_data)?)
+        }
     }
 }
 
@@ -394,6 +453,79 @@ pub struct DashboardMetrics {
 struct StatisticsExport {
     global_stats: GlobalStatistics,
     pool_stats: HashMap<String, PoolStatistics>,
+    wallet_stats: HashMap<String, WalletStatistics>,
     compound_history: Vec<CompoundHistoryEntry>,
+    incidents: Vec<IncidentRecord>,
+    paper_ledger: Option<Box<StatisticsExport>>,
     exported_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+/// Add a compound result to a set of aggregate totals
+fn accumulate_totals(stats: &mut GlobalStatistics, result: &CompoundResult) {
+    stats.total_compounds += 1;
+
+    if result.success {
+        stats.successful_compounds += 1;
+        stats.total_rewards_harvested += result.rewards_harvested;
+        stats.total_reinvested += result.amount_reinvested;
+    } else {
+        stats.failed_compounds += 1;
+    }
+
+    stats.total_gas_spent += result.gas_used;
+    stats.success_rate = if stats.total_compounds > 0 {
+        (stats.successful_compounds as f64 / stats.total_compounds as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    stats.net_profit = stats.total_rewards_harvested - stats.total_gas_spent;
+    stats.last_compound_time = Some(result.timestamp);
+}
+
+/// Add fees paid outside a compound (e.g. an emergency withdrawal) to a set of totals and the pool's statistics
+fn accumulate_gas(
+    stats: &mut GlobalStatistics,
+    pool_stats: &mut HashMap<String, PoolStatistics>,
+    pool_address: &str,
+    gas_used: f64,
+) {
+    if gas_used <= 0.0 {
+        return;
+    }
+
+    stats.total_gas_spent += gas_used;
+    stats.net_profit = stats.total_rewards_harvested - stats.total_gas_spent;
+    pool_entry(pool_stats, pool_address).total_gas += gas_used;
+}
+
+/// Statistics of a pool, created on first use
+fn pool_entry<'a>(pool_stats: &'a mut HashMap<String, PoolStatistics>, pool_address: &str) -> &'a mut PoolStatistics {
+    pool_stats.entry(pool_address.to_string()).or_insert_with(|| {
+        PoolStatistics {
+            pool_address: pool_address.parse().unwrap_or_default(),
+            ..Default::default()
+        }
+    })
+}
+
+/// Add a compound result to the statistics of the pool it ran against
+fn accumulate_pool(pool_stats: &mut HashMap<String, PoolStatistics>, pool_address: &str, result: &CompoundResult) {
+    let pool_stats = pool_entry(pool_stats, pool_address);
+
+    pool_stats.compounds += 1;
+
+    if result.success {
+        pool_stats.total_harvested += result.rewards_harvested;
+        pool_stats.total_reinvested += result.amount_reinvested;
+    }
+
+    pool_stats.total_gas += result.gas_used;
+    pool_stats.last_compound = Some(result.timestamp);
+
+    pool_stats.average_reward = if pool_stats.compounds > 0 {
+        pool_stats.total_harvested / pool_stats.compounds as f64
+    } else {
+        0.0
+    };
+}
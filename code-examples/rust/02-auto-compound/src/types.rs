@@ -1,23 +1,208 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use saros_dlmm_sdk::{CostBasisMethod, LedgerEntry, PriorityLevel, RpcEndpoint, SignerSource};
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
-use std::{fmt, str::FromStr};
+use std::{collections::HashMap, fmt, path::PathBuf, str::FromStr};
 
 /// Auto-compound configuration
 #[derive(Debug, Clone)]
 pub struct AutoCompoundConfig {
-    pub rpc_url: String,
-    pub private_key: String,
+    /// RPC endpoints, the first being the primary
+    pub rpc_endpoints: Vec<RpcEndpoint>,
+    pub rpc_timeout_secs: u64,
+    pub rpc_max_retries: u32,
+    /// Wallets served by this process; strategies select one by name
+    pub wallets: Vec<WalletConfig>,
     pub network: String,
+    pub dry_run: bool,
+    /// Highest per-transaction network fee at which compounds still run (SOL)
     pub max_gas_price: f64,
+    /// Percentile of recent priority fees to bid at
+    pub priority_fee_level: PriorityLevel,
+    pub gas_budget: GasBudgetConfig,
     pub enable_notifications: bool,
     pub webhook_url: Option<String>,
+    pub notifications: NotificationConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Where to export the accounting ledger on shutdown (None = no export)
+    pub ledger_export: Option<LedgerExportConfig>,
 }
 
-/// Compound strategy configuration
+/// Accounting ledger export settings
 #[derive(Debug, Clone)]
+pub struct LedgerExportConfig {
+    /// Directory receiving the event, realised gain and open lot CSV files
+    pub dir: PathBuf,
+    /// JSON lines file every ledger entry is appended to as it is recorded
+    pub journal: PathBuf,
+    pub cost_basis_method: CostBasisMethod,
+}
+
+/// Name of the wallet used when a strategy does not select one
+pub const DEFAULT_WALLET: &str = "default";
+
+/// A wallet (or sub-account) with its own signer, limits and notifications
+#[derive(Debug, Clone)]
+pub struct WalletConfig {
+    pub name: String,
+    /// Where to load the signer from; required unless running dry
+    pub signer: Option<SignerSource>,
+    /// Fee limits across all of the wallet's strategies
+    pub gas_budget: BudgetLimits,
+    /// Maximum number of strategies running for this wallet (None = unlimited)
+    pub max_strategies: Option<usize>,
+    /// Webhook receiving only this wallet's notifications
+    pub webhook_url: Option<String>,
+}
+
+impl WalletConfig {
+    pub fn new(name: impl Into<String>, signer: Option<SignerSource>) -> Self {
+        Self {
+            name: name.into(),
+            signer,
+            gas_budget: BudgetLimits::default(),
+            max_strategies: None,
+            webhook_url: None,
+        }
+    }
+}
+
+/// Fee spending limits for one budget scope (SOL over rolling windows; None = unlimited)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetLimits {
+    #[serde(default)]
+    pub daily: Option<f64>,
+    #[serde(default)]
+    pub weekly: Option<f64>,
+}
+
+impl BudgetLimits {
+    /// Fill unset limits from `fallback`
+    pub fn or(self, fallback: BudgetLimits) -> BudgetLimits {
+        BudgetLimits {
+            daily: self.daily.or(fallback.daily),
+            weekly: self.weekly.or(fallback.weekly),
+        }
+    }
+}
+
+/// Fee budget enforcement settings
+#[derive(Debug, Clone, Default)]
+pub struct GasBudgetConfig {
+    /// Limits applied to every strategy that does not set its own
+    pub strategy: BudgetLimits,
+    /// Limits across all wallets and strategies
+    pub global: BudgetLimits,
+    /// Highest priority fee paid per transaction (lamports)
+    pub max_priority_fee_lamports: Option<u64>,
+    /// File the spend ledger is persisted to so restarts keep counting
+    pub state_file: Option<PathBuf>,
+}
+
+/// Circuit breaker thresholds for pausing strategies and emergency withdrawal
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    pub max_consecutive_failures: u32,
+    /// Trip when APY falls this far below its moving average (%)
+    pub max_apy_drop_percent: f64,
+    /// Trip when TVL falls this far between two consecutive checks (%)
+    pub max_tvl_drop_percent: f64,
+    /// Trip when the active bin price deviates this far from its moving average (%)
+    pub max_price_deviation_percent: f64,
+    /// Wallet that receives funds after an emergency withdrawal
+    pub safe_address: Option<Pubkey>,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_consecutive_failures: 3,
+            max_apy_drop_percent: 50.0,
+            max_tvl_drop_percent: 30.0,
+            max_price_deviation_percent: 20.0,
+            safe_address: None,
+        }
+    }
+}
+
+/// Notification channel, routing and delivery configuration
+#[derive(Debug, Clone, Default)]
+pub struct NotificationConfig {
+    pub discord_webhook_url: Option<String>,
+    pub slack_webhook_url: Option<String>,
+    pub telegram: Option<TelegramConfig>,
+    pub email: Option<EmailConfig>,
+    pub log_file: Option<String>,
+    pub stdout_json: bool,
+    pub routes: HashMap<String, NotificationRoute>,
+    pub templates: HashMap<NotificationEventType, String>,
+    pub dedup_window_secs: u64,
+    pub digest_interval_secs: u64,
+    pub retry_max_elapsed_secs: u64,
+}
+
+/// Telegram bot configuration
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub chat_id: String,
+}
+
+/// SMTP email configuration
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Routing rule deciding which events a channel receives and how
+#[derive(Debug, Clone)]
+pub struct NotificationRoute {
+    /// Event types delivered to the channel (empty = all)
+    pub event_types: Vec<NotificationEventType>,
+    pub min_severity: NotificationSeverity,
+    /// Maximum immediate deliveries per minute (0 = unlimited); overflow goes to the digest
+    pub max_per_minute: u32,
+    /// Batch every matching event into the periodic digest instead of sending immediately
+    pub digest: bool,
+    /// Wallets whose events are delivered to the channel (empty = all)
+    pub wallets: Vec<String>,
+}
+
+impl Default for NotificationRoute {
+    fn default() -> Self {
+        Self {
+            event_types: Vec::new(),
+            min_severity: NotificationSeverity::Info,
+            max_per_minute: 30,
+            digest: false,
+            wallets: Vec::new(),
+        }
+    }
+}
+
+impl NotificationRoute {
+    /// Check whether an event should be delivered through this route
+    pub fn matches(&self, event: &NotificationEvent) -> bool {
+        event.event_type.severity() >= self.min_severity
+            && (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && (self.wallets.is_empty() || event.wallet.as_ref().is_some_and(|wallet| self.wallets.contains(wallet)))
+    }
+}
+
+/// Compound strategy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompoundStrategyConfig {
+    /// Name of the wallet that owns the position
+    #[serde(default = "default_wallet")]
+    pub wallet: String,
     pub pool_address: Pubkey,
     pub strategy_type: StrategyType,
     pub interval_ms: u64,
@@ -25,6 +210,54 @@ pub struct CompoundStrategyConfig {
     pub reinvest_percentage: u8,
     pub max_slippage: Option<f64>,
     pub emergency_withdraw: bool,
+    /// Fee limits for this strategy, falling back to the configured defaults
+    #[serde(default)]
+    pub gas_budget: BudgetLimits,
+}
+
+impl CompoundStrategyConfig {
+    /// Key identifying this strategy among all running strategies
+    pub fn key(&self) -> StrategyKey {
+        StrategyKey::new(self.wallet.clone(), self.pool_address)
+    }
+}
+
+fn default_wallet() -> String {
+    DEFAULT_WALLET.to_string()
+}
+
+/// Identifies a strategy by the wallet that owns the position and the pool
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StrategyKey {
+    pub wallet: String,
+    pub pool_address: Pubkey,
+}
+
+impl StrategyKey {
+    pub fn new(wallet: impl Into<String>, pool_address: Pubkey) -> Self {
+        Self {
+            wallet: wallet.into(),
+            pool_address,
+        }
+    }
+}
+
+impl fmt::Display for StrategyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.wallet, self.pool_address)
+    }
+}
+
+/// Parses `<wallet>/<pool>`, or a bare pool address for the default wallet
+impl FromStr for StrategyKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (wallet, pool_address) = s.rsplit_once('/').unwrap_or((DEFAULT_WALLET, s));
+        let pool_address = Pubkey::from_str(pool_address.trim())
+            .map_err(|e| anyhow::anyhow!("Invalid pool address in '{}': {}", s, e))?;
+        Ok(Self::new(wallet.trim(), pool_address))
+    }
 }
 
 /// Strategy types for different compound operations
@@ -72,12 +305,51 @@ pub struct CompoundResult {
     pub transaction_signature: String,
     pub timestamp: DateTime<Utc>,
     pub error: Option<String>,
+    /// True when the compound was skipped by a pre-check rather than failing
+    pub skipped: bool,
+    /// True when transactions were simulated in dry-run mode instead of sent
+    pub simulated: bool,
+    pub zap_cost: Option<ZapCost>,
+    /// Realised events (harvest, reinvestment, gas) for the accounting ledger
+    #[serde(default)]
+    pub ledger: Vec<LedgerEntry>,
+}
+
+/// Cost of converting harvested rewards into the pool's X/Y pair (in reward tokens)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZapCost {
+    pub reward_to_x: f64,
+    pub reward_to_y: f64,
+    pub amount_x: f64,
+    pub amount_y: f64,
+    pub swap_fees: f64,
+    pub price_impact_cost: f64,
+    pub total_cost: f64,
+    /// Rewards left in the wallet because their swap failed after the other side's landed
+    #[serde(default)]
+    pub unswapped_reward: f64,
+}
+
+/// Result of an emergency withdrawal
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmergencyWithdrawResult {
+    pub withdraw_signature: Option<String>,
+    pub claim_signature: Option<String>,
+    pub transfer_signature: Option<String>,
+    pub safe_address: Option<String>,
+    /// Network fees paid by the withdrawal transactions (SOL)
+    pub gas_used: f64,
+    pub errors: Vec<String>,
+    /// Realised events (withdrawn liquidity, claimed rewards, gas) for the accounting ledger
+    #[serde(default)]
+    pub ledger: Vec<LedgerEntry>,
 }
 
 /// Result of starting a strategy
 #[derive(Debug, Clone)]
 pub struct StartResult {
     pub success: bool,
+    pub wallet: String,
     pub pool_address: String,
     pub strategy_type: String,
     pub interval_ms: u64,
@@ -121,10 +393,17 @@ pub struct GlobalStatistics {
     pub total_rewards_harvested: f64,
     pub total_reinvested: f64,
     pub total_gas_spent: f64,
+    /// Fees recorded by the budget ledger over the last 24 hours, including previous runs
+    #[serde(default)]
+    pub gas_spent_24h: f64,
+    /// Fees recorded by the budget ledger over the last 7 days, including previous runs
+    #[serde(default)]
+    pub gas_spent_7d: f64,
     pub net_profit: f64,
     pub average_apy_boost: f64,
     pub last_compound_time: Option<DateTime<Utc>>,
     pub uptime_hours: f64,
+    pub emergency_stops: u64,
 }
 
 /// Statistics for a specific pool
@@ -155,6 +434,9 @@ pub struct GasOptimizationResult {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationEvent {
     pub event_type: NotificationEventType,
+    /// Wallet the event concerns, if it is tied to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<String>,
     pub pool_address: String,
     pub message: String,
     pub data: serde_json::Value,
@@ -162,7 +444,7 @@ pub struct NotificationEvent {
 }
 
 /// Types of notification events
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NotificationEventType {
     CompoundStarted,
     CompoundSuccess,
@@ -173,6 +455,7 @@ pub enum NotificationEventType {
     EmergencyStop,
     PositionChanged,
     APYUpdate,
+    GasBudgetExceeded,
 }
 
 impl fmt::Display for NotificationEventType {
@@ -187,6 +470,73 @@ impl fmt::Display for NotificationEventType {
             NotificationEventType::EmergencyStop => write!(f, "EMERGENCY_STOP"),
             NotificationEventType::PositionChanged => write!(f, "POSITION_CHANGED"),
             NotificationEventType::APYUpdate => write!(f, "APY_UPDATE"),
+            NotificationEventType::GasBudgetExceeded => write!(f, "GAS_BUDGET_EXCEEDED"),
+        }
+    }
+}
+
+impl FromStr for NotificationEventType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "COMPOUND_STARTED" => Ok(NotificationEventType::CompoundStarted),
+            "COMPOUND_SUCCESS" => Ok(NotificationEventType::CompoundSuccess),
+            "COMPOUND_FAILED" => Ok(NotificationEventType::CompoundFailed),
+            "COMPOUND_STOPPED" => Ok(NotificationEventType::CompoundStopped),
+            "HIGH_GAS_PRICE" => Ok(NotificationEventType::HighGasPrice),
+            "LOW_REWARDS" => Ok(NotificationEventType::LowRewards),
+            "EMERGENCY_STOP" => Ok(NotificationEventType::EmergencyStop),
+            "POSITION_CHANGED" => Ok(NotificationEventType::PositionChanged),
+            "APY_UPDATE" => Ok(NotificationEventType::APYUpdate),
+            "GAS_BUDGET_EXCEEDED" => Ok(NotificationEventType::GasBudgetExceeded),
+            _ => Err(anyhow::anyhow!("Invalid notification event type: {}", s)),
+        }
+    }
+}
+
+impl NotificationEventType {
+    /// Default severity for the event type
+    pub fn severity(&self) -> NotificationSeverity {
+        match self {
+            NotificationEventType::EmergencyStop => NotificationSeverity::Critical,
+            NotificationEventType::CompoundFailed
+            | NotificationEventType::HighGasPrice
+            | NotificationEventType::GasBudgetExceeded => {
+                NotificationSeverity::Warning
+            }
+            _ => NotificationSeverity::Info,
+        }
+    }
+}
+
+/// Notification severity levels (ordered from least to most severe)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum NotificationSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for NotificationSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotificationSeverity::Info => write!(f, "INFO"),
+            NotificationSeverity::Warning => write!(f, "WARNING"),
+            NotificationSeverity::Critical => write!(f, "CRITICAL"),
         }
     }
-}
\ No newline at end of file
+}
+
+impl FromStr for NotificationSeverity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_uppercase().as_str() {
+            "INFO" => Ok(NotificationSeverity::Info),
+            "WARNING" | "WARN" => Ok(NotificationSeverity::Warning),
+            "CRITICAL" => Ok(NotificationSeverity::Critical),
+            _ => Err(anyhow::anyhow!("Invalid notification severity: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,784 @@
+//! Declarative alert rules evaluated against each position analysis
+//!
+//! Rules are loaded from a config file (TOML, JSON or YAML) and checked every time a
+//! position is re-analysed. A rule fires once when its condition is breached and stays
+//! latched until the reading moves back past the threshold by the rule's hysteresis, and
+//! never fires twice for the same position within its cooldown. Fired events are queued
+//! for every configured sink and delivered by a background task, so a slow webhook never
+//! holds up a refresh; the JSON lines sink also persists them, and cooldowns are restored
+//! from it on start so a restart does not repeat recent alerts.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::{error, info, warn};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::types::{short_id, AnalysisConfig, ILEventType, ILNotificationEvent, NotificationSeverity, PositionAnalysis};
+
+/// A named condition with its severity, hysteresis and cooldown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    #[serde(flatten)]
+    pub condition: AlertCondition,
+    /// Overrides the condition's default severity
+    #[serde(default)]
+    pub severity: Option<NotificationSeverity>,
+    /// How far the reading must move back past the threshold before the rule can fire
+    /// again, in the condition's own unit (percentage points, ratio or bins)
+    #[serde(default)]
+    pub hysteresis: Decimal,
+    /// Minimum seconds between two events from this rule for the same position
+    #[serde(default)]
+    pub cooldown_secs: u64,
+}
+
+/// What a rule checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// IL below `percent` (e.g. -5 for a 5% loss)
+    IlBelow { percent: Decimal },
+    /// Active bin outside the position's range for at least `minutes`
+    OutOfRange { minutes: u64 },
+    /// Fees earned cover less than `ratio` times the position's loss
+    FeeIlRatioBelow { ratio: Decimal },
+    /// IL back up by at least `percent` points from its lowest reading
+    Recovery { percent: Decimal },
+    /// Either token's price moved more than `percent` since the previous analysis
+    PriceMove { percent: Decimal },
+}
+
+/// Per rule and position evaluation state
+#[derive(Debug, Clone, Default)]
+struct RuleState {
+    /// Fired and not yet cleared
+    latched: bool,
+    last_fired: Option<DateTime<Utc>>,
+    out_of_range_since: Option<DateTime<Utc>>,
+    /// Lowest IL percentage seen since the last recovery
+    trough: Option<Decimal>,
+    last_prices: Option<(Decimal, Decimal)>,
+}
+
+/// Result of checking a condition against one analysis
+enum Reading {
+    Breached {
+        threshold: Decimal,
+        severity: NotificationSeverity,
+        message: String,
+    },
+    /// Back past the hysteresis band: the rule re-arms
+    Clear,
+    /// Neither breached nor clear: the rule keeps its state
+    Hold,
+}
+
+impl AlertCondition {
+    pub fn event_type(&self) -> ILEventType {
+        match self {
+            AlertCondition::IlBelow { .. } => ILEventType::HighImpermanentLoss,
+            AlertCondition::OutOfRange { .. } => ILEventType::PositionOutOfRange,
+            AlertCondition::FeeIlRatioBelow { .. } => ILEventType::LowFeeCompensation,
+            AlertCondition::Recovery { .. } => ILEventType::RecoveryDetected,
+            AlertCondition::PriceMove { .. } => ILEventType::PriceVoLatilitySpike,
+        }
+    }
+
+    fn read(&self, analysis: &PositionAnalysis, state: &mut RuleState, hysteresis: Decimal) -> Reading {
+        let il_result = &analysis.il_result;
+        let il_percent = il_result.il_percentage * Decimal::ONE_HUNDRED;
+
+        match *self {
+            AlertCondition::IlBelow { percent } => {
+                if il_percent < percent {
+                    // A loss twice the threshold is critical
+                    let severity = if percent < Decimal::ZERO && il_percent < percent * Decimal::TWO {
+                        NotificationSeverity::Critical
+                    } else {
+                        NotificationSeverity::Warning
+                    };
+                    Reading::Breached {
+                        threshold: percent,
+                        severity,
+                        message: format!("IL at {:.2}% (${:.2}), below {}%", il_percent, il_result.il_usd_value, percent),
+                    }
+                } else if il_percent >= percent + hysteresis {
+                    Reading::Clear
+                } else {
+                    Reading::Hold
+                }
+            }
+            AlertCondition::OutOfRange { minutes } => {
+                let (lower, upper) = il_result
+                    .metadata
+                    .bin_range
+                    .unwrap_or((analysis.position_info.lower_bin_id, analysis.position_info.upper_bin_id));
+                let Some(active) = il_result.metadata.active_bin_id else {
+                    return Reading::Hold;
+                };
+
+                if (lower..=upper).contains(&active) {
+                    state.out_of_range_since = None;
+                    let depth = (active - lower).min(upper - active);
+                    return if Decimal::from(depth) >= hysteresis { Reading::Clear } else { Reading::Hold };
+                }
+
+                let since = *state.out_of_range_since.get_or_insert(analysis.timestamp);
+                let elapsed = analysis.timestamp - since;
+                if elapsed >= ChronoDuration::minutes(minutes as i64) {
+                    Reading::Breached {
+                        threshold: Decimal::from(minutes),
+                        severity: NotificationSeverity::Warning,
+                        message: format!(
+                            "out of range for {}m: active bin {} outside [{}, {}]",
+                            elapsed.num_minutes(), active, lower, upper
+                        ),
+                    }
+                } else {
+                    Reading::Hold
+                }
+            }
+            AlertCondition::FeeIlRatioBelow { ratio } => {
+                // Only a loss needs compensating
+                let fee_ratio = analysis.fee_analysis.fee_vs_il_ratio;
+                if il_result.il_usd_value >= Decimal::ZERO || fee_ratio >= ratio + hysteresis {
+                    Reading::Clear
+                } else if fee_ratio < ratio {
+                    Reading::Breached {
+                        threshold: ratio,
+                        severity: NotificationSeverity::Warning,
+                        message: format!(
+                            "fees ${:.2} cover {:.2}x the ${:.2} loss, below {}x",
+                            analysis.fee_analysis.total_fees_earned, fee_ratio, -il_result.il_usd_value, ratio
+                        ),
+                    }
+                } else {
+                    Reading::Hold
+                }
+            }
+            AlertCondition::Recovery { percent } => {
+                let trough = state.trough.map_or(il_percent, |trough| trough.min(il_percent));
+                state.trough = Some(trough);
+                let recovered = il_percent - trough;
+                if trough < Decimal::ZERO && recovered >= percent {
+                    Reading::Breached {
+                        threshold: percent,
+                        severity: NotificationSeverity::Info,
+                        message: format!("IL recovered {:.2} points from {:.2}% to {:.2}%", recovered, trough, il_percent),
+                    }
+                } else if recovered < percent - hysteresis {
+                    Reading::Clear
+                } else {
+                    Reading::Hold
+                }
+            }
+            AlertCondition::PriceMove { percent } => {
+                let prices = (il_result.current_price_x, il_result.current_price_y);
+                let Some(previous) = state.last_prices.replace(prices) else {
+                    return Reading::Hold;
+                };
+                let change = |current: Decimal, previous: Decimal| {
+                    if previous.is_zero() {
+                        Decimal::ZERO
+                    } else {
+                        (current - previous) / previous * Decimal::ONE_HUNDRED
+                    }
+                };
+                let (change_x, change_y) = (change(prices.0, previous.0), change(prices.1, previous.1));
+                let (token, largest) = if change_x.abs() >= change_y.abs() { ("X", change_x) } else { ("Y", change_y) };
+
+                if largest.abs() > percent {
+                    let severity = if largest.abs() > percent * Decimal::new(3, 0) {
+                        NotificationSeverity::Emergency
+                    } else if largest.abs() > percent * Decimal::new(15, 1) {
+                        NotificationSeverity::Critical
+                    } else {
+                        NotificationSeverity::Warning
+                    };
+                    Reading::Breached {
+                        threshold: percent,
+                        severity,
+                        message: format!("token {} price moved {:.2}% (threshold {}%)", token, largest, percent),
+                    }
+                } else if largest.abs() <= percent - hysteresis {
+                    Reading::Clear
+                } else {
+                    Reading::Hold
+                }
+            }
+        }
+    }
+
+    /// Reset state that only makes sense until the rule fires
+    fn fired(&self, analysis: &PositionAnalysis, state: &mut RuleState) {
+        if let AlertCondition::Recovery { .. } = self {
+            state.trough = Some(analysis.il_result.il_percentage * Decimal::ONE_HUNDRED);
+        }
+    }
+}
+
+/// Rules and sinks as read from an alert config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+    /// POST every fired event as JSON to this URL
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+impl AlertConfig {
+    /// Load rules from a TOML, JSON or YAML file, picked by extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let config: Self = config::Config::builder()
+            .add_source(config::File::from(path))
+            .build()
+            .and_then(|source| source.try_deserialize())
+            .with_context(|| format!("invalid alert rules in {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Rules used when no config file is given, from the analysis thresholds
+    pub fn defaults(config: &AnalysisConfig) -> Self {
+        let rule = |name: &str, condition, hysteresis, cooldown_secs| AlertRule {
+            name: name.to_string(),
+            condition,
+            severity: None,
+            hysteresis,
+            cooldown_secs,
+        };
+
+        Self {
+            rules: vec![
+                rule("high-il", AlertCondition::IlBelow { percent: -config.max_price_deviation }, Decimal::new(5, 1), 300),
+                rule("out-of-range", AlertCondition::OutOfRange { minutes: 0 }, Decimal::ZERO, 300),
+                rule("low-fee-compensation", AlertCondition::FeeIlRatioBelow { ratio: Decimal::ONE }, Decimal::new(1, 1), 3600),
+                rule("recovery", AlertCondition::Recovery { percent: Decimal::TWO }, Decimal::ZERO, 300),
+                rule("price-move", AlertCondition::PriceMove { percent: config.max_price_deviation }, Decimal::ZERO, 300),
+            ],
+            webhook_url: None,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut names = BTreeSet::new();
+        for rule in &self.rules {
+            if !names.insert(rule.name.as_str()) {
+                return Err(anyhow!("duplicate alert rule name '{}'", rule.name));
+            }
+            if rule.hysteresis < Decimal::ZERO {
+                return Err(anyhow!("alert rule '{}' has a negative hysteresis", rule.name));
+            }
+            match rule.condition {
+                AlertCondition::FeeIlRatioBelow { ratio: value }
+                | AlertCondition::Recovery { percent: value }
+                | AlertCondition::PriceMove { percent: value }
+                    if value <= Decimal::ZERO =>
+                {
+                    return Err(anyhow!("alert rule '{}' needs a positive threshold", rule.name));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Destination for fired alert events
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn deliver(&self, event: &ILNotificationEvent) -> Result<()>;
+}
+
+pub type SharedAlertSink = Arc<dyn AlertSink>;
+
+/// Writes events to the log at a level matching their severity
+pub struct LogAlertSink;
+
+#[async_trait]
+impl AlertSink for LogAlertSink {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn deliver(&self, event: &ILNotificationEvent) -> Result<()> {
+        match event.severity {
+            NotificationSeverity::Info => info!("🔔 {}", event.message),
+            NotificationSeverity::Warning => warn!("⚠️  {}", event.message),
+            NotificationSeverity::Critical | NotificationSeverity::Emergency => error!("🚨 {}", event.message),
+        }
+        Ok(())
+    }
+}
+
+/// Appends events to a JSON lines file
+pub struct JsonLinesAlertSink {
+    path: PathBuf,
+}
+
+impl JsonLinesAlertSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Events persisted so far, oldest first; none if the file does not exist yet
+    pub fn load(&self) -> Result<Vec<ILNotificationEvent>> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(anyhow!("cannot read {}: {}", self.path.display(), e)),
+        };
+
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("invalid alert event on line {} of {}", index + 1, self.path.display()))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AlertSink for JsonLinesAlertSink {
+    fn name(&self) -> &str {
+        "jsonl"
+    }
+
+    async fn deliver(&self, event: &ILNotificationEvent) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        file.write_all((serde_json::to_string(event)? + "\n").as_bytes()).await?;
+        Ok(())
+    }
+}
+
+/// Longest a webhook delivery may take before it is abandoned
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POSTs each event as JSON to a URL
+pub struct WebhookAlertSink {
+    url: String,
+    client: Client,
+}
+
+impl WebhookAlertSink {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        let client = Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+        Ok(Self { url: url.into(), client })
+    }
+}
+
+#[async_trait]
+impl AlertSink for WebhookAlertSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn deliver(&self, event: &ILNotificationEvent) -> Result<()> {
+        self.client.post(&self.url).json(event).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Events waiting for the sinks, delivered in order by a background task
+struct Delivery {
+    queue: mpsc::UnboundedSender<ILNotificationEvent>,
+    task: JoinHandle<()>,
+}
+
+impl Delivery {
+    /// Start delivering to `sinks` once `previous`, which takes no more events, has drained
+    fn spawn(sinks: Vec<SharedAlertSink>, previous: Option<Delivery>) -> Self {
+        let previous = previous.map(|Delivery { task, .. }| task);
+        let (queue, mut events) = mpsc::unbounded_channel::<ILNotificationEvent>();
+        let task = tokio::spawn(async move {
+            if let Some(previous) = previous {
+                if let Err(e) = previous.await {
+                    warn!("Alert delivery stopped: {}", e);
+                }
+            }
+            while let Some(event) = events.recv().await {
+                for sink in &sinks {
+                    if let Err(e) = sink.deliver(&event).await {
+                        warn!("Alert sink {} failed: {}", sink.name(), e);
+                    }
+                }
+            }
+        });
+        Self { queue, task }
+    }
+
+    /// Stop taking events and wait for the queued ones to be delivered
+    async fn finish(self) {
+        drop(self.queue);
+        if let Err(e) = self.task.await {
+            warn!("Alert delivery stopped: {}", e);
+        }
+    }
+}
+
+/// Evaluates alert rules and delivers the events they fire
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    sinks: Vec<SharedAlertSink>,
+    /// Keyed by rule name and position (the pool when the analysis has no position)
+    state: BTreeMap<(String, Pubkey), RuleState>,
+    /// Started on the first dispatch after the sinks last changed
+    delivery: Option<Delivery>,
+    /// Delivery to the sinks before the last `add_sink`, drained ahead of the next one
+    retired: Option<Delivery>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self { rules, sinks: Vec::new(), state: BTreeMap::new(), delivery: None, retired: None }
+    }
+
+    /// Engine for `config` logging its events, persisting them to `events_path` and,
+    /// if configured, posting them to a webhook. Cooldowns resume from the persisted events.
+    pub fn from_config(config: AlertConfig, events_path: impl Into<PathBuf>) -> Result<Self> {
+        let store = JsonLinesAlertSink::new(events_path);
+        let mut engine = Self::new(config.rules);
+        engine.restore(&store.load()?);
+        engine.add_sink(Arc::new(LogAlertSink));
+        engine.add_sink(Arc::new(store));
+        if let Some(url) = config.webhook_url {
+            engine.add_sink(Arc::new(WebhookAlertSink::new(url)?));
+        }
+        Ok(engine)
+    }
+
+    /// Add a sink for events dispatched from now on
+    pub fn add_sink(&mut self, sink: SharedAlertSink) {
+        self.sinks.push(sink);
+        // Events already queued still go to the previous sinks, ahead of later ones
+        if let Some(delivery) = self.delivery.take() {
+            self.retired = Some(delivery);
+        }
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Resume cooldowns from previously fired events
+    pub fn restore(&mut self, events: &[ILNotificationEvent]) {
+        for event in events {
+            let Some(rule) = event.rule.as_ref().filter(|rule| self.rules.iter().any(|r| &r.name == *rule)) else {
+                continue;
+            };
+            let key = (rule.clone(), event.position_id.unwrap_or(event.pool_address));
+            let state = self.state.entry(key).or_default();
+            state.last_fired = state.last_fired.max(Some(event.timestamp));
+        }
+    }
+
+    /// Check every rule against `analysis` and return the events that fire
+    pub fn evaluate(&mut self, analysis: &PositionAnalysis) -> Vec<ILNotificationEvent> {
+        let position_id = analysis.position_info.position_id.or(analysis.il_result.metadata.position_id);
+        let pool_address = analysis.position_info.pool_address;
+        let now = analysis.timestamp;
+        let mut events = Vec::new();
+
+        for rule in &self.rules {
+            let state = self.state.entry((rule.name.clone(), position_id.unwrap_or(pool_address))).or_default();
+            match rule.condition.read(analysis, state, rule.hysteresis) {
+                Reading::Breached { threshold, severity, message } => {
+                    let cooling_down = state
+                        .last_fired
+                        .is_some_and(|fired| now - fired < ChronoDuration::seconds(rule.cooldown_secs as i64));
+                    if state.latched || cooling_down {
+                        continue;
+                    }
+                    state.latched = true;
+                    state.last_fired = Some(now);
+                    rule.condition.fired(analysis, state);
+
+                    events.push(ILNotificationEvent {
+                        event_type: rule.condition.event_type(),
+                        position_id,
+                        pool_address,
+                        il_percentage: analysis.il_result.il_percentage,
+                        threshold_crossed: Some(threshold),
+                        message: format!("[{}] {}: {}", rule.name, short_id(&position_id.unwrap_or(pool_address)), message),
+                        timestamp: now,
+                        severity: rule.severity.clone().unwrap_or(severity),
+                        rule: Some(rule.name.clone()),
+                    });
+                }
+                Reading::Clear => state.latched = false,
+                Reading::Hold => {}
+            }
+        }
+
+        events
+    }
+
+    /// Queue `events` for every sink without waiting for delivery. Events reach each sink
+    /// in order; a failing sink is logged and does not stop the others. Must be called
+    /// within a Tokio runtime.
+    pub fn dispatch(&mut self, events: &[ILNotificationEvent]) {
+        if events.is_empty() || self.sinks.is_empty() {
+            return;
+        }
+        let (sinks, retired) = (&self.sinks, &mut self.retired);
+        let delivery = self.delivery.get_or_insert_with(|| Delivery::spawn(sinks.clone(), retired.take()));
+        for event in events {
+            if delivery.queue.send(event.clone()).is_err() {
+                warn!("Alert delivery stopped; dropping {}", event.message);
+            }
+        }
+    }
+
+    /// Evaluate the rules against `analysis` and queue what fires for delivery
+    pub fn process(&mut self, analysis: &PositionAnalysis) -> Vec<ILNotificationEvent> {
+        let events = self.evaluate(analysis);
+        self.dispatch(&events);
+        events
+    }
+
+    /// Wait until every dispatched event has been delivered
+    pub async fn flush(&mut self) {
+        // A running delivery drains the retired one first
+        if let Some(delivery) = self.delivery.take().or_else(|| self.retired.take()) {
+            delivery.finish().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report_generator::tests::create_mock_analysis;
+    use rust_decimal_macros::dec;
+    use std::sync::Mutex;
+
+    /// Position over bins 95..=105 of pool `[7; 32]` worth $1000, with $20 of fees and
+    /// prices at 1, analysed `minute` minutes into the test
+    fn analysis(position_id: Pubkey, il_percentage: Decimal, active_bin_id: i32, minute: i64) -> PositionAnalysis {
+        let now = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap() + ChronoDuration::minutes(minute);
+        let pool_address = Pubkey::new_from_array([7; 32]);
+        let mut analysis = create_mock_analysis();
+        analysis.timestamp = now;
+
+        let position = &mut analysis.position_info;
+        position.position_id = Some(position_id);
+        position.pool_address = pool_address;
+        position.lower_bin_id = 95;
+        position.upper_bin_id = 105;
+
+        let il_result = &mut analysis.il_result;
+        il_result.il_percentage = il_percentage;
+        il_result.il_usd_value = il_percentage * dec!(1000);
+        il_result.current_value_usd = dec!(1000);
+        il_result.hold_value_usd = dec!(1000);
+        il_result.current_price_x = dec!(1);
+        il_result.current_price_y = dec!(1);
+        il_result.timestamp = now;
+        il_result.metadata.pool_address = pool_address;
+        il_result.metadata.position_id = Some(position_id);
+        il_result.metadata.bin_range = Some((95, 105));
+        il_result.metadata.active_bin_id = Some(active_bin_id);
+
+        analysis.fee_analysis.total_fees_earned = dec!(20);
+        analysis.fee_analysis.fee_vs_il_ratio =
+            if il_percentage.is_zero() { dec!(0) } else { dec!(20) / (il_percentage * dec!(1000)).abs() };
+        analysis
+    }
+
+    fn rule(name: &str, condition: AlertCondition, hysteresis: Decimal, cooldown_secs: u64) -> AlertRule {
+        AlertRule { name: name.to_string(), condition, severity: None, hysteresis, cooldown_secs }
+    }
+
+    fn fired(events: &[ILNotificationEvent]) -> Vec<&str> {
+        events.iter().filter_map(|event| event.rule.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_rules_fire_once_and_rearm_past_hysteresis() {
+        let id = Pubkey::new_unique();
+        let mut engine = AlertEngine::new(vec![
+            rule("loss", AlertCondition::IlBelow { percent: dec!(-5) }, dec!(1), 0),
+            rule("range", AlertCondition::OutOfRange { minutes: 10 }, dec!(2), 0),
+            rule("fees", AlertCondition::FeeIlRatioBelow { ratio: dec!(0.5) }, dec!(0), 0),
+            rule("recovery", AlertCondition::Recovery { percent: dec!(2) }, dec!(0), 0),
+        ]);
+
+        assert!(engine.evaluate(&analysis(id, dec!(-0.01), 100, 0)).is_empty());
+
+        // Loss of 6% on $1000 with $20 of fees; out of range but not for long enough yet
+        let events = engine.evaluate(&analysis(id, dec!(-0.06), 110, 1));
+        assert_eq!(fired(&events), ["loss", "fees"]);
+        assert!(matches!(events[0].event_type, ILEventType::HighImpermanentLoss));
+        assert!(matches!(events[1].event_type, ILEventType::LowFeeCompensation));
+        assert_eq!(events[0].pool_address, Pubkey::new_from_array([7; 32]));
+
+        // Still breached: latched, except the range rule whose duration is now reached
+        let events = engine.evaluate(&analysis(id, dec!(-0.07), 111, 11));
+        assert_eq!(fired(&events), ["range"]);
+        assert!(matches!(events[0].event_type, ILEventType::PositionOutOfRange));
+
+        // Within the hysteresis band: no re-arm, but IL is 2.5 points off its low
+        let events = engine.evaluate(&analysis(id, dec!(-0.045), 105, 12));
+        assert_eq!(fired(&events), ["recovery"]);
+        assert!(matches!(events[0].event_type, ILEventType::RecoveryDetected));
+        assert!(engine.evaluate(&analysis(id, dec!(-0.06), 111, 13)).is_empty());
+
+        // Clear of every band, which is also a recovery from the last low, then breached again
+        let events = engine.evaluate(&analysis(id, dec!(-0.03), 100, 14));
+        assert_eq!(fired(&events), ["recovery"]);
+        let events = engine.evaluate(&analysis(id, dec!(-0.06), 111, 15));
+        assert_eq!(fired(&events), ["loss", "fees"]);
+        let events = engine.evaluate(&analysis(id, dec!(-0.06), 111, 25));
+        assert_eq!(fired(&events), ["range"]);
+    }
+
+    #[test]
+    fn test_cooldown_survives_restore_from_persisted_events() {
+        let id = Pubkey::new_unique();
+        let rules = vec![rule("loss", AlertCondition::IlBelow { percent: dec!(-5) }, dec!(0), 600)];
+        let mut engine = AlertEngine::new(rules.clone());
+
+        let events = engine.evaluate(&analysis(id, dec!(-0.06), 100, 0));
+        assert_eq!(events.len(), 1);
+        assert!(engine.evaluate(&analysis(id, dec!(-0.01), 100, 1)).is_empty());
+        // Re-armed but cooling down
+        assert!(engine.evaluate(&analysis(id, dec!(-0.06), 100, 5)).is_empty());
+        assert_eq!(engine.evaluate(&analysis(id, dec!(-0.06), 100, 10)).len(), 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = JsonLinesAlertSink::new(dir.path().join("alerts.jsonl"));
+        tokio_test::block_on(async {
+            for event in &events {
+                store.deliver(event).await.unwrap();
+            }
+        });
+        let persisted = store.load().unwrap();
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].position_id, Some(id));
+
+        let mut restarted = AlertEngine::new(rules);
+        restarted.restore(&persisted);
+        assert!(restarted.evaluate(&analysis(id, dec!(-0.06), 100, 5)).is_empty());
+        assert_eq!(restarted.evaluate(&analysis(id, dec!(-0.06), 100, 10)).len(), 1);
+    }
+
+    struct RecordingSink(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl AlertSink for RecordingSink {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn deliver(&self, event: &ILNotificationEvent) -> Result<()> {
+            self.0.lock().unwrap().push(event.message.clone());
+            Ok(())
+        }
+    }
+
+    /// Holds each delivery until a permit is released
+    struct StalledSink(tokio::sync::Semaphore, Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl AlertSink for StalledSink {
+        fn name(&self) -> &str {
+            "stalled"
+        }
+
+        async fn deliver(&self, event: &ILNotificationEvent) -> Result<()> {
+            self.0.acquire().await?.forget();
+            self.1.lock().unwrap().push(event.message.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stalled_sink_does_not_hold_up_evaluation() {
+        let id = Pubkey::new_unique();
+        let sink = Arc::new(StalledSink(tokio::sync::Semaphore::new(0), Mutex::new(Vec::new())));
+        let mut engine = AlertEngine::new(vec![rule("loss", AlertCondition::IlBelow { percent: dec!(-5) }, dec!(0), 0)]);
+        engine.add_sink(sink.clone());
+
+        assert_eq!(engine.process(&analysis(id, dec!(-0.06), 100, 0)).len(), 1);
+        assert!(engine.process(&analysis(id, dec!(-0.01), 100, 1)).is_empty());
+
+        // A sink added while an event is still queued only gets what is dispatched after it
+        let recording = Arc::new(RecordingSink(Mutex::new(Vec::new())));
+        engine.add_sink(recording.clone());
+        assert_eq!(engine.process(&analysis(id, dec!(-0.07), 100, 2)).len(), 1);
+        assert!(sink.1.lock().unwrap().is_empty());
+
+        sink.0.add_permits(2);
+        engine.flush().await;
+        let delivered = sink.1.lock().unwrap();
+        assert_eq!(delivered.len(), 2);
+        assert!(delivered[0].contains("-6.00%") && delivered[1].contains("-7.00%"));
+        let recorded = recording.0.lock().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert!(recorded[0].contains("-7.00%"));
+    }
+
+    #[tokio::test]
+    async fn test_loads_rules_and_delivers_price_moves_to_sinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.toml");
+        std::fs::write(
+            &path,
+            r#"
+webhook_url = "http://127.0.0.1:9/alerts"
+
+[[rules]]
+name = "price-move"
+type = "price_move"
+percent = 5
+severity = "Critical"
+cooldown_secs = 60
+"#,
+        )
+        .unwrap();
+        let config = AlertConfig::load(&path).unwrap();
+        assert_eq!(config.webhook_url.as_deref(), Some("http://127.0.0.1:9/alerts"));
+        assert!(matches!(config.rules[0].condition, AlertCondition::PriceMove { percent } if percent == dec!(5)));
+
+        let sink = Arc::new(RecordingSink(Mutex::new(Vec::new())));
+        let mut engine = AlertEngine::new(config.rules);
+        engine.add_sink(sink.clone());
+
+        let id = Pubkey::new_unique();
+        let mut moved = analysis(id, dec!(0), 100, 1);
+        moved.il_result.current_price_x = dec!(1.08);
+        assert!(engine.process(&analysis(id, dec!(0), 100, 0)).is_empty());
+        let events = engine.process(&moved);
+        engine.flush().await;
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0].event_type, ILEventType::PriceVoLatilitySpike));
+        assert!(matches!(events[0].severity, NotificationSeverity::Critical));
+        assert_eq!(sink.0.lock().unwrap().len(), 1);
+        assert!(sink.0.lock().unwrap()[0].contains("token X price moved 8.00%"));
+
+        std::fs::write(&path, "[[rules]]\nname = \"a\"\ntype = \"recovery\"\npercent = 1\n[[rules]]\nname = \"a\"\ntype = \"recovery\"\npercent = 2\n").unwrap();
+        assert!(AlertConfig::load(&path).is_err());
+    }
+}
@@ -0,0 +1,438 @@
+//! Bin-accurate concentrated-liquidity model for DLMM positions
+//!
+//! A DLMM bin holds constant-sum liquidity `L = P * x + y` at its price `P` (token X
+//! priced in token Y). Bins above the active bin hold only X, bins below hold only Y,
+//! and the active bin holds a mix. When the active bin moves, every bin it crossed is
+//! converted entirely to the other token at that bin's price, and the new active bin
+//! is partially converted. Re-pricing a position's entry composition this way gives
+//! the amounts it would actually withdraw.
+
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use saros_dlmm_sdk::bin_math;
+use serde::{Deserialize, Serialize};
+
+use crate::types::ILError;
+
+/// Token amounts a position holds in one bin
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BinLiquidity {
+    pub bin_id: i32,
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+}
+
+/// Per-bin composition of a position at entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionBins {
+    pub bin_step: u16,
+    /// Active bin when the position was opened
+    pub entry_active_bin_id: i32,
+    pub bins: Vec<BinLiquidity>,
+}
+
+/// How a deposit is spread across a position's bins
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LiquidityDistribution {
+    /// Equal amounts in every bin (the "spot" shape)
+    Uniform,
+    /// Gaussian weights centred on the entry active bin, `std_dev_bins` wide (the "curve" shape)
+    Normal { std_dev_bins: f64 },
+    /// Weights growing linearly away from the entry active bin
+    BidAsk,
+}
+
+impl std::fmt::Display for LiquidityDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiquidityDistribution::Uniform => write!(f, "Spot"),
+            LiquidityDistribution::Normal { std_dev_bins } => write!(f, "Curve (σ {:.1} bins)", std_dev_bins),
+            LiquidityDistribution::BidAsk => write!(f, "Bid-Ask"),
+        }
+    }
+}
+
+impl LiquidityDistribution {
+    fn weight(&self, bin_id: i32, active_bin_id: i32) -> Decimal {
+        match self {
+            LiquidityDistribution::Uniform => Decimal::ONE,
+            LiquidityDistribution::Normal { std_dev_bins } => {
+                let z = (bin_id - active_bin_id) as f64 / std_dev_bins.max(f64::EPSILON);
+                Decimal::from_f64((-0.5 * z * z).exp()).unwrap_or(Decimal::ZERO)
+            }
+            LiquidityDistribution::BidAsk => Decimal::from((bin_id - active_bin_id).unsigned_abs() + 1),
+        }
+    }
+}
+
+/// One bin of a position re-priced at the current active bin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinILResult {
+    pub bin_id: i32,
+    /// Price of token X in token Y at this bin
+    pub price: Decimal,
+    pub entry_amount_x: Decimal,
+    pub entry_amount_y: Decimal,
+    /// Amounts withdrawable from this bin now
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+    pub value_usd: Decimal,
+    /// Value of the entry amounts had they been held
+    pub hold_value_usd: Decimal,
+    pub il_usd_value: Decimal,
+    pub il_percentage: Decimal,
+    /// The active bin moved across this bin since entry, converting it to the other token
+    pub crossed: bool,
+}
+
+/// Per-bin and aggregate IL of a position re-priced at the current active bin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinILBreakdown {
+    pub bin_step: u16,
+    pub entry_active_bin_id: i32,
+    pub active_bin_id: i32,
+    /// Share of the active bin's value held in token Y
+    pub active_bin_y_share: Decimal,
+    pub bins: Vec<BinILResult>,
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+    pub value_usd: Decimal,
+    pub hold_value_usd: Decimal,
+    pub il_usd_value: Decimal,
+    pub il_percentage: Decimal,
+}
+
+impl BinILBreakdown {
+    /// Bins the active bin has crossed since entry
+    pub fn crossed_bins(&self) -> usize {
+        self.bins.iter().filter(|bin| bin.crossed).count()
+    }
+
+    /// The position's current amounts, as if it had been opened at the current active bin
+    pub fn holdings(&self) -> PositionBins {
+        PositionBins {
+            bin_step: self.bin_step,
+            entry_active_bin_id: self.active_bin_id,
+            bins: self.bins
+                .iter()
+                .map(|bin| BinLiquidity { bin_id: bin.bin_id, amount_x: bin.amount_x, amount_y: bin.amount_y })
+                .collect(),
+        }
+    }
+}
+
+/// Price of token X in token Y at `bin_id`: `(1 + bin_step / 10000) ^ bin_id`
+pub fn bin_price(bin_id: i32, bin_step: u16) -> Decimal {
+    Decimal::from_f64(bin_math::bin_id_to_price(bin_id, bin_step, 0)).unwrap_or(Decimal::ZERO)
+}
+
+/// Bin containing the price ratio `price_x / price_y`
+pub fn price_to_bin_id(price_ratio: Decimal, bin_step: u16) -> i32 {
+    bin_math::price_to_bin_id(price_ratio.to_f64().unwrap_or(1.0), bin_step, 0)
+}
+
+/// Share of the active bin's value held in token Y, from the bin's reserves
+pub fn reserves_y_share(reserve_x: Decimal, reserve_y: Decimal, bin_id: i32, bin_step: u16) -> Decimal {
+    let liquidity = reserve_x * bin_price(bin_id, bin_step) + reserve_y;
+    if liquidity > Decimal::ZERO {
+        reserve_y / liquidity
+    } else {
+        Decimal::new(5, 1)
+    }
+}
+
+/// Estimate of the active bin's Y share from where the market price sits inside it,
+/// for when the bin's reserves aren't known. Half at the bin price, rising as the
+/// price moves up through the bin and X is sold for Y.
+pub fn price_y_share(price_ratio: Decimal, active_bin_id: i32, bin_step: u16) -> Decimal {
+    let (Some(price), Some(bin_price)) = (price_ratio.to_f64(), bin_price(active_bin_id, bin_step).to_f64()) else {
+        return Decimal::new(5, 1);
+    };
+    if price <= 0.0 || bin_price <= 0.0 {
+        return Decimal::new(5, 1);
+    }
+
+    let step = (1.0 + bin_step as f64 / 10_000.0).ln();
+    let offset = (price / bin_price).ln() / step;
+    Decimal::from_f64((0.5 + offset).clamp(0.0, 1.0)).unwrap_or(Decimal::new(5, 1))
+}
+
+impl PositionBins {
+    pub fn new(bin_step: u16, entry_active_bin_id: i32, bins: Vec<BinLiquidity>) -> Result<Self, ILError> {
+        if bin_step == 0 {
+            return Err(ILError::InvalidPosition("Bin step must be positive".to_string()));
+        }
+        if bins.iter().any(|bin| bin.amount_x < Decimal::ZERO || bin.amount_y < Decimal::ZERO) {
+            return Err(ILError::InvalidPosition("Bin amounts must not be negative".to_string()));
+        }
+
+        Ok(Self { bin_step, entry_active_bin_id, bins })
+    }
+
+    /// Spread deposits evenly the way a uniform DLMM deposit does: token X over the bins
+    /// at and above the active bin, token Y over the bins at and below it
+    pub fn uniform(
+        lower_bin_id: i32,
+        upper_bin_id: i32,
+        entry_active_bin_id: i32,
+        bin_step: u16,
+        amount_x: Decimal,
+        amount_y: Decimal,
+    ) -> Result<Self, ILError> {
+        Self::with_distribution(
+            lower_bin_id,
+            upper_bin_id,
+            entry_active_bin_id,
+            bin_step,
+            amount_x,
+            amount_y,
+            LiquidityDistribution::Uniform,
+        )
+    }
+
+    /// Spread deposits by `distribution`'s weights: token X over the bins at and above the
+    /// active bin, token Y over the bins at and below it
+    pub fn with_distribution(
+        lower_bin_id: i32,
+        upper_bin_id: i32,
+        entry_active_bin_id: i32,
+        bin_step: u16,
+        amount_x: Decimal,
+        amount_y: Decimal,
+        distribution: LiquidityDistribution,
+    ) -> Result<Self, ILError> {
+        if lower_bin_id > upper_bin_id {
+            return Err(ILError::InvalidPosition(format!("Invalid bin range [{}, {}]", lower_bin_id, upper_bin_id)));
+        }
+
+        if let LiquidityDistribution::Normal { std_dev_bins } = distribution {
+            if std_dev_bins.is_nan() || std_dev_bins <= 0.0 {
+                return Err(ILError::InvalidPosition("Distribution width must be positive".to_string()));
+            }
+        }
+
+        let weight = |bin_id| distribution.weight(bin_id, entry_active_bin_id);
+        let x_weight: Decimal = (lower_bin_id.max(entry_active_bin_id)..=upper_bin_id).map(weight).sum();
+        let y_weight: Decimal = (lower_bin_id..=upper_bin_id.min(entry_active_bin_id)).map(weight).sum();
+
+        // A range entirely on one side of the active bin can only hold that side's token
+        let (amount_x, amount_y) = match (x_weight.is_zero(), y_weight.is_zero()) {
+            (true, _) => (Decimal::ZERO, amount_y + amount_x * bin_price(entry_active_bin_id, bin_step)),
+            (_, true) => (amount_x + amount_y / bin_price(entry_active_bin_id, bin_step), Decimal::ZERO),
+            _ => (amount_x, amount_y),
+        };
+
+        let bins = (lower_bin_id..=upper_bin_id)
+            .map(|bin_id| BinLiquidity {
+                bin_id,
+                amount_x: if bin_id >= entry_active_bin_id { amount_x * weight(bin_id) / x_weight } else { Decimal::ZERO },
+                amount_y: if bin_id <= entry_active_bin_id { amount_y * weight(bin_id) / y_weight } else { Decimal::ZERO },
+            })
+            .collect();
+
+        Self::new(bin_step, entry_active_bin_id, bins)
+    }
+
+    /// Re-price every bin at the bin holding the market ratio `price_x / price_y`, splitting
+    /// the active bin by where the price sits inside it. Both prices must be positive.
+    pub fn reprice_at_market(&self, price_x: Decimal, price_y: Decimal) -> BinILBreakdown {
+        let ratio = price_x / price_y;
+        let active_bin_id = price_to_bin_id(ratio, self.bin_step);
+        self.reprice(active_bin_id, price_y_share(ratio, active_bin_id, self.bin_step), price_x, price_y)
+    }
+
+    /// Re-price every bin at `active_bin_id` and value the result at the given USD prices.
+    /// `active_bin_y_share` is the share of the active bin's value held in Y once the price
+    /// has left the entry bin; while it is still active that bin keeps its deposited amounts.
+    pub fn reprice(
+        &self,
+        active_bin_id: i32,
+        active_bin_y_share: Decimal,
+        price_x: Decimal,
+        price_y: Decimal,
+    ) -> BinILBreakdown {
+        let y_share = active_bin_y_share.clamp(Decimal::ZERO, Decimal::ONE);
+
+        let bins: Vec<BinILResult> = self.bins
+            .iter()
+            .map(|bin| {
+                let price = bin_price(bin.bin_id, self.bin_step);
+                // Constant-sum liquidity in Y units is preserved as the bin is swapped through
+                let liquidity = bin.amount_x * price + bin.amount_y;
+
+                let (amount_x, amount_y) = match bin.bin_id.cmp(&active_bin_id) {
+                    std::cmp::Ordering::Equal if active_bin_id == self.entry_active_bin_id => (bin.amount_x, bin.amount_y),
+                    std::cmp::Ordering::Less => (Decimal::ZERO, liquidity),
+                    std::cmp::Ordering::Greater if price > Decimal::ZERO => (liquidity / price, Decimal::ZERO),
+                    std::cmp::Ordering::Greater => (Decimal::ZERO, Decimal::ZERO),
+                    std::cmp::Ordering::Equal if price > Decimal::ZERO => {
+                        (liquidity * (Decimal::ONE - y_share) / price, liquidity * y_share)
+                    }
+                    std::cmp::Ordering::Equal => (Decimal::ZERO, liquidity),
+                };
+
+                let value_usd = amount_x * price_x + amount_y * price_y;
+                let hold_value_usd = bin.amount_x * price_x + bin.amount_y * price_y;
+                let il_usd_value = value_usd - hold_value_usd;
+
+                BinILResult {
+                    bin_id: bin.bin_id,
+                    price,
+                    entry_amount_x: bin.amount_x,
+                    entry_amount_y: bin.amount_y,
+                    amount_x,
+                    amount_y,
+                    value_usd,
+                    hold_value_usd,
+                    il_usd_value,
+                    il_percentage: ratio(il_usd_value, hold_value_usd),
+                    crossed: is_crossed(bin.bin_id, self.entry_active_bin_id, active_bin_id),
+                }
+            })
+            .collect();
+
+        let amount_x = bins.iter().map(|bin| bin.amount_x).sum();
+        let amount_y = bins.iter().map(|bin| bin.amount_y).sum();
+        let value_usd = bins.iter().map(|bin| bin.value_usd).sum();
+        let hold_value_usd: Decimal = bins.iter().map(|bin| bin.hold_value_usd).sum();
+        let il_usd_value = value_usd - hold_value_usd;
+
+        BinILBreakdown {
+            bin_step: self.bin_step,
+            entry_active_bin_id: self.entry_active_bin_id,
+            active_bin_id,
+            active_bin_y_share: y_share,
+            bins,
+            amount_x,
+            amount_y,
+            value_usd,
+            hold_value_usd,
+            il_usd_value,
+            il_percentage: ratio(il_usd_value, hold_value_usd),
+        }
+    }
+}
+
+/// Whether the active bin passed over `bin_id` moving from `from` to `to`
+fn is_crossed(bin_id: i32, from: i32, to: i32) -> bool {
+    (from <= bin_id && bin_id < to) || (to < bin_id && bin_id <= from)
+}
+
+fn ratio(numerator: Decimal, denominator: Decimal) -> Decimal {
+    if denominator > Decimal::ZERO {
+        numerator / denominator
+    } else {
+        Decimal::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn assert_close(actual: Decimal, expected: Decimal) {
+        assert!((actual - expected).abs() < dec!(0.000001), "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_uniform_deposit_splits_around_active_bin() {
+        let position = PositionBins::uniform(-2, 2, 0, 20, dec!(30), dec!(30)).unwrap();
+
+        let amounts: Vec<(Decimal, Decimal)> = position.bins.iter().map(|bin| (bin.amount_x, bin.amount_y)).collect();
+        assert_eq!(amounts, vec![
+            (dec!(0), dec!(10)),
+            (dec!(0), dec!(10)),
+            (dec!(10), dec!(10)),
+            (dec!(10), dec!(0)),
+            (dec!(10), dec!(0)),
+        ]);
+    }
+
+    #[test]
+    fn test_normal_deposit_concentrates_at_active_bin() {
+        let distribution = LiquidityDistribution::Normal { std_dev_bins: 1.0 };
+        let position = PositionBins::with_distribution(-3, 3, 0, 20, dec!(30), dec!(30), distribution).unwrap();
+
+        let total_x: Decimal = position.bins.iter().map(|bin| bin.amount_x).sum();
+        assert_close(total_x, dec!(30));
+        assert!(position.bins[3].amount_x > position.bins[4].amount_x);
+        assert!(position.bins[4].amount_x > position.bins[6].amount_x);
+        assert_close(position.bins[2].amount_y, position.bins[4].amount_x);
+
+        let bid_ask = PositionBins::with_distribution(-3, 3, 0, 20, dec!(30), dec!(30), LiquidityDistribution::BidAsk).unwrap();
+        assert!(bid_ask.bins[6].amount_x > bid_ask.bins[4].amount_x);
+        assert_close(bid_ask.bins[0].amount_y, dec!(12));
+
+        let flat = LiquidityDistribution::Normal { std_dev_bins: 0.0 };
+        assert!(PositionBins::with_distribution(-3, 3, 0, 20, dec!(30), dec!(30), flat).is_err());
+    }
+
+    #[test]
+    fn test_unchanged_active_bin_has_no_il() {
+        let position = PositionBins::uniform(-5, 5, 0, 25, dec!(100), dec!(100)).unwrap();
+        let breakdown = position.reprice(0, dec!(0.5), dec!(1), dec!(1));
+
+        assert_close(breakdown.il_usd_value, Decimal::ZERO);
+        assert_eq!(breakdown.crossed_bins(), 0);
+    }
+
+    #[test]
+    fn test_unchanged_active_bin_keeps_entry_composition() {
+        // The active bin is deposited 10 X and 20 Y, not the even split a 0.5 share implies
+        let position = PositionBins::uniform(-2, 2, 0, 20, dec!(30), dec!(60)).unwrap();
+        let breakdown = position.reprice(0, dec!(0.5), dec!(3), dec!(1));
+
+        let active = &breakdown.bins[2];
+        assert_eq!((active.amount_x, active.amount_y), (dec!(10), dec!(20)));
+        assert_eq!(active.il_usd_value, Decimal::ZERO);
+        assert_eq!(breakdown.il_usd_value, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_crossed_bins_flip_to_other_token() {
+        let bin_step = 100;
+        let position = PositionBins::uniform(0, 4, 0, bin_step, dec!(50), dec!(10)).unwrap();
+
+        // Price rises to bin 3: bins 0-2 sell their X for Y at their own prices
+        let breakdown = position.reprice(3, dec!(0.5), bin_price(3, bin_step), dec!(1));
+
+        for bin in &breakdown.bins[..3] {
+            assert!(bin.crossed);
+            assert_eq!(bin.amount_x, Decimal::ZERO);
+            assert_close(bin.amount_y, bin.entry_amount_x * bin.price + bin.entry_amount_y);
+        }
+
+        let active = &breakdown.bins[3];
+        assert!(!active.crossed);
+        assert_close(active.amount_x, dec!(5));
+        assert_close(active.amount_y, dec!(5) * active.price);
+
+        let untouched = &breakdown.bins[4];
+        assert_eq!((untouched.amount_x, untouched.amount_y), (dec!(10), dec!(0)));
+
+        // Selling X on the way up always trails holding it
+        assert!(breakdown.il_usd_value < Decimal::ZERO);
+        assert_close(breakdown.amount_y, breakdown.bins.iter().map(|bin| bin.amount_y).sum());
+    }
+
+    #[test]
+    fn test_price_moving_below_range_ends_all_in_x() {
+        let position = PositionBins::uniform(0, 4, 2, 20, dec!(30), dec!(30)).unwrap();
+        let breakdown = position.reprice(-10, dec!(0.5), dec!(1), dec!(1));
+
+        assert_eq!(breakdown.amount_y, Decimal::ZERO);
+        assert_eq!(breakdown.crossed_bins(), 3);
+        assert!(breakdown.amount_x > dec!(30));
+    }
+
+    #[test]
+    fn test_active_bin_share_from_reserves_and_price() {
+        assert_close(reserves_y_share(dec!(1), dec!(1), 0, 20), dec!(0.5));
+        assert_close(reserves_y_share(dec!(0), dec!(1), 0, 20), dec!(1));
+
+        assert_close(price_y_share(dec!(1), 0, 20), dec!(0.5));
+        assert!(price_y_share(bin_price(0, 20) * dec!(1.0009), 0, 20) > dec!(0.9));
+        assert_eq!(price_to_bin_id(bin_price(42, 20), 20), 42);
+    }
+}
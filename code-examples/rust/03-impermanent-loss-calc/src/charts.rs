@@ -0,0 +1,689 @@
+//! Inline SVG charts for reports
+//!
+//! Charts are rendered in Rust to standalone SVG documents, so HTML reports embed them
+//! directly and open without JavaScript or network access. The same SVG can be written
+//! out as its own file next to the JSON and CSV reports.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use std::fmt::Write;
+
+use crate::bin_model::{self, BinILBreakdown};
+use crate::types::{ImpermanentLossResult, PositionAnalysis, PriceDataPoint};
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 320.0;
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 50.0;
+const MARGIN_BOTTOM: f64 = 50.0;
+const Y_TICKS: usize = 5;
+const X_TICKS: usize = 6;
+/// Most bar labels drawn before they are thinned out
+const MAX_BAR_LABELS: usize = 20;
+
+const BLUE: &str = "#3498db";
+const RED: &str = "#e74c3c";
+const GREEN: &str = "#27ae60";
+const PURPLE: &str = "#9b59b6";
+const YELLOW: &str = "#f1c40f";
+const GREY: &str = "#7f8c8d";
+
+/// A chart and the data needed to draw it
+#[derive(Debug, Clone)]
+pub struct Chart {
+    /// Short name used in exported file names
+    pub name: &'static str,
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub kind: ChartKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ChartKind {
+    /// Lines over a shared x axis, with optional horizontal bands behind them
+    Line {
+        x_axis: XAxis,
+        series: Vec<Series>,
+        bands: Vec<Band>,
+    },
+    /// One bar per category
+    Bars {
+        bars: Vec<Bar>,
+        legend: Vec<(String, &'static str)>,
+    },
+}
+
+/// How x values of a line chart are labelled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XAxis {
+    /// Unix timestamps in seconds
+    Time,
+    /// Plain numbers
+    Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct Series {
+    pub label: String,
+    pub color: &'static str,
+    pub dashed: bool,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// A shaded horizontal range, such as a position's price range
+#[derive(Debug, Clone)]
+pub struct Band {
+    pub label: String,
+    pub color: &'static str,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bar {
+    pub label: String,
+    pub value: f64,
+    pub color: &'static str,
+}
+
+/// Charts for a single position analysis: fee accrual against IL, and the bin distribution
+pub fn position_charts(analysis: &PositionAnalysis, il_result: &ImpermanentLossResult) -> Vec<Chart> {
+    [
+        Some(fee_accrual_vs_il(analysis, il_result)),
+        il_result.bin_breakdown.as_ref().map(bin_distribution),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Charts for a historical analysis: IL over time, fees collected against IL, the price
+/// path with the position's range, and the latest bin distribution
+pub fn historical_charts(il_history: &[ImpermanentLossResult], price_history: &[PriceDataPoint]) -> Vec<Chart> {
+    let latest = il_history.last();
+    [
+        il_over_time(il_history),
+        fees_vs_il(il_history),
+        price_path(price_history, latest),
+        latest.and_then(|result| result.bin_breakdown.as_ref()).map(bin_distribution),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// IL percentage at each historical point
+pub fn il_over_time(il_history: &[ImpermanentLossResult]) -> Option<Chart> {
+    if il_history.len() < 2 {
+        return None;
+    }
+
+    Some(Chart {
+        name: "il_over_time",
+        title: "Impermanent Loss Over Time".to_string(),
+        x_label: "Date".to_string(),
+        y_label: "IL (%)".to_string(),
+        kind: ChartKind::Line {
+            x_axis: XAxis::Time,
+            series: vec![Series {
+                label: "Impermanent loss".to_string(),
+                color: RED,
+                dashed: false,
+                points: il_history
+                    .iter()
+                    .map(|result| (timestamp(result.timestamp), to_f64(result.il_percentage * Decimal::new(100, 0))))
+                    .collect(),
+            }],
+            bands: Vec::new(),
+        },
+    })
+}
+
+/// Fees the position collected against its IL, for results replayed from its transactions
+pub fn fees_vs_il(il_history: &[ImpermanentLossResult]) -> Option<Chart> {
+    let points: Vec<(f64, f64, f64)> = il_history
+        .iter()
+        .filter_map(|result| {
+            result.fees_collected_usd.map(|fees| (timestamp(result.timestamp), to_f64(fees), to_f64(result.il_usd_value)))
+        })
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+
+    Some(Chart {
+        name: "fees_vs_il",
+        title: "Fee Accumulation vs Impermanent Loss".to_string(),
+        x_label: "Date".to_string(),
+        y_label: "USD".to_string(),
+        kind: ChartKind::Line {
+            x_axis: XAxis::Time,
+            series: vec![
+                Series {
+                    label: "Fees collected".to_string(),
+                    color: GREEN,
+                    dashed: false,
+                    points: points.iter().map(|&(t, fees, _)| (t, fees)).collect(),
+                },
+                Series {
+                    label: "IL cost".to_string(),
+                    color: RED,
+                    dashed: false,
+                    points: points.iter().map(|&(t, _, il)| (t, -il)).collect(),
+                },
+                Series {
+                    label: "Fees net of IL".to_string(),
+                    color: BLUE,
+                    dashed: true,
+                    points: points.iter().map(|&(t, fees, il)| (t, fees + il)).collect(),
+                },
+            ],
+            bands: Vec::new(),
+        },
+    })
+}
+
+/// Fees accrued at the position's average daily rate against its current IL, projected
+/// to the break-even day when that is within three times the position's age
+pub fn fee_accrual_vs_il(analysis: &PositionAnalysis, il_result: &ImpermanentLossResult) -> Chart {
+    let days = analysis.performance_summary.days_active.max(1) as f64;
+    let fees = to_f64(analysis.fee_analysis.total_fees_earned);
+    let daily_fees = fees / days;
+    let horizon = analysis
+        .fee_analysis
+        .break_even_days
+        .map_or(days, |break_even| (break_even as f64).clamp(days, days * 3.0));
+    let il_cost = -to_f64(il_result.il_usd_value);
+
+    let mut series = vec![Series {
+        label: "Fees earned".to_string(),
+        color: GREEN,
+        dashed: false,
+        points: vec![(0.0, 0.0), (days, fees)],
+    }];
+    if horizon > days {
+        series.push(Series {
+            label: "Projected fees".to_string(),
+            color: GREEN,
+            dashed: true,
+            points: vec![(days, fees), (horizon, daily_fees * horizon)],
+        });
+    }
+    series.push(Series {
+        label: "Current IL cost".to_string(),
+        color: RED,
+        dashed: true,
+        points: vec![(0.0, il_cost), (horizon, il_cost)],
+    });
+
+    Chart {
+        name: "fee_accrual_vs_il",
+        title: "Fee Accumulation vs Impermanent Loss".to_string(),
+        x_label: "Days since position opened".to_string(),
+        y_label: "USD".to_string(),
+        kind: ChartKind::Line {
+            x_axis: XAxis::Value,
+            series,
+            bands: Vec::new(),
+        },
+    }
+}
+
+/// Token X price in token Y over time, with the position's bin range and entry price
+pub fn price_path(price_history: &[PriceDataPoint], position: Option<&ImpermanentLossResult>) -> Option<Chart> {
+    let points: Vec<(f64, f64)> = price_history
+        .iter()
+        .filter(|point| point.price_y > Decimal::ZERO)
+        .map(|point| (timestamp(point.timestamp), to_f64(point.price_x / point.price_y)))
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+    let (start, end) = (points[0].0, points[points.len() - 1].0);
+
+    let mut series = vec![Series {
+        label: "Price".to_string(),
+        color: BLUE,
+        dashed: false,
+        points,
+    }];
+    let mut bands = Vec::new();
+    if let Some(result) = position {
+        if let Some((lower, upper)) = position_price_range(result) {
+            bands.push(Band {
+                label: "Position range".to_string(),
+                color: GREEN,
+                lower: to_f64(lower),
+                upper: to_f64(upper),
+            });
+        }
+        if result.initial_price_y > Decimal::ZERO && result.initial_price_x > Decimal::ZERO {
+            let entry = to_f64(result.initial_price_x / result.initial_price_y);
+            series.push(Series {
+                label: "Entry price".to_string(),
+                color: GREY,
+                dashed: true,
+                points: vec![(start, entry), (end, entry)],
+            });
+        }
+    }
+
+    Some(Chart {
+        name: "price_path",
+        title: "Price Path and Position Range".to_string(),
+        x_label: "Date".to_string(),
+        y_label: "Price (token Y per token X)".to_string(),
+        kind: ChartKind::Line {
+            x_axis: XAxis::Time,
+            series,
+            bands,
+        },
+    })
+}
+
+/// Value held in each of the position's bins
+pub fn bin_distribution(breakdown: &BinILBreakdown) -> Chart {
+    let bars = breakdown
+        .bins
+        .iter()
+        .map(|bin| Bar {
+            label: bin.bin_id.to_string(),
+            value: to_f64(bin.value_usd),
+            color: match bin.bin_id.cmp(&breakdown.active_bin_id) {
+                std::cmp::Ordering::Less => BLUE,
+                std::cmp::Ordering::Equal => YELLOW,
+                std::cmp::Ordering::Greater => PURPLE,
+            },
+        })
+        .collect();
+
+    Chart {
+        name: "bin_distribution",
+        title: "Liquidity by Bin".to_string(),
+        x_label: "Bin ID".to_string(),
+        y_label: "Value (USD)".to_string(),
+        kind: ChartKind::Bars {
+            bars,
+            legend: vec![
+                ("Token Y bins".to_string(), BLUE),
+                ("Active bin".to_string(), YELLOW),
+                ("Token X bins".to_string(), PURPLE),
+            ],
+        },
+    }
+}
+
+/// Price range covered by a result's bins, when its range and bin step are known
+fn position_price_range(result: &ImpermanentLossResult) -> Option<(Decimal, Decimal)> {
+    let (lower_bin_id, upper_bin_id) = result.metadata.bin_range?;
+    let bin_step = result.bin_breakdown.as_ref()?.bin_step;
+    Some((bin_model::bin_price(lower_bin_id, bin_step), bin_model::bin_price(upper_bin_id, bin_step)))
+}
+
+impl Chart {
+    /// Render as a standalone SVG document that scales to its container's width
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        let _ = write!(
+            svg,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {WIDTH} {HEIGHT}" width="100%" font-family="Arial, sans-serif" font-size="11" role="img"><title>{title}</title><rect width="{WIDTH}" height="{HEIGHT}" fill="white"/><text x="{x}" y="22" text-anchor="middle" font-size="15" font-weight="bold" fill="#2c3e50">{title}</text>"##,
+            title = escape(&self.title),
+            x = WIDTH / 2.0,
+        );
+
+        match &self.kind {
+            ChartKind::Line { x_axis, series, bands } => self.draw_line(&mut svg, *x_axis, series, bands),
+            ChartKind::Bars { bars, legend } => self.draw_bars(&mut svg, bars, legend),
+        }
+
+        let plot_bottom = HEIGHT - MARGIN_BOTTOM;
+        let _ = write!(
+            svg,
+            r##"<line x1="{MARGIN_LEFT}" y1="{plot_bottom}" x2="{right}" y2="{plot_bottom}" stroke="#34495e"/><line x1="{MARGIN_LEFT}" y1="{MARGIN_TOP}" x2="{MARGIN_LEFT}" y2="{plot_bottom}" stroke="#34495e"/><text x="{x_center}" y="{x_label_y}" text-anchor="middle" fill="{GREY}">{x_label}</text><text transform="translate(16 {y_center}) rotate(-90)" text-anchor="middle" fill="{GREY}">{y_label}</text></svg>"##,
+            right = WIDTH - MARGIN_RIGHT,
+            x_center = MARGIN_LEFT + plot_width() / 2.0,
+            x_label_y = HEIGHT - 10.0,
+            y_center = MARGIN_TOP + plot_height() / 2.0,
+            x_label = escape(&self.x_label),
+            y_label = escape(&self.y_label),
+        );
+        svg
+    }
+
+    fn draw_line(&self, svg: &mut String, x_axis: XAxis, series: &[Series], bands: &[Band]) {
+        let xs = series.iter().flat_map(|s| s.points.iter().map(|p| p.0));
+        let ys = series
+            .iter()
+            .flat_map(|s| s.points.iter().map(|p| p.1))
+            .chain(bands.iter().flat_map(|band| [band.lower, band.upper]));
+        let (x_min, x_max) = bounds(xs);
+        let y_scale = Scale::nice(bounds(ys));
+        let x_scale = match x_axis {
+            XAxis::Time => Scale::plain(x_min, x_max, X_TICKS),
+            XAxis::Value => Scale::nice((x_min, x_max)),
+        };
+
+        draw_y_grid(svg, &y_scale);
+        for &tick in &x_scale.ticks {
+            let label = match x_axis {
+                XAxis::Time => format_time(tick, x_max - x_min),
+                XAxis::Value => format_tick(tick, x_scale.step),
+            };
+            let _ = write!(
+                svg,
+                r#"<text x="{x:.1}" y="{y}" text-anchor="middle" fill="{GREY}">{label}</text>"#,
+                x = x_scale.to_x(tick),
+                y = HEIGHT - MARGIN_BOTTOM + 16.0,
+            );
+        }
+
+        for band in bands {
+            let (top, bottom) = (y_scale.to_y(band.upper), y_scale.to_y(band.lower));
+            let _ = write!(
+                svg,
+                r#"<rect x="{MARGIN_LEFT}" y="{top:.1}" width="{width}" height="{height:.1}" fill="{color}" fill-opacity="0.15"/><text x="{label_x}" y="{label_y:.1}" text-anchor="end" fill="{color}">{label}</text>"#,
+                width = plot_width(),
+                height = (bottom - top).max(1.0),
+                color = band.color,
+                label_x = WIDTH - MARGIN_RIGHT - 4.0,
+                label_y = top + 12.0,
+                label = escape(&band.label),
+            );
+        }
+
+        for s in series {
+            let points = s
+                .points
+                .iter()
+                .map(|&(x, y)| format!("{:.1},{:.1}", x_scale.to_x(x), y_scale.to_y(y)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = write!(
+                svg,
+                r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="2"{dash}/>"#,
+                color = s.color,
+                dash = if s.dashed { r#" stroke-dasharray="6 4""# } else { "" },
+            );
+        }
+
+        draw_legend(svg, series.iter().map(|s| (s.label.as_str(), s.color)));
+    }
+
+    fn draw_bars(&self, svg: &mut String, bars: &[Bar], legend: &[(String, &'static str)]) {
+        let (low, high) = bounds(bars.iter().map(|bar| bar.value).chain([0.0]));
+        let y_scale = Scale::nice((low, high));
+        draw_y_grid(svg, &y_scale);
+
+        let slot = plot_width() / bars.len().max(1) as f64;
+        let label_every = bars.len().div_ceil(MAX_BAR_LABELS).max(1);
+        let zero = y_scale.to_y(0.0);
+        for (index, bar) in bars.iter().enumerate() {
+            let x = MARGIN_LEFT + slot * index as f64;
+            let y = y_scale.to_y(bar.value);
+            let _ = write!(
+                svg,
+                r#"<rect x="{x:.1}" y="{top:.1}" width="{width:.1}" height="{height:.1}" fill="{color}"><title>{label}: {value:.2}</title></rect>"#,
+                x = x + slot * 0.1,
+                top = y.min(zero),
+                width = slot * 0.8,
+                height = (zero - y).abs(),
+                color = bar.color,
+                label = escape(&bar.label),
+                value = bar.value,
+            );
+            if index % label_every == 0 {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{x:.1}" y="{y}" text-anchor="middle" fill="{GREY}">{label}</text>"#,
+                    x = x + slot / 2.0,
+                    y = HEIGHT - MARGIN_BOTTOM + 16.0,
+                    label = escape(&bar.label),
+                );
+            }
+        }
+
+        draw_legend(svg, legend.iter().map(|(label, color)| (label.as_str(), *color)));
+    }
+}
+
+/// Linear mapping of one axis onto the plot area, with its tick values
+struct Scale {
+    min: f64,
+    max: f64,
+    step: f64,
+    ticks: Vec<f64>,
+}
+
+impl Scale {
+    /// Extend the range to round tick boundaries
+    fn nice((min, max): (f64, f64)) -> Self {
+        let step = nice_step((max - min) / Y_TICKS as f64);
+        let (min, max) = ((min / step).floor() * step, (max / step).ceil() * step);
+        let count = ((max - min) / step).round() as usize;
+        let ticks = (0..=count).map(|i| min + step * i as f64).collect();
+        Scale { min, max, step, ticks }
+    }
+
+    /// Keep the range as is, with evenly spaced ticks
+    fn plain(min: f64, max: f64, count: usize) -> Self {
+        let step = (max - min) / (count - 1) as f64;
+        let ticks = (0..count).map(|i| min + step * i as f64).collect();
+        Scale { min, max, step, ticks }
+    }
+
+    fn fraction(&self, value: f64) -> f64 {
+        if self.max > self.min {
+            (value - self.min) / (self.max - self.min)
+        } else {
+            0.5
+        }
+    }
+
+    fn to_x(&self, value: f64) -> f64 {
+        MARGIN_LEFT + self.fraction(value) * plot_width()
+    }
+
+    fn to_y(&self, value: f64) -> f64 {
+        HEIGHT - MARGIN_BOTTOM - self.fraction(value) * plot_height()
+    }
+}
+
+fn draw_y_grid(svg: &mut String, y_scale: &Scale) {
+    for &tick in &y_scale.ticks {
+        let y = y_scale.to_y(tick);
+        let _ = write!(
+            svg,
+            r##"<line x1="{MARGIN_LEFT}" y1="{y:.1}" x2="{right}" y2="{y:.1}" stroke="#ecf0f1"/><text x="{label_x}" y="{label_y:.1}" text-anchor="end" fill="{GREY}">{label}</text>"##,
+            right = WIDTH - MARGIN_RIGHT,
+            label_x = MARGIN_LEFT - 6.0,
+            label_y = y + 4.0,
+            label = format_tick(tick, y_scale.step),
+        );
+    }
+}
+
+fn draw_legend<'a>(svg: &mut String, items: impl Iterator<Item = (&'a str, &'a str)>) {
+    let mut x = MARGIN_LEFT;
+    for (label, color) in items {
+        let _ = write!(
+            svg,
+            r##"<rect x="{x}" y="33" width="12" height="4" fill="{color}"/><text x="{text_x}" y="39" fill="#2c3e50">{label}</text>"##,
+            text_x = x + 16.0,
+            label = escape(label),
+        );
+        x += 16.0 + 7.0 * label.chars().count() as f64 + 20.0;
+    }
+}
+
+fn plot_width() -> f64 {
+    WIDTH - MARGIN_LEFT - MARGIN_RIGHT
+}
+
+fn plot_height() -> f64 {
+    HEIGHT - MARGIN_TOP - MARGIN_BOTTOM
+}
+
+/// Smallest and largest value, widened when they are equal so the range is never empty
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values
+        .filter(|value| value.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)));
+    if min > max {
+        (0.0, 1.0)
+    } else if min == max {
+        let pad = if min == 0.0 { 1.0 } else { min.abs() * 0.1 };
+        (min - pad, max + pad)
+    } else {
+        (min, max)
+    }
+}
+
+/// Round a raw tick step up to 1, 2, 2.5 or 5 times a power of ten
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|step| *step >= raw * (1.0 - 1e-9))
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Tick label with just enough decimals to tell neighbouring ticks apart
+fn format_tick(value: f64, step: f64) -> String {
+    if value.abs() < step * 1e-6 {
+        return "0".to_string();
+    }
+    let (value, step, suffix) = if step >= 1e6 {
+        (value / 1e6, step / 1e6, "M")
+    } else if step >= 1e3 {
+        (value / 1e3, step / 1e3, "k")
+    } else {
+        (value, step, "")
+    };
+    let decimals = (0..6)
+        .find(|&decimals| {
+            let scaled = step * 10f64.powi(decimals as i32);
+            (scaled - scaled.round()).abs() < 1e-6
+        })
+        .unwrap_or(6);
+    format!("{:.*}{}", decimals, value, suffix)
+}
+
+fn format_time(seconds: f64, span_seconds: f64) -> String {
+    let Some(time) = DateTime::<Utc>::from_timestamp(seconds as i64, 0) else {
+        return String::new();
+    };
+    if span_seconds > 2.0 * 86_400.0 {
+        time.format("%m-%d").to_string()
+    } else {
+        time.format("%m-%d %H:%M").to_string()
+    }
+}
+
+fn timestamp(time: DateTime<Utc>) -> f64 {
+    time.timestamp() as f64
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
+
+/// Escape text for SVG and HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bin_model::BinILResult;
+    use rust_decimal_macros::dec;
+
+    fn bin(bin_id: i32, value_usd: Decimal) -> BinILResult {
+        BinILResult {
+            bin_id,
+            price: dec!(1),
+            entry_amount_x: dec!(0),
+            entry_amount_y: dec!(0),
+            amount_x: dec!(0),
+            amount_y: dec!(0),
+            value_usd,
+            hold_value_usd: value_usd,
+            il_usd_value: dec!(0),
+            il_percentage: dec!(0),
+            crossed: false,
+        }
+    }
+
+    #[test]
+    fn test_nice_ticks_and_labels() {
+        assert_eq!(nice_step(0.23), 0.25);
+        assert_eq!(nice_step(3.0), 5.0);
+        assert_eq!(nice_step(1400.0), 2000.0);
+
+        let scale = Scale::nice((-7.3, 1.2));
+        assert_eq!(scale.ticks.first(), Some(&-8.0));
+        assert_eq!(scale.ticks.last(), Some(&2.0));
+
+        assert_eq!(format_tick(0.25, 0.25), "0.25");
+        assert_eq!(format_tick(-4.0, 2.0), "-4");
+        assert_eq!(format_tick(2500.0, 2500.0), "2.5k");
+        assert_eq!(format_tick(1e-17, 5000.0), "0");
+    }
+
+    #[test]
+    fn test_line_chart_svg() {
+        let chart = Chart {
+            name: "test",
+            title: "Fees & <IL>".to_string(),
+            x_label: "Days".to_string(),
+            y_label: "USD".to_string(),
+            kind: ChartKind::Line {
+                x_axis: XAxis::Value,
+                series: vec![Series {
+                    label: "Fees".to_string(),
+                    color: GREEN,
+                    dashed: true,
+                    points: vec![(0.0, 0.0), (1.0, 5.0), (2.0, 12.0)],
+                }],
+                bands: vec![Band { label: "Range".to_string(), color: BLUE, lower: 2.0, upper: 4.0 }],
+            },
+        };
+
+        let svg = chart.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains("Fees &amp; &lt;IL&gt;"));
+        assert!(!svg.contains("<IL>"));
+        assert!(svg.contains("stroke-dasharray"));
+        assert!(svg.contains("fill-opacity=\"0.15\""));
+        let polyline = svg.split("<polyline points=\"").nth(1).unwrap().split('"').next().unwrap();
+        assert_eq!(polyline.split(' ').count(), 3);
+    }
+
+    #[test]
+    fn test_bin_distribution_highlights_active_bin() {
+        let breakdown = BinILBreakdown {
+            bin_step: 20,
+            entry_active_bin_id: 0,
+            active_bin_id: 1,
+            active_bin_y_share: dec!(0.5),
+            bins: (-1..=2).map(|bin_id| bin(bin_id, dec!(100))).collect(),
+            amount_x: dec!(0),
+            amount_y: dec!(0),
+            value_usd: dec!(400),
+            hold_value_usd: dec!(400),
+            il_usd_value: dec!(0),
+            il_percentage: dec!(0),
+        };
+
+        let chart = bin_distribution(&breakdown);
+        let ChartKind::Bars { bars, .. } = &chart.kind else { panic!("expected a bar chart") };
+        let colors: Vec<&str> = bars.iter().map(|bar| bar.color).collect();
+        assert_eq!(colors, vec![BLUE, BLUE, YELLOW, PURPLE]);
+        assert_eq!(chart.to_svg().matches("<rect x=").count(), 4 + 3);
+    }
+}
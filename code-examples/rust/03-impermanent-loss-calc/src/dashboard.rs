@@ -0,0 +1,656 @@
+//! Interactive terminal dashboard for live IL monitoring
+//!
+//! `il_calc --mode monitor` opens this dashboard when stdout is a terminal. Each refresh
+//! re-calculates IL for the tracked positions and shows them in a table, with IL over
+//! time and the pool's bin liquidity for the selected position and an alert pane fed by
+//! the alert rules each fresh analysis is checked against.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use log::LevelFilter;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::VecDeque;
+use std::io::{self, Stdout};
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration};
+use tui::backend::{Backend, CrosstermBackend};
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Line, Span};
+use tui::widgets::{Bar, BarChart, BarGroup, Block, Borders, Cell, List, ListItem, Paragraph, Row, Sparkline, Table, TableState};
+use tui::{Frame, Terminal};
+
+use crate::alerts::AlertEngine;
+use crate::bin_model;
+use crate::il_calculator::ILCalculator;
+use crate::position_analyzer::PositionAnalyzer;
+use crate::report_generator::ReportGenerator;
+use crate::types::{
+    short_id, AnalysisConfig, BinData, ILNotificationEvent, NotificationSeverity, PositionAnalysis,
+    ReportConfig, ReportFormat,
+};
+
+/// IL readings kept per position for the sparkline
+const IL_HISTORY_LEN: usize = 120;
+/// Alerts kept in the alert pane
+const MAX_ALERTS: usize = 100;
+/// Bins shown either side of a position's range in the liquidity histogram
+const HISTOGRAM_PADDING_BINS: i32 = 10;
+
+/// Latest figures for a tracked position
+#[derive(Debug, Clone)]
+pub struct PositionStatus {
+    pub il_percentage: Decimal,
+    pub il_usd_value: Decimal,
+    pub fees_earned: Decimal,
+    pub net_pnl: Decimal,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub active_bin_id: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PositionStatus {
+    fn from_analysis(analysis: &PositionAnalysis) -> Self {
+        let il_result = &analysis.il_result;
+        let (lower_bin_id, upper_bin_id) = il_result
+            .metadata
+            .bin_range
+            .unwrap_or((analysis.position_info.lower_bin_id, analysis.position_info.upper_bin_id));
+
+        Self {
+            il_percentage: il_result.il_percentage,
+            il_usd_value: il_result.il_usd_value,
+            fees_earned: analysis.fee_analysis.total_fees_earned,
+            net_pnl: analysis.performance_summary.net_pnl,
+            lower_bin_id,
+            upper_bin_id,
+            active_bin_id: il_result.metadata.active_bin_id.unwrap_or_default(),
+            updated_at: analysis.timestamp,
+        }
+    }
+
+    pub fn in_range(&self) -> bool {
+        (self.lower_bin_id..=self.upper_bin_id).contains(&self.active_bin_id)
+    }
+}
+
+/// A position shown on the dashboard
+#[derive(Debug, Clone)]
+pub struct TrackedPosition {
+    pub position_id: Pubkey,
+    pub status: Option<PositionStatus>,
+    /// IL percentage at each refresh, oldest first
+    pub il_history: VecDeque<Decimal>,
+    /// Pool bins around the position's range
+    pub bins: Vec<BinData>,
+    pub last_error: Option<String>,
+    analysis: Option<PositionAnalysis>,
+}
+
+impl TrackedPosition {
+    fn new(position_id: Pubkey) -> Self {
+        Self {
+            position_id,
+            status: None,
+            il_history: VecDeque::new(),
+            bins: Vec::new(),
+            last_error: None,
+            analysis: None,
+        }
+    }
+}
+
+/// Everything the dashboard draws
+#[derive(Debug, Clone)]
+pub struct DashboardState {
+    pub pool_address: Pubkey,
+    pub bin_step: u16,
+    pub positions: Vec<TrackedPosition>,
+    pub selected: usize,
+    /// Newest first
+    pub alerts: VecDeque<ILNotificationEvent>,
+    pub status_line: String,
+    pub last_refresh: Option<DateTime<Utc>>,
+}
+
+impl DashboardState {
+    pub fn new(pool_address: Pubkey, bin_step: u16, position_ids: &[Pubkey]) -> Self {
+        Self {
+            pool_address,
+            bin_step,
+            positions: position_ids.iter().copied().map(TrackedPosition::new).collect(),
+            selected: 0,
+            alerts: VecDeque::new(),
+            status_line: "Loading positions...".to_string(),
+            last_refresh: None,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&TrackedPosition> {
+        self.positions.get(self.selected)
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.positions.is_empty() {
+            self.selected = (self.selected + 1) % self.positions.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.positions.is_empty() {
+            self.selected = (self.selected + self.positions.len() - 1) % self.positions.len();
+        }
+    }
+
+    pub fn push_alert(&mut self, alert: ILNotificationEvent) {
+        self.alerts.push_front(alert);
+        self.alerts.truncate(MAX_ALERTS);
+    }
+
+    /// Record a fresh analysis for the position at `index`
+    pub fn record_analysis(&mut self, index: usize, analysis: PositionAnalysis, bins: Vec<BinData>) {
+        let status = PositionStatus::from_analysis(&analysis);
+        let Some(position) = self.positions.get_mut(index) else {
+            return;
+        };
+
+        position.il_history.push_back(status.il_percentage);
+        if position.il_history.len() > IL_HISTORY_LEN {
+            position.il_history.pop_front();
+        }
+        position.status = Some(status);
+        position.bins = bins;
+        position.last_error = None;
+        position.analysis = Some(analysis);
+    }
+
+    pub fn record_error(&mut self, index: usize, error: String) {
+        if let Some(position) = self.positions.get_mut(index) {
+            position.last_error = Some(error);
+        }
+    }
+}
+
+/// Run the dashboard until the user quits
+pub async fn run_dashboard(
+    il_calculator: &mut ILCalculator,
+    position_analyzer: &mut PositionAnalyzer,
+    report_generator: &mut ReportGenerator,
+    alert_engine: &mut AlertEngine,
+    config: &AnalysisConfig,
+    formats: &[ReportFormat],
+) -> Result<()> {
+    let pool_info = position_analyzer.get_pool_info(config.pool_address).await?;
+    let mut state = DashboardState::new(config.pool_address, pool_info.bin_step, &config.position_ids);
+
+    // Log lines would draw over the dashboard
+    let log_level = log::max_level();
+    log::set_max_level(LevelFilter::Off);
+    let mut terminal = TerminalGuard::enter()?;
+    let mut keys = spawn_key_reader();
+    let mut refresh = interval(Duration::from_secs(config.interval_secs.max(1)));
+
+    let result = async {
+        loop {
+            tokio::select! {
+                _ = refresh.tick() => {
+                    refresh_dashboard(&mut state, il_calculator, position_analyzer, alert_engine, config).await;
+                }
+                key = keys.recv() => {
+                    let Some(key) = key else { break };
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => break,
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                        KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => state.select_next(),
+                        KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => state.select_previous(),
+                        KeyCode::Char('r') => {
+                            refresh_dashboard(&mut state, il_calculator, position_analyzer, alert_engine, config).await;
+                        }
+                        KeyCode::Char('e') => {
+                            state.status_line = match export_selected(&state, report_generator, formats).await {
+                                Ok(paths) => format!("Exported {} report(s) to {}", paths, config.output_directory),
+                                Err(e) => format!("Export failed: {}", e),
+                            };
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+            terminal.0.draw(|frame| draw(frame, &state))?;
+        }
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+
+    drop(terminal);
+    log::set_max_level(log_level);
+    alert_engine.flush().await;
+    result
+}
+
+/// Re-calculate every tracked position and check each fresh analysis against the alert
+/// rules
+async fn refresh_dashboard(
+    state: &mut DashboardState,
+    il_calculator: &mut ILCalculator,
+    position_analyzer: &mut PositionAnalyzer,
+    alert_engine: &mut AlertEngine,
+    config: &AnalysisConfig,
+) {
+    for index in 0..state.positions.len() {
+        let position_id = state.positions[index].position_id;
+        match refresh_position(il_calculator, position_analyzer, config, position_id).await {
+            Ok((analysis, bins)) => {
+                for alert in alert_engine.process(&analysis) {
+                    state.push_alert(alert);
+                }
+                state.record_analysis(index, analysis, bins);
+            }
+            Err(e) => state.record_error(index, e.to_string()),
+        }
+    }
+
+    let failed = state.positions.iter().filter(|position| position.last_error.is_some()).count();
+    state.last_refresh = Some(Utc::now());
+    state.status_line = if failed > 0 {
+        format!("Refreshed; {} of {} positions failed", failed, state.positions.len())
+    } else {
+        format!("Refreshed {} position(s)", state.positions.len())
+    };
+}
+
+async fn refresh_position(
+    il_calculator: &mut ILCalculator,
+    position_analyzer: &mut PositionAnalyzer,
+    config: &AnalysisConfig,
+    position_id: Pubkey,
+) -> Result<(PositionAnalysis, Vec<BinData>)> {
+    let il_result = il_calculator.calculate_il_from_position(config.pool_address, position_id).await?;
+    let position = position_analyzer.get_position_info(position_id).await?;
+    let analysis = position_analyzer.analyze_position_performance(config.pool_address, Some(position), &il_result).await?;
+
+    let status = PositionStatus::from_analysis(&analysis);
+    let lower = status.lower_bin_id.min(status.active_bin_id) - HISTOGRAM_PADDING_BINS;
+    let upper = status.upper_bin_id.max(status.active_bin_id) + HISTOGRAM_PADDING_BINS;
+    let bins = position_analyzer.get_bin_data(config.pool_address, lower, upper, status.active_bin_id).await?;
+
+    Ok((analysis, bins))
+}
+
+/// Write reports for the selected position in every requested format
+async fn export_selected(
+    state: &DashboardState,
+    report_generator: &mut ReportGenerator,
+    formats: &[ReportFormat],
+) -> Result<usize> {
+    let position = state.selected().ok_or_else(|| anyhow::anyhow!("no position selected"))?;
+    let analysis = position
+        .analysis
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("{} has not been analysed yet", short_id(&position.position_id)))?;
+
+    let report_config = ReportConfig {
+        title: format!("DLMM Monitor Report - {}", position.position_id),
+        include_charts: true,
+        include_raw_data: true,
+        timestamp: Utc::now(),
+    };
+    for format in formats {
+        report_generator.generate_report(analysis, &analysis.il_result, &report_config, format.clone()).await?;
+    }
+    Ok(formats.len())
+}
+
+/// Read key presses on a blocking thread; the thread ends once the receiver is dropped
+fn spawn_key_reader() -> mpsc::UnboundedReceiver<KeyEvent> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while !sender.is_closed() {
+            match event::poll(std::time::Duration::from_millis(100)) {
+                Ok(true) => {
+                    if let Ok(Event::Key(key)) = event::read() {
+                        if key.kind == KeyEventKind::Press && sender.send(key).is_err() {
+                            break;
+                        }
+                    }
+                }
+                Ok(false) => {}
+                Err(_) => break,
+            }
+        }
+    });
+    receiver
+}
+
+/// Raw mode and the alternate screen for the dashboard's lifetime
+struct TerminalGuard(Terminal<CrosstermBackend<Stdout>>);
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+        terminal.hide_cursor()?;
+        Ok(Self(terminal))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.0.backend_mut(), LeaveAlternateScreen);
+        let _ = self.0.show_cursor();
+    }
+}
+
+/// Draw the dashboard
+pub fn draw<B: Backend>(frame: &mut Frame<B>, state: &DashboardState) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(state.positions.len().clamp(1, 10) as u16 + 3),
+            Constraint::Min(8),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+
+    draw_header(frame, rows[0], state);
+    draw_positions(frame, rows[1], state);
+
+    let charts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rows[2]);
+    draw_il_sparkline(frame, charts[0], state);
+    draw_bin_histogram(frame, charts[1], state);
+
+    draw_alerts(frame, rows[3], state);
+
+    let help = Paragraph::new(Line::from(vec![
+        Span::styled(" ↑/↓ ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw("switch position  "),
+        Span::styled("e ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw("export report  "),
+        Span::styled("r ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw("refresh  "),
+        Span::styled("q ", Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw("quit  │ "),
+        Span::raw(state.status_line.as_str()),
+    ]));
+    frame.render_widget(help, rows[4]);
+}
+
+fn draw_header<B: Backend>(frame: &mut Frame<B>, area: Rect, state: &DashboardState) {
+    let refreshed = state
+        .last_refresh
+        .map(|time| time.format("%H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    let header = Paragraph::new(Line::from(vec![
+        Span::styled("Pool ", Style::default().fg(Color::Gray)),
+        Span::raw(state.pool_address.to_string()),
+        Span::styled("  bin step ", Style::default().fg(Color::Gray)),
+        Span::raw(state.bin_step.to_string()),
+        Span::styled("  refreshed ", Style::default().fg(Color::Gray)),
+        Span::raw(refreshed),
+    ]))
+    .block(Block::default().borders(Borders::ALL).title(" DLMM IL Monitor "));
+    frame.render_widget(header, area);
+}
+
+fn draw_positions<B: Backend>(frame: &mut Frame<B>, area: Rect, state: &DashboardState) {
+    let header = Row::new(["Position", "IL %", "IL USD", "Fees", "Net PnL", "Range", "Updated"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = state.positions.iter().map(|position| {
+        let id = Cell::from(short_id(&position.position_id));
+        match (&position.status, &position.last_error) {
+            (_, Some(error)) => Row::new(vec![id, Cell::from(format!("error: {}", error))])
+                .style(Style::default().fg(Color::Red)),
+            (Some(status), None) => {
+                let range = if status.in_range() {
+                    Cell::from("in range").style(Style::default().fg(Color::Green))
+                } else {
+                    Cell::from("out of range").style(Style::default().fg(Color::Red))
+                };
+                Row::new(vec![
+                    id,
+                    Cell::from(format!("{:.2}%", status.il_percentage * Decimal::new(100, 0)))
+                        .style(Style::default().fg(signed_color(status.il_percentage))),
+                    Cell::from(format!("${:.2}", status.il_usd_value)),
+                    Cell::from(format!("${:.2}", status.fees_earned)),
+                    Cell::from(format!("${:.2}", status.net_pnl)).style(Style::default().fg(signed_color(status.net_pnl))),
+                    range,
+                    Cell::from(status.updated_at.format("%H:%M:%S").to_string()),
+                ])
+            }
+            (None, None) => Row::new(vec![id, Cell::from("loading...")]),
+        }
+    });
+
+    let widths = [
+        Constraint::Length(12),
+        Constraint::Length(9),
+        Constraint::Length(12),
+        Constraint::Length(11),
+        Constraint::Length(12),
+        Constraint::Length(13),
+        Constraint::Min(8),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(" Positions "))
+        .widths(&widths)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("▶ ");
+
+    let mut table_state = TableState::default();
+    table_state.select(Some(state.selected));
+    frame.render_stateful_widget(table, area, &mut table_state);
+}
+
+fn draw_il_sparkline<B: Backend>(frame: &mut Frame<B>, area: Rect, state: &DashboardState) {
+    let position = state.selected();
+    // Plot the loss magnitude in basis points; the sparkline can't go negative
+    let data: Vec<u64> = position
+        .map(|position| {
+            position
+                .il_history
+                .iter()
+                .map(|il| (il.abs() * Decimal::new(10_000, 0)).to_u64().unwrap_or(0))
+                .collect()
+        })
+        .unwrap_or_default();
+    let title = match position.and_then(|position| position.il_history.back()) {
+        Some(latest) => format!(" |IL| (bps), now {:.2}% ", latest * Decimal::new(100, 0)),
+        None => " |IL| (bps) ".to_string(),
+    };
+
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .data(&data)
+        .style(Style::default().fg(Color::Magenta));
+    frame.render_widget(sparkline, area);
+}
+
+fn draw_bin_histogram<B: Backend>(frame: &mut Frame<B>, area: Rect, state: &DashboardState) {
+    let position = state.selected();
+    let status = position.and_then(|position| position.status.as_ref());
+    let bins = position.map(|position| position.bins.as_slice()).unwrap_or_default();
+
+    let title = match status {
+        Some(status) => format!(" Bin liquidity, range [{}, {}], active {} ", status.lower_bin_id, status.upper_bin_id, status.active_bin_id),
+        None => " Bin liquidity ".to_string(),
+    };
+
+    let bars: Vec<Bar> = bins
+        .iter()
+        .map(|bin| {
+            // Liquidity in token Y at the bin's price
+            let value = bin.liquidity_x * bin_model::bin_price(bin.bin_id, state.bin_step) + bin.liquidity_y;
+            let color = match status {
+                _ if bin.is_active => Color::Yellow,
+                Some(status) if (status.lower_bin_id..=status.upper_bin_id).contains(&bin.bin_id) => Color::Cyan,
+                _ => Color::DarkGray,
+            };
+            Bar::default()
+                .value(value.to_u64().unwrap_or(0))
+                .text_value(String::new())
+                .style(Style::default().fg(color))
+        })
+        .collect();
+
+    let inner_width = area.width.saturating_sub(2) as usize;
+    let bar_width = (inner_width / bars.len().max(1)).saturating_sub(1).clamp(1, 3) as u16;
+    let chart = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(bar_width)
+        .bar_gap(1);
+    frame.render_widget(chart, area);
+}
+
+fn draw_alerts<B: Backend>(frame: &mut Frame<B>, area: Rect, state: &DashboardState) {
+    let items: Vec<ListItem> = state
+        .alerts
+        .iter()
+        .map(|alert| {
+            let color = match alert.severity {
+                NotificationSeverity::Info => Color::Blue,
+                NotificationSeverity::Warning => Color::Yellow,
+                NotificationSeverity::Critical => Color::Red,
+                NotificationSeverity::Emergency => Color::LightRed,
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(alert.timestamp.format("%H:%M:%S ").to_string()),
+                Span::styled(format!("{:<9}", format!("{:?}", alert.severity)), Style::default().fg(color)),
+                Span::raw(alert.message.as_str()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(format!(" Alerts ({}) ", state.alerts.len())));
+    frame.render_widget(list, area);
+}
+
+fn signed_color(value: Decimal) -> Color {
+    if value < Decimal::ZERO {
+        Color::Red
+    } else {
+        Color::Green
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{AlertCondition, AlertRule};
+    use crate::report_generator::tests::create_mock_analysis;
+    use crate::types::ILEventType;
+    use rust_decimal_macros::dec;
+    use tui::backend::TestBackend;
+
+    /// Position over bins 95..=105 worth $1000, analysed now
+    fn analysis(position_id: Pubkey, il_percentage: Decimal, active_bin_id: i32) -> PositionAnalysis {
+        let now = Utc::now();
+        let mut analysis = create_mock_analysis();
+        analysis.timestamp = now;
+        analysis.position_info.position_id = Some(position_id);
+        analysis.position_info.lower_bin_id = 95;
+        analysis.position_info.upper_bin_id = 105;
+
+        let il_result = &mut analysis.il_result;
+        il_result.il_percentage = il_percentage;
+        il_result.il_usd_value = il_percentage * dec!(1000);
+        il_result.timestamp = now;
+        il_result.metadata.position_id = Some(position_id);
+        il_result.metadata.bin_range = Some((95, 105));
+        il_result.metadata.active_bin_id = Some(active_bin_id);
+        analysis
+    }
+
+    #[test]
+    fn test_selection_wraps_around_positions() {
+        let ids = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut state = DashboardState::new(Pubkey::new_unique(), 20, &ids);
+
+        state.select_previous();
+        assert_eq!(state.selected().unwrap().position_id, ids[2]);
+        state.select_next();
+        state.select_next();
+        assert_eq!(state.selected().unwrap().position_id, ids[1]);
+    }
+
+    #[test]
+    fn test_alert_pane_shows_rule_events_newest_first() {
+        let id = Pubkey::new_unique();
+        let mut state = DashboardState::new(Pubkey::new_unique(), 20, &[id]);
+        let rule = |name: &str, condition| AlertRule {
+            name: name.to_string(),
+            condition,
+            severity: None,
+            hysteresis: dec!(0),
+            cooldown_secs: 0,
+        };
+        let mut engine = AlertEngine::new(vec![
+            rule("high-il", AlertCondition::IlBelow { percent: dec!(-5) }),
+            rule("out-of-range", AlertCondition::OutOfRange { minutes: 0 }),
+            rule("recovery", AlertCondition::Recovery { percent: dec!(2) }),
+        ]);
+
+        for (il_percentage, active_bin_id) in [(dec!(-0.01), 100), (dec!(-0.06), 110), (dec!(-0.07), 111), (dec!(-0.02), 104)] {
+            let analysis = analysis(id, il_percentage, active_bin_id);
+            for alert in engine.evaluate(&analysis) {
+                state.push_alert(alert);
+            }
+            state.record_analysis(0, analysis, Vec::new());
+        }
+
+        assert_eq!(state.alerts.len(), 3);
+        assert!(matches!(state.alerts[0].event_type, ILEventType::RecoveryDetected));
+        assert!(matches!(state.alerts[1].event_type, ILEventType::PositionOutOfRange));
+        assert!(matches!(state.alerts[2].event_type, ILEventType::HighImpermanentLoss));
+        assert_eq!(state.positions[0].il_history.len(), 4);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| draw(frame, &state)).unwrap();
+        let text: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol.as_str()).collect();
+        assert!(text.contains("Alerts (3)"));
+    }
+
+    #[test]
+    fn test_draws_positions_and_bins() {
+        let id = Pubkey::new_unique();
+        let mut state = DashboardState::new(Pubkey::new_unique(), 20, &[id]);
+        let bins = (90..=110)
+            .map(|bin_id| BinData {
+                bin_id,
+                price: bin_model::bin_price(bin_id, 20),
+                liquidity_x: dec!(5),
+                liquidity_y: dec!(2),
+                total_liquidity: dec!(7),
+                fee_rate: dec!(0.002),
+                is_active: bin_id == 100,
+            })
+            .collect();
+        state.record_analysis(0, analysis(id, dec!(-0.0123), 100), bins);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| draw(frame, &state)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol.as_str()).collect();
+        assert!(text.contains("Net PnL"));
+        assert!(text.contains("-1.23%"));
+        assert!(text.contains("in range"));
+        assert!(text.contains("range [95, 105], active 100"));
+    }
+}
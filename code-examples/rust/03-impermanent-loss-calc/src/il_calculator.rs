@@ -1,6 +1,6 @@
 //! Core impermanent loss calculation logic with mathematical precision
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use log::{debug, info, warn};
 use rust_decimal::prelude::*;
@@ -8,21 +8,35 @@ use rust_decimal::Decimal;
 use std::collections::BTreeMap;
 use tokio::time::{sleep, Duration};
 
-use saros_dlmm_sdk::{DLMMClient as MockSarosClient, Position as SdkPosition, DLMMPoolInfo};
+use saros_dlmm_sdk::{DLMMClient as MockSarosClient, Position as SdkPosition, DLMMPoolInfo, SharedPriceSource};
+use solana_sdk::pubkey::Pubkey;
+use crate::bin_model::{self, PositionBins};
+use crate::position_history::{PositionHistory, PositionPerformance};
+use crate::price_monitor::default_price_source;
 use crate::types::{
-    ImpermanentLossResult, ILMetadata, CalculationMethod, PriceDataPoint, ILError, PositionSnapshot,
+    ImpermanentLossResult, ILMetadata, CalculationMethod, PriceDataPoint, ILError,
 };
 
 /// Core impermanent loss calculator with high-precision arithmetic
 pub struct ILCalculator {
     client: MockSarosClient,
+    price_source: SharedPriceSource,
+    /// Token X and Y decimals for converting raw position amounts
+    token_decimals: (u8, u8),
+    /// Exported histories used instead of the history backend
+    position_histories: BTreeMap<Pubkey, PositionHistory>,
     calculation_cache: BTreeMap<String, (ImpermanentLossResult, DateTime<Utc>)>,
     cache_ttl_secs: u64,
 }
 
 impl ILCalculator {
-    /// Create a new IL calculator instance
+    /// Create a new IL calculator instance using the default price source
     pub async fn new() -> Result<Self> {
+        Self::with_price_source(default_price_source()).await
+    }
+
+    /// Create an IL calculator valuing pool tokens with `price_source`
+    pub async fn with_price_source(price_source: SharedPriceSource) -> Result<Self> {
         let rpc_url = std::env::var("RPC_URL")
             .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
         
@@ -30,17 +44,30 @@ impl ILCalculator {
         
         Ok(Self {
             client,
+            price_source,
+            token_decimals: (9, 6),
+            position_histories: BTreeMap::new(),
             calculation_cache: BTreeMap::new(),
             cache_ttl_secs: 30, // Cache results for 30 seconds
         })
     }
 
+    /// Set the token decimals used to convert raw position amounts
+    pub fn set_token_decimals(&mut self, decimals_x: u8, decimals_y: u8) {
+        self.token_decimals = (decimals_x, decimals_y);
+    }
+
+    /// Use an exported history for its position instead of querying the history backend
+    pub fn add_position_history(&mut self, history: PositionHistory) {
+        self.position_histories.insert(history.position_id, history);
+    }
+
     /// Calculate impermanent loss manually with provided parameters
     /// 
-    /// This function implements the standard DLMM impermanent loss formula:
+    /// Without a bin range this is the full-range reference formula:
     /// IL = (2 * sqrt(price_ratio) / (1 + price_ratio)) - 1
     /// 
-    /// For DLMM pools with concentrated liquidity, we adjust for bin range effects.
+    /// Use `calculate_il_manual_bins` for a concentrated DLMM position.
     pub async fn calculate_il_manual(
         &self,
         initial_price_x: Decimal,
@@ -66,13 +93,12 @@ impl ILCalculator {
         // Calculate initial investment value
         let initial_value_usd = (initial_amount_x * initial_price_x) + (initial_amount_y * initial_price_y);
         
-        // Calculate current position value in DLMM (assuming uniform distribution)
-        let current_position_value = self.calculate_dlmm_position_value(
+        // Without a bin range, value the position as full-range liquidity
+        let current_position_value = self.calculate_full_range_position_value(
             initial_amount_x,
             initial_amount_y,
-            initial_ratio,
             current_ratio,
-            None, // No specific bin range provided
+            current_price_y,
         )?;
         
         // Calculate hold value (if tokens were held separately)
@@ -108,6 +134,71 @@ impl ILCalculator {
                 price_range_coverage: None,
                 calculation_method: CalculationMethod::Manual,
             },
+            bin_breakdown: None,
+            fees_collected_usd: None,
+        })
+    }
+
+    /// Calculate impermanent loss of a DLMM position over `[lower_bin_id, upper_bin_id]`
+    /// from manual prices and deposits.
+    ///
+    /// The deposit is spread uniformly around the bin holding the initial price ratio, then
+    /// every bin is re-priced at the bin holding the current ratio: crossed bins flip to the
+    /// other token and the active bin is split by where the price sits inside it.
+    pub async fn calculate_il_manual_bins(
+        &self,
+        initial_prices: (Decimal, Decimal),
+        current_prices: (Decimal, Decimal),
+        initial_amounts: (Decimal, Decimal),
+        bin_range: (i32, i32),
+        bin_step: u16,
+    ) -> Result<ImpermanentLossResult> {
+        let (initial_price_x, initial_price_y) = initial_prices;
+        let (current_price_x, current_price_y) = current_prices;
+        let (lower_bin_id, upper_bin_id) = bin_range;
+        info!("Calculating bin-level IL for bins [{}, {}] at {} bps", lower_bin_id, upper_bin_id, bin_step);
+
+        self.validate_price_inputs(initial_price_x, initial_price_y, current_price_x, current_price_y)?;
+
+        let initial_ratio = initial_price_x / initial_price_y;
+        let current_ratio = current_price_x / current_price_y;
+        let entry_active_bin_id = bin_model::price_to_bin_id(initial_ratio, bin_step);
+        let active_bin_id = bin_model::price_to_bin_id(current_ratio, bin_step);
+
+        let bins = PositionBins::uniform(
+            lower_bin_id,
+            upper_bin_id,
+            entry_active_bin_id,
+            bin_step,
+            initial_amounts.0,
+            initial_amounts.1,
+        )?;
+        let breakdown = bins.reprice_at_market(current_price_x, current_price_y);
+
+        info!("Bin-level IL - {:.4}% (${:.2}), {} bin(s) crossed",
+              breakdown.il_percentage * Decimal::new(100, 0), breakdown.il_usd_value, breakdown.crossed_bins());
+
+        Ok(ImpermanentLossResult {
+            il_percentage: breakdown.il_percentage,
+            il_usd_value: breakdown.il_usd_value,
+            current_value_usd: breakdown.value_usd,
+            hold_value_usd: breakdown.hold_value_usd,
+            current_price_x,
+            current_price_y,
+            initial_price_x,
+            initial_price_y,
+            price_ratio_change: current_ratio / initial_ratio,
+            timestamp: Utc::now(),
+            metadata: ILMetadata {
+                pool_address: Default::default(), // Not applicable for manual calculation
+                position_id: None,
+                bin_range: Some(bin_range),
+                active_bin_id: Some(active_bin_id),
+                price_range_coverage: Some(range_coverage(lower_bin_id, upper_bin_id, active_bin_id)),
+                calculation_method: CalculationMethod::Manual,
+            },
+            bin_breakdown: Some(breakdown),
+            fees_collected_usd: None,
         })
     }
 
@@ -129,45 +220,8 @@ impl ILCalculator {
 
         info!("Calculating IL from position {} in pool {}", position_id, pool_address);
 
-        // Get position data from SDK
-        let position = self.client.get_position(position_id).await?;
-        let pool_info = self.client.get_pool(pool_address).await?;
-        
-        // Get historical position data to determine initial state
-        let position_history = self.get_position_creation_data(&position).await?;
-        
-        // Calculate current prices
-        let current_prices = self.get_current_token_prices(&pool_info).await?;
-        let (current_price_x, current_price_y) = current_prices;
-        
-        // Calculate initial value and prices
-        let initial_prices = self.estimate_initial_prices(&position, &position_history).await?;
-        let (initial_price_x, initial_price_y) = initial_prices;
-        
-        // Get current position value including unclaimed fees
-        let current_position_value = self.calculate_position_current_value(
-            &position,
-            current_price_x,
-            current_price_y,
-        )?;
-        
-        // Estimate initial amounts from position data
-        let initial_amounts = self.estimate_initial_amounts(&position, &position_history)?;
-        let (initial_amount_x, initial_amount_y) = initial_amounts;
-        
-        // Calculate hold value
-        let hold_value_usd = (initial_amount_x * current_price_x) + (initial_amount_y * current_price_y);
-        
-        // Calculate IL considering DLMM specifics
-        let il_result = self.calculate_dlmm_il_with_bins(
-            &position,
-            &pool_info,
-            initial_amounts,
-            initial_prices,
-            current_prices,
-            current_position_value,
-            hold_value_usd,
-        ).await?;
+        let (_, performance) = self.calculate_position_performance(pool_address, position_id).await?;
+        let il_result = self.il_result_from_performance(&performance, CalculationMethod::FromPosition);
 
         // Cache the result
         self.calculation_cache.insert(cache_key, (il_result.clone(), Utc::now()));
@@ -178,11 +232,80 @@ impl ILCalculator {
         Ok(il_result)
     }
 
-    /// Calculate historical impermanent loss progression
+    /// Replay a position's transaction history and measure it against its actual deposits
+    pub async fn calculate_position_performance(
+        &self,
+        pool_address: Pubkey,
+        position_id: Pubkey,
+    ) -> Result<(PositionHistory, PositionPerformance)> {
+        let position = self.client.get_position(position_id).await?;
+        let pool_info = self.client.get_pool(pool_address).await?;
+        let history = self.load_position_history(&pool_info, &position).await?;
+
+        let current_prices = self.get_current_token_prices(&pool_info).await?;
+        let fees_x = Decimal::from(position.unclaimed_fees_x) / Decimal::from(10u64.pow(self.token_decimals.0 as u32));
+        let fees_y = Decimal::from(position.unclaimed_fees_y) / Decimal::from(10u64.pow(self.token_decimals.1 as u32));
+        let unclaimed_fees_usd = fees_x * current_prices.0 + fees_y * current_prices.1;
+
+        // A position owns a pro-rata share of the active bin, so it has the bin's composition
+        let active_bin = self.client.get_bin(pool_info.address, pool_info.active_bin_id).await?;
+        let active_bin_y_share = bin_model::reserves_y_share(
+            Decimal::from(active_bin.liquidity_x),
+            Decimal::from(active_bin.liquidity_y),
+            pool_info.active_bin_id,
+            pool_info.bin_step,
+        );
+
+        let ledger = history.replay()?;
+        if ledger.liquidity() != position.liquidity {
+            warn!("Position {} history nets to {} liquidity but the position holds {}",
+                  position_id, ledger.liquidity(), position.liquidity);
+        }
+        let performance = ledger.performance(
+            Utc::now(),
+            pool_info.active_bin_id,
+            Some(active_bin_y_share),
+            current_prices,
+            unclaimed_fees_usd,
+        );
+        debug!("Replayed {} transactions for position {}: TWR {:.4}, MWR {:?}",
+               history.events.len(), position_id, performance.time_weighted_return, performance.money_weighted_return);
+
+        Ok((history, performance))
+    }
+
+    /// Get a position's transaction history with USD prices at each transaction, from an
+    /// exported history when one was added or from the history backend
+    pub async fn position_history(&self, pool_address: Pubkey, position_id: Pubkey) -> Result<PositionHistory> {
+        let position = self.client.get_position(position_id).await?;
+        let pool_info = self.client.get_pool(pool_address).await?;
+        self.load_position_history(&pool_info, &position).await
+    }
+
+    async fn load_position_history(&self, pool_info: &DLMMPoolInfo, position: &SdkPosition) -> Result<PositionHistory> {
+        let mut history = match self.position_histories.get(&position.id) {
+            Some(history) => history.clone(),
+            None => {
+                let transactions = self.client.get_position_history(position.id).await?;
+                PositionHistory::from_transactions(position, pool_info.bin_step, &transactions, self.token_decimals)?
+            }
+        };
+        if history.events.is_empty() {
+            return Err(ILError::InvalidPosition(format!("No transactions for position {}", position.id)).into());
+        }
+
+        history.resolve_prices(self.price_source.as_ref(), pool_info.token_x, pool_info.token_y).await?;
+        Ok(history)
+    }
+
+    /// Calculate historical impermanent loss progression. With a position history each
+    /// point replays the transactions made up to it; without one a notional $1000/$1000
+    /// deposit at the first point is tracked.
     pub async fn calculate_historical_il(
         &self,
         pool_address: solana_sdk::pubkey::Pubkey,
         historical_data: &[PriceDataPoint],
+        position_history: Option<&PositionHistory>,
     ) -> Result<Vec<ImpermanentLossResult>> {
         info!("Calculating historical IL for {} data points", historical_data.len());
         
@@ -190,6 +313,10 @@ impl ILCalculator {
             return Err(anyhow::anyhow!("Insufficient historical data"));
         }
 
+        if let Some(history) = position_history {
+            return self.calculate_historical_position_il(history, historical_data);
+        }
+
         let mut il_history = Vec::new();
         let first_point = &historical_data[0];
         
@@ -226,196 +353,120 @@ impl ILCalculator {
         Ok(il_history)
     }
 
-    /// Calculate DLMM-specific IL considering bin ranges and liquidity distribution
-    async fn calculate_dlmm_il_with_bins(
+    fn calculate_historical_position_il(
         &self,
-        position: &SdkPosition,
-        pool_info: &DLMMPoolInfo,
-        initial_amounts: (Decimal, Decimal),
-        initial_prices: (Decimal, Decimal),
-        current_prices: (Decimal, Decimal),
-        current_position_value: Decimal,
-        hold_value_usd: Decimal,
-    ) -> Result<ImpermanentLossResult> {
-        let (initial_price_x, initial_price_y) = initial_prices;
-        let (current_price_x, current_price_y) = current_prices;
-        
-        // Calculate price ratios
-        let initial_ratio = initial_price_x / initial_price_y;
-        let current_ratio = current_price_x / current_price_y;
-        let price_ratio_change = current_ratio / initial_ratio;
-        
-        // Calculate impermanent loss
-        let il_usd_value = current_position_value - hold_value_usd;
-        let il_percentage = if hold_value_usd > Decimal::ZERO {
-            il_usd_value / hold_value_usd
+        history: &PositionHistory,
+        historical_data: &[PriceDataPoint],
+    ) -> Result<Vec<ImpermanentLossResult>> {
+        let mut il_history = Vec::new();
+
+        for data_point in historical_data {
+            let ledger = history.replay_until(data_point.timestamp)?;
+            if ledger.liquidity() == 0 {
+                continue;
+            }
+
+            let price_ratio = data_point.price_x / data_point.price_y;
+            let active_bin_id = bin_model::price_to_bin_id(price_ratio, history.bin_step);
+            let performance = ledger.performance(
+                data_point.timestamp,
+                active_bin_id,
+                None,
+                (data_point.price_x, data_point.price_y),
+                Decimal::ZERO,
+            );
+            il_history.push(self.il_result_from_performance(&performance, CalculationMethod::Historical));
+        }
+
+        info!("Completed historical IL calculations for position {} ({} points with liquidity)",
+              history.position_id, il_history.len());
+        Ok(il_history)
+    }
+
+    /// Express a replayed position's performance as an IL result. Initial prices are the
+    /// deposit-weighted entry prices and the hold value is the net deposited tokens; fees
+    /// are left out of the position value.
+    pub fn il_result_from_performance(
+        &self,
+        performance: &PositionPerformance,
+        calculation_method: CalculationMethod,
+    ) -> ImpermanentLossResult {
+        let initial_ratio = if performance.average_entry_price_y > Decimal::ZERO {
+            performance.average_entry_price_x / performance.average_entry_price_y
         } else {
             Decimal::ZERO
         };
-        
-        // Calculate price range coverage for this position
-        let price_range_coverage = self.calculate_price_range_coverage(
-            position,
-            pool_info,
-            current_ratio,
-        )?;
-        
-        Ok(ImpermanentLossResult {
-            il_percentage,
-            il_usd_value,
-            current_value_usd: current_position_value,
-            hold_value_usd,
-            current_price_x,
-            current_price_y,
-            initial_price_x,
-            initial_price_y,
+        let current_ratio = performance.price_x / performance.price_y;
+        let price_ratio_change = if initial_ratio > Decimal::ZERO { current_ratio / initial_ratio } else { Decimal::ONE };
+
+        ImpermanentLossResult {
+            il_percentage: performance.il_percentage,
+            il_usd_value: performance.il_usd_value,
+            current_value_usd: performance.current_value_usd,
+            hold_value_usd: performance.hold_value_usd,
+            current_price_x: performance.price_x,
+            current_price_y: performance.price_y,
+            initial_price_x: performance.average_entry_price_x,
+            initial_price_y: performance.average_entry_price_y,
             price_ratio_change,
-            timestamp: Utc::now(),
+            timestamp: performance.as_of,
             metadata: ILMetadata {
-                pool_address: position.pool_address,
-                position_id: Some(position.id),
-                bin_range: Some((position.lower_bin_id, position.upper_bin_id)),
-                active_bin_id: Some(pool_info.active_bin_id),
-                price_range_coverage: Some(price_range_coverage),
-                calculation_method: CalculationMethod::FromPosition,
+                pool_address: performance.pool_address,
+                position_id: Some(performance.position_id),
+                bin_range: Some((performance.lower_bin_id, performance.upper_bin_id)),
+                active_bin_id: Some(performance.active_bin_id),
+                price_range_coverage: Some(range_coverage(
+                    performance.lower_bin_id,
+                    performance.upper_bin_id,
+                    performance.active_bin_id,
+                )),
+                calculation_method,
             },
-        })
+            bin_breakdown: performance.bin_breakdown.clone(),
+            fees_collected_usd: Some(performance.fees_claimed_usd + performance.unclaimed_fees_usd),
+        }
     }
 
-    /// Calculate DLMM position value considering concentrated liquidity
-    fn calculate_dlmm_position_value(
+    /// USD value of full-range constant-product liquidity (`x * y = k`), used when no bin
+    /// range is known
+    fn calculate_full_range_position_value(
         &self,
         initial_amount_x: Decimal,
         initial_amount_y: Decimal,
-        initial_ratio: Decimal,
         current_ratio: Decimal,
-        bin_range: Option<(i32, i32)>,
-    ) -> Result<Decimal> {
-        // For concentrated liquidity, we need to consider if the current price
-        // is within the position's range
-        
-        if let Some((lower_bin, upper_bin)) = bin_range {
-            // Convert bin IDs to price ratios (simplified)
-            let lower_price_ratio = self.bin_to_price_ratio(lower_bin);
-            let upper_price_ratio = self.bin_to_price_ratio(upper_bin);
-            
-            if current_ratio < lower_price_ratio {
-                // Price moved below range - all in token Y
-                return Ok((initial_amount_x + initial_amount_y) * current_ratio);
-            } else if current_ratio > upper_price_ratio {
-                // Price moved above range - all in token X  
-                return Ok(initial_amount_x + initial_amount_y);
-            }
-        }
-        
-        // Standard AMM formula for positions within range
-        let initial_k = initial_amount_x * initial_amount_y * initial_ratio;
-        let ratio_change = current_ratio / initial_ratio;
-        let sqrt_ratio_change = self.decimal_sqrt(ratio_change).unwrap_or(Decimal::ONE);
-        
-        let new_amount_x = self.decimal_sqrt(initial_k).unwrap_or(Decimal::ZERO) / self.decimal_sqrt(current_ratio).unwrap_or(Decimal::ONE);
-        let new_amount_y = self.decimal_sqrt(initial_k * current_ratio).unwrap_or(Decimal::ZERO);
-        
-        Ok(new_amount_x + new_amount_y * current_ratio)
-    }
-
-    /// Convert bin ID to approximate price ratio (simplified implementation)
-    fn bin_to_price_ratio(&self, bin_id: i32) -> Decimal {
-        // This is a simplified conversion - in reality, this would depend on bin_step
-        // Formula: price = (1 + bin_step/10000)^bin_id
-        let bin_step_decimal = Decimal::new(25, 4); // 0.0025 = 25bps typical
-        let exp_result = self.decimal_pow(Decimal::ONE + bin_step_decimal, bin_id as i64).unwrap_or(Decimal::ONE);
-        exp_result
-    }
-
-    /// Get current token prices from pool data
-    async fn get_current_token_prices(&self, pool_info: &DLMMPoolInfo) -> Result<(Decimal, Decimal)> {
-        // In a real implementation, this would fetch from price oracles
-        // For now, derive from pool data
-        let base_price = Decimal::new(100, 0); // $100 baseline
-        let price_x = base_price;
-        let price_y = base_price;
-        
-        Ok((price_x, price_y))
-    }
-
-    /// Estimate initial prices when position was created
-    async fn estimate_initial_prices(
-        &self,
-        _position: &SdkPosition,
-        _history: &PositionSnapshot,
-    ) -> Result<(Decimal, Decimal)> {
-        // In a real implementation, this would look up historical price data
-        // For now, return estimated values
-        Ok((Decimal::new(95, 0), Decimal::new(100, 0)))
-    }
-
-    /// Get position creation data from historical records  
-    async fn get_position_creation_data(&self, position: &SdkPosition) -> Result<PositionSnapshot> {
-        // Mock implementation - in reality would query transaction history
-        Ok(PositionSnapshot {
-            timestamp: Utc::now() - chrono::Duration::days(30),
-            position_value_usd: Decimal::new(2000, 0),
-            hold_value_usd: Decimal::new(2000, 0),
-            il_percentage: Decimal::ZERO,
-            fees_earned: Decimal::ZERO,
-            active_bin_id: position.lower_bin_id + (position.upper_bin_id - position.lower_bin_id) / 2,
-            price_x: Decimal::new(95, 0),
-            price_y: Decimal::new(100, 0),
-        })
-    }
-
-    /// Calculate current position value including fees
-    fn calculate_position_current_value(
-        &self,
-        position: &SdkPosition,
-        current_price_x: Decimal,
         current_price_y: Decimal,
     ) -> Result<Decimal> {
-        // Get position balances and convert to USD
-        let token_x_value = Decimal::from_f64(position.value_usd / 2.0).unwrap_or_default() * current_price_x / Decimal::new(100, 0);
-        let token_y_value = Decimal::from_f64(position.value_usd / 2.0).unwrap_or_default() * current_price_y / Decimal::new(100, 0);
+        let initial_k = initial_amount_x * initial_amount_y;
         
-        // Add unclaimed fees
-        let fees_x_value = Decimal::new(position.unclaimed_fees_x as i64, 6) * current_price_x;
-        let fees_y_value = Decimal::new(position.unclaimed_fees_y as i64, 6) * current_price_y;
+        let new_amount_x = self.decimal_sqrt(initial_k / current_ratio)?;
+        let new_amount_y = self.decimal_sqrt(initial_k * current_ratio)?;
         
-        Ok(token_x_value + token_y_value + fees_x_value + fees_y_value)
-    }
-
-    /// Estimate initial amounts from position and history
-    fn estimate_initial_amounts(
-        &self,
-        position: &SdkPosition,
-        _history: &PositionSnapshot,
-    ) -> Result<(Decimal, Decimal)> {
-        // Estimate based on current position value and typical 50/50 split
-        let total_value = Decimal::from_f64(position.value_usd).unwrap_or_default();
-        let amount_x = total_value / Decimal::new(2, 0) / Decimal::new(95, 0); // Assume $95 initial price
-        let amount_y = total_value / Decimal::new(2, 0) / Decimal::new(100, 0); // Assume $100 initial price
-        
-        Ok((amount_x, amount_y))
+        Ok((new_amount_x * current_ratio + new_amount_y) * current_price_y)
     }
 
-    /// Calculate what percentage of the price range this position covers
-    fn calculate_price_range_coverage(
-        &self,
-        position: &SdkPosition,
-        pool_info: &DLMMPoolInfo,
-        current_ratio: Decimal,
-    ) -> Result<Decimal> {
-        let position_range = position.upper_bin_id - position.lower_bin_id;
-        let active_distance = (pool_info.active_bin_id - position.lower_bin_id).abs();
-        
-        if position_range > 0 && pool_info.active_bin_id >= position.lower_bin_id && pool_info.active_bin_id <= position.upper_bin_id {
-            // Position is in range
-            Ok(Decimal::ONE)
-        } else if position_range > 0 {
-            // Position is out of range  
-            let distance_ratio = Decimal::new(active_distance as i64, 0) / Decimal::new(position_range as i64, 0);
-            Ok(Decimal::ONE - distance_ratio.min(Decimal::ONE))
-        } else {
-            Ok(Decimal::ZERO)
+    /// Get current USD token prices from the price source. The pool's active bin fixes
+    /// the X/Y ratio, so a token without a quote is valued against the other one.
+    async fn get_current_token_prices(&self, pool_info: &DLMMPoolInfo) -> Result<(Decimal, Decimal)> {
+        let quote_x = self.price_source.get_price(&pool_info.token_x).await;
+        let quote_y = self.price_source.get_price(&pool_info.token_y).await;
+        let active_price = bin_model::bin_price(pool_info.active_bin_id, pool_info.bin_step);
+        
+        match (quote_x, quote_y) {
+            (Ok(quote_x), Ok(quote_y)) => Ok((quote_x.price, quote_y.price)),
+            (Ok(quote_x), Err(e)) if !active_price.is_zero() => {
+                debug!("No price for token Y {}, deriving from active bin: {}", pool_info.token_y, e);
+                Ok((quote_x.price, quote_x.price / active_price))
+            }
+            (Err(e), Ok(quote_y)) => {
+                debug!("No price for token X {}, deriving from active bin: {}", pool_info.token_x, e);
+                Ok((quote_y.price * active_price, quote_y.price))
+            }
+            (Err(e), _) | (_, Err(e)) => Err(anyhow!(
+                "No {} price for pool {}: {}",
+                self.price_source.name(),
+                pool_info.address,
+                e
+            )),
         }
     }
 
@@ -472,40 +523,22 @@ impl ILCalculator {
         
         Ok(Decimal::from_f64(sqrt_f64).unwrap_or(Decimal::ZERO))
     }
+}
+
+/// How much of the price range a position over `[lower_bin_id, upper_bin_id]` covers at `active_bin_id`
+fn range_coverage(lower_bin_id: i32, upper_bin_id: i32, active_bin_id: i32) -> Decimal {
+    let position_range = upper_bin_id - lower_bin_id;
+    let active_distance = (active_bin_id - lower_bin_id).abs();
     
-    /// Calculate power using repeated multiplication for integer exponents
-    fn decimal_pow(&self, base: Decimal, exp: i64) -> Result<Decimal> {
-        if exp == 0 {
-            return Ok(Decimal::ONE);
-        }
-        
-        if exp == 1 {
-            return Ok(base);
-        }
-        
-        if exp < 0 {
-            // Handle negative exponents
-            let positive_result = self.decimal_pow(base, -exp)?;
-            if positive_result == Decimal::ZERO {
-                return Err(anyhow::anyhow!("Division by zero in negative power"));
-            }
-            return Ok(Decimal::ONE / positive_result);
-        }
-        
-        // For small positive exponents, use repeated multiplication
-        if exp <= 20 {
-            let mut result = base;
-            for _ in 1..exp {
-                result *= base;
-            }
-            return Ok(result);
-        }
-        
-        // For larger exponents, convert to f64 (loss of precision but practical)
-        let base_f64 = base.to_f64().unwrap_or(0.0);
-        let result_f64 = base_f64.powf(exp as f64);
-        
-        Ok(Decimal::from_f64(result_f64).unwrap_or(Decimal::ZERO))
+    if position_range > 0 && active_bin_id >= lower_bin_id && active_bin_id <= upper_bin_id {
+        // Position is in range
+        Decimal::ONE
+    } else if position_range > 0 {
+        // Position is out of range  
+        let distance_ratio = Decimal::new(active_distance as i64, 0) / Decimal::new(position_range as i64, 0);
+        Decimal::ONE - distance_ratio.min(Decimal::ONE)
+    } else {
+        Decimal::ZERO
     }
 }
 
@@ -565,19 +598,47 @@ mod tests {
 
     #[test]
     fn test_bin_to_price_ratio() {
-        let calculator = ILCalculator::new().await.unwrap();
-        
         // Test basic bin conversion
-        let ratio_positive = calculator.bin_to_price_ratio(100);
-        let ratio_negative = calculator.bin_to_price_ratio(-100);
+        let ratio_positive = bin_model::bin_price(100, 20);
+        let ratio_negative = bin_model::bin_price(-100, 20);
         
         assert!(ratio_positive > Decimal::ONE);
         assert!(ratio_negative < Decimal::ONE);
-        assert_eq!(calculator.bin_to_price_ratio(0), Decimal::ONE);
+        assert_eq!(bin_model::bin_price(0, 20), Decimal::ONE);
+
+        // The pool's bin step is honoured
+        assert_relative_eq!(bin_model::bin_price(1, 20).to_f64().unwrap(), 1.002, epsilon = 1e-12);
+        assert_relative_eq!(bin_model::bin_price(1, 100).to_f64().unwrap(), 1.01, epsilon = 1e-12);
     }
 
-    #[test]
-    fn test_price_validation() {
+    #[tokio::test]
+    async fn test_manual_bin_il_calculation() {
+        let calculator = ILCalculator::new().await.unwrap();
+        
+        // Token X rallies 10% through a 20-bin range at 1% bin step
+        let result = calculator.calculate_il_manual_bins(
+            (Decimal::new(100, 0), Decimal::new(100, 0)),
+            (Decimal::new(110, 0), Decimal::new(100, 0)),
+            (Decimal::new(10, 0), Decimal::new(10, 0)),
+            (-10, 10),
+            100,
+        ).await.unwrap();
+        
+        let breakdown = result.bin_breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.bins.len(), 21);
+        assert_eq!(breakdown.entry_active_bin_id, 0);
+        assert_eq!(breakdown.active_bin_id, 10);
+        assert_eq!(breakdown.crossed_bins(), 10);
+        assert!(result.il_usd_value < Decimal::ZERO);
+        assert_eq!(result.current_value_usd, breakdown.value_usd);
+        
+        // Aggregate equals the sum of the bins
+        let bin_il: Decimal = breakdown.bins.iter().map(|bin| bin.il_usd_value).sum();
+        assert_relative_eq!(bin_il.to_f64().unwrap(), result.il_usd_value.to_f64().unwrap(), epsilon = 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_price_validation() {
         let calculator = ILCalculator::new().await.unwrap();
         
         // Test negative price validation
@@ -604,4 +665,22 @@ mod tests {
             Decimal::new(100, 0),
         ).is_ok());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_current_prices_fill_missing_quote_from_active_bin() {
+        let calculator = ILCalculator::new().await.unwrap();
+        let pool_info = calculator.client.get_pool(solana_sdk::pubkey::Pubkey::new_unique()).await.unwrap();
+        let usdc = saros_dlmm_sdk::StaticPriceSource::new().with_price(pool_info.token_y, Decimal::ONE);
+        let calculator = ILCalculator::with_price_source(std::sync::Arc::new(usdc)).await.unwrap();
+        
+        let (price_x, price_y) = calculator.get_current_token_prices(&pool_info).await.unwrap();
+        
+        assert_eq!(price_y, Decimal::ONE);
+        assert_eq!(price_x, bin_model::bin_price(pool_info.active_bin_id, pool_info.bin_step));
+        
+        let unpriced = ILCalculator::with_price_source(std::sync::Arc::new(saros_dlmm_sdk::StaticPriceSource::new()))
+            .await
+            .unwrap();
+        assert!(unpriced.get_current_token_prices(&pool_info).await.is_err());
+    }
+}
@@ -2,32 +2,53 @@
 //! 
 //! This application provides comprehensive analytics for DLMM positions including:
 //! - Impermanent loss calculations with mathematical precision
-//! - Real-time position monitoring and tracking
+//! - Real-time position monitoring and tracking, with a terminal dashboard
 //! - Fee vs IL analysis and profitability metrics
 //! - Historical price data analysis and volatility tracking
-//! - Multi-format report generation (JSON, CSV, HTML)
+//! - Position history replay with cost basis, time- and money-weighted returns
+//! - Forward-looking price shock, price path and Monte Carlo scenarios
+//! - Portfolio aggregation of IL, correlations, VaR and token exposure across positions
+//! - Range and liquidity shape optimisation for new positions (`il_calc optimize`)
+//! - Multi-format report generation (JSON, CSV, Markdown, HTML)
 
+mod alerts;
+mod bin_model;
+mod charts;
+mod dashboard;
 mod il_calculator;
+mod optimizer;
+mod portfolio;
 mod position_analyzer;
+mod position_history;
 mod price_monitor;
 mod report_generator;
+mod report_model;
+mod scenario;
 mod types;
 
 use anyhow::Result;
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use dotenv::dotenv;
-use log::info;
+use log::{info, warn};
 use rust_decimal::Decimal;
+use saros_dlmm_sdk::PriceSourceConfig;
 use solana_sdk::pubkey::Pubkey;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use tokio::time::{sleep, Duration};
 
+use crate::alerts::{AlertConfig, AlertEngine};
 use crate::il_calculator::ILCalculator;
+use crate::optimizer::{OptimizerConfig, RangeOptimizer};
 use crate::position_analyzer::PositionAnalyzer;
-use crate::price_monitor::PriceMonitor;
+use crate::position_history::{PositionHistory, PositionPerformance};
+use crate::price_monitor::{default_price_source, PriceMonitor};
 use crate::report_generator::ReportGenerator;
-use crate::types::ReportFormat;
-use crate::types::{AnalysisConfig, MonitoringMode, ReportConfig, ImpermanentLossResult};
+use crate::scenario::{MonteCarloConfig, ScenarioEngine, ScenarioReport};
+use crate::bin_model::{LiquidityDistribution, PositionBins};
+use crate::types::{il_error_category, ReportFormat};
+use crate::types::{AnalysisConfig, CalculationMethod, ManualPosition, MonitoringMode, ReportConfig, ImpermanentLossResult, OptimizeParams, ScenarioParams};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -43,21 +64,52 @@ async fn main() -> Result<()> {
                 .long("pool")
                 .value_name("POOL_ADDRESS")
                 .help("DLMM pool address to analyze")
-                .required(true),
+                .required_unless_present_any(["owner", "position"]),
         )
         .arg(
             Arg::new("position")
                 .short('P')
                 .long("position")
                 .value_name("POSITION_ID")
-                .help("Specific position ID to analyze (optional)"),
+                .help("Specific position ID to analyze (optional); repeat to track several in monitor mode")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("owner")
+                .long("owner")
+                .value_name("WALLET")
+                .help("Wallet whose positions to aggregate (for portfolio mode)"),
+        )
+        .arg(
+            Arg::new("export-charts")
+                .long("export-charts")
+                .help("Also write report charts as standalone SVG files")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no-tui")
+                .long("no-tui")
+                .help("Log monitor updates instead of opening the terminal dashboard")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("alert-rules")
+                .long("alert-rules")
+                .value_name("FILE")
+                .help("Alert rules for monitor mode (TOML, JSON or YAML); defaults to built-in thresholds"),
+        )
+        .arg(
+            Arg::new("no-alerts")
+                .long("no-alerts")
+                .action(ArgAction::SetTrue)
+                .help("Do not evaluate alert rules in monitor mode"),
         )
         .arg(
             Arg::new("mode")
                 .short('m')
                 .long("mode")
                 .value_name("MODE")
-                .help("Analysis mode: snapshot, monitor, historical")
+                .help("Analysis mode: snapshot, monitor, historical, scenario, portfolio")
                 .default_value("snapshot"),
         )
         .arg(
@@ -68,12 +120,55 @@ async fn main() -> Result<()> {
                 .help("Output directory for reports")
                 .default_value("./reports"),
         )
+        .arg(
+            Arg::new("price-file")
+                .long("price-file")
+                .value_name("FILE")
+                .help("Replay recorded prices from a CSV (timestamp,mint,price) or JSON file")
+                .global(true),
+        )
+        .arg(
+            Arg::new("price-url")
+                .long("price-url")
+                .value_name("URL")
+                .help("Read prices from an HTTP endpoint serving GET /price/{mint}")
+                .global(true),
+        )
+        .arg(
+            Arg::new("max-price-age")
+                .long("max-price-age")
+                .value_name("SECONDS")
+                .help("Ignore quotes older than this and take the median of the configured sources")
+                .global(true),
+        )
+        .arg(
+            Arg::new("decimals-x")
+                .long("decimals-x")
+                .value_name("DECIMALS")
+                .help("Token X decimals for raw position amounts")
+                .default_value("9")
+                .global(true),
+        )
+        .arg(
+            Arg::new("decimals-y")
+                .long("decimals-y")
+                .value_name("DECIMALS")
+                .help("Token Y decimals for raw position amounts")
+                .default_value("6")
+                .global(true),
+        )
+        .arg(
+            Arg::new("history-file")
+                .long("history-file")
+                .value_name("FILE")
+                .help("Replay the position's transactions from an exported JSON history instead of the history backend"),
+        )
         .arg(
             Arg::new("format")
                 .short('f')
                 .long("format")
                 .value_name("FORMAT")
-                .help("Report format: json, csv, html, all")
+                .help("Report format: json, csv, html, markdown, all")
                 .default_value("json"),
         )
         .arg(
@@ -108,22 +203,201 @@ async fn main() -> Result<()> {
                 .value_name("AMOUNT")
                 .help("Initial amount of token Y deposited"),
         )
+        .arg(
+            Arg::new("lower-bin")
+                .long("lower-bin")
+                .value_name("BIN_ID")
+                .allow_negative_numbers(true)
+                .requires("upper-bin")
+                .help("Lower bin of the position, for bin-level manual IL calculation"),
+        )
+        .arg(
+            Arg::new("upper-bin")
+                .long("upper-bin")
+                .value_name("BIN_ID")
+                .allow_negative_numbers(true)
+                .requires("lower-bin")
+                .help("Upper bin of the position, for bin-level manual IL calculation"),
+        )
+        .arg(
+            Arg::new("bin-step")
+                .long("bin-step")
+                .value_name("BPS")
+                .help("Bin step in basis points (defaults to the pool's)"),
+        )
+        .arg(
+            Arg::new("range-bins")
+                .long("range-bins")
+                .value_name("BINS")
+                .help("Bins either side of the active bin when no range is given (for scenario mode)")
+                .default_value("10"),
+        )
+        .arg(
+            Arg::new("distribution")
+                .long("distribution")
+                .value_name("SHAPE")
+                .help("Liquidity distribution: uniform, normal, bid-ask (for scenario mode)")
+                .default_value("uniform"),
+        )
+        .arg(
+            Arg::new("shock")
+                .long("shock")
+                .value_name("PERCENT")
+                .help("Largest price shock to stress test, in percent (for scenario mode)")
+                .default_value("20"),
+        )
+        .arg(
+            Arg::new("price-path")
+                .long("price-path")
+                .value_name("PRICES")
+                .help("Comma-separated daily token X prices to evaluate (for scenario mode)"),
+        )
+        .arg(
+            Arg::new("horizon-days")
+                .long("horizon-days")
+                .value_name("DAYS")
+                .help("Scenario horizon in days (for scenario mode)")
+                .default_value("30"),
+        )
+        .arg(
+            Arg::new("paths")
+                .long("paths")
+                .value_name("COUNT")
+                .help("Monte Carlo price paths (for scenario mode)")
+                .default_value("1000"),
+        )
+        .arg(
+            Arg::new("volatility")
+                .long("volatility")
+                .value_name("ANNUALIZED")
+                .help("Annualized volatility, e.g. 0.8 (defaults to the observed X/Y price volatility)"),
+        )
+        .arg(
+            Arg::new("daily-fee-rate")
+                .long("daily-fee-rate")
+                .value_name("RATE")
+                .help("Daily fee income as a fraction of position value (defaults to pool fees / TVL)"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Monte Carlo random seed")
+                .default_value("42"),
+        )
+        .subcommand(
+            Command::new("optimize")
+                .about("Recommend a bin range and liquidity shape for a new position")
+                .arg(
+                    Arg::new("capital")
+                        .long("capital")
+                        .value_name("USD")
+                        .help("Capital to deploy, in USD")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("horizon-days")
+                        .long("horizon-days")
+                        .value_name("DAYS")
+                        .help("Holding horizon in days")
+                        .default_value("30"),
+                )
+                .arg(
+                    Arg::new("max-drawdown")
+                        .long("max-drawdown")
+                        .value_name("PERCENT")
+                        .help("Largest acceptable 5th percentile loss against holding, in percent of capital")
+                        .default_value("2"),
+                )
+                .arg(
+                    Arg::new("widths")
+                        .long("widths")
+                        .value_name("BINS")
+                        .help("Comma-separated range half-widths to search, in bins")
+                        .default_value("2,5,10,20,35,50"),
+                )
+                .arg(
+                    Arg::new("paths")
+                        .long("paths")
+                        .value_name("COUNT")
+                        .help("Monte Carlo price paths per candidate")
+                        .default_value("500"),
+                )
+                .arg(
+                    Arg::new("volatility")
+                        .long("volatility")
+                        .value_name("ANNUALIZED")
+                        .help("Annualized volatility, e.g. 0.8 (defaults to the observed X/Y price volatility)"),
+                )
+                .arg(
+                    Arg::new("active-bin-fee-rate")
+                        .long("active-bin-fee-rate")
+                        .value_name("RATE")
+                        .help("Daily fees as a fraction of active-bin liquidity (defaults to an estimate from pool data)"),
+                )
+                .arg(
+                    Arg::new("pool-width-bins")
+                        .long("pool-width-bins")
+                        .value_name("BINS")
+                        .help("Bins the pool's TVL is assumed to span when estimating fees")
+                        .default_value("50"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("SEED")
+                        .help("Monte Carlo random seed")
+                        .default_value("42"),
+                ),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Write the JSON Schemas of the JSON report formats")
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("DIR")
+                        .help("Directory to write the schemas to")
+                        .default_value("./schemas"),
+                ),
+        )
+        .subcommand_negates_reqs(true)
         .get_matches();
 
+    if let Some(schema) = matches.subcommand_matches("schema") {
+        return write_report_schemas(schema.get_one::<String>("output").unwrap());
+    }
+
     // Parse command line arguments
-    let pool_address = Pubkey::from_str(matches.get_one::<String>("pool").unwrap())?;
-    let position_id = matches
-        .get_one::<String>("position")
+    let pool_address = matches.get_one::<String>("pool").map(|s| Pubkey::from_str(s)).transpose()?;
+    let owner = matches.get_one::<String>("owner").map(|s| Pubkey::from_str(s)).transpose()?;
+    let position_ids = matches
+        .get_many::<String>("position")
+        .unwrap_or_default()
         .map(|s| Pubkey::from_str(s))
-        .transpose()?;
+        .collect::<Result<Vec<_>, _>>()?;
+    let position_id = position_ids.first().copied();
     
     let mode = match matches.get_one::<String>("mode").unwrap().as_str() {
         "snapshot" => MonitoringMode::Snapshot,
         "monitor" => MonitoringMode::RealTime,
         "historical" => MonitoringMode::Historical,
+        "scenario" => MonitoringMode::Scenario,
+        "portfolio" => MonitoringMode::Portfolio,
         _ => MonitoringMode::Snapshot,
     };
 
+    // Portfolio positions carry their own pools; every other mode analyses one pool
+    let pool_address = match (pool_address, mode) {
+        (Some(pool_address), _) => pool_address,
+        (None, MonitoringMode::Portfolio) => Pubkey::default(),
+        (None, _) => return Err(anyhow::anyhow!("--pool is required outside portfolio mode")),
+    };
+    if mode == MonitoringMode::Portfolio && owner.is_none() && position_ids.is_empty() {
+        return Err(anyhow::anyhow!("Portfolio mode needs --owner or at least one --position"));
+    }
+
     let output_dir = matches.get_one::<String>("output").unwrap().to_string();
     let format_str = matches.get_one::<String>("format").unwrap();
     let formats = parse_report_formats(format_str)?;
@@ -133,6 +407,12 @@ async fn main() -> Result<()> {
         .unwrap()
         .parse()?;
 
+    let bin_range = match (matches.get_one::<String>("lower-bin"), matches.get_one::<String>("upper-bin")) {
+        (Some(lower), Some(upper)) => Some((lower.parse()?, upper.parse()?)),
+        _ => None,
+    };
+    let bin_step = matches.get_one::<String>("bin-step").map(|step| step.parse()).transpose()?;
+
     // Parse manual IL calculation parameters
     let manual_params = if let (Some(px), Some(py), Some(ax), Some(ay)) = (
         matches.get_one::<String>("initial-price-x"),
@@ -140,41 +420,133 @@ async fn main() -> Result<()> {
         matches.get_one::<String>("initial-amount-x"),
         matches.get_one::<String>("initial-amount-y"),
     ) {
-        Some((
-            Decimal::from_str(px)?,
-            Decimal::from_str(py)?,
-            Decimal::from_str(ax)?,
-            Decimal::from_str(ay)?,
-        ))
+        Some(ManualPosition {
+            initial_price_x: Decimal::from_str(px)?,
+            initial_price_y: Decimal::from_str(py)?,
+            initial_amount_x: Decimal::from_str(ax)?,
+            initial_amount_y: Decimal::from_str(ay)?,
+            bin_range,
+            bin_step,
+        })
+    } else {
+        None
+    };
+
+    // Parse scenario parameters
+    let scenario_params = if mode == MonitoringMode::Scenario {
+        let (Some(ax), Some(ay)) = (
+            matches.get_one::<String>("initial-amount-x"),
+            matches.get_one::<String>("initial-amount-y"),
+        ) else {
+            return Err(anyhow::anyhow!("Scenario mode needs --initial-amount-x and --initial-amount-y"));
+        };
+
+        let range_bins: i32 = matches.get_one::<String>("range-bins").unwrap().parse()?;
+        let distribution = match matches.get_one::<String>("distribution").unwrap().as_str() {
+            "uniform" | "spot" => LiquidityDistribution::Uniform,
+            "normal" | "curve" => LiquidityDistribution::Normal { std_dev_bins: (range_bins as f64 / 2.0).max(1.0) },
+            "bid-ask" => LiquidityDistribution::BidAsk,
+            other => return Err(anyhow::anyhow!("Invalid distribution: {}. Use: uniform, normal, bid-ask", other)),
+        };
+
+        Some(ScenarioParams {
+            amount_x: Decimal::from_str(ax)?,
+            amount_y: Decimal::from_str(ay)?,
+            bin_range,
+            range_bins,
+            bin_step,
+            distribution,
+            max_shock: Decimal::from_str(matches.get_one::<String>("shock").unwrap())? / Decimal::new(100, 0),
+            price_path: matches
+                .get_one::<String>("price-path")
+                .map(|path| path.split(',').map(|price| Decimal::from_str(price.trim())).collect::<Result<Vec<_>, _>>())
+                .transpose()?
+                .unwrap_or_default(),
+            horizon_days: matches.get_one::<String>("horizon-days").unwrap().parse()?,
+            paths: matches.get_one::<String>("paths").unwrap().parse()?,
+            volatility: matches.get_one::<String>("volatility").map(|v| Decimal::from_str(v)).transpose()?,
+            daily_fee_rate: matches.get_one::<String>("daily-fee-rate").map(|r| Decimal::from_str(r)).transpose()?,
+            seed: matches.get_one::<String>("seed").unwrap().parse()?,
+        })
     } else {
         None
     };
 
+    // Parse range optimiser parameters
+    let token_decimals: (u8, u8) = (
+        matches.get_one::<String>("decimals-x").unwrap().parse()?,
+        matches.get_one::<String>("decimals-y").unwrap().parse()?,
+    );
+
+    let optimize_params = match matches.subcommand_matches("optimize") {
+        Some(optimize) => Some(OptimizeParams {
+            capital_usd: Decimal::from_str(optimize.get_one::<String>("capital").unwrap())?,
+            horizon_days: optimize.get_one::<String>("horizon-days").unwrap().parse()?,
+            max_drawdown: Decimal::from_str(optimize.get_one::<String>("max-drawdown").unwrap())? / Decimal::new(100, 0),
+            half_widths: optimize
+                .get_one::<String>("widths")
+                .unwrap()
+                .split(',')
+                .map(|width| width.trim().parse())
+                .collect::<Result<Vec<_>, _>>()?,
+            paths: optimize.get_one::<String>("paths").unwrap().parse()?,
+            volatility: optimize.get_one::<String>("volatility").map(|v| Decimal::from_str(v)).transpose()?,
+            active_bin_daily_fee_rate: optimize.get_one::<String>("active-bin-fee-rate").map(|r| Decimal::from_str(r)).transpose()?,
+            pool_width_bins: optimize.get_one::<String>("pool-width-bins").unwrap().parse()?,
+            token_decimals,
+            seed: optimize.get_one::<String>("seed").unwrap().parse()?,
+        }),
+        None => None,
+    };
+
     info!("Starting DLMM Impermanent Loss Calculator");
     info!("Pool: {}", pool_address);
     info!("Mode: {:?}", mode);
     info!("Output: {}", output_dir);
 
+    let price_source = PriceSourceConfig {
+        price_file: matches.get_one::<String>("price-file").map(PathBuf::from),
+        price_url: matches.get_one::<String>("price-url").cloned(),
+        max_age_secs: matches.get_one::<String>("max-price-age").map(|age| age.parse()).transpose()?,
+    }
+    .build()?
+    .unwrap_or_else(default_price_source);
+    info!("Price source: {}", price_source.name());
+
     // Initialize components
-    let mut il_calculator = ILCalculator::new().await?;
+    let mut il_calculator = ILCalculator::with_price_source(price_source.clone()).await?;
+    il_calculator.set_token_decimals(token_decimals.0, token_decimals.1);
+    if let Some(path) = matches.get_one::<String>("history-file") {
+        let history = PositionHistory::load_json(path)?;
+        info!("Loaded {} transactions for position {} from {}", history.events.len(), history.position_id, path);
+        il_calculator.add_position_history(history);
+    }
     let mut position_analyzer = PositionAnalyzer::new().await?;
-    let mut price_monitor = PriceMonitor::new().await?;
+    let mut price_monitor = PriceMonitor::with_price_source(price_source).await?;
     let mut report_generator = ReportGenerator::new(output_dir.clone())?;
 
     // Create analysis configuration
     let config = AnalysisConfig {
         pool_address,
         position_id,
+        position_ids,
         mode,
         interval_secs,
         output_directory: output_dir,
-        enable_notifications: true,
+        export_charts: matches.get_flag("export-charts"),
+        enable_notifications: !matches.get_flag("no-alerts"),
         max_price_deviation: Decimal::new(500, 2), // 5%
         min_fee_threshold: Decimal::new(100, 2), // $1.00
         historical_days: 30,
         volatility_window: 24, // hours
     };
 
+    if let Some(params) = optimize_params {
+        run_range_optimization(&mut position_analyzer, &mut price_monitor, &config, params).await?;
+        info!("Optimisation completed successfully");
+        return Ok(());
+    }
+
     match mode {
         MonitoringMode::Snapshot => {
             run_snapshot_analysis(
@@ -187,12 +559,25 @@ async fn main() -> Result<()> {
                 manual_params,
             ).await?;
         }
+        MonitoringMode::RealTime if !matches.get_flag("no-tui") && std::io::stdout().is_terminal() && !config.position_ids.is_empty() => {
+            let mut alert_engine = build_alert_engine(&config, matches.get_one::<String>("alert-rules"))?;
+            dashboard::run_dashboard(
+                &mut il_calculator,
+                &mut position_analyzer,
+                &mut report_generator,
+                &mut alert_engine,
+                &config,
+                &formats,
+            ).await?;
+        }
         MonitoringMode::RealTime => {
+            let mut alert_engine = build_alert_engine(&config, matches.get_one::<String>("alert-rules"))?;
             run_realtime_monitoring(
                 &mut il_calculator,
                 &mut position_analyzer,
                 &mut price_monitor,
                 &mut report_generator,
+                &mut alert_engine,
                 &config,
                 &formats,
             ).await?;
@@ -207,6 +592,27 @@ async fn main() -> Result<()> {
                 &formats,
             ).await?;
         }
+        MonitoringMode::Scenario => {
+            run_scenario_analysis(
+                &mut position_analyzer,
+                &mut price_monitor,
+                &mut report_generator,
+                &config,
+                &formats,
+                scenario_params.expect("scenario parameters are parsed in scenario mode"),
+            ).await?;
+        }
+        MonitoringMode::Portfolio => {
+            run_portfolio_analysis(
+                &mut il_calculator,
+                &mut position_analyzer,
+                &mut price_monitor,
+                &mut report_generator,
+                &config,
+                &formats,
+                owner,
+            ).await?;
+        }
     }
 
     info!("Analysis completed successfully");
@@ -220,7 +626,7 @@ async fn run_snapshot_analysis(
     report_generator: &mut ReportGenerator,
     config: &AnalysisConfig,
     formats: &[ReportFormat],
-    manual_params: Option<(Decimal, Decimal, Decimal, Decimal)>,
+    manual_params: Option<ManualPosition>,
 ) -> Result<()> {
     info!("Running snapshot analysis...");
 
@@ -244,26 +650,60 @@ async fn run_snapshot_analysis(
           current_prices.0, current_prices.1);
 
     // Calculate impermanent loss
-    let il_result = if let Some((initial_px, initial_py, amount_x, amount_y)) = manual_params {
-        il_calculator.calculate_il_manual(
-            initial_px,
-            initial_py,
-            current_prices.0,
-            current_prices.1,
-            amount_x,
-            amount_y,
-        ).await?
+    let il_result = if let Some(manual) = manual_params {
+        if let Some(bin_range) = manual.bin_range {
+            il_calculator.calculate_il_manual_bins(
+                (manual.initial_price_x, manual.initial_price_y),
+                current_prices,
+                (manual.initial_amount_x, manual.initial_amount_y),
+                bin_range,
+                manual.bin_step.unwrap_or(pool_info.bin_step),
+            ).await?
+        } else {
+            il_calculator.calculate_il_manual(
+                manual.initial_price_x,
+                manual.initial_price_y,
+                current_prices.0,
+                current_prices.1,
+                manual.initial_amount_x,
+                manual.initial_amount_y,
+            ).await?
+        }
     } else if let Some(position) = &position_data {
-        il_calculator.calculate_il_from_position(
+        let (history, performance) = il_calculator.calculate_position_performance(
             config.pool_address,
             position.position_id.unwrap(),
-        ).await?
+        ).await?;
+        log_position_performance(&performance);
+
+        let report_config = ReportConfig {
+            title: format!("DLMM Position History - {}", performance.position_id),
+            include_charts: false,
+            include_raw_data: true,
+            timestamp: chrono::Utc::now(),
+        };
+        // The JSON report is always written so the history can be reloaded with --history-file
+        let json = (!formats.iter().any(|format| matches!(format, ReportFormat::Json))).then_some(ReportFormat::Json);
+        for format in formats.iter().cloned().chain(json) {
+            report_generator.generate_position_history_report(&history, &performance, &report_config, format).await?;
+        }
+
+        il_calculator.il_result_from_performance(&performance, CalculationMethod::FromPosition)
     } else {
         return Err(anyhow::anyhow!("Need either position ID or manual parameters for IL calculation"));
     };
 
     info!("Impermanent Loss: {:.4}% (${:.2})", 
           il_result.il_percentage * Decimal::new(100, 0), il_result.il_usd_value);
+    if let Some(breakdown) = &il_result.bin_breakdown {
+        info!("Withdrawable: {:.6} X + {:.6} Y across {} bins ({} crossed since entry)",
+              breakdown.amount_x, breakdown.amount_y, breakdown.bins.len(), breakdown.crossed_bins());
+        for bin in &breakdown.bins {
+            log::debug!("Bin {}: {:.6} X + {:.6} Y, IL {:.4}% (${:.2})",
+                        bin.bin_id, bin.amount_x, bin.amount_y,
+                        bin.il_percentage * Decimal::new(100, 0), bin.il_usd_value);
+        }
+    }
 
     // Generate comprehensive analysis
     let analysis = position_analyzer.analyze_position_performance(
@@ -292,19 +732,43 @@ async fn run_snapshot_analysis(
         ).await?;
         info!("Generated {} report", format);
     }
+    if config.export_charts {
+        report_generator.export_charts(
+            "il_analysis",
+            &charts::position_charts(&analysis, &il_result),
+            &report_config,
+        ).await?;
+    }
 
     Ok(())
 }
 
+fn log_position_performance(performance: &PositionPerformance) {
+    info!("Replayed {} cash flows: deposited ${:.2}, withdrawn ${:.2}, fees claimed ${:.2} (+${:.2} unclaimed)",
+          performance.cash_flows.len(), performance.deposited_usd, performance.withdrawn_usd,
+          performance.fees_claimed_usd, performance.unclaimed_fees_usd);
+    info!("Cost basis: ${:.2}, realized PnL: ${:.2}, unrealized PnL: ${:.2}",
+          performance.cost_basis_usd, performance.realized_pnl_usd, performance.unrealized_pnl_usd);
+    match performance.money_weighted_return {
+        Some(mwr) => info!("Net PnL: ${:.2}, time-weighted return: {:.2}%, money-weighted return: {:.2}% a year",
+                           performance.net_pnl_usd, performance.time_weighted_return * Decimal::new(100, 0),
+                           mwr * Decimal::new(100, 0)),
+        None => info!("Net PnL: ${:.2}, time-weighted return: {:.2}%",
+                      performance.net_pnl_usd, performance.time_weighted_return * Decimal::new(100, 0)),
+    }
+}
+
 async fn run_realtime_monitoring(
     il_calculator: &mut ILCalculator,
     position_analyzer: &mut PositionAnalyzer,
     price_monitor: &mut PriceMonitor,
     report_generator: &mut ReportGenerator,
+    alert_engine: &mut AlertEngine,
     config: &AnalysisConfig,
     formats: &[ReportFormat],
 ) -> Result<()> {
-    info!("Starting real-time monitoring (interval: {}s)...", config.interval_secs);
+    info!("Starting real-time monitoring (interval: {}s, {} alert rules)...",
+          config.interval_secs, alert_engine.rules().len());
 
     let mut iteration = 0u64;
     
@@ -331,37 +795,39 @@ async fn run_realtime_monitoring(
                           il_result.il_percentage * Decimal::new(100, 0), 
                           il_result.il_usd_value);
 
-                    // Check for significant changes
-                    if il_result.il_percentage.abs() > config.max_price_deviation / Decimal::new(100, 0) {
-                        info!("⚠️  High impermanent loss detected!");
-                    }
+                    match position_analyzer.analyze_position_performance(
+                        config.pool_address,
+                        position_data.clone(),
+                        &il_result,
+                    ).await {
+                        Ok(analysis) => {
+                            alert_engine.process(&analysis);
 
-                    // Generate periodic reports (every 10th iteration)
-                    if iteration % 10 == 0 {
-                        let analysis = position_analyzer.analyze_position_performance(
-                            config.pool_address,
-                            position_data.clone(),
-                            &il_result,
-                        ).await?;
-
-                        let report_config = ReportConfig {
-                            title: format!("DLMM Real-time Report #{} - {}", iteration, config.pool_address),
-                            include_charts: true,
-                            include_raw_data: false,
-                            timestamp: chrono::Utc::now(),
-                        };
-
-                        // Generate JSON report for real-time monitoring
-                        report_generator.generate_report(
-                            &analysis,
-                            &il_result,
-                            &report_config,
-                            ReportFormat::Json,
-                        ).await?;
+                            // Generate periodic reports (every 10th iteration)
+                            if iteration % 10 == 0 {
+                                let report_config = ReportConfig {
+                                    title: format!("DLMM Real-time Report #{} - {}", iteration, config.pool_address),
+                                    include_charts: true,
+                                    include_raw_data: false,
+                                    timestamp: chrono::Utc::now(),
+                                };
+
+                                // Generate JSON report for real-time monitoring
+                                report_generator.generate_report(
+                                    &analysis,
+                                    &il_result,
+                                    &report_config,
+                                    ReportFormat::Json,
+                                ).await?;
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Failed to analyze position performance: {}", e);
+                        }
                     }
                 }
                 Err(e) => {
-                    log::warn!("Failed to calculate IL: {}", e);
+                    log::warn!("Failed to calculate IL ({}): {}", il_error_category(&e), e);
                 }
             }
         }
@@ -390,11 +856,21 @@ async fn run_historical_analysis(
 
     info!("Retrieved {} historical data points", historical_data.len());
 
+    // Replay the position's own transactions at each point when analysing a position
+    let position_history = match config.position_id {
+        Some(position_id) => Some(il_calculator.position_history(config.pool_address, position_id).await?),
+        None => None,
+    };
+
     // Calculate historical IL progression
     let il_history = il_calculator.calculate_historical_il(
         config.pool_address,
         &historical_data,
+        position_history.as_ref(),
     ).await?;
+    if il_history.is_empty() {
+        return Err(anyhow::anyhow!("No historical data points after the position was created"));
+    }
 
     info!("Calculated IL for {} historical points", il_history.len());
 
@@ -428,7 +904,307 @@ async fn run_historical_analysis(
         ).await?;
         info!("Generated historical {} report", format);
     }
+    if config.export_charts {
+        report_generator.export_charts(
+            "historical_analysis",
+            &charts::historical_charts(&il_history, &historical_data),
+            &report_config,
+        ).await?;
+    }
+
+    Ok(())
+}
+
+async fn run_portfolio_analysis(
+    il_calculator: &mut ILCalculator,
+    position_analyzer: &mut PositionAnalyzer,
+    price_monitor: &mut PriceMonitor,
+    report_generator: &mut ReportGenerator,
+    config: &AnalysisConfig,
+    formats: &[ReportFormat],
+    owner: Option<Pubkey>,
+) -> Result<()> {
+    info!("Running portfolio analysis ({} days of returns)...", config.historical_days);
+
+    let report = portfolio::analyze_portfolio(
+        il_calculator,
+        position_analyzer,
+        price_monitor,
+        owner,
+        &config.position_ids,
+        config.historical_days,
+    ).await?;
+    let statistics = &report.statistics;
+
+    info!(
+        "Portfolio: {} positions across {} pools, value ${:.2}",
+        statistics.total_positions,
+        statistics.pool_rankings.len(),
+        statistics.total_tvl
+    );
+    info!("Value-weighted IL: {:.4}%", statistics.average_il * Decimal::new(100, 0));
+    info!(
+        "1-day VaR (95%): ${:.2} (${:.2} without diversification)",
+        statistics.portfolio_var_95,
+        statistics.undiversified_var_95
+    );
+    info!("Token exposure concentration (HHI): {:.3}", statistics.exposure_concentration);
+    if let Some(best) = statistics.pool_rankings.first() {
+        info!("Best risk-adjusted pool: {} ({:.3})", best.pool_address, best.risk_adjusted_return);
+    }
+
+    let report_config = ReportConfig {
+        title: match report.owner {
+            Some(owner) => format!("DLMM Portfolio Analysis - {}", owner),
+            None => format!("DLMM Portfolio Analysis - {} positions", statistics.total_positions),
+        },
+        include_charts: true,
+        include_raw_data: true,
+        timestamp: report.generated_at,
+    };
+
+    for format in formats {
+        report_generator.generate_portfolio_report(&report, &report_config, format.clone()).await?;
+    }
+
+    Ok(())
+}
+
+async fn run_scenario_analysis(
+    position_analyzer: &mut PositionAnalyzer,
+    price_monitor: &mut PriceMonitor,
+    report_generator: &mut ReportGenerator,
+    config: &AnalysisConfig,
+    formats: &[ReportFormat],
+    params: ScenarioParams,
+) -> Result<()> {
+    info!("Running scenario analysis ({} day horizon)...", params.horizon_days);
+
+    let pool_info = position_analyzer.get_pool_info(config.pool_address).await?;
+    let (price_x, price_y) = price_monitor.get_current_prices(
+        pool_info.token_x,
+        pool_info.token_y,
+    ).await?;
+    info!("Entry prices - Token X: ${:.6}, Token Y: ${:.6}", price_x, price_y);
+
+    // The position is sized as if opened now, at the current price's bin
+    let bin_step = params.bin_step.unwrap_or(pool_info.bin_step);
+    let entry_active_bin_id = bin_model::price_to_bin_id(price_x / price_y, bin_step);
+    let (lower_bin_id, upper_bin_id) = params.bin_range.unwrap_or((
+        entry_active_bin_id - params.range_bins,
+        entry_active_bin_id + params.range_bins,
+    ));
+
+    let bins = PositionBins::with_distribution(
+        lower_bin_id,
+        upper_bin_id,
+        entry_active_bin_id,
+        bin_step,
+        params.amount_x,
+        params.amount_y,
+        params.distribution,
+    )?;
+
+    let daily_fee_rate = match params.daily_fee_rate {
+        Some(rate) => rate,
+        None if pool_info.tvl > Decimal::ZERO => pool_info.fees_24h / pool_info.tvl,
+        None => Decimal::ZERO,
+    };
+    let engine = ScenarioEngine::new(bins, (price_x, price_y), daily_fee_rate)?;
+    info!("Range [{}, {}] around bin {} at {} bps, entry value ${:.2}, fees {:.4}%/day",
+          lower_bin_id, upper_bin_id, entry_active_bin_id, bin_step,
+          engine.entry_value(), daily_fee_rate * Decimal::new(100, 0));
+
+    let shocks = engine.stress_test(&scenario::shock_grid(params.max_shock, 5), params.horizon_days);
+    for point in &shocks {
+        info!("Shock {:>+7.2}%: bin {} ({}), IL {:.4}% (${:.2}), fees ${:.2}, net PnL ${:.2}",
+              point.price_change * Decimal::new(100, 0),
+              point.active_bin_id,
+              if point.in_range { "in range" } else { "out of range" },
+              point.il_percentage * Decimal::new(100, 0),
+              point.il_usd_value,
+              point.fee_income_usd,
+              point.net_pnl_usd);
+    }
+
+    let path = engine.evaluate_path(&params.price_path, Decimal::ONE);
+    for (day, point) in path.iter().enumerate() {
+        info!("Day {}: price ${:.6} ({}), IL {:.4}%, fees ${:.2}, net PnL ${:.2}",
+              day,
+              point.price_x,
+              if point.in_range { "in range" } else { "out of range" },
+              point.il_percentage * Decimal::new(100, 0),
+              point.fee_income_usd,
+              point.net_pnl_usd);
+    }
+
+    let volatility = match params.volatility {
+        Some(volatility) => volatility,
+        None => price_monitor.calculate_pair_volatility(pool_info.token_x, pool_info.token_y, config.volatility_window).await?,
+    };
+
+    let monte_carlo = if volatility > Decimal::ZERO {
+        let result = engine.monte_carlo(&MonteCarloConfig {
+            paths: params.paths,
+            horizon_days: params.horizon_days,
+            steps_per_day: 24,
+            volatility,
+            drift: Decimal::ZERO,
+            seed: params.seed,
+        })?;
+
+        info!("Monte Carlo ({} paths, {:.2}% volatility): exits range on {:.1}% of paths, {:.1}% out of range at horizon",
+              result.paths,
+              volatility * Decimal::new(100, 0),
+              result.probability_exit_range * Decimal::new(100, 0),
+              result.probability_out_of_range_at_horizon * Decimal::new(100, 0));
+        info!("Expected IL ${:.2}, fees ${:.2}, net PnL ${:.2}; fees cover IL on {:.1}% of paths",
+              result.expected_il_usd,
+              result.expected_fee_income_usd,
+              result.expected_net_pnl_usd,
+              result.probability_fees_cover_il * Decimal::new(100, 0));
+        match result.breakeven_days {
+            Some(days) => info!("Expected fee-vs-IL breakeven: {:.1} days", days),
+            None => info!("Expected fee-vs-IL breakeven: never (no fee income)"),
+        }
+        for band in &result.percentiles {
+            info!("P{:02}: price ${:.6}, IL {:.4}%, net PnL ${:.2}",
+                  band.percentile, band.price_x, band.il_percentage * Decimal::new(100, 0), band.net_pnl_usd);
+        }
+        Some(result)
+    } else {
+        warn!("No volatility estimate from price history; pass --volatility to run Monte Carlo");
+        None
+    };
 
+    let report = ScenarioReport {
+        pool_address: config.pool_address,
+        generated_at: chrono::Utc::now(),
+        entry_price_x: price_x,
+        entry_price_y: price_y,
+        bin_step,
+        lower_bin_id,
+        upper_bin_id,
+        entry_active_bin_id,
+        distribution: params.distribution,
+        daily_fee_rate,
+        entry_value_usd: engine.entry_value(),
+        shocks,
+        path,
+        monte_carlo,
+    };
+
+    let report_config = ReportConfig {
+        title: format!("DLMM Scenario Analysis - {}", config.pool_address),
+        include_charts: false,
+        include_raw_data: true,
+        timestamp: report.generated_at,
+    };
+    for format in formats {
+        report_generator.generate_scenario_report(&report, &report_config, format.clone()).await?;
+    }
+
+    Ok(())
+}
+
+async fn run_range_optimization(
+    position_analyzer: &mut PositionAnalyzer,
+    price_monitor: &mut PriceMonitor,
+    config: &AnalysisConfig,
+    params: OptimizeParams,
+) -> Result<()> {
+    info!("Optimising range for ${} over {} days...", params.capital_usd, params.horizon_days);
+
+    let pool_info = position_analyzer.get_pool_info(config.pool_address).await?;
+    let entry_prices = price_monitor.get_current_prices(
+        pool_info.token_x,
+        pool_info.token_y,
+    ).await?;
+
+    let volatility = match params.volatility {
+        Some(volatility) => volatility,
+        None => price_monitor.calculate_pair_volatility(pool_info.token_x, pool_info.token_y, config.volatility_window).await?,
+    };
+    if volatility <= Decimal::ZERO {
+        return Err(anyhow::anyhow!("No volatility estimate from price history; pass --volatility"));
+    }
+
+    let active_bin_daily_fee_rate = params
+        .active_bin_daily_fee_rate
+        .unwrap_or_else(|| optimizer::estimate_active_bin_fee_rate(&pool_info, params.pool_width_bins));
+
+    let optimizer = RangeOptimizer::new(entry_prices, pool_info.bin_step)?;
+    let recommendation = optimizer.optimize(&OptimizerConfig {
+        capital_usd: params.capital_usd,
+        horizon_days: params.horizon_days,
+        volatility,
+        max_drawdown: params.max_drawdown,
+        active_bin_daily_fee_rate,
+        half_widths: params.half_widths,
+        paths: params.paths,
+        steps_per_day: 24,
+        seed: params.seed,
+    })?;
+
+    println!("Active bin {} at {} bps, volatility {:.2}%, active-bin fees {:.4}%/day, max drawdown {:.2}%",
+             recommendation.entry_active_bin_id,
+             recommendation.bin_step,
+             volatility * Decimal::new(100, 0),
+             active_bin_daily_fee_rate * Decimal::new(100, 0),
+             params.max_drawdown * Decimal::new(100, 0));
+    println!("{:<15} {:>6} {:<20} {:>12} {:>12} {:>12} {:>8} {:>9}",
+             "Range", "Bins", "Shape", "E[fees] $", "E[IL] $", "E[net] $", "P(exit)", "Drawdown");
+    for candidate in &recommendation.candidates {
+        println!("{:<15} {:>6} {:<20} {:>12.2} {:>12.2} {:>12.2} {:>7.1}% {:>8.2}%{}",
+                 format!("[{}, {}]", candidate.lower_bin_id, candidate.upper_bin_id),
+                 candidate.bin_count(),
+                 candidate.distribution.to_string(),
+                 candidate.expected_fee_income_usd,
+                 candidate.expected_il_usd,
+                 candidate.expected_net_usd,
+                 candidate.probability_exit_range * Decimal::new(100, 0),
+                 candidate.drawdown * Decimal::new(100, 0),
+                 if candidate.within_drawdown { "" } else { " ✗" });
+    }
+
+    let Some(best) = &recommendation.recommended else {
+        warn!("No range keeps the 5th percentile loss against holding within {:.2}% of capital",
+              params.max_drawdown * Decimal::new(100, 0));
+        return Ok(());
+    };
+
+    let position_params = optimizer.position_params(config.pool_address, best, params.token_decimals)?;
+    println!("Recommended: {} over [{}, {}]", best.distribution, best.lower_bin_id, best.upper_bin_id);
+    println!("{:?}", position_params);
+
+    Ok(())
+}
+
+/// Alert engine for monitor mode: rules from `rules_path` or the built-in defaults, with
+/// fired events persisted next to the reports
+fn build_alert_engine(config: &AnalysisConfig, rules_path: Option<&String>) -> Result<AlertEngine> {
+    if !config.enable_notifications {
+        return Ok(AlertEngine::new(Vec::new()));
+    }
+    let alert_config = match rules_path {
+        Some(path) => AlertConfig::load(path)?,
+        None => AlertConfig::defaults(config),
+    };
+    let events_path = Path::new(&config.output_directory).join("alerts.jsonl");
+    info!("Alert rules: {}; events persisted to {}",
+          alert_config.rules.iter().map(|rule| rule.name.as_str()).collect::<Vec<_>>().join(", "),
+          events_path.display());
+    AlertEngine::from_config(alert_config, events_path)
+}
+
+/// Publish the JSON Schema of every report type, one file per schema version
+fn write_report_schemas(directory: &str) -> Result<()> {
+    std::fs::create_dir_all(directory)?;
+    for schema in report_model::SCHEMAS {
+        let path = Path::new(directory).join(schema.file_name());
+        std::fs::write(&path, serde_json::to_string_pretty(&schema.json_schema())? + "\n")?;
+        println!("{}", path.display());
+    }
     Ok(())
 }
 
@@ -438,10 +1214,12 @@ fn parse_report_formats(format_str: &str) -> Result<Vec<ReportFormat>> {
             ReportFormat::Json,
             ReportFormat::Csv,
             ReportFormat::Html,
+            ReportFormat::Markdown,
         ]),
         "json" => Ok(vec![ReportFormat::Json]),
         "csv" => Ok(vec![ReportFormat::Csv]),
         "html" => Ok(vec![ReportFormat::Html]),
-        _ => Err(anyhow::anyhow!("Invalid format: {}. Use: json, csv, html, all", format_str)),
+        "markdown" | "md" => Ok(vec![ReportFormat::Markdown]),
+        _ => Err(anyhow::anyhow!("Invalid format: {}. Use: json, csv, html, markdown, all", format_str)),
     }
 }
\ No newline at end of file
@@ -0,0 +1,361 @@
+//! Range optimiser for new DLMM positions
+//!
+//! Searches range widths and liquidity shapes around the current active bin. Each
+//! candidate is run through the scenario engine's Monte Carlo simulation and scored by
+//! expected fees plus expected IL. The best candidate whose 5th percentile loss against
+//! holding the deposit stays within the drawdown limit is recommended.
+
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use saros_dlmm_sdk::{LiquidityDistribution as SdkDistribution, PositionParams};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::bin_model::{self, LiquidityDistribution, PositionBins};
+use crate::scenario::{MonteCarloConfig, ScenarioEngine};
+use crate::types::{ILError, PoolInfo};
+
+/// Percentile whose loss is held to the drawdown limit
+const DRAWDOWN_PERCENTILE: u8 = 5;
+
+/// Search space and risk limits for the optimiser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizerConfig {
+    pub capital_usd: Decimal,
+    pub horizon_days: u32,
+    /// Annualized volatility of token X's price
+    pub volatility: Decimal,
+    /// Largest acceptable 5th percentile loss against holding, as a fraction of capital
+    pub max_drawdown: Decimal,
+    /// Fee income per day as a fraction of the position's liquidity in the active bin
+    pub active_bin_daily_fee_rate: Decimal,
+    /// Range half-widths to search, in bins either side of the active bin
+    pub half_widths: Vec<i32>,
+    pub paths: u32,
+    pub steps_per_day: u32,
+    pub seed: u64,
+}
+
+/// Expected outcome of one range and shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeCandidate {
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub distribution: LiquidityDistribution,
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+    pub expected_fee_income_usd: Decimal,
+    pub expected_il_usd: Decimal,
+    /// Expected fees plus expected (negative) IL
+    pub expected_net_usd: Decimal,
+    pub probability_exit_range: Decimal,
+    /// 5th percentile loss against holding, as a fraction of capital
+    pub drawdown: Decimal,
+    pub within_drawdown: bool,
+}
+
+impl RangeCandidate {
+    pub fn bin_count(&self) -> i32 {
+        self.upper_bin_id - self.lower_bin_id + 1
+    }
+}
+
+/// Every candidate evaluated, best first, and the one recommended
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeRecommendation {
+    pub entry_price_x: Decimal,
+    pub entry_price_y: Decimal,
+    pub bin_step: u16,
+    pub entry_active_bin_id: i32,
+    pub config: OptimizerConfig,
+    /// Best candidate within the drawdown limit, if any
+    pub recommended: Option<RangeCandidate>,
+    pub candidates: Vec<RangeCandidate>,
+}
+
+/// Recommends a bin range and liquidity shape for a new position
+#[derive(Debug, Clone)]
+pub struct RangeOptimizer {
+    entry_price_x: Decimal,
+    entry_price_y: Decimal,
+    bin_step: u16,
+    entry_active_bin_id: i32,
+}
+
+impl RangeOptimizer {
+    /// Optimiser for a position opened now at `entry_prices`
+    pub fn new(entry_prices: (Decimal, Decimal), bin_step: u16) -> Result<Self, ILError> {
+        let (entry_price_x, entry_price_y) = entry_prices;
+        if entry_price_x <= Decimal::ZERO || entry_price_y <= Decimal::ZERO {
+            return Err(ILError::PriceDataUnavailable("Entry prices must be positive".to_string()));
+        }
+        if bin_step == 0 {
+            return Err(ILError::InvalidPosition("Bin step must be positive".to_string()));
+        }
+
+        Ok(Self {
+            entry_price_x,
+            entry_price_y,
+            bin_step,
+            entry_active_bin_id: bin_model::price_to_bin_id(entry_price_x / entry_price_y, bin_step),
+        })
+    }
+
+    /// Evaluate every width and shape and recommend the best within the drawdown limit
+    pub fn optimize(&self, config: &OptimizerConfig) -> Result<RangeRecommendation, ILError> {
+        if config.capital_usd <= Decimal::ZERO {
+            return Err(ILError::InvalidPosition("Capital must be positive".to_string()));
+        }
+        if config.half_widths.iter().any(|&half_width| half_width < 0) {
+            return Err(ILError::InvalidPosition("Range widths must not be negative".to_string()));
+        }
+
+        let mut candidates = Vec::new();
+        for &half_width in &config.half_widths {
+            for distribution in candidate_shapes(half_width) {
+                candidates.push(self.evaluate_candidate(half_width, distribution, config)?);
+            }
+        }
+        candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.expected_net_usd));
+
+        Ok(RangeRecommendation {
+            entry_price_x: self.entry_price_x,
+            entry_price_y: self.entry_price_y,
+            bin_step: self.bin_step,
+            entry_active_bin_id: self.entry_active_bin_id,
+            config: config.clone(),
+            recommended: candidates.iter().find(|candidate| candidate.within_drawdown).cloned(),
+            candidates,
+        })
+    }
+
+    /// Simulate one range of `half_width` bins either side of the active bin
+    pub fn evaluate_candidate(
+        &self,
+        half_width: i32,
+        distribution: LiquidityDistribution,
+        config: &OptimizerConfig,
+    ) -> Result<RangeCandidate, ILError> {
+        let lower_bin_id = self.entry_active_bin_id - half_width;
+        let upper_bin_id = self.entry_active_bin_id + half_width;
+
+        // Capital is split evenly by value, as the range is symmetric around the active bin
+        let half_capital = config.capital_usd / Decimal::TWO;
+        let amount_x = half_capital / self.entry_price_x;
+        let amount_y = half_capital / self.entry_price_y;
+
+        let engine = ScenarioEngine::with_active_bin_fees(
+            self.position_bins(lower_bin_id, upper_bin_id, amount_x, amount_y, distribution)?,
+            (self.entry_price_x, self.entry_price_y),
+            config.active_bin_daily_fee_rate,
+        )?;
+        let result = engine.monte_carlo(&MonteCarloConfig {
+            paths: config.paths,
+            horizon_days: config.horizon_days,
+            steps_per_day: config.steps_per_day,
+            volatility: config.volatility,
+            drift: Decimal::ZERO,
+            seed: config.seed,
+        })?;
+
+        // Price moves hit holding the tokens too, so only what the position adds is a drawdown
+        let tail_vs_hold = result.percentiles
+            .iter()
+            .find(|band| band.percentile == DRAWDOWN_PERCENTILE)
+            .map(|band| band.net_vs_hold_usd)
+            .unwrap_or_default();
+        let drawdown = tail_vs_hold.min(Decimal::ZERO).abs() / config.capital_usd;
+
+        Ok(RangeCandidate {
+            lower_bin_id,
+            upper_bin_id,
+            distribution,
+            amount_x,
+            amount_y,
+            expected_fee_income_usd: result.expected_fee_income_usd,
+            expected_il_usd: result.expected_il_usd,
+            expected_net_usd: result.expected_fee_income_usd + result.expected_il_usd,
+            probability_exit_range: result.probability_exit_range,
+            drawdown,
+            within_drawdown: drawdown <= config.max_drawdown,
+        })
+    }
+
+    /// SDK parameters opening `candidate`, with amounts in raw token units
+    pub fn position_params(
+        &self,
+        pool_address: Pubkey,
+        candidate: &RangeCandidate,
+        decimals: (u8, u8),
+    ) -> Result<PositionParams, ILError> {
+        let scale_x = Decimal::from(10u64.pow(decimals.0 as u32));
+        let scale_y = Decimal::from(10u64.pow(decimals.1 as u32));
+
+        let liquidity_distribution = match candidate.distribution {
+            LiquidityDistribution::Uniform => SdkDistribution::Uniform,
+            LiquidityDistribution::Normal { std_dev_bins } => SdkDistribution::Normal {
+                mean: self.entry_active_bin_id,
+                std_dev: std_dev_bins,
+            },
+            // The SDK has no bid-ask shape, so its per-bin liquidity is given explicitly
+            LiquidityDistribution::BidAsk => {
+                let bins = self.position_bins(
+                    candidate.lower_bin_id,
+                    candidate.upper_bin_id,
+                    candidate.amount_x,
+                    candidate.amount_y,
+                    candidate.distribution,
+                )?;
+                SdkDistribution::Custom(
+                    bins.bins
+                        .iter()
+                        .map(|bin| {
+                            let liquidity = bin.amount_x * bin_model::bin_price(bin.bin_id, self.bin_step) + bin.amount_y;
+                            (bin.bin_id, (liquidity * scale_y).trunc().to_u128().unwrap_or(0))
+                        })
+                        .collect(),
+                )
+            }
+        };
+
+        let raw = |amount: Decimal, scale: Decimal| {
+            (amount * scale)
+                .trunc()
+                .to_u64()
+                .ok_or_else(|| ILError::CalculationError(format!("Amount {} overflows raw token units", amount)))
+        };
+
+        Ok(PositionParams {
+            pool_address,
+            lower_bin_id: candidate.lower_bin_id,
+            upper_bin_id: candidate.upper_bin_id,
+            liquidity_distribution,
+            total_amount_x: raw(candidate.amount_x, scale_x)?,
+            total_amount_y: raw(candidate.amount_y, scale_y)?,
+        })
+    }
+
+    fn position_bins(
+        &self,
+        lower_bin_id: i32,
+        upper_bin_id: i32,
+        amount_x: Decimal,
+        amount_y: Decimal,
+        distribution: LiquidityDistribution,
+    ) -> Result<PositionBins, ILError> {
+        PositionBins::with_distribution(
+            lower_bin_id,
+            upper_bin_id,
+            self.entry_active_bin_id,
+            self.bin_step,
+            amount_x,
+            amount_y,
+            distribution,
+        )
+    }
+}
+
+/// Shapes searched at each width: spot, a wide and a tight curve, and bid-ask
+pub fn candidate_shapes(half_width: i32) -> Vec<LiquidityDistribution> {
+    let mut shapes = vec![LiquidityDistribution::Uniform];
+    if half_width > 0 {
+        shapes.push(LiquidityDistribution::Normal { std_dev_bins: (half_width as f64 / 2.0).max(1.0) });
+        if half_width >= 4 {
+            shapes.push(LiquidityDistribution::Normal { std_dev_bins: half_width as f64 / 4.0 });
+        }
+        shapes.push(LiquidityDistribution::BidAsk);
+    }
+    shapes
+}
+
+/// Daily fee rate on active-bin liquidity, from the pool's 24h fees (or volume at the
+/// bin step's base fee) and its TVL spread evenly over `pool_width_bins` bins
+pub fn estimate_active_bin_fee_rate(pool_info: &PoolInfo, pool_width_bins: u32) -> Decimal {
+    let daily_fees = if pool_info.fees_24h > Decimal::ZERO {
+        pool_info.fees_24h
+    } else {
+        pool_info.volume_24h * Decimal::from(pool_info.bin_step) / Decimal::from(10_000)
+    };
+
+    let active_bin_liquidity = pool_info.tvl / Decimal::from(pool_width_bins.max(1));
+    if active_bin_liquidity > Decimal::ZERO {
+        daily_fees / active_bin_liquidity
+    } else {
+        Decimal::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn config(active_bin_daily_fee_rate: Decimal, max_drawdown: Decimal) -> OptimizerConfig {
+        OptimizerConfig {
+            capital_usd: dec!(10000),
+            horizon_days: 7,
+            volatility: dec!(0.6),
+            max_drawdown,
+            active_bin_daily_fee_rate,
+            half_widths: vec![2, 10, 40],
+            paths: 150,
+            steps_per_day: 4,
+            seed: 11,
+        }
+    }
+
+    #[test]
+    fn test_candidate_shapes_cover_spot_curve_and_bid_ask() {
+        assert_eq!(candidate_shapes(0), vec![LiquidityDistribution::Uniform]);
+        assert_eq!(candidate_shapes(10).len(), 4);
+        assert!(candidate_shapes(10).contains(&LiquidityDistribution::BidAsk));
+    }
+
+    #[test]
+    fn test_without_fees_widest_range_loses_least() {
+        let optimizer = RangeOptimizer::new((dec!(100), dec!(1)), 20).unwrap();
+        let recommendation = optimizer.optimize(&config(Decimal::ZERO, Decimal::ONE)).unwrap();
+
+        let best = recommendation.recommended.unwrap();
+        assert_eq!(best.bin_count(), 81);
+        assert_eq!(recommendation.candidates.len(), 3 + 4 + 4);
+        assert!(recommendation.candidates.windows(2).all(|pair| pair[0].expected_net_usd >= pair[1].expected_net_usd));
+    }
+
+    #[test]
+    fn test_rich_fees_favour_concentration_until_drawdown_binds() {
+        let optimizer = RangeOptimizer::new((dec!(100), dec!(1)), 20).unwrap();
+
+        let unconstrained = optimizer.optimize(&config(Decimal::ONE, Decimal::ONE)).unwrap();
+        let best = unconstrained.recommended.unwrap();
+        assert!(best.bin_count() < 81);
+        assert!(best.expected_net_usd > Decimal::ZERO);
+
+        let strict = optimizer.optimize(&config(Decimal::ONE, dec!(0.0001))).unwrap();
+        assert!(strict.candidates.iter().all(|candidate| !candidate.within_drawdown));
+        assert!(strict.recommended.is_none());
+    }
+
+    #[test]
+    fn test_position_params_use_raw_units_and_sdk_shapes() {
+        let optimizer = RangeOptimizer::new((dec!(100), dec!(1)), 20).unwrap();
+        let config = config(dec!(0.01), Decimal::ONE);
+        let curve = optimizer
+            .evaluate_candidate(10, LiquidityDistribution::Normal { std_dev_bins: 5.0 }, &config)
+            .unwrap();
+
+        let params = optimizer.position_params(Pubkey::new_unique(), &curve, (9, 6)).unwrap();
+        assert_eq!(params.total_amount_x, 50_000_000_000);
+        assert_eq!(params.total_amount_y, 5_000_000_000);
+        assert_eq!(params.upper_bin_id - params.lower_bin_id, 20);
+        assert!(matches!(params.liquidity_distribution, SdkDistribution::Normal { mean, .. } if mean == bin_model::price_to_bin_id(dec!(100), 20)));
+
+        let bid_ask = optimizer.evaluate_candidate(10, LiquidityDistribution::BidAsk, &config).unwrap();
+        let params = optimizer.position_params(Pubkey::new_unique(), &bid_ask, (9, 6)).unwrap();
+        let SdkDistribution::Custom(bins) = params.liquidity_distribution else {
+            panic!("bid-ask should map to custom liquidity");
+        };
+        assert_eq!(bins.len(), 21);
+        assert!(bins[0].1 > bins[10].1);
+    }
+}
@@ -0,0 +1,470 @@
+//! Portfolio-level IL aggregation across positions and pools
+//!
+//! Each position is analysed on its own, then its liquidity is re-priced through its bins
+//! along its tokens' price history so pools can be compared day by day: daily return
+//! correlations between pools, a historical simulation of the portfolio's one-day value
+//! at risk, token exposure and a ranking of pools by risk-adjusted return.
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{info, warn};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+
+use crate::bin_model::{self, PositionBins};
+use crate::il_calculator::ILCalculator;
+use crate::position_analyzer::PositionAnalyzer;
+use crate::price_monitor::PriceMonitor;
+use crate::types::{pubkey_string, AggregateStatistics, PoolRanking, PositionAnalysis, PositionInfo, PriceDataPoint};
+
+const DAYS_PER_YEAR: f64 = 365.0;
+/// Tail probability of the value at risk
+const VAR_CONFIDENCE_TAIL: f64 = 0.05;
+
+/// A value path keyed by UTC day
+type DailySeries = BTreeMap<NaiveDate, f64>;
+
+/// A position with its analysis and the value of its current tokens along the price history
+#[derive(Debug, Clone)]
+pub struct PortfolioPosition {
+    pub analysis: PositionAnalysis,
+    pub token_x: Pubkey,
+    pub token_y: Pubkey,
+    /// Current token amounts
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+    /// Value of the position's current liquidity re-priced at each historical price point,
+    /// oldest first
+    pub value_history: Vec<(DateTime<Utc>, Decimal)>,
+}
+
+impl PortfolioPosition {
+    fn pool_address(&self) -> Pubkey {
+        self.analysis.position_info.pool_address
+    }
+
+    fn value_usd(&self) -> Decimal {
+        self.analysis.il_result.current_value_usd
+    }
+}
+
+/// Portfolio analysis written to reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioReport {
+    #[serde(with = "pubkey_string::option")]
+    pub owner: Option<Pubkey>,
+    pub generated_at: DateTime<Utc>,
+    pub history_days: u32,
+    pub positions: Vec<PositionAnalysis>,
+    pub statistics: AggregateStatistics,
+}
+
+/// Analyse every position and aggregate them. Positions that fail are logged and left
+/// out rather than failing the whole portfolio.
+pub async fn analyze_portfolio(
+    il_calculator: &mut ILCalculator,
+    position_analyzer: &mut PositionAnalyzer,
+    price_monitor: &mut PriceMonitor,
+    owner: Option<Pubkey>,
+    position_ids: &[Pubkey],
+    history_days: u32,
+) -> Result<PortfolioReport> {
+    let mut positions = match owner {
+        Some(owner) => position_analyzer.get_owner_positions(owner).await?,
+        None => Vec::new(),
+    };
+    for &position_id in position_ids {
+        if !positions.iter().any(|position| position.position_id == Some(position_id)) {
+            positions.push(position_analyzer.get_position_info(position_id).await?);
+        }
+    }
+    if positions.is_empty() {
+        return Err(anyhow::anyhow!("No positions to analyse"));
+    }
+    info!("Analysing portfolio of {} positions", positions.len());
+
+    let mut portfolio = Vec::new();
+    for position in positions {
+        let position_id = position.position_id.expect("owner and listed positions have IDs");
+        match analyze_position(il_calculator, position_analyzer, price_monitor, position, history_days).await {
+            Ok(analysed) => portfolio.push(analysed),
+            Err(e) => warn!("Skipping position {}: {}", position_id, e),
+        }
+    }
+    if portfolio.is_empty() {
+        return Err(anyhow::anyhow!("Every position in the portfolio failed to analyse"));
+    }
+
+    let statistics = aggregate_statistics(&portfolio);
+    Ok(PortfolioReport {
+        owner,
+        generated_at: Utc::now(),
+        history_days,
+        positions: portfolio.into_iter().map(|position| position.analysis).collect(),
+        statistics,
+    })
+}
+
+async fn analyze_position(
+    il_calculator: &mut ILCalculator,
+    position_analyzer: &mut PositionAnalyzer,
+    price_monitor: &mut PriceMonitor,
+    position: PositionInfo,
+    history_days: u32,
+) -> Result<PortfolioPosition> {
+    let position_id = position.position_id.expect("owner and listed positions have IDs");
+    let pool_address = position.pool_address;
+
+    let il_result = il_calculator.calculate_il_from_position(pool_address, position_id).await?;
+    let analysis = position_analyzer.analyze_position_performance(pool_address, Some(position), &il_result).await?;
+    let pool_info = position_analyzer.get_pool_info(pool_address).await?;
+    let (price_x, price_y) = (il_result.current_price_x, il_result.current_price_y);
+
+    // Without a bin breakdown, split the value evenly between the tokens across the range
+    let holdings = match &il_result.bin_breakdown {
+        Some(breakdown) => breakdown.holdings(),
+        None if price_x > Decimal::ZERO && price_y > Decimal::ZERO => {
+            let half = il_result.current_value_usd / Decimal::new(2, 0);
+            PositionBins::uniform(
+                analysis.position_info.lower_bin_id,
+                analysis.position_info.upper_bin_id,
+                bin_model::price_to_bin_id(price_x / price_y, pool_info.bin_step),
+                pool_info.bin_step,
+                half / price_x,
+                half / price_y,
+            )?
+        }
+        None => PositionBins::new(pool_info.bin_step, pool_info.active_bin_id, Vec::new())?,
+    };
+    let amount_x = holdings.bins.iter().map(|bin| bin.amount_x).sum();
+    let amount_y = holdings.bins.iter().map(|bin| bin.amount_y).sum();
+
+    let history = price_monitor.get_historical_data(pool_info.token_x, pool_info.token_y, history_days).await?;
+    let value_history = value_history(&holdings, &history);
+
+    Ok(PortfolioPosition {
+        analysis,
+        token_x: pool_info.token_x,
+        token_y: pool_info.token_y,
+        amount_x,
+        amount_y,
+        value_history,
+    })
+}
+
+/// Value `holdings` at each price point, converting bins the way the market moving to
+/// that point's price would. Points without positive prices are skipped.
+fn value_history(holdings: &PositionBins, history: &[PriceDataPoint]) -> Vec<(DateTime<Utc>, Decimal)> {
+    history
+        .iter()
+        .filter(|point| point.price_x > Decimal::ZERO && point.price_y > Decimal::ZERO)
+        .map(|point| (point.timestamp, holdings.reprice_at_market(point.price_x, point.price_y).value_usd))
+        .collect()
+}
+
+/// Aggregate analysed positions into portfolio statistics
+pub fn aggregate_statistics(positions: &[PortfolioPosition]) -> AggregateStatistics {
+    let total_value: Decimal = positions.iter().map(PortfolioPosition::value_usd).sum();
+    let average_il = if total_value > Decimal::ZERO {
+        positions.iter().map(|position| position.analysis.il_result.il_percentage * position.value_usd()).sum::<Decimal>() / total_value
+    } else {
+        Decimal::ZERO
+    };
+
+    // Pool value paths, summing positions in the same pool on the days they all cover
+    let mut pool_positions: BTreeMap<Pubkey, Vec<&PortfolioPosition>> = BTreeMap::new();
+    for position in positions {
+        pool_positions.entry(position.pool_address()).or_default().push(position);
+    }
+    let pool_values: BTreeMap<Pubkey, DailySeries> = pool_positions
+        .iter()
+        .map(|(pool, positions)| {
+            let histories: Vec<DailySeries> = positions.iter().map(|position| daily_series(&position.value_history)).collect();
+            (*pool, sum_on_common_days(&histories.iter().collect::<Vec<_>>()))
+        })
+        .collect();
+    let pool_returns: BTreeMap<Pubkey, DailySeries> = pool_values
+        .iter()
+        .map(|(pool, values)| (*pool, daily_returns(values)))
+        .collect();
+
+    let mut correlation_matrix = BTreeMap::new();
+    for (pool_a, returns_a) in &pool_returns {
+        let row = pool_returns
+            .iter()
+            .map(|(pool_b, returns_b)| {
+                let correlation = if pool_a == pool_b { 1.0 } else { correlation(returns_a, returns_b) };
+                (pool_b.to_string(), Decimal::from_f64(correlation).unwrap_or_default().round_dp(6))
+            })
+            .collect();
+        correlation_matrix.insert(pool_a.to_string(), row);
+    }
+
+    // Historical simulation: one-day value changes of the whole portfolio
+    let pool_paths: Vec<&DailySeries> = pool_values.values().collect();
+    let portfolio_changes = value_changes(&sum_on_common_days(&pool_paths));
+    let portfolio_var_95 = value_at_risk(&portfolio_changes);
+    let undiversified_var_95: f64 = pool_values.values().map(|values| value_at_risk(&value_changes(values))).sum();
+
+    let mut token_exposure: BTreeMap<String, Decimal> = BTreeMap::new();
+    for position in positions {
+        let price_x = position.analysis.il_result.current_price_x;
+        let price_y = position.analysis.il_result.current_price_y;
+        *token_exposure.entry(position.token_x.to_string()).or_default() += position.amount_x * price_x;
+        *token_exposure.entry(position.token_y.to_string()).or_default() += position.amount_y * price_y;
+    }
+    let exposure_total: Decimal = token_exposure.values().sum();
+    if exposure_total > Decimal::ZERO {
+        for share in token_exposure.values_mut() {
+            *share /= exposure_total;
+        }
+    }
+    let exposure_concentration = token_exposure.values().map(|share| share * share).sum();
+
+    let mut pool_rankings: Vec<PoolRanking> = pool_positions
+        .iter()
+        .map(|(pool, positions)| {
+            let value_usd: Decimal = positions.iter().map(|position| position.value_usd()).sum();
+            let weighted = |metric: fn(&PositionAnalysis) -> Decimal| -> Decimal {
+                if value_usd > Decimal::ZERO {
+                    positions.iter().map(|position| metric(&position.analysis) * position.value_usd()).sum::<Decimal>() / value_usd
+                } else {
+                    Decimal::ZERO
+                }
+            };
+            let annualized_return = weighted(|analysis| analysis.performance_summary.annualized_return);
+            let returns: Vec<f64> = pool_returns[pool].values().copied().collect();
+            let volatility = standard_deviation(&returns) * DAYS_PER_YEAR.sqrt();
+            let volatility = Decimal::from_f64(volatility).unwrap_or_default();
+
+            PoolRanking {
+                rank: 0,
+                pool_address: *pool,
+                positions: positions.len(),
+                value_usd,
+                il_usd_value: positions.iter().map(|position| position.analysis.il_result.il_usd_value).sum(),
+                fees_earned: positions.iter().map(|position| position.analysis.fee_analysis.total_fees_earned).sum(),
+                net_pnl: positions.iter().map(|position| position.analysis.performance_summary.net_pnl).sum(),
+                annualized_return,
+                annualized_volatility: volatility,
+                risk_adjusted_return: if volatility > Decimal::ZERO { annualized_return / volatility } else { Decimal::ZERO },
+            }
+        })
+        .collect();
+    pool_rankings.sort_by_key(|ranking| std::cmp::Reverse(ranking.risk_adjusted_return));
+    for (index, ranking) in pool_rankings.iter_mut().enumerate() {
+        ranking.rank = index + 1;
+    }
+
+    AggregateStatistics {
+        total_positions: positions.len(),
+        total_tvl: total_value,
+        average_il,
+        total_fees_earned: positions.iter().map(|position| position.analysis.fee_analysis.total_fees_earned).sum(),
+        best_performing_pool: pool_rankings.first().map(|ranking| ranking.pool_address),
+        worst_performing_pool: pool_rankings.last().map(|ranking| ranking.pool_address),
+        correlation_matrix,
+        risk_adjusted_returns: pool_rankings
+            .iter()
+            .map(|ranking| (ranking.pool_address.to_string(), ranking.risk_adjusted_return))
+            .collect(),
+        portfolio_var_95: Decimal::from_f64(portfolio_var_95).unwrap_or_default().round_dp(2),
+        undiversified_var_95: Decimal::from_f64(undiversified_var_95).unwrap_or_default().round_dp(2),
+        token_exposure,
+        exposure_concentration,
+        pool_rankings,
+    }
+}
+
+/// Key a value path by UTC day, keeping each day's latest value
+fn daily_series(history: &[(DateTime<Utc>, Decimal)]) -> DailySeries {
+    history
+        .iter()
+        .map(|(timestamp, value)| (timestamp.date_naive(), value.to_f64().unwrap_or(0.0)))
+        .collect()
+}
+
+/// Sum value paths on the days every one of them covers
+fn sum_on_common_days(paths: &[&DailySeries]) -> DailySeries {
+    let Some((first, rest)) = paths.split_first() else {
+        return DailySeries::new();
+    };
+    first
+        .iter()
+        .filter_map(|(day, value)| {
+            let others: Option<f64> = rest.iter().map(|path| path.get(day)).sum();
+            others.map(|others| (*day, value + others))
+        })
+        .collect()
+}
+
+/// Return from each covered day to the next, keyed by the later day
+fn daily_returns(values: &DailySeries) -> DailySeries {
+    values
+        .iter()
+        .zip(values.iter().skip(1))
+        .map(|((_, previous), (day, value))| (*day, if *previous > 0.0 { value / previous - 1.0 } else { 0.0 }))
+        .collect()
+}
+
+fn value_changes(values: &DailySeries) -> Vec<f64> {
+    values.values().zip(values.values().skip(1)).map(|(previous, value)| value - previous).collect()
+}
+
+/// Loss not exceeded on 95% of days, as a positive amount
+fn value_at_risk(changes: &[f64]) -> f64 {
+    if changes.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = changes.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let index = ((sorted.len() as f64 * VAR_CONFIDENCE_TAIL).floor() as usize).min(sorted.len() - 1);
+    (-sorted[index]).max(0.0)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+fn standard_deviation(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(values);
+    (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+/// Pearson correlation over the days both series cover
+fn correlation(a: &DailySeries, b: &DailySeries) -> f64 {
+    let (a, b): (Vec<f64>, Vec<f64>) = a.iter().filter_map(|(day, x)| b.get(day).map(|y| (*x, *y))).unzip();
+    if a.len() < 2 {
+        return 0.0;
+    }
+    let (mean_a, mean_b) = (mean(&a), mean(&b));
+    let covariance: f64 = a.iter().zip(&b).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum();
+    let variance_a: f64 = a.iter().map(|x| (x - mean_a).powi(2)).sum();
+    let variance_b: f64 = b.iter().map(|y| (y - mean_b).powi(2)).sum();
+    if variance_a == 0.0 || variance_b == 0.0 {
+        0.0
+    } else {
+        covariance / (variance_a * variance_b).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report_generator::tests::create_mock_analysis;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn day(index: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap() + Duration::days(index)
+    }
+
+    fn position(pool: Pubkey, tokens: (Pubkey, Pubkey), values: Vec<Decimal>) -> PortfolioPosition {
+        let value_history: Vec<_> = values.iter().enumerate().map(|(index, value)| (day(index as i64), *value)).collect();
+        let mut analysis = create_mock_analysis();
+        analysis.position_info.pool_address = pool;
+        analysis.il_result.current_value_usd = *values.last().unwrap();
+        analysis.il_result.current_price_x = dec!(1);
+        analysis.il_result.current_price_y = dec!(1);
+        PortfolioPosition {
+            analysis,
+            token_x: tokens.0,
+            token_y: tokens.1,
+            amount_x: *values.last().unwrap() / dec!(2),
+            amount_y: *values.last().unwrap() / dec!(2),
+            value_history,
+        }
+    }
+
+    #[test]
+    fn test_correlation_and_diversified_var() {
+        let (pool_a, pool_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (usdc, sol, btc) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        // Pool B moves exactly against pool A, so the portfolio's value never changes
+        let positions = vec![
+            position(pool_a, (sol, usdc), vec![dec!(100), dec!(110), dec!(99), dec!(108.9)]),
+            position(pool_b, (btc, usdc), vec![dec!(100), dec!(90), dec!(101), dec!(91.1)]),
+        ];
+
+        let statistics = aggregate_statistics(&positions);
+        assert_eq!(statistics.total_positions, 2);
+        assert_eq!(statistics.correlation_matrix[&pool_a.to_string()][&pool_a.to_string()], dec!(1));
+        assert!(statistics.correlation_matrix[&pool_a.to_string()][&pool_b.to_string()] < dec!(-0.99));
+        assert_eq!(statistics.portfolio_var_95, dec!(0));
+        assert!(statistics.undiversified_var_95 > dec!(10));
+    }
+
+    #[test]
+    fn test_token_exposure_and_rankings() {
+        let (pool_a, pool_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (usdc, sol) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut steady = position(pool_a, (sol, usdc), vec![dec!(100), dec!(101), dec!(100), dec!(101)]);
+        steady.analysis.performance_summary.annualized_return = dec!(0.2);
+        let mut volatile = position(pool_b, (sol, usdc), vec![dec!(100), dec!(130), dec!(80), dec!(300)]);
+        volatile.analysis.performance_summary.annualized_return = dec!(0.2);
+
+        let statistics = aggregate_statistics(&[steady, volatile]);
+        assert_eq!(statistics.token_exposure[&sol.to_string()], dec!(0.5));
+        assert_eq!(statistics.exposure_concentration, dec!(0.5));
+        assert_eq!(statistics.pool_rankings[0].pool_address, pool_a);
+        assert_eq!(statistics.pool_rankings[1].rank, 2);
+        assert_eq!(statistics.best_performing_pool, Some(pool_a));
+        assert_eq!(statistics.worst_performing_pool, Some(pool_b));
+        assert_eq!(statistics.total_tvl, dec!(401));
+    }
+
+    #[test]
+    fn test_value_paths_join_on_day() {
+        let (pool_a, pool_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (usdc, sol) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let a = position(pool_a, (sol, usdc), vec![dec!(100), dec!(110), dec!(99), dec!(108.9)]);
+        // Pool B's history starts a day later and ends on the same day as pool A's
+        let mut b = position(pool_b, (sol, usdc), vec![dec!(90), dec!(101), dec!(91.1)]);
+        for (index, point) in b.value_history.iter_mut().enumerate() {
+            point.0 = day(index as i64 + 1);
+        }
+
+        let paths = [daily_series(&a.value_history), daily_series(&b.value_history)];
+        let combined = sum_on_common_days(&paths.iter().collect::<Vec<_>>());
+        let days: Vec<NaiveDate> = (1..=3).map(|index| day(index).date_naive()).collect();
+        assert_eq!(combined.keys().copied().collect::<Vec<_>>(), days);
+        assert_eq!(combined.values().copied().collect::<Vec<_>>(), vec![200.0, 200.0, 200.0]);
+
+        let statistics = aggregate_statistics(&[a, b]);
+        assert!(statistics.correlation_matrix[&pool_a.to_string()][&pool_b.to_string()] < dec!(-0.99));
+        assert_eq!(statistics.portfolio_var_95, dec!(0));
+    }
+
+    #[test]
+    fn test_value_history_reprices_bins() {
+        let holdings = PositionBins::uniform(-5, 5, 0, 100, dec!(10), dec!(10)).unwrap();
+        let point = |price_x: Decimal| PriceDataPoint {
+            timestamp: day(0),
+            price_x,
+            price_y: dec!(1),
+            volume_24h: Decimal::ZERO,
+            liquidity: Decimal::ZERO,
+            active_bin_id: 0,
+        };
+
+        // Above the range the position is all Y, so further gains in X no longer count
+        let values = value_history(&holdings, &[point(dec!(1)), point(dec!(3)), point(dec!(6))]);
+        assert_eq!(values.len(), 3);
+        assert!(values[1].1 < dec!(10) * dec!(3) + dec!(10));
+        assert_eq!(values[1].1, values[2].1);
+        assert!(value_history(&holdings, &[point(Decimal::ZERO)]).is_empty());
+    }
+
+    #[test]
+    fn test_value_at_risk_is_fifth_percentile_loss() {
+        let changes: Vec<f64> = (1..=100).map(|change| change as f64 - 50.0).collect();
+        assert_eq!(value_at_risk(&changes), 44.0);
+        assert_eq!(value_at_risk(&[1.0, 2.0]), 0.0);
+    }
+}
@@ -13,7 +13,7 @@ use saros_dlmm_sdk::{DLMMClient as MockSarosClient, Position as SdkPosition, DLM
 use crate::types::{
     PositionAnalysis, PositionInfo, FeeAnalysis, RiskMetrics, PerformanceSummary,
     HistoricalTrends, TrendDirection, RecoveryPeriod, PoolInfo, ImpermanentLossResult, PriceDataPoint,
-    PositionSnapshot, ILError,
+    PositionSnapshot, ILError, BinData,
 };
 
 /// Analyzer for position performance and risk metrics
@@ -78,6 +78,45 @@ impl PositionAnalyzer {
         Ok(self.convert_position_info(&position).await?)
     }
 
+    /// Get every position held by `owner`
+    pub async fn get_owner_positions(&mut self, owner: Pubkey) -> Result<Vec<PositionInfo>> {
+        info!("Fetching positions for owner {}", owner);
+        let positions = self.client.get_user_positions(owner).await?;
+
+        let mut infos = Vec::with_capacity(positions.len());
+        for position in positions {
+            infos.push(self.convert_position_info(&position).await?);
+            self.position_cache.insert(position.id, (position, Utc::now()));
+        }
+        Ok(infos)
+    }
+
+    /// Get liquidity for each bin from `lower_bin_id` to `upper_bin_id`
+    pub async fn get_bin_data(
+        &self,
+        pool_address: Pubkey,
+        lower_bin_id: i32,
+        upper_bin_id: i32,
+        active_bin_id: i32,
+    ) -> Result<Vec<BinData>> {
+        let bins = futures::future::try_join_all(
+            (lower_bin_id..=upper_bin_id).map(|bin_id| self.client.get_bin(pool_address, bin_id)),
+        ).await?;
+
+        Ok(bins
+            .into_iter()
+            .map(|bin| BinData {
+                bin_id: bin.id,
+                price: Decimal::from_f64(bin.price).unwrap_or_default(),
+                liquidity_x: Decimal::from(bin.liquidity_x),
+                liquidity_y: Decimal::from(bin.liquidity_y),
+                total_liquidity: Decimal::from_u128(bin.total_liquidity).unwrap_or_default(),
+                fee_rate: Decimal::new(bin.fee_rate as i64, 4),
+                is_active: bin.id == active_bin_id,
+            })
+            .collect())
+    }
+
     /// Analyze comprehensive position performance
     pub async fn analyze_position_performance(
         &mut self,
@@ -570,8 +609,8 @@ mod tests {
         assert!((correlation - (-1.0)).abs() < 0.001);
     }
 
-    #[test]
-    fn test_max_drawdown_calculation() {
+    #[tokio::test]
+    async fn test_max_drawdown_calculation() {
         let analyzer = PositionAnalyzer::new().await.unwrap();
         
         // Data with a 3-day drawdown
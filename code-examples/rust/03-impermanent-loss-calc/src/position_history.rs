@@ -0,0 +1,723 @@
+//! Position history reconstruction from transactions
+//!
+//! Replays a position's transactions (creation, liquidity added and removed, fee claims)
+//! into a cash-flow ledger priced at each transaction. Deposits build a hold basket and
+//! an average cost basis, and withdrawals release both pro rata to the liquidity removed.
+//! Between transactions the bin model values the position, so IL, fees and PnL are
+//! measured against what was actually put in, with time-weighted and money-weighted
+//! returns across every deposit and withdrawal.
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use saros_dlmm_sdk::{Position as SdkPosition, PositionTransaction, PositionTransactionKind, PriceQuote, PriceSource};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+use std::path::Path;
+
+use crate::bin_model::{self, BinILBreakdown, BinLiquidity, PositionBins};
+use crate::types::{pubkey_string, ILError};
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0;
+
+/// Position transaction with token amounts in UI units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionEvent {
+    pub signature: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: PositionTransactionKind,
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+    /// Liquidity added or removed; zero for fee claims
+    #[serde(default, deserialize_with = "liquidity_from_number_or_string")]
+    pub liquidity: u128,
+    /// Pool active bin when the transaction executed
+    pub active_bin_id: i32,
+    /// USD price of token X at the time, filled in by `resolve_prices` when missing
+    #[serde(default)]
+    pub price_x: Option<Decimal>,
+    /// USD price of token Y at the time, filled in by `resolve_prices` when missing
+    #[serde(default)]
+    pub price_y: Option<Decimal>,
+}
+
+impl PositionEvent {
+    fn prices(&self) -> Result<(Decimal, Decimal), ILError> {
+        match (self.price_x, self.price_y) {
+            (Some(price_x), Some(price_y)) => Ok((price_x, price_y)),
+            _ => Err(ILError::PriceDataUnavailable(format!("no prices for transaction {}", self.signature))),
+        }
+    }
+}
+
+/// A position's transactions, oldest first. Exported as JSON and loaded back with
+/// [`PositionHistory::load_json`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionHistory {
+    #[serde(with = "pubkey_string")]
+    pub position_id: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub pool_address: Pubkey,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub bin_step: u16,
+    pub events: Vec<PositionEvent>,
+}
+
+impl PositionHistory {
+    /// Convert raw transactions from a history backend
+    pub fn from_transactions(
+        position: &SdkPosition,
+        bin_step: u16,
+        transactions: &[PositionTransaction],
+        token_decimals: (u8, u8),
+    ) -> Result<Self, ILError> {
+        let events = transactions
+            .iter()
+            .map(|tx| PositionEvent {
+                signature: tx.signature.clone(),
+                timestamp: tx.timestamp,
+                kind: tx.kind,
+                amount_x: Decimal::from(tx.amount_x) / Decimal::from(10u64.pow(token_decimals.0 as u32)),
+                amount_y: Decimal::from(tx.amount_y) / Decimal::from(10u64.pow(token_decimals.1 as u32)),
+                liquidity: tx.liquidity,
+                active_bin_id: tx.active_bin_id,
+                price_x: None,
+                price_y: None,
+            })
+            .collect();
+
+        Self::new(position.id, position.pool_address, position.lower_bin_id, position.upper_bin_id, bin_step, events)
+    }
+
+    /// Load a history exported as JSON, on its own or from a position history report,
+    /// where the transactions are the report's `events` table
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, ILError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ILError::InvalidPosition(format!("cannot read {}: {}", path.display(), e)))?;
+        let mut value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| ILError::InvalidPosition(format!("invalid JSON in {}: {}", path.display(), e)))?;
+        if let Some(mut history) = value.get_mut("history").map(serde_json::Value::take) {
+            if let (Some(fields), Some(events)) = (history.as_object_mut(), value.get_mut("events")) {
+                fields.entry("events").or_insert_with(|| events.take());
+            }
+            value = history;
+        }
+        let history: Self = serde_json::from_value(value)
+            .map_err(|e| ILError::InvalidPosition(format!("invalid position history {}: {}", path.display(), e)))?;
+
+        Self::new(
+            history.position_id,
+            history.pool_address,
+            history.lower_bin_id,
+            history.upper_bin_id,
+            history.bin_step,
+            history.events,
+        )
+    }
+
+    fn new(
+        position_id: Pubkey,
+        pool_address: Pubkey,
+        lower_bin_id: i32,
+        upper_bin_id: i32,
+        bin_step: u16,
+        mut events: Vec<PositionEvent>,
+    ) -> Result<Self, ILError> {
+        if lower_bin_id > upper_bin_id {
+            return Err(ILError::InvalidPosition(format!("Invalid bin range [{}, {}]", lower_bin_id, upper_bin_id)));
+        }
+        events.sort_by_key(|event| event.timestamp);
+
+        Ok(Self { position_id, pool_address, lower_bin_id, upper_bin_id, bin_step, events })
+    }
+
+    /// Fill in missing USD prices from `source`'s recorded history at each transaction.
+    /// Token X falls back to token Y's price through the active bin, and token Y falls
+    /// back to its current price when the source has no history.
+    pub async fn resolve_prices(
+        &mut self,
+        source: &dyn PriceSource,
+        token_x: Pubkey,
+        token_y: Pubkey,
+    ) -> Result<(), ILError> {
+        if self.events.iter().all(|event| event.price_x.is_some() && event.price_y.is_some()) {
+            return Ok(());
+        }
+
+        let history_x = source.get_history(&token_x, DateTime::<Utc>::MIN_UTC).await.unwrap_or_default();
+        let history_y = source.get_history(&token_y, DateTime::<Utc>::MIN_UTC).await.unwrap_or_default();
+        let mut current_y = None;
+
+        for event in &mut self.events {
+            if event.price_y.is_none() {
+                event.price_y = match price_at(&history_y, event.timestamp) {
+                    Some(price) => Some(price),
+                    None => {
+                        if current_y.is_none() {
+                            warn!("No recorded price for token Y {}, valuing its history at the current price", token_y);
+                            let quote = source.get_price(&token_y).await
+                                .map_err(|e| ILError::PriceDataUnavailable(format!("token Y {}: {}", token_y, e)))?;
+                            current_y = Some(quote.price);
+                        }
+                        current_y
+                    }
+                };
+            }
+            if event.price_x.is_none() {
+                event.price_x = price_at(&history_x, event.timestamp).or_else(|| {
+                    event.price_y.map(|price_y| price_y * bin_model::bin_price(event.active_bin_id, self.bin_step))
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replay every transaction
+    pub fn replay(&self) -> Result<PositionLedger, ILError> {
+        self.replay_until(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Replay the transactions executed at or before `until`
+    pub fn replay_until(&self, until: DateTime<Utc>) -> Result<PositionLedger, ILError> {
+        let mut ledger = PositionLedger::new(self);
+        for event in self.events.iter().take_while(|event| event.timestamp <= until) {
+            ledger.apply(event)?;
+        }
+        Ok(ledger)
+    }
+}
+
+/// Money moved between the owner and the position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashFlow {
+    pub timestamp: DateTime<Utc>,
+    pub kind: PositionTransactionKind,
+    pub signature: String,
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+    /// USD value from the owner's side: negative for deposits
+    pub value_usd: Decimal,
+}
+
+/// Position state after replaying its transactions
+#[derive(Debug, Clone)]
+pub struct PositionLedger {
+    position_id: Pubkey,
+    pool_address: Pubkey,
+    lower_bin_id: i32,
+    upper_bin_id: i32,
+    bin_step: u16,
+    /// Composition after the last transaction, at that transaction's active bin
+    position: Option<PositionBins>,
+    liquidity: u128,
+    hold_x: Decimal,
+    hold_y: Decimal,
+    cost_basis_usd: Decimal,
+    realized_pnl_usd: Decimal,
+    /// Deposit values weighted by the prices they were made at
+    weighted_entry: (Decimal, Decimal, Decimal),
+    cash_flows: Vec<CashFlow>,
+    /// Growth of $1 over the periods between transactions so far
+    twr_growth: Decimal,
+    value_after_last_flow: Decimal,
+}
+
+impl PositionLedger {
+    fn new(history: &PositionHistory) -> Self {
+        Self {
+            position_id: history.position_id,
+            pool_address: history.pool_address,
+            lower_bin_id: history.lower_bin_id,
+            upper_bin_id: history.upper_bin_id,
+            bin_step: history.bin_step,
+            position: None,
+            liquidity: 0,
+            hold_x: Decimal::ZERO,
+            hold_y: Decimal::ZERO,
+            cost_basis_usd: Decimal::ZERO,
+            realized_pnl_usd: Decimal::ZERO,
+            weighted_entry: (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO),
+            cash_flows: Vec::new(),
+            twr_growth: Decimal::ONE,
+            value_after_last_flow: Decimal::ZERO,
+        }
+    }
+
+    /// Liquidity still in the position
+    pub fn liquidity(&self) -> u128 {
+        self.liquidity
+    }
+
+    fn apply(&mut self, event: &PositionEvent) -> Result<(), ILError> {
+        let (price_x, price_y) = event.prices()?;
+        let composition = self.composition_at(event.active_bin_id, price_x, price_y);
+        let value_before: Decimal = composition.iter().map(|bin| bin.amount_x * price_x + bin.amount_y * price_y).sum();
+        let event_value = event.amount_x * price_x + event.amount_y * price_y;
+
+        let (bins, flow_value, income) = match event.kind {
+            PositionTransactionKind::Create | PositionTransactionKind::AddLiquidity => {
+                if event.liquidity == 0 {
+                    return Err(ILError::InvalidPosition(format!("deposit {} adds no liquidity", event.signature)));
+                }
+                let deposit = PositionBins::uniform(
+                    self.lower_bin_id,
+                    self.upper_bin_id,
+                    event.active_bin_id,
+                    self.bin_step,
+                    event.amount_x,
+                    event.amount_y,
+                )?;
+                let bins = if composition.is_empty() {
+                    deposit.bins
+                } else {
+                    composition
+                        .iter()
+                        .zip(&deposit.bins)
+                        .map(|(held, added)| BinLiquidity {
+                            bin_id: held.bin_id,
+                            amount_x: held.amount_x + added.amount_x,
+                            amount_y: held.amount_y + added.amount_y,
+                        })
+                        .collect()
+                };
+
+                self.liquidity += event.liquidity;
+                self.hold_x += event.amount_x;
+                self.hold_y += event.amount_y;
+                self.cost_basis_usd += event_value;
+                self.weighted_entry.0 += event_value * price_x;
+                self.weighted_entry.1 += event_value * price_y;
+                self.weighted_entry.2 += event_value;
+                (bins, -event_value, Decimal::ZERO)
+            }
+            PositionTransactionKind::RemoveLiquidity => {
+                if event.liquidity == 0 || event.liquidity > self.liquidity {
+                    return Err(ILError::InvalidPosition(format!(
+                        "withdrawal {} removes {} of {} liquidity",
+                        event.signature, event.liquidity, self.liquidity
+                    )));
+                }
+                let share = liquidity_share(event.liquidity, self.liquidity)?;
+                let kept = Decimal::ONE - share;
+                let bins = composition
+                    .iter()
+                    .map(|bin| BinLiquidity {
+                        bin_id: bin.bin_id,
+                        amount_x: bin.amount_x * kept,
+                        amount_y: bin.amount_y * kept,
+                    })
+                    .collect();
+
+                let released_cost = self.cost_basis_usd * share;
+                self.liquidity -= event.liquidity;
+                self.hold_x *= kept;
+                self.hold_y *= kept;
+                self.cost_basis_usd -= released_cost;
+                self.realized_pnl_usd += event_value - released_cost;
+                (bins, event_value, Decimal::ZERO)
+            }
+            PositionTransactionKind::ClaimFees => (composition, event_value, event_value),
+        };
+
+        // Close the period since the last transaction; claimed fees are income within it
+        if self.value_after_last_flow > Decimal::ZERO {
+            self.twr_growth *= (value_before + income) / self.value_after_last_flow;
+        }
+
+        self.position = Some(PositionBins::new(self.bin_step, event.active_bin_id, bins)?);
+        self.value_after_last_flow = self.composition_at(event.active_bin_id, price_x, price_y)
+            .iter()
+            .map(|bin| bin.amount_x * price_x + bin.amount_y * price_y)
+            .sum();
+        self.cash_flows.push(CashFlow {
+            timestamp: event.timestamp,
+            kind: event.kind,
+            signature: event.signature.clone(),
+            amount_x: event.amount_x,
+            amount_y: event.amount_y,
+            value_usd: flow_value,
+        });
+
+        Ok(())
+    }
+
+    /// Token amounts per bin once the active bin moves to `active_bin_id`
+    fn composition_at(&self, active_bin_id: i32, price_x: Decimal, price_y: Decimal) -> Vec<BinLiquidity> {
+        match &self.position {
+            Some(position) => self.reprice(position, active_bin_id, None, price_x, price_y)
+                .bins
+                .into_iter()
+                .map(|bin| BinLiquidity { bin_id: bin.bin_id, amount_x: bin.amount_x, amount_y: bin.amount_y })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Re-price the position, splitting the active bin by `active_bin_y_share` or, when
+    /// that isn't known, by where the price sits inside the bin
+    fn reprice(
+        &self,
+        position: &PositionBins,
+        active_bin_id: i32,
+        active_bin_y_share: Option<Decimal>,
+        price_x: Decimal,
+        price_y: Decimal,
+    ) -> BinILBreakdown {
+        let y_share = active_bin_y_share.unwrap_or_else(|| {
+            let price_ratio = if price_y > Decimal::ZERO { price_x / price_y } else { Decimal::ONE };
+            bin_model::price_y_share(price_ratio, active_bin_id, self.bin_step)
+        });
+        position.reprice(active_bin_id, y_share, price_x, price_y)
+    }
+
+    /// Performance as of `as_of` at the given active bin and USD prices. Per-bin IL in the
+    /// breakdown is measured from the last transaction; the totals are measured against
+    /// everything deposited.
+    pub fn performance(
+        &self,
+        as_of: DateTime<Utc>,
+        active_bin_id: i32,
+        active_bin_y_share: Option<Decimal>,
+        current_prices: (Decimal, Decimal),
+        unclaimed_fees_usd: Decimal,
+    ) -> PositionPerformance {
+        let (price_x, price_y) = current_prices;
+        let breakdown = self.position
+            .as_ref()
+            .map(|position| self.reprice(position, active_bin_id, active_bin_y_share, price_x, price_y));
+        let current_value_usd = breakdown.as_ref().map_or(Decimal::ZERO, |breakdown| breakdown.value_usd);
+        let hold_value_usd = self.hold_x * price_x + self.hold_y * price_y;
+        let il_usd_value = current_value_usd - hold_value_usd;
+
+        let total = |kind: PositionTransactionKind| -> Decimal {
+            self.cash_flows.iter().filter(|flow| flow.kind == kind).map(|flow| flow.value_usd).sum()
+        };
+        let deposited_usd = -(total(PositionTransactionKind::Create) + total(PositionTransactionKind::AddLiquidity));
+        let withdrawn_usd = total(PositionTransactionKind::RemoveLiquidity);
+        let fees_claimed_usd = total(PositionTransactionKind::ClaimFees);
+        let terminal_value = current_value_usd + unclaimed_fees_usd;
+
+        let mut twr_growth = self.twr_growth;
+        if self.value_after_last_flow > Decimal::ZERO {
+            twr_growth *= terminal_value / self.value_after_last_flow;
+        }
+
+        let mut flows: Vec<(DateTime<Utc>, f64)> = self.cash_flows
+            .iter()
+            .map(|flow| (flow.timestamp, flow.value_usd.to_f64().unwrap_or(0.0)))
+            .collect();
+        flows.push((as_of, terminal_value.to_f64().unwrap_or(0.0)));
+
+        let (weighted_x, weighted_y, weight) = self.weighted_entry;
+        let average_entry = |weighted: Decimal| if weight > Decimal::ZERO { weighted / weight } else { Decimal::ZERO };
+
+        PositionPerformance {
+            position_id: self.position_id,
+            pool_address: self.pool_address,
+            as_of,
+            lower_bin_id: self.lower_bin_id,
+            upper_bin_id: self.upper_bin_id,
+            active_bin_id,
+            liquidity: self.liquidity,
+            price_x,
+            price_y,
+            average_entry_price_x: average_entry(weighted_x),
+            average_entry_price_y: average_entry(weighted_y),
+            hold_amount_x: self.hold_x,
+            hold_amount_y: self.hold_y,
+            deposited_usd,
+            withdrawn_usd,
+            fees_claimed_usd,
+            unclaimed_fees_usd,
+            cost_basis_usd: self.cost_basis_usd,
+            realized_pnl_usd: self.realized_pnl_usd,
+            unrealized_pnl_usd: current_value_usd - self.cost_basis_usd,
+            current_value_usd,
+            hold_value_usd,
+            il_usd_value,
+            il_percentage: if hold_value_usd > Decimal::ZERO { il_usd_value / hold_value_usd } else { Decimal::ZERO },
+            net_pnl_usd: terminal_value + withdrawn_usd + fees_claimed_usd - deposited_usd,
+            time_weighted_return: twr_growth - Decimal::ONE,
+            money_weighted_return: money_weighted_return(&flows).and_then(Decimal::from_f64),
+            cash_flows: self.cash_flows.clone(),
+            bin_breakdown: breakdown,
+        }
+    }
+}
+
+/// Position IL, fees and returns measured against its actual deposits and withdrawals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionPerformance {
+    #[serde(with = "pubkey_string")]
+    pub position_id: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub pool_address: Pubkey,
+    pub as_of: DateTime<Utc>,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub active_bin_id: i32,
+    pub liquidity: u128,
+    pub price_x: Decimal,
+    pub price_y: Decimal,
+    /// Deposit-value-weighted price of token X across deposits
+    pub average_entry_price_x: Decimal,
+    /// Deposit-value-weighted price of token Y across deposits
+    pub average_entry_price_y: Decimal,
+    /// Tokens that would be held had the deposits not been made, net of withdrawals
+    pub hold_amount_x: Decimal,
+    pub hold_amount_y: Decimal,
+    pub deposited_usd: Decimal,
+    pub withdrawn_usd: Decimal,
+    pub fees_claimed_usd: Decimal,
+    pub unclaimed_fees_usd: Decimal,
+    /// Average cost of the liquidity still in the position
+    pub cost_basis_usd: Decimal,
+    /// Withdrawal proceeds less the cost basis they released
+    pub realized_pnl_usd: Decimal,
+    pub unrealized_pnl_usd: Decimal,
+    /// Position value excluding unclaimed fees
+    pub current_value_usd: Decimal,
+    pub hold_value_usd: Decimal,
+    pub il_usd_value: Decimal,
+    pub il_percentage: Decimal,
+    /// Current value, unclaimed fees, withdrawals and claimed fees less deposits
+    pub net_pnl_usd: Decimal,
+    /// Compounded return between transactions, independent of deposit timing and size
+    pub time_weighted_return: Decimal,
+    /// Annualised internal rate of return of the cash flows
+    pub money_weighted_return: Option<Decimal>,
+    pub cash_flows: Vec<CashFlow>,
+    pub bin_breakdown: Option<BinILBreakdown>,
+}
+
+/// Liquidity as a JSON integer, or as the decimal string position history reports write
+fn liquidity_from_number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+    struct LiquidityVisitor;
+
+    impl de::Visitor<'_> for LiquidityVisitor {
+        type Value = u128;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an unsigned integer or a decimal string")
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<u128, E> {
+            Ok(value.into())
+        }
+
+        fn visit_u128<E: de::Error>(self, value: u128) -> Result<u128, E> {
+            Ok(value)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<u128, E> {
+            value.parse().map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(LiquidityVisitor)
+}
+
+/// Last recorded price at or before `timestamp`
+fn price_at(history: &[PriceQuote], timestamp: DateTime<Utc>) -> Option<Decimal> {
+    history.iter().take_while(|quote| quote.timestamp <= timestamp).last().map(|quote| quote.price)
+}
+
+fn liquidity_share(removed: u128, total: u128) -> Result<Decimal, ILError> {
+    match (Decimal::from_u128(removed), Decimal::from_u128(total)) {
+        (Some(removed), Some(total)) if total > Decimal::ZERO => Ok(removed / total),
+        _ => Err(ILError::CalculationError(format!("cannot represent liquidity {} of {}", removed, total))),
+    }
+}
+
+/// Annualised rate at which the cash flows' net present value is zero, found by
+/// bisection. `None` when the flows never change sign or span no time.
+fn money_weighted_return(flows: &[(DateTime<Utc>, f64)]) -> Option<f64> {
+    let start = flows.first()?.0;
+    let npv = |rate: f64| -> f64 {
+        flows
+            .iter()
+            .map(|(timestamp, value)| {
+                let years = (*timestamp - start).num_seconds() as f64 / SECONDS_PER_YEAR;
+                value / (1.0 + rate).powf(years)
+            })
+            .sum()
+    };
+
+    let mut low = -0.9999;
+    let mut high = 1.0;
+    let low_sign = npv(low).signum();
+    while npv(high).signum() == low_sign {
+        high = high * 2.0 + 1.0;
+        if high > 1e9 {
+            return None;
+        }
+    }
+    if !npv(low).is_finite() || npv(low) == 0.0 {
+        return None;
+    }
+
+    for _ in 0..200 {
+        let middle = (low + high) / 2.0;
+        if npv(middle).signum() == low_sign {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn at(days: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc) + Duration::days(days)
+    }
+
+    fn event(days: i64, kind: PositionTransactionKind, amounts: (Decimal, Decimal), liquidity: u128, prices: (Decimal, Decimal)) -> PositionEvent {
+        PositionEvent {
+            signature: format!("tx{}", days),
+            timestamp: at(days),
+            kind,
+            amount_x: amounts.0,
+            amount_y: amounts.1,
+            liquidity,
+            active_bin_id: 0,
+            price_x: Some(prices.0),
+            price_y: Some(prices.1),
+        }
+    }
+
+    /// A range below the active bin holds only token Y, so its value follows Y's price
+    fn y_only_history(events: Vec<PositionEvent>) -> PositionHistory {
+        PositionHistory::new(Pubkey::new_unique(), Pubkey::new_unique(), -10, -1, 20, events).unwrap()
+    }
+
+    fn assert_close(actual: Decimal, expected: Decimal) {
+        assert!((actual - expected).abs() < dec!(0.000001), "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_withdrawal_releases_basis_pro_rata() {
+        use PositionTransactionKind::*;
+        let history = y_only_history(vec![
+            event(0, Create, (dec!(0), dec!(100)), 100, (dec!(1), dec!(1))),
+            event(10, AddLiquidity, (dec!(0), dec!(100)), 100, (dec!(1), dec!(2))),
+            event(20, RemoveLiquidity, (dec!(0), dec!(50)), 50, (dec!(1), dec!(3))),
+            event(25, ClaimFees, (dec!(0), dec!(5)), 0, (dec!(1), dec!(3))),
+        ]);
+
+        let ledger = history.replay().unwrap();
+        assert_eq!(ledger.liquidity(), 150);
+        let performance = ledger.performance(at(30), 0, None, (dec!(1), dec!(3)), dec!(0));
+
+        assert_eq!(performance.deposited_usd, dec!(300));
+        assert_eq!(performance.withdrawn_usd, dec!(150));
+        assert_eq!(performance.fees_claimed_usd, dec!(15));
+        // A quarter of the $300 basis is released by the withdrawal
+        assert_close(performance.cost_basis_usd, dec!(225));
+        assert_close(performance.realized_pnl_usd, dec!(75));
+        assert_close(performance.hold_amount_y, dec!(150));
+        assert_close(performance.current_value_usd, dec!(450));
+        assert_close(performance.il_usd_value, dec!(0));
+        assert_close(performance.net_pnl_usd, dec!(315));
+        assert_close(performance.average_entry_price_y, dec!(5) / dec!(3));
+    }
+
+    #[test]
+    fn test_time_and_money_weighted_returns_diverge_with_deposit_timing() {
+        use PositionTransactionKind::*;
+        // Y doubles on a small stake, then halves after a large top-up
+        let history = y_only_history(vec![
+            event(0, Create, (dec!(0), dec!(100)), 100, (dec!(1), dec!(1))),
+            event(100, AddLiquidity, (dec!(0), dec!(900)), 900, (dec!(1), dec!(2))),
+        ]);
+
+        let performance = history.replay().unwrap().performance(at(200), 0, None, (dec!(1), dec!(1)), dec!(0));
+
+        assert_close(performance.time_weighted_return, dec!(0));
+        assert!(performance.money_weighted_return.unwrap() < Decimal::ZERO);
+        assert_close(performance.net_pnl_usd, dec!(1000) - dec!(1900));
+    }
+
+    #[test]
+    fn test_replay_until_skips_later_transactions() {
+        use PositionTransactionKind::*;
+        let history = y_only_history(vec![
+            event(0, Create, (dec!(0), dec!(100)), 100, (dec!(1), dec!(1))),
+            event(10, RemoveLiquidity, (dec!(0), dec!(100)), 100, (dec!(1), dec!(1))),
+        ]);
+
+        assert_eq!(history.replay_until(at(5)).unwrap().liquidity(), 100);
+        assert_eq!(history.replay().unwrap().liquidity(), 0);
+
+        let overdrawn = y_only_history(vec![
+            event(0, Create, (dec!(0), dec!(100)), 100, (dec!(1), dec!(1))),
+            event(10, RemoveLiquidity, (dec!(0), dec!(100)), 101, (dec!(1), dec!(1))),
+        ]);
+        assert!(matches!(overdrawn.replay(), Err(ILError::InvalidPosition(_))));
+    }
+
+    #[test]
+    fn test_money_weighted_return_of_simple_gain() {
+        let flows = [(at(0), -100.0), (at(0) + Duration::seconds(SECONDS_PER_YEAR as i64), 110.0)];
+        assert!((money_weighted_return(&flows).unwrap() - 0.1).abs() < 1e-9);
+        assert_eq!(money_weighted_return(&[(at(0), -100.0)]), None);
+    }
+
+    #[tokio::test]
+    async fn test_exported_history_round_trips_and_resolves_prices() {
+        let (token_x, token_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut created = event(0, PositionTransactionKind::Create, (dec!(1), dec!(100)), 100, (dec!(0), dec!(0)));
+        created.price_x = None;
+        created.price_y = None;
+        created.active_bin_id = 5;
+        let history = PositionHistory::new(Pubkey::new_unique(), Pubkey::new_unique(), -10, 10, 20, vec![created]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        std::fs::write(&path, serde_json::to_string(&history).unwrap()).unwrap();
+        let mut loaded = PositionHistory::load_json(&path).unwrap();
+        assert_eq!(loaded.position_id, history.position_id);
+        assert!(matches!(loaded.replay(), Err(ILError::PriceDataUnavailable(_))));
+
+        let replay = saros_dlmm_sdk::ReplayPriceSource::from_quotes([PriceQuote {
+            mint: token_y,
+            price: dec!(2),
+            timestamp: at(-1),
+            source: "replay".to_string(),
+        }]);
+        loaded.resolve_prices(&replay, token_x, token_y).await.unwrap();
+
+        let event = &loaded.events[0];
+        assert_eq!(event.price_y, Some(dec!(2)));
+        assert_eq!(event.price_x, Some(dec!(2) * bin_model::bin_price(5, 20)));
+        assert_eq!(loaded.replay().unwrap().liquidity(), 100);
+
+        // A position history report loads back with its priced transactions
+        let performance = loaded.replay().unwrap().performance(at(1), 5, None, (dec!(3), dec!(2)), dec!(0));
+        let config = crate::types::ReportConfig {
+            title: "History".to_string(),
+            include_charts: false,
+            include_raw_data: true,
+            timestamp: at(1),
+        };
+        let report = crate::report_model::position_history_report(&loaded, &performance, &config).unwrap();
+        let report_path = dir.path().join("position_history.json");
+        std::fs::write(&report_path, serde_json::to_string(&report.to_json()).unwrap()).unwrap();
+        let reloaded = PositionHistory::load_json(&report_path).unwrap();
+        assert_eq!(reloaded.pool_address, history.pool_address);
+        assert_eq!(reloaded.events[0].kind, PositionTransactionKind::Create);
+        assert_eq!(reloaded.events[0].liquidity, 100);
+        assert_eq!(reloaded.events[0].price_y, Some(dec!(2)));
+        assert_eq!(reloaded.replay().unwrap().liquidity(), 100);
+    }
+}
@@ -1,6 +1,7 @@
 //! Price tracking and volatility monitoring for DLMM pools
 
 use anyhow::Result;
+use async_trait::async_trait;
 use chrono::{DateTime, Utc, Duration as ChronoDuration};
 use indexmap::IndexMap;
 use log::{debug, info, warn};
@@ -10,10 +11,14 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 
-use saros_dlmm_sdk::DLMMClient as MockSarosClient;
-use crate::types::{PriceDataPoint, ILNotificationEvent, ILEventType, NotificationSeverity};
+use saros_dlmm_sdk::{
+    DLMMClient as MockSarosClient, DLMMError, DLMMResult, FallbackPriceSource, PriceQuote, PriceSource,
+    SharedPriceSource,
+};
+use crate::types::PriceDataPoint;
 
 /// External price API response structure
 #[derive(Debug, Deserialize)]
@@ -53,23 +58,126 @@ struct TokenMetadata {
     pub price_feeds: Vec<String>, // Pyth, Chainlink, etc.
 }
 
+/// Seconds in a year, for annualising volatility
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0;
+
+/// Volatility cache key: token, quote token for pair volatility, window in hours
+type VolatilityKey = (Pubkey, Option<Pubkey>, u32);
+
+/// Current USD prices from the CoinGecko simple price API
+pub struct CoinGeckoPriceSource {
+    http_client: Client,
+    coingecko_ids: BTreeMap<Pubkey, String>,
+}
+
+impl CoinGeckoPriceSource {
+    pub fn new() -> Self {
+        Self {
+            http_client: Client::new(),
+            coingecko_ids: known_tokens()
+                .into_iter()
+                .filter_map(|token| token.coingecko_id.map(|id| (token.mint, id)))
+                .collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoPriceSource {
+    fn name(&self) -> &str {
+        "coingecko"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        let coingecko_id = self.coingecko_ids
+            .get(mint)
+            .ok_or_else(|| DLMMError::PriceUnavailable(format!("no CoinGecko id for {}", mint)))?;
+
+        let url = format!(
+            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies=usd",
+            coingecko_id
+        );
+
+        let data: serde_json::Value = self.http_client
+            .get(&url)
+            .timeout(Duration::from_secs(10))
+            .send()
+            .await
+            .map_err(|e| DLMMError::PriceFeed(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| DLMMError::PriceFeed(e.to_string()))?;
+
+        let price = data[coingecko_id.as_str()]["usd"]
+            .as_f64()
+            .and_then(Decimal::from_f64)
+            .ok_or_else(|| DLMMError::PriceFeed(format!("price for {} not found in response", coingecko_id)))?;
+
+        Ok(PriceQuote { mint: *mint, price, timestamp: Utc::now(), source: self.name().to_string() })
+    }
+}
+
+/// Deterministic made-up prices derived from the mint address, for unknown tokens
+pub struct MockPriceSource;
+
+impl MockPriceSource {
+    /// Price between $0.01 and $1000 that is the same on every call for a mint
+    pub fn price(token_mint: &Pubkey) -> Decimal {
+        let mint_bytes = token_mint.to_bytes();
+        let seed = u64::from_le_bytes([
+            mint_bytes[0], mint_bytes[1], mint_bytes[2], mint_bytes[3],
+            mint_bytes[4], mint_bytes[5], mint_bytes[6], mint_bytes[7],
+        ]);
+        
+        let base_price = (seed % 100000) as f64 / 100.0; // $0.00 to $999.99
+        let price = base_price.clamp(0.01, 1000.0);
+        
+        Decimal::from_f64(price).unwrap_or(Decimal::new(100, 0))
+    }
+}
+
+#[async_trait]
+impl PriceSource for MockPriceSource {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        warn!("No live price for {}, using mock price", mint);
+        Ok(PriceQuote { mint: *mint, price: Self::price(mint), timestamp: Utc::now(), source: self.name().to_string() })
+    }
+}
+
+/// CoinGecko for known tokens, mock prices for everything else
+pub fn default_price_source() -> SharedPriceSource {
+    Arc::new(FallbackPriceSource::new(vec![
+        Arc::new(CoinGeckoPriceSource::new()),
+        Arc::new(MockPriceSource),
+    ]))
+}
+
 /// Price monitor for tracking token prices and volatility
 pub struct PriceMonitor {
     client: MockSarosClient,
     http_client: Client,
+    price_source: SharedPriceSource,
     price_cache: BTreeMap<Pubkey, (Decimal, DateTime<Utc>)>,
     historical_cache: BTreeMap<(Pubkey, u32), Vec<PriceDataPoint>>, // (token, days) -> data
     token_metadata: BTreeMap<Pubkey, TokenMetadata>,
     price_history: BTreeMap<Pubkey, VecDeque<PriceDataPoint>>,
-    volatility_cache: BTreeMap<Pubkey, (Decimal, DateTime<Utc>)>,
-    notification_thresholds: BTreeMap<Pubkey, Decimal>, // Price change thresholds
+    volatility_cache: BTreeMap<VolatilityKey, (Decimal, DateTime<Utc>)>,
     cache_ttl_secs: u64,
     max_history_points: usize,
 }
 
 impl PriceMonitor {
-    /// Create a new price monitor
+    /// Create a new price monitor using CoinGecko with mock prices as fallback
     pub async fn new() -> Result<Self> {
+        Self::with_price_source(default_price_source()).await
+    }
+
+    /// Create a price monitor reading prices and history from `price_source`
+    pub async fn with_price_source(price_source: SharedPriceSource) -> Result<Self> {
         let rpc_url = std::env::var("RPC_URL")
             .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
         
@@ -79,12 +187,12 @@ impl PriceMonitor {
         let mut monitor = Self {
             client,
             http_client,
+            price_source,
             price_cache: BTreeMap::new(),
             historical_cache: BTreeMap::new(),
             token_metadata: BTreeMap::new(),
             price_history: BTreeMap::new(),
             volatility_cache: BTreeMap::new(),
-            notification_thresholds: BTreeMap::new(),
             cache_ttl_secs: 30, // 30 seconds cache
             max_history_points: 10000,
         };
@@ -119,27 +227,9 @@ impl PriceMonitor {
             }
         }
 
-        info!("Fetching current price for token {}", token_mint);
-        
-        let price = if let Some(metadata) = self.token_metadata.get(&token_mint) {
-            // Try to get price from external API
-            if let Some(coingecko_id) = &metadata.coingecko_id {
-                match self.fetch_coingecko_price(coingecko_id).await {
-                    Ok(price) => price,
-                    Err(e) => {
-                        warn!("Failed to fetch CoinGecko price for {}: {}", coingecko_id, e);
-                        self.get_mock_price(&token_mint).await?
-                    }
-                }
-            } else {
-                // Try price feeds or use mock data
-                self.get_mock_price(&token_mint).await?
-            }
-        } else {
-            // Unknown token, use mock price
-            warn!("Unknown token {}, using mock price", token_mint);
-            self.get_mock_price(&token_mint).await?
-        };
+        info!("Fetching current price for token {} from {}", token_mint, self.price_source.name());
+        
+        let price = self.price_source.get_price(&token_mint).await?.price;
 
         // Cache the result
         self.price_cache.insert(token_mint, (price, Utc::now()));
@@ -172,7 +262,10 @@ impl PriceMonitor {
 
         info!("Fetching historical data for {} days", days);
         
-        let historical_data = if let (Some(metadata_x), Some(metadata_y)) = 
+        let recorded = self.recorded_pair_history(token_x, token_y, ChronoDuration::days(days as i64)).await;
+        let historical_data = if !recorded.is_empty() {
+            recorded
+        } else if let (Some(metadata_x), Some(metadata_y)) = 
             (self.token_metadata.get(&token_x), self.token_metadata.get(&token_y)) {
             
             // Try to get real historical data
@@ -195,134 +288,121 @@ impl PriceMonitor {
         Ok(historical_data)
     }
 
-    /// Calculate price volatility over a specified window
+    /// Calculate annualised volatility of a token's USD price over a specified window
     pub async fn calculate_volatility(
         &mut self,
         token_mint: Pubkey,
         window_hours: u32,
     ) -> Result<Decimal> {
-        let cache_key = token_mint;
-        
-        // Check cache
-        if let Some((cached_volatility, cached_time)) = self.volatility_cache.get(&cache_key) {
-            if Utc::now().signed_duration_since(*cached_time).num_minutes() < 10 {
-                return Ok(*cached_volatility);
-            }
+        let cache_key = (token_mint, None, window_hours);
+        if let Some(volatility) = self.cached_volatility(&cache_key) {
+            return Ok(volatility);
         }
 
-        // Get price history for the token
-        if let Some(history) = self.price_history.get(&token_mint) {
-            let cutoff_time = Utc::now() - ChronoDuration::hours(window_hours as i64);
-            
-            let recent_prices: Vec<f64> = history.iter()
-                .filter(|point| point.timestamp > cutoff_time)
-                .map(|point| point.price_x.to_f64().unwrap_or(0.0))
-                .collect();
-            
-            if recent_prices.len() < 2 {
-                return Ok(Decimal::ZERO);
-            }
-            
-            // Calculate returns
-            let returns: Vec<f64> = recent_prices.windows(2)
-                .map(|window| (window[1] / window[0] - 1.0).ln())
-                .collect();
-            
-            if returns.is_empty() {
-                return Ok(Decimal::ZERO);
-            }
-            
-            // Calculate standard deviation of returns
-            let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
-            let variance = returns.iter()
-                .map(|&r| (r - mean_return).powi(2))
-                .sum::<f64>() / returns.len() as f64;
-            
-            let volatility = variance.sqrt() * (365.25 * 24.0 / window_hours as f64).sqrt(); // Annualized
-            let volatility_decimal = Decimal::from_f64(volatility).unwrap_or_default();
-            
-            // Cache result
-            self.volatility_cache.insert(cache_key, (volatility_decimal, Utc::now()));
-            
-            Ok(volatility_decimal)
-        } else {
-            Ok(Decimal::ZERO)
+        let prices = self.token_history(token_mint, ChronoDuration::hours(window_hours as i64)).await;
+        let volatility = annualized_volatility(&prices);
+        
+        self.volatility_cache.insert(cache_key, (volatility, Utc::now()));
+        Ok(volatility)
+    }
+
+    /// Calculate annualised volatility of the X/Y price ratio, which is what moves a
+    /// pool's active bin and drives impermanent loss
+    pub async fn calculate_pair_volatility(
+        &mut self,
+        token_x: Pubkey,
+        token_y: Pubkey,
+        window_hours: u32,
+    ) -> Result<Decimal> {
+        let cache_key = (token_x, Some(token_y), window_hours);
+        if let Some(volatility) = self.cached_volatility(&cache_key) {
+            return Ok(volatility);
         }
+
+        let window = ChronoDuration::hours(window_hours as i64);
+        let history_x = self.token_history(token_x, window).await;
+        let history_y = self.token_history(token_y, window).await;
+        let ratios: Vec<(DateTime<Utc>, f64)> = align_prices(&history_x, &history_y)
+            .into_iter()
+            .map(|(timestamp, price_x, price_y)| (timestamp, price_x / price_y))
+            .collect();
+        let volatility = annualized_volatility(&ratios);
+        
+        self.volatility_cache.insert(cache_key, (volatility, Utc::now()));
+        Ok(volatility)
     }
 
-    /// Set price change notification threshold for a token
-    pub fn set_notification_threshold(&mut self, token_mint: Pubkey, threshold_percentage: Decimal) {
-        self.notification_thresholds.insert(token_mint, threshold_percentage);
-        info!("Set notification threshold for {}: {:.2}%", 
-              token_mint, threshold_percentage * Decimal::new(100, 0));
+    fn cached_volatility(&self, key: &VolatilityKey) -> Option<Decimal> {
+        self.volatility_cache
+            .get(key)
+            .filter(|(_, cached_time)| Utc::now().signed_duration_since(*cached_time).num_minutes() < 10)
+            .map(|(volatility, _)| *volatility)
     }
 
-    /// Check for price alerts and generate notifications
-    pub async fn check_price_alerts(
-        &self,
-        token_mint: Pubkey,
-        current_price: Decimal,
-        previous_price: Decimal,
-    ) -> Result<Option<ILNotificationEvent>> {
-        if let Some(&threshold) = self.notification_thresholds.get(&token_mint) {
-            let price_change = (current_price - previous_price) / previous_price;
-            
-            if price_change.abs() > threshold {
-                let event_type = if price_change.abs() > threshold * Decimal::new(2, 0) {
-                    ILEventType::PriceVoLatilitySpike
-                } else {
-                    ILEventType::HighImpermanentLoss
-                };
-                
-                let severity = if price_change.abs() > threshold * Decimal::new(3, 0) {
-                    NotificationSeverity::Emergency
-                } else if price_change.abs() > threshold * Decimal::new(15, 1) {
-                    NotificationSeverity::Critical
-                } else {
-                    NotificationSeverity::Warning
-                };
-                
-                return Ok(Some(ILNotificationEvent {
-                    event_type,
-                    position_id: None,
-                    pool_address: Default::default(),
-                    il_percentage: price_change,
-                    threshold_crossed: Some(threshold),
-                    message: format!(
-                        "Price alert: {} changed by {:.2}% (threshold: {:.2}%)",
-                        token_mint,
-                        price_change * Decimal::new(100, 0),
-                        threshold * Decimal::new(100, 0)
-                    ),
-                    timestamp: Utc::now(),
-                    severity,
-                }));
-            }
+    /// Positive USD prices of a token within `window` of its latest price, oldest first.
+    /// History recorded by the price source is preferred over prices this monitor has
+    /// fetched itself.
+    async fn token_history(&self, token_mint: Pubkey, window: ChronoDuration) -> Vec<(DateTime<Utc>, f64)> {
+        let recorded = self.price_source
+            .get_history(&token_mint, DateTime::<Utc>::MIN_UTC)
+            .await
+            .unwrap_or_default();
+
+        let mut prices: Vec<(DateTime<Utc>, f64)> = if recorded.is_empty() {
+            self.price_history
+                .get(&token_mint)
+                .into_iter()
+                .flatten()
+                .filter_map(|point| Some((point.timestamp, point.price_x.to_f64()?)))
+                .collect()
+        } else {
+            recorded
+                .iter()
+                .filter_map(|quote| Some((quote.timestamp, quote.price.to_f64()?)))
+                .collect()
+        };
+        prices.retain(|(_, price)| *price > 0.0);
+
+        if let Some(&(latest, _)) = prices.last() {
+            prices.retain(|(timestamp, _)| *timestamp > latest - window);
         }
-        
-        Ok(None)
+        prices
     }
 
-    /// Fetch price from CoinGecko API
-    async fn fetch_coingecko_price(&self, coingecko_id: &str) -> Result<Decimal> {
-        let url = format!(
-            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies=usd",
-            coingecko_id
-        );
-        
-        let response = self.http_client
-            .get(&url)
-            .timeout(Duration::from_secs(10))
-            .send()
-            .await?;
-        
-        let data: serde_json::Value = response.json().await?;
-        
-        if let Some(price) = data[coingecko_id]["usd"].as_f64() {
-            Ok(Decimal::from_f64(price).unwrap_or_default())
-        } else {
-            Err(anyhow::anyhow!("Price not found in response"))
+    /// Pair history replayed from the price source, empty unless it records both tokens
+    async fn recorded_pair_history(
+        &self,
+        token_x: Pubkey,
+        token_y: Pubkey,
+        window: ChronoDuration,
+    ) -> Vec<PriceDataPoint> {
+        let history = |token: Pubkey| async move {
+            self.price_source
+                .get_history(&token, DateTime::<Utc>::MIN_UTC)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|quote| (quote.timestamp, quote.price))
+                .collect::<Vec<_>>()
+        };
+        let (history_x, history_y) = (history(token_x).await, history(token_y).await);
+
+        let mut points: Vec<PriceDataPoint> = align_prices(&history_x, &history_y)
+            .into_iter()
+            .map(|(timestamp, price_x, price_y)| PriceDataPoint {
+                timestamp,
+                price_x,
+                price_y,
+                volume_24h: Decimal::ZERO,
+                liquidity: Decimal::ZERO,
+                active_bin_id: 0,
+            })
+            .collect();
+
+        if let Some(latest) = points.last().map(|point| point.timestamp) {
+            points.retain(|point| point.timestamp > latest - window);
         }
+        points
     }
 
     /// Fetch historical prices from external API
@@ -432,22 +512,6 @@ impl PriceMonitor {
         Ok(data)
     }
 
-    /// Get mock price for unknown tokens
-    async fn get_mock_price(&self, token_mint: &Pubkey) -> Result<Decimal> {
-        // Generate deterministic "random" price based on mint address
-        let mint_bytes = token_mint.to_bytes();
-        let seed = u64::from_le_bytes([
-            mint_bytes[0], mint_bytes[1], mint_bytes[2], mint_bytes[3],
-            mint_bytes[4], mint_bytes[5], mint_bytes[6], mint_bytes[7],
-        ]);
-        
-        // Use seed to generate consistent price between $0.01 and $1000
-        let base_price = (seed % 100000) as f64 / 100.0; // $0.00 to $999.99
-        let price = base_price.max(0.01).min(1000.0); // Clamp to reasonable range
-        
-        Ok(Decimal::from_f64(price).unwrap_or(Decimal::new(100, 0)))
-    }
-
     /// Add price to historical tracking
     async fn add_price_to_history(&mut self, token_mint: Pubkey, price: Decimal) -> Result<()> {
         let history = self.price_history.entry(token_mint).or_insert_with(VecDeque::new);
@@ -471,35 +535,7 @@ impl PriceMonitor {
 
     /// Initialize common token metadata
     async fn initialize_token_metadata(&mut self) -> Result<()> {
-        // Common Solana tokens - in a real implementation, this would be loaded from a configuration file
-        let tokens = vec![
-            TokenMetadata {
-                mint: Pubkey::try_from("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap(), // USDC
-                symbol: "USDC".to_string(),
-                name: "USD Coin".to_string(),
-                decimals: 6,
-                coingecko_id: Some("usd-coin".to_string()),
-                price_feeds: vec!["pyth".to_string()],
-            },
-            TokenMetadata {
-                mint: Pubkey::try_from("So11111111111111111111111111111111111111112").unwrap(), // SOL
-                symbol: "SOL".to_string(),
-                name: "Solana".to_string(),
-                decimals: 9,
-                coingecko_id: Some("solana".to_string()),
-                price_feeds: vec!["pyth".to_string(), "chainlink".to_string()],
-            },
-            TokenMetadata {
-                mint: Pubkey::try_from("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So").unwrap(), // mSOL
-                symbol: "mSOL".to_string(),
-                name: "Marinade Staked SOL".to_string(),
-                decimals: 9,
-                coingecko_id: Some("marinade-staked-sol".to_string()),
-                price_feeds: vec!["pyth".to_string()],
-            },
-        ];
-        
-        for token in tokens {
+        for token in known_tokens() {
             self.token_metadata.insert(token.mint, token);
         }
         
@@ -548,17 +584,87 @@ impl PriceMonitor {
     }
 }
 
+/// Common Solana tokens - in a real implementation, this would be loaded from a configuration file
+fn known_tokens() -> Vec<TokenMetadata> {
+    vec![
+        TokenMetadata {
+            mint: Pubkey::try_from("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap(), // USDC
+            symbol: "USDC".to_string(),
+            name: "USD Coin".to_string(),
+            decimals: 6,
+            coingecko_id: Some("usd-coin".to_string()),
+            price_feeds: vec!["pyth".to_string()],
+        },
+        TokenMetadata {
+            mint: Pubkey::try_from("So11111111111111111111111111111111111111112").unwrap(), // SOL
+            symbol: "SOL".to_string(),
+            name: "Solana".to_string(),
+            decimals: 9,
+            coingecko_id: Some("solana".to_string()),
+            price_feeds: vec!["pyth".to_string(), "chainlink".to_string()],
+        },
+        TokenMetadata {
+            mint: Pubkey::try_from("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So").unwrap(), // mSOL
+            symbol: "mSOL".to_string(),
+            name: "Marinade Staked SOL".to_string(),
+            decimals: 9,
+            coingecko_id: Some("marinade-staked-sol".to_string()),
+            price_feeds: vec!["pyth".to_string()],
+        },
+    ]
+}
+
+/// Pair prices where both tokens are known, taking for each X price the latest Y price
+/// at or before it
+fn align_prices<T: Copy>(
+    history_x: &[(DateTime<Utc>, T)],
+    history_y: &[(DateTime<Utc>, T)],
+) -> Vec<(DateTime<Utc>, T, T)> {
+    let mut next_y = 0;
+    let mut latest_y = None;
+    history_x
+        .iter()
+        .filter_map(|&(timestamp, price_x)| {
+            while next_y < history_y.len() && history_y[next_y].0 <= timestamp {
+                latest_y = Some(history_y[next_y].1);
+                next_y += 1;
+            }
+            latest_y.map(|price_y| (timestamp, price_x, price_y))
+        })
+        .collect()
+}
+
+/// Annualised standard deviation of log returns, scaled by the average sampling interval
+fn annualized_volatility(prices: &[(DateTime<Utc>, f64)]) -> Decimal {
+    if prices.len() < 2 {
+        return Decimal::ZERO;
+    }
+
+    let returns: Vec<f64> = prices.windows(2)
+        .map(|window| (window[1].1 / window[0].1).ln())
+        .collect();
+    let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter()
+        .map(|&r| (r - mean_return).powi(2))
+        .sum::<f64>() / returns.len() as f64;
+
+    let elapsed_secs = (prices[prices.len() - 1].0 - prices[0].0).num_seconds() as f64;
+    let interval_secs = (elapsed_secs / returns.len() as f64).max(1.0);
+    let volatility = variance.sqrt() * (SECONDS_PER_YEAR / interval_secs).sqrt();
+
+    Decimal::from_f64(volatility).unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
     async fn test_mock_price_generation() {
-        let monitor = PriceMonitor::new().await.unwrap();
         let test_mint = Pubkey::new_unique();
         
-        let price1 = monitor.get_mock_price(&test_mint).await.unwrap();
-        let price2 = monitor.get_mock_price(&test_mint).await.unwrap();
+        let price1 = MockPriceSource.get_price(&test_mint).await.unwrap().price;
+        let price2 = MockPriceSource::price(&test_mint);
         
         // Same mint should generate same price
         assert_eq!(price1, price2);
@@ -596,6 +702,34 @@ mod tests {
         assert!(volatility > Decimal::ZERO);
     }
 
+    #[tokio::test]
+    async fn test_pair_volatility_from_replayed_prices() {
+        let (token_x, token_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let start = Utc::now() - ChronoDuration::days(30);
+        
+        // Both tokens move together, so the X/Y ratio never changes
+        let moves = [1.0, 1.1, 0.95, 1.2, 1.05, 0.9];
+        let quotes = moves.iter().enumerate().flat_map(|(hour, scale)| {
+            let timestamp = start + ChronoDuration::hours(hour as i64);
+            [(token_x, 100.0), (token_y, 4.0)].map(|(mint, base)| PriceQuote {
+                mint,
+                price: Decimal::from_f64(base * scale).unwrap(),
+                timestamp,
+                source: "replay".to_string(),
+            })
+        });
+        let replay = saros_dlmm_sdk::ReplayPriceSource::from_quotes(quotes);
+        let mut monitor = PriceMonitor::with_price_source(Arc::new(replay)).await.unwrap();
+        
+        assert_eq!(monitor.get_token_price(token_x).await.unwrap(), Decimal::new(90, 0));
+        assert!(monitor.calculate_volatility(token_x, 24).await.unwrap() > Decimal::ZERO);
+        assert!(monitor.calculate_pair_volatility(token_x, token_y, 24).await.unwrap() < Decimal::new(1, 6));
+        
+        let history = monitor.get_historical_data(token_x, token_y, 1).await.unwrap();
+        assert_eq!(history.len(), moves.len());
+        assert_eq!(history[0].price_y, Decimal::new(4, 0));
+    }
+
     #[tokio::test]
     async fn test_historical_data_generation() {
         let monitor = PriceMonitor::new().await.unwrap();
@@ -613,15 +747,4 @@ mod tests {
             assert!(window[1].timestamp > window[0].timestamp);
         }
     }
-
-    #[test]
-    fn test_notification_threshold() {
-        let mut monitor = PriceMonitor::new().await.unwrap();
-        let test_mint = Pubkey::new_unique();
-        let threshold = Decimal::new(5, 2); // 5%
-        
-        monitor.set_notification_threshold(test_mint, threshold);
-        
-        assert_eq!(monitor.notification_thresholds.get(&test_mint), Some(&threshold));
-    }
 }
\ No newline at end of file
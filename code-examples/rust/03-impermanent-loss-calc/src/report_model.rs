@@ -0,0 +1,1573 @@
+//! One model behind every machine-readable report format
+//!
+//! Each report type has a versioned schema: named sections of typed fields, and tables of
+//! typed columns. Report data is converted into a [`ReportModel`] that follows the schema,
+//! and the JSON, columnar CSV, Markdown and HTML outputs are all rendered from it, as is
+//! the published JSON Schema. Adding a field means adding it to the schema and the builder;
+//! every format picks it up. The published schemas are strict, so bump a schema's version
+//! whenever a field is added, renamed, removed or changes type.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde_json::{json, Map, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::bin_model::BinILBreakdown;
+use crate::charts::Chart;
+use crate::portfolio::PortfolioReport;
+use crate::position_history::{PositionHistory, PositionPerformance};
+use crate::scenario::{ScenarioPoint, ScenarioReport};
+use crate::types::{
+    HistoricalTrends, ImpermanentLossResult, PositionAnalysis, PriceDataPoint, ReportConfig,
+};
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Type of a field, which also decides how it is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Text,
+    Integer,
+    Number,
+    /// Decimal amount in USD
+    Usd,
+    /// Decimal fraction, shown as a percentage (0.05 is 5%)
+    Ratio,
+    Timestamp,
+    Boolean,
+}
+
+/// A field of a section, or a column of a table
+#[derive(Debug)]
+pub struct Column {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: ValueKind,
+    pub nullable: bool,
+}
+
+const fn column(key: &'static str, label: &'static str, kind: ValueKind) -> Column {
+    Column { key, label, kind, nullable: false }
+}
+
+const fn nullable(key: &'static str, label: &'static str, kind: ValueKind) -> Column {
+    Column { key, label, kind, nullable: true }
+}
+
+/// A named group of columns: a section's fields or a table's columns
+#[derive(Debug)]
+pub struct Group {
+    pub key: &'static str,
+    pub title: &'static str,
+    pub columns: &'static [Column],
+}
+
+/// Layout of one report type
+#[derive(Debug)]
+pub struct ReportSchema {
+    pub report_type: &'static str,
+    pub version: u32,
+    pub description: &'static str,
+    pub sections: &'static [Group],
+    pub tables: &'static [Group],
+    /// Table written as the CSV; without one the sections are written as a single row
+    pub csv_table: Option<&'static str>,
+}
+
+use ValueKind::*;
+
+/// Bins of a position re-priced at the current active bin
+const BIN_COLUMNS: &[Column] = &[
+    column("bin_id", "Bin", Integer),
+    column("price", "Price", Number),
+    column("amount_x", "Amount X", Number),
+    column("amount_y", "Amount Y", Number),
+    column("value_usd", "Value", Usd),
+    column("hold_value_usd", "Hold value", Usd),
+    column("il_usd_value", "IL value", Usd),
+    column("il_percentage", "IL", Ratio),
+    column("crossed", "Crossed", Boolean),
+];
+
+/// Outcome of a position at one simulated price
+const SCENARIO_POINT_COLUMNS: &[Column] = &[
+    column("price_change", "Price change X", Ratio),
+    column("price_x", "Price X", Usd),
+    column("price_y", "Price Y", Usd),
+    column("active_bin_id", "Active bin", Integer),
+    column("in_range", "In range", Boolean),
+    column("value_usd", "Value", Usd),
+    column("hold_value_usd", "Hold value", Usd),
+    column("il_usd_value", "IL value", Usd),
+    column("il_percentage", "IL", Ratio),
+    column("days_in_range", "Days in range", Number),
+    column("fee_income_usd", "Fee income", Usd),
+    column("net_pnl_usd", "Net PnL", Usd),
+    column("net_vs_hold_usd", "Net vs hold", Usd),
+];
+
+/// Snapshot and monitor reports for one position or pool
+pub const POSITION_SCHEMA: ReportSchema = ReportSchema {
+    report_type: "il_analysis",
+    version: 1,
+    description: "Impermanent loss, fee, risk and performance analysis of a DLMM position",
+    sections: &[
+        Group {
+            key: "position_details",
+            title: "Position",
+            columns: &[
+                column("pool_address", "Pool address", Text),
+                nullable("position_id", "Position ID", Text),
+                column("token_x_symbol", "Token X", Text),
+                column("token_y_symbol", "Token Y", Text),
+                column("lower_bin_id", "Lower bin", Integer),
+                column("upper_bin_id", "Upper bin", Integer),
+                nullable("active_bin_id", "Active bin", Integer),
+                column("liquidity", "Liquidity", Number),
+                column("created_at", "Created at", Timestamp),
+                column("days_active", "Days active", Integer),
+            ],
+        },
+        Group {
+            key: "impermanent_loss",
+            title: "Impermanent Loss",
+            columns: &[
+                column("il_percentage", "Impermanent loss", Ratio),
+                column("il_usd_value", "IL value", Usd),
+                column("current_value_usd", "Current position value", Usd),
+                column("hold_value_usd", "Hold strategy value", Usd),
+                column("initial_price_x", "Initial price X", Usd),
+                column("initial_price_y", "Initial price Y", Usd),
+                column("current_price_x", "Current price X", Usd),
+                column("current_price_y", "Current price Y", Usd),
+                column("price_ratio_change", "Price ratio change", Number),
+                nullable("price_range_coverage", "Price range coverage", Ratio),
+                column("calculation_method", "Calculation method", Text),
+                column("calculated_at", "Calculated at", Timestamp),
+            ],
+        },
+        Group {
+            key: "fee_analysis",
+            title: "Fees",
+            columns: &[
+                column("total_fees_earned", "Total fees earned", Usd),
+                column("fees_token_x", "Fees in token X", Number),
+                column("fees_token_y", "Fees in token Y", Number),
+                column("fee_apy", "Fee APY", Ratio),
+                column("daily_fee_rate", "Daily fee rate", Ratio),
+                column("fee_vs_il_ratio", "Fee vs IL ratio", Number),
+                nullable("break_even_days", "Break-even days", Integer),
+            ],
+        },
+        Group {
+            key: "risk_metrics",
+            title: "Risk",
+            columns: &[
+                column("price_volatility", "Price volatility", Ratio),
+                column("max_il_observed", "Max IL observed", Ratio),
+                column("var_95", "Value at risk (95%)", Usd),
+                column("sharpe_ratio", "Sharpe ratio", Number),
+                column("concentration_risk", "Concentration risk", Number),
+                column("bin_utilization", "Bin utilization", Ratio),
+            ],
+        },
+        Group {
+            key: "performance_metrics",
+            title: "Performance",
+            columns: &[
+                column("total_return_usd", "Total return", Usd),
+                column("total_return_percentage", "Total return %", Ratio),
+                column("annualized_return", "Annualized return", Ratio),
+                column("net_pnl", "Net PnL", Usd),
+                column("vs_hold_performance", "Performance vs hold", Usd),
+                nullable("vs_market_performance", "Performance vs market", Number),
+                column("initial_investment_usd", "Initial investment", Usd),
+            ],
+        },
+    ],
+    tables: &[Group { key: "bins", title: "Bins", columns: BIN_COLUMNS }],
+    csv_table: None,
+};
+
+/// Historical trend reports
+pub const HISTORICAL_SCHEMA: ReportSchema = ReportSchema {
+    report_type: "historical_analysis",
+    version: 1,
+    description: "Impermanent loss of a DLMM position or pool over a historical period",
+    sections: &[
+        Group {
+            key: "analysis_period",
+            title: "Analysis Period",
+            columns: &[
+                column("days", "Days", Integer),
+                column("data_points", "IL data points", Integer),
+                column("price_points", "Price data points", Integer),
+            ],
+        },
+        Group {
+            key: "trend_summary",
+            title: "Trend",
+            columns: &[
+                column("direction", "Direction", Text),
+                column("max_il_percentage", "Maximum IL", Ratio),
+                column("min_il_percentage", "Minimum IL", Ratio),
+                column("average_il_percentage", "Average IL", Ratio),
+                column("il_volatility", "IL volatility", Number),
+                column("market_correlation", "Market correlation", Number),
+                column("avg_daily_price_change", "Average daily price change", Ratio),
+                column("max_drawdown_days", "Max drawdown days", Integer),
+                column("recovery_period_count", "Recovery periods", Integer),
+            ],
+        },
+    ],
+    tables: &[
+        Group {
+            key: "il_history",
+            title: "IL History",
+            columns: &[
+                column("timestamp", "Time", Timestamp),
+                column("il_percentage", "IL", Ratio),
+                column("il_usd_value", "IL value", Usd),
+                column("price_x", "Price X", Usd),
+                column("price_y", "Price Y", Usd),
+                column("price_ratio_change", "Price ratio change", Number),
+                column("current_value_usd", "Value", Usd),
+                column("hold_value_usd", "Hold value", Usd),
+                nullable("fees_collected_usd", "Fees collected", Usd),
+                nullable("active_bin_id", "Active bin", Integer),
+            ],
+        },
+        Group {
+            key: "recovery_periods",
+            title: "Recovery Periods",
+            columns: &[
+                column("start_date", "Start", Timestamp),
+                column("end_date", "End", Timestamp),
+                column("max_il", "Max IL", Ratio),
+                column("recovery_days", "Recovery days", Integer),
+                column("fee_compensation", "Fee compensation", Usd),
+            ],
+        },
+        Group {
+            key: "price_history",
+            title: "Price History",
+            columns: &[
+                column("timestamp", "Time", Timestamp),
+                column("price_x", "Price X", Usd),
+                column("price_y", "Price Y", Usd),
+                column("volume_24h", "24h volume", Usd),
+                column("liquidity", "Liquidity", Usd),
+                column("active_bin_id", "Active bin", Integer),
+            ],
+        },
+    ],
+    csv_table: Some("il_history"),
+};
+
+/// Portfolio reports across positions and pools
+pub const PORTFOLIO_SCHEMA: ReportSchema = ReportSchema {
+    report_type: "portfolio_analysis",
+    version: 1,
+    description: "Impermanent loss, correlation, value at risk and exposure across DLMM positions",
+    sections: &[Group {
+        key: "portfolio",
+        title: "Portfolio",
+        columns: &[
+            nullable("owner", "Owner", Text),
+            column("history_days", "Return history days", Integer),
+            column("total_positions", "Positions", Integer),
+            column("pools", "Pools", Integer),
+            column("total_value_usd", "Total value", Usd),
+            column("average_il", "Value-weighted IL", Ratio),
+            column("total_fees_earned", "Total fees earned", Usd),
+            column("portfolio_var_95", "1-day VaR (95%)", Usd),
+            column("undiversified_var_95", "Sum of pool VaRs", Usd),
+            column("exposure_concentration", "Token concentration (HHI)", Number),
+            nullable("best_performing_pool", "Best risk-adjusted pool", Text),
+            nullable("worst_performing_pool", "Worst risk-adjusted pool", Text),
+        ],
+    }],
+    tables: &[
+        Group {
+            key: "pool_rankings",
+            title: "Pool Ranking",
+            columns: &[
+                column("rank", "Rank", Integer),
+                column("pool_address", "Pool", Text),
+                column("positions", "Positions", Integer),
+                column("value_usd", "Value", Usd),
+                column("il_usd_value", "IL value", Usd),
+                column("fees_earned", "Fees", Usd),
+                column("net_pnl", "Net PnL", Usd),
+                column("annualized_return", "Annualized return", Ratio),
+                column("annualized_volatility", "Annualized volatility", Ratio),
+                column("risk_adjusted_return", "Risk-adjusted return", Number),
+            ],
+        },
+        Group {
+            key: "positions",
+            title: "Positions",
+            columns: &[
+                nullable("position_id", "Position", Text),
+                column("pool_address", "Pool", Text),
+                column("token_pair", "Pair", Text),
+                column("current_value_usd", "Value", Usd),
+                column("il_percentage", "IL", Ratio),
+                column("il_usd_value", "IL value", Usd),
+                column("total_fees_earned", "Fees", Usd),
+                column("net_pnl", "Net PnL", Usd),
+            ],
+        },
+        Group {
+            key: "token_exposure",
+            title: "Token Exposure",
+            columns: &[
+                column("token", "Token", Text),
+                column("share", "Share of value", Ratio),
+            ],
+        },
+        Group {
+            key: "correlations",
+            title: "Return Correlations",
+            columns: &[
+                column("pool_a", "Pool", Text),
+                column("pool_b", "Pool", Text),
+                column("correlation", "Correlation", Number),
+            ],
+        },
+    ],
+    csv_table: Some("pool_rankings"),
+};
+
+/// Price shock, price path and Monte Carlo projections for a position
+pub const SCENARIO_SCHEMA: ReportSchema = ReportSchema {
+    report_type: "scenario_analysis",
+    version: 1,
+    description: "Projected impermanent loss, fees and PnL of a DLMM position under price shocks, a price path and Monte Carlo simulation",
+    sections: &[
+        Group {
+            key: "scenario",
+            title: "Scenario",
+            columns: &[
+                column("pool_address", "Pool address", Text),
+                column("entry_price_x", "Entry price X", Usd),
+                column("entry_price_y", "Entry price Y", Usd),
+                column("bin_step", "Bin step", Integer),
+                column("lower_bin_id", "Lower bin", Integer),
+                column("upper_bin_id", "Upper bin", Integer),
+                column("entry_active_bin_id", "Entry active bin", Integer),
+                column("distribution", "Liquidity shape", Text),
+                column("daily_fee_rate", "Daily fee rate", Ratio),
+                column("entry_value_usd", "Entry value", Usd),
+            ],
+        },
+        Group {
+            key: "monte_carlo",
+            title: "Monte Carlo",
+            columns: &[
+                nullable("paths", "Paths", Integer),
+                nullable("horizon_days", "Horizon days", Integer),
+                nullable("volatility", "Annualized volatility", Ratio),
+                nullable("probability_exit_range", "Probability of leaving the range", Ratio),
+                nullable("probability_out_of_range_at_horizon", "Probability out of range at horizon", Ratio),
+                nullable("probability_fees_cover_il", "Probability fees cover IL", Ratio),
+                nullable("expected_days_in_range", "Expected days in range", Number),
+                nullable("expected_il_usd", "Expected IL", Usd),
+                nullable("expected_fee_income_usd", "Expected fee income", Usd),
+                nullable("expected_net_pnl_usd", "Expected net PnL", Usd),
+                nullable("breakeven_days", "Break-even days", Number),
+            ],
+        },
+    ],
+    tables: &[
+        Group { key: "shocks", title: "Price Shocks", columns: SCENARIO_POINT_COLUMNS },
+        Group { key: "path", title: "Price Path", columns: SCENARIO_POINT_COLUMNS },
+        Group {
+            key: "percentiles",
+            title: "Monte Carlo Percentiles",
+            columns: &[
+                column("percentile", "Percentile", Integer),
+                column("price_x", "Price X", Usd),
+                column("il_percentage", "IL", Ratio),
+                column("il_usd_value", "IL value", Usd),
+                column("fee_income_usd", "Fee income", Usd),
+                column("net_pnl_usd", "Net PnL", Usd),
+                column("net_vs_hold_usd", "Net vs hold", Usd),
+            ],
+        },
+    ],
+    csv_table: Some("shocks"),
+};
+
+/// Performance of a position replayed from its transactions. The `history` section and
+/// `events` table load back with `--history-file`.
+pub const POSITION_HISTORY_SCHEMA: ReportSchema = ReportSchema {
+    report_type: "position_history",
+    version: 1,
+    description: "Impermanent loss, fees and returns of a DLMM position replayed from its priced transaction history",
+    sections: &[
+        Group {
+            key: "history",
+            title: "Position",
+            columns: &[
+                column("position_id", "Position ID", Text),
+                column("pool_address", "Pool address", Text),
+                column("lower_bin_id", "Lower bin", Integer),
+                column("upper_bin_id", "Upper bin", Integer),
+                column("bin_step", "Bin step", Integer),
+            ],
+        },
+        Group {
+            key: "performance",
+            title: "Performance",
+            columns: &[
+                column("as_of", "As of", Timestamp),
+                column("active_bin_id", "Active bin", Integer),
+                column("liquidity", "Liquidity", Text),
+                column("price_x", "Price X", Usd),
+                column("price_y", "Price Y", Usd),
+                column("average_entry_price_x", "Average entry price X", Usd),
+                column("average_entry_price_y", "Average entry price Y", Usd),
+                column("hold_amount_x", "Hold amount X", Number),
+                column("hold_amount_y", "Hold amount Y", Number),
+                column("deposited_usd", "Deposited", Usd),
+                column("withdrawn_usd", "Withdrawn", Usd),
+                column("fees_claimed_usd", "Fees claimed", Usd),
+                column("unclaimed_fees_usd", "Unclaimed fees", Usd),
+                column("cost_basis_usd", "Cost basis", Usd),
+                column("realized_pnl_usd", "Realized PnL", Usd),
+                column("unrealized_pnl_usd", "Unrealized PnL", Usd),
+                column("current_value_usd", "Current value", Usd),
+                column("hold_value_usd", "Hold value", Usd),
+                column("il_usd_value", "IL value", Usd),
+                column("il_percentage", "Impermanent loss", Ratio),
+                column("net_pnl_usd", "Net PnL", Usd),
+                column("time_weighted_return", "Time-weighted return", Ratio),
+                nullable("money_weighted_return", "Money-weighted return (annualized)", Ratio),
+            ],
+        },
+    ],
+    tables: &[
+        Group {
+            key: "events",
+            title: "Transactions",
+            columns: &[
+                column("signature", "Signature", Text),
+                column("timestamp", "Time", Timestamp),
+                column("kind", "Kind", Text),
+                column("amount_x", "Amount X", Number),
+                column("amount_y", "Amount Y", Number),
+                column("liquidity", "Liquidity", Text),
+                column("active_bin_id", "Active bin", Integer),
+                nullable("price_x", "Price X", Usd),
+                nullable("price_y", "Price Y", Usd),
+            ],
+        },
+        Group {
+            key: "cash_flows",
+            title: "Cash Flows",
+            columns: &[
+                column("timestamp", "Time", Timestamp),
+                column("kind", "Kind", Text),
+                column("signature", "Signature", Text),
+                column("amount_x", "Amount X", Number),
+                column("amount_y", "Amount Y", Number),
+                column("value_usd", "Value", Usd),
+            ],
+        },
+        Group { key: "bins", title: "Bins", columns: BIN_COLUMNS },
+    ],
+    csv_table: Some("events"),
+};
+
+/// Every published report schema
+pub const SCHEMAS: [&ReportSchema; 5] = [
+    &POSITION_SCHEMA,
+    &HISTORICAL_SCHEMA,
+    &PORTFOLIO_SCHEMA,
+    &SCENARIO_SCHEMA,
+    &POSITION_HISTORY_SCHEMA,
+];
+
+/// A single value in a report
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Null,
+    Text(String),
+    Integer(i64),
+    Decimal(Decimal),
+    Timestamp(DateTime<Utc>),
+    Boolean(bool),
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Text(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::Text(value.to_string())
+    }
+}
+
+impl From<Pubkey> for FieldValue {
+    fn from(value: Pubkey) -> Self {
+        FieldValue::Text(value.to_string())
+    }
+}
+
+impl From<u8> for FieldValue {
+    fn from(value: u8) -> Self {
+        FieldValue::Integer(value.into())
+    }
+}
+
+impl From<u16> for FieldValue {
+    fn from(value: u16) -> Self {
+        FieldValue::Integer(value.into())
+    }
+}
+
+impl From<i32> for FieldValue {
+    fn from(value: i32) -> Self {
+        FieldValue::Integer(value.into())
+    }
+}
+
+impl From<u32> for FieldValue {
+    fn from(value: u32) -> Self {
+        FieldValue::Integer(value.into())
+    }
+}
+
+impl From<usize> for FieldValue {
+    fn from(value: usize) -> Self {
+        FieldValue::Integer(value as i64)
+    }
+}
+
+impl From<Decimal> for FieldValue {
+    fn from(value: Decimal) -> Self {
+        FieldValue::Decimal(value)
+    }
+}
+
+impl From<DateTime<Utc>> for FieldValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        FieldValue::Timestamp(value)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Boolean(value)
+    }
+}
+
+impl<T: Into<FieldValue>> From<Option<T>> for FieldValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(FieldValue::Null, Into::into)
+    }
+}
+
+/// Build a row of values from expressions of any convertible type
+macro_rules! row {
+    ($($value:expr),* $(,)?) => {
+        vec![$(FieldValue::from($value)),*]
+    };
+}
+
+type Rows = Vec<Vec<FieldValue>>;
+
+/// Report data laid out by a [`ReportSchema`]
+#[derive(Debug, Clone)]
+pub struct ReportModel {
+    pub schema: &'static ReportSchema,
+    pub title: String,
+    pub generated_at: DateTime<Utc>,
+    /// Values of each section's fields, in schema order
+    pub sections: Vec<Vec<FieldValue>>,
+    /// Rows of each table, or `None` when the table was left out
+    pub tables: Vec<Option<Rows>>,
+}
+
+/// Highlighted message at the top of an HTML report
+#[derive(Debug, Clone)]
+pub enum Callout {
+    Warning(String),
+    Success(String),
+}
+
+/// Screen styles, plus print styles so the page saves straight to PDF: A4 pages, no
+/// backgrounds, tables and charts kept whole and each table starting on a new page
+const HTML_STYLE: &str = r#"
+        body { font-family: Arial, sans-serif; margin: 40px; background-color: #f5f5f5; color: #2c3e50; }
+        .container { max-width: 1200px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 0 20px rgba(0,0,0,0.1); }
+        h1 { border-bottom: 3px solid #3498db; padding-bottom: 10px; }
+        h2 { color: #34495e; margin-top: 30px; }
+        .meta { color: #7f8c8d; }
+        .sections { display: grid; grid-template-columns: repeat(auto-fit, minmax(420px, 1fr)); gap: 0 30px; }
+        .info-table { width: 100%; border-collapse: collapse; margin: 15px 0; font-size: 0.9em; }
+        .info-table th, .info-table td { padding: 8px 10px; text-align: left; border-bottom: 1px solid #ddd; }
+        .info-table th { background-color: #34495e; color: white; }
+        .info-table td.num { text-align: right; font-variant-numeric: tabular-nums; }
+        .negative { color: #e74c3c; }
+        .alert { background: #e74c3c; color: white; padding: 15px; border-radius: 8px; margin: 10px 0; }
+        .success { background: #27ae60; color: white; padding: 15px; border-radius: 8px; margin: 10px 0; }
+        .chart { margin: 20px 0; }
+        .chart svg { max-width: 100%; height: auto; }
+        footer { margin-top: 40px; text-align: center; color: #7f8c8d; font-size: 0.9em; }
+        @page { size: A4; margin: 15mm; }
+        @media print {
+            body { margin: 0; background: none; font-size: 10pt; }
+            .container { max-width: none; padding: 0; box-shadow: none; border-radius: 0; }
+            .sections { display: block; }
+            .info-table th { background: none; color: #2c3e50; border-bottom: 2px solid #34495e; }
+            .alert, .success { background: none; color: #2c3e50; border: 2px solid currentColor; }
+            .alert { border-color: #e74c3c; }
+            .success { border-color: #27ae60; }
+            h2 { break-after: avoid; }
+            thead { display: table-header-group; }
+            tr, .chart, .sections table { break-inside: avoid; }
+            .charts, .data-table { break-before: page; }
+        }
+"#;
+
+impl ReportModel {
+    /// Build a model, checking every value against the schema
+    pub fn new(
+        schema: &'static ReportSchema,
+        config: &ReportConfig,
+        sections: Vec<Vec<FieldValue>>,
+        tables: Vec<Option<Rows>>,
+    ) -> Result<Self> {
+        if sections.len() != schema.sections.len() || tables.len() != schema.tables.len() {
+            return Err(anyhow!("{} report does not match its schema layout", schema.report_type));
+        }
+        for (group, values) in schema.sections.iter().zip(&sections) {
+            check_row(schema, group, values)?;
+        }
+        for (group, rows) in schema.tables.iter().zip(&tables) {
+            for row in rows.iter().flatten() {
+                check_row(schema, group, row)?;
+            }
+        }
+
+        Ok(Self {
+            schema,
+            title: config.title.clone(),
+            generated_at: config.timestamp,
+            sections,
+            tables,
+        })
+    }
+
+    /// JSON document: report info, one object per section and one array per table
+    pub fn to_json(&self) -> Value {
+        let mut document = Map::new();
+        document.insert(
+            "report_info".to_string(),
+            json!({
+                "title": self.title,
+                "generated_at": format_timestamp(self.generated_at),
+                "type": self.schema.report_type,
+                "schema_version": self.schema.version,
+            }),
+        );
+        for (group, values) in self.schema.sections.iter().zip(&self.sections) {
+            document.insert(group.key.to_string(), json_object(group, values));
+        }
+        for (group, rows) in self.schema.tables.iter().zip(&self.tables) {
+            if let Some(rows) = rows {
+                let rows = rows.iter().map(|row| json_object(group, row)).collect();
+                document.insert(group.key.to_string(), Value::Array(rows));
+            }
+        }
+        Value::Object(document)
+    }
+
+    /// Columnar CSV: one row per record of the schema's CSV table, or one row of every
+    /// section field. Each row starts with the report type, schema version and report time
+    /// so files can be loaded into one warehouse table per report type.
+    pub fn to_csv(&self) -> Result<String> {
+        let (columns, rows): (Vec<&Column>, Rows) = match self.schema.csv_table {
+            Some(key) => {
+                let index = self
+                    .schema
+                    .tables
+                    .iter()
+                    .position(|group| group.key == key)
+                    .ok_or_else(|| anyhow!("{} has no table {}", self.schema.report_type, key))?;
+                (
+                    self.schema.tables[index].columns.iter().collect(),
+                    self.tables[index].clone().unwrap_or_default(),
+                )
+            }
+            None => (
+                self.schema.sections.iter().flat_map(|group| group.columns).collect(),
+                vec![self.sections.concat()],
+            ),
+        };
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(
+            ["report_type", "schema_version", "generated_at"]
+                .into_iter()
+                .chain(columns.iter().map(|column| column.key)),
+        )?;
+        let version = self.schema.version.to_string();
+        let generated_at = format_timestamp(self.generated_at);
+        for row in &rows {
+            writer.write_record(
+                [self.schema.report_type.to_string(), version.clone(), generated_at.clone()]
+                    .into_iter()
+                    .chain(row.iter().map(csv_value)),
+            )?;
+        }
+
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    /// Markdown document for pasting into pull requests and wikis
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "# {}\n\n_Generated {} · {} schema v{}_\n",
+            escape_markdown(&self.title),
+            self.generated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            self.schema.report_type,
+            self.schema.version
+        );
+
+        for (group, values) in self.schema.sections.iter().zip(&self.sections) {
+            markdown.push_str(&format!("\n## {}\n\n| Metric | Value |\n| --- | ---: |\n", group.title));
+            for (column, value) in group.columns.iter().zip(values) {
+                markdown.push_str(&format!("| {} | {} |\n", column.label, markdown_value(column.kind, value)));
+            }
+        }
+
+        for (group, rows) in self.schema.tables.iter().zip(&self.tables) {
+            let Some(rows) = rows else { continue };
+            markdown.push_str(&format!("\n## {}\n\n", group.title));
+            if rows.is_empty() {
+                markdown.push_str("_None_\n");
+                continue;
+            }
+            let labels: Vec<&str> = group.columns.iter().map(|column| column.label).collect();
+            let alignments: Vec<&str> = group
+                .columns
+                .iter()
+                .map(|column| if matches!(column.kind, Text | Timestamp | Boolean) { "---" } else { "---:" })
+                .collect();
+            markdown.push_str(&format!("| {} |\n| {} |\n", labels.join(" | "), alignments.join(" | ")));
+            for row in rows {
+                let cells: Vec<String> = group
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, value)| markdown_value(column.kind, value))
+                    .collect();
+                markdown.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+
+        markdown
+    }
+
+    /// Standalone HTML page of every section and table, opening with `callouts` and
+    /// embedding `charts` as inline SVG. Nothing is loaded from the network, and the print
+    /// stylesheet lays the page out for printing or saving to PDF.
+    pub fn to_html(&self, callouts: &[Callout], charts: &[Chart]) -> String {
+        let title = escape_html(&self.title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"UTF-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n\
+             <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<div class=\"container\">\n\
+             <h1>{}</h1>\n<p class=\"meta\">Generated {} · {} schema v{}</p>\n",
+            title,
+            HTML_STYLE,
+            title,
+            self.generated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            self.schema.report_type,
+            self.schema.version
+        );
+
+        for callout in callouts {
+            let (class, message) = match callout {
+                Callout::Warning(message) => ("alert", message),
+                Callout::Success(message) => ("success", message),
+            };
+            html.push_str(&format!("<div class=\"{}\">{}</div>\n", class, escape_html(message)));
+        }
+
+        html.push_str("<div class=\"sections\">\n");
+        for (group, values) in self.schema.sections.iter().zip(&self.sections) {
+            html.push_str(&format!(
+                "<section>\n<h2>{}</h2>\n<table class=\"info-table\">\n<thead><tr><th>Metric</th><th>Value</th></tr></thead>\n<tbody>\n",
+                group.title
+            ));
+            for (column, value) in group.columns.iter().zip(values) {
+                html.push_str(&format!("<tr><td>{}</td>{}</tr>\n", column.label, html_cell(column.kind, value)));
+            }
+            html.push_str("</tbody>\n</table>\n</section>\n");
+        }
+        html.push_str("</div>\n");
+
+        if !charts.is_empty() {
+            html.push_str("<section class=\"charts\">\n<h2>Charts</h2>\n");
+            for chart in charts {
+                html.push_str(&format!("<div class=\"chart\">{}</div>\n", chart.to_svg()));
+            }
+            html.push_str("</section>\n");
+        }
+
+        for (group, rows) in self.schema.tables.iter().zip(&self.tables) {
+            let Some(rows) = rows else { continue };
+            html.push_str(&format!("<section class=\"data-table\">\n<h2>{}</h2>\n", group.title));
+            if rows.is_empty() {
+                html.push_str("<p>None</p>\n</section>\n");
+                continue;
+            }
+            let labels: String = group.columns.iter().map(|column| format!("<th>{}</th>", column.label)).collect();
+            html.push_str(&format!("<table class=\"info-table\">\n<thead><tr>{}</tr></thead>\n<tbody>\n", labels));
+            for row in rows {
+                let cells: String = group.columns.iter().zip(row).map(|(column, value)| html_cell(column.kind, value)).collect();
+                html.push_str(&format!("<tr>{}</tr>\n", cells));
+            }
+            html.push_str("</tbody>\n</table>\n</section>\n");
+        }
+
+        html.push_str(&format!(
+            "<footer>Generated by Saros DLMM Impermanent Loss Calculator | {}<br>\n\
+             This report is for informational purposes only and should not be considered financial advice.</footer>\n\
+             </div>\n</body>\n</html>\n",
+            self.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        html
+    }
+}
+
+impl ReportSchema {
+    /// Name of the published schema file, e.g. `il_analysis.v1.schema.json`
+    pub fn file_name(&self) -> String {
+        format!("{}.v{}.schema.json", self.report_type, self.version)
+    }
+
+    /// JSON Schema of the report's JSON output
+    pub fn json_schema(&self) -> Value {
+        let mut properties = Map::new();
+        properties.insert(
+            "report_info".to_string(),
+            json!({
+                "type": "object",
+                "required": ["title", "generated_at", "type", "schema_version"],
+                "properties": {
+                    "title": { "type": "string" },
+                    "generated_at": { "type": "string", "format": "date-time" },
+                    "type": { "const": self.report_type },
+                    "schema_version": { "const": self.version }
+                },
+                "additionalProperties": false
+            }),
+        );
+        for group in self.sections {
+            properties.insert(group.key.to_string(), object_schema(group));
+        }
+        for group in self.tables {
+            properties.insert(
+                group.key.to_string(),
+                json!({ "description": group.title, "type": "array", "items": object_schema(group) }),
+            );
+        }
+
+        let required: Vec<&str> = std::iter::once("report_info")
+            .chain(self.sections.iter().map(|group| group.key))
+            .chain(self.csv_table)
+            .collect();
+
+        json!({
+            "$schema": JSON_SCHEMA_DIALECT,
+            "$id": self.file_name(),
+            "title": self.report_type,
+            "description": self.description,
+            "type": "object",
+            "required": required,
+            "properties": properties,
+            "additionalProperties": false
+        })
+    }
+}
+
+/// Snapshot or monitor report for a position analysis
+pub fn position_report(
+    analysis: &PositionAnalysis,
+    il_result: &ImpermanentLossResult,
+    config: &ReportConfig,
+) -> Result<ReportModel> {
+    let position = &analysis.position_info;
+    let fees = &analysis.fee_analysis;
+    let risk = &analysis.risk_metrics;
+    let performance = &analysis.performance_summary;
+
+    let sections = vec![
+        row![
+            position.pool_address,
+            position.position_id,
+            position.token_x_symbol.as_str(),
+            position.token_y_symbol.as_str(),
+            position.lower_bin_id,
+            position.upper_bin_id,
+            il_result.metadata.active_bin_id,
+            position.current_liquidity,
+            position.created_at,
+            performance.days_active,
+        ],
+        row![
+            il_result.il_percentage,
+            il_result.il_usd_value,
+            il_result.current_value_usd,
+            il_result.hold_value_usd,
+            il_result.initial_price_x,
+            il_result.initial_price_y,
+            il_result.current_price_x,
+            il_result.current_price_y,
+            il_result.price_ratio_change,
+            il_result.metadata.price_range_coverage,
+            format!("{:?}", il_result.metadata.calculation_method),
+            il_result.timestamp,
+        ],
+        row![
+            fees.total_fees_earned,
+            fees.fees_token_x,
+            fees.fees_token_y,
+            fees.fee_apy,
+            fees.daily_fee_rate,
+            fees.fee_vs_il_ratio,
+            fees.break_even_days,
+        ],
+        row![
+            risk.price_volatility,
+            risk.max_il_observed,
+            risk.var_95,
+            risk.sharpe_ratio,
+            risk.concentration_risk,
+            risk.bin_utilization,
+        ],
+        row![
+            performance.total_return_usd,
+            performance.total_return_percentage,
+            performance.annualized_return,
+            performance.net_pnl,
+            performance.vs_hold_performance,
+            performance.vs_market_performance,
+            position.initial_investment_usd,
+        ],
+    ];
+
+    let bins = il_result.bin_breakdown.as_ref().map(bin_rows);
+
+    ReportModel::new(&POSITION_SCHEMA, config, sections, vec![bins])
+}
+
+/// Historical trend report; the price history is included with the raw data
+pub fn historical_report(
+    trends: &HistoricalTrends,
+    il_history: &[ImpermanentLossResult],
+    price_history: &[PriceDataPoint],
+    config: &ReportConfig,
+) -> Result<ReportModel> {
+    let sections = vec![
+        row![trends.period_days, il_history.len(), price_history.len()],
+        row![
+            format!("{:?}", trends.trend_direction),
+            trends.max_il_percentage,
+            trends.min_il_percentage,
+            trends.avg_il_percentage,
+            trends.il_volatility,
+            trends.market_correlation,
+            trends.avg_daily_price_change,
+            trends.max_drawdown_days,
+            trends.recovery_periods.len(),
+        ],
+    ];
+
+    let il_rows = il_history
+        .iter()
+        .map(|result| {
+            row![
+                result.timestamp,
+                result.il_percentage,
+                result.il_usd_value,
+                result.current_price_x,
+                result.current_price_y,
+                result.price_ratio_change,
+                result.current_value_usd,
+                result.hold_value_usd,
+                result.fees_collected_usd,
+                result.metadata.active_bin_id,
+            ]
+        })
+        .collect();
+    let recovery_rows = trends
+        .recovery_periods
+        .iter()
+        .map(|period| {
+            row![
+                period.start_date,
+                period.end_date,
+                period.max_il_in_period,
+                period.recovery_days,
+                period.fee_compensation,
+            ]
+        })
+        .collect();
+    let price_rows = config.include_raw_data.then(|| {
+        price_history
+            .iter()
+            .map(|point| {
+                row![
+                    point.timestamp,
+                    point.price_x,
+                    point.price_y,
+                    point.volume_24h,
+                    point.liquidity,
+                    point.active_bin_id,
+                ]
+            })
+            .collect()
+    });
+
+    ReportModel::new(
+        &HISTORICAL_SCHEMA,
+        config,
+        sections,
+        vec![Some(il_rows), Some(recovery_rows), price_rows],
+    )
+}
+
+/// Portfolio report across positions and pools
+pub fn portfolio_report(report: &PortfolioReport, config: &ReportConfig) -> Result<ReportModel> {
+    let statistics = &report.statistics;
+
+    let sections = vec![row![
+        report.owner,
+        report.history_days,
+        statistics.total_positions,
+        statistics.pool_rankings.len(),
+        statistics.total_tvl,
+        statistics.average_il,
+        statistics.total_fees_earned,
+        statistics.portfolio_var_95,
+        statistics.undiversified_var_95,
+        statistics.exposure_concentration,
+        statistics.best_performing_pool,
+        statistics.worst_performing_pool,
+    ]];
+
+    let ranking_rows = statistics
+        .pool_rankings
+        .iter()
+        .map(|ranking| {
+            row![
+                ranking.rank,
+                ranking.pool_address,
+                ranking.positions,
+                ranking.value_usd,
+                ranking.il_usd_value,
+                ranking.fees_earned,
+                ranking.net_pnl,
+                ranking.annualized_return,
+                ranking.annualized_volatility,
+                ranking.risk_adjusted_return,
+            ]
+        })
+        .collect();
+    let position_rows = report
+        .positions
+        .iter()
+        .map(|analysis| {
+            row![
+                analysis.position_info.position_id,
+                analysis.position_info.pool_address,
+                format!("{}/{}", analysis.position_info.token_x_symbol, analysis.position_info.token_y_symbol),
+                analysis.il_result.current_value_usd,
+                analysis.il_result.il_percentage,
+                analysis.il_result.il_usd_value,
+                analysis.fee_analysis.total_fees_earned,
+                analysis.performance_summary.net_pnl,
+            ]
+        })
+        .collect();
+    let exposure_rows = statistics
+        .token_exposure
+        .iter()
+        .map(|(token, share)| row![token.as_str(), *share])
+        .collect();
+    let correlation_rows = statistics
+        .correlation_matrix
+        .iter()
+        .flat_map(|(pool_a, row)| {
+            row.iter()
+                .map(move |(pool_b, correlation)| row![pool_a.as_str(), pool_b.as_str(), *correlation])
+        })
+        .collect();
+
+    ReportModel::new(
+        &PORTFOLIO_SCHEMA,
+        config,
+        sections,
+        vec![Some(ranking_rows), Some(position_rows), Some(exposure_rows), Some(correlation_rows)],
+    )
+}
+
+/// Scenario report; the percentiles table is left out without a Monte Carlo run
+pub fn scenario_report(report: &ScenarioReport, config: &ReportConfig) -> Result<ReportModel> {
+    let monte_carlo = report.monte_carlo.as_ref();
+
+    let sections = vec![
+        row![
+            report.pool_address,
+            report.entry_price_x,
+            report.entry_price_y,
+            report.bin_step,
+            report.lower_bin_id,
+            report.upper_bin_id,
+            report.entry_active_bin_id,
+            report.distribution.to_string(),
+            report.daily_fee_rate,
+            report.entry_value_usd,
+        ],
+        row![
+            monte_carlo.map(|result| result.paths),
+            monte_carlo.map(|result| result.horizon_days),
+            monte_carlo.map(|result| result.volatility),
+            monte_carlo.map(|result| result.probability_exit_range),
+            monte_carlo.map(|result| result.probability_out_of_range_at_horizon),
+            monte_carlo.map(|result| result.probability_fees_cover_il),
+            monte_carlo.map(|result| result.expected_days_in_range),
+            monte_carlo.map(|result| result.expected_il_usd),
+            monte_carlo.map(|result| result.expected_fee_income_usd),
+            monte_carlo.map(|result| result.expected_net_pnl_usd),
+            monte_carlo.and_then(|result| result.breakeven_days),
+        ],
+    ];
+
+    let point_rows = |points: &[ScenarioPoint]| -> Rows {
+        points
+            .iter()
+            .map(|point| {
+                row![
+                    point.price_change,
+                    point.price_x,
+                    point.price_y,
+                    point.active_bin_id,
+                    point.in_range,
+                    point.value_usd,
+                    point.hold_value_usd,
+                    point.il_usd_value,
+                    point.il_percentage,
+                    point.days_in_range,
+                    point.fee_income_usd,
+                    point.net_pnl_usd,
+                    point.net_vs_hold_usd,
+                ]
+            })
+            .collect()
+    };
+    let percentile_rows = monte_carlo.map(|result| {
+        result
+            .percentiles
+            .iter()
+            .map(|band| {
+                row![
+                    band.percentile,
+                    band.price_x,
+                    band.il_percentage,
+                    band.il_usd_value,
+                    band.fee_income_usd,
+                    band.net_pnl_usd,
+                    band.net_vs_hold_usd,
+                ]
+            })
+            .collect()
+    });
+
+    ReportModel::new(
+        &SCENARIO_SCHEMA,
+        config,
+        sections,
+        vec![Some(point_rows(&report.shocks)), Some(point_rows(&report.path)), percentile_rows],
+    )
+}
+
+/// Position history report: the replayed performance, the priced transactions it was
+/// replayed from and the resulting cash flows. Liquidity is written as a decimal string
+/// because it can exceed what a JSON number holds exactly.
+pub fn position_history_report(
+    history: &PositionHistory,
+    performance: &PositionPerformance,
+    config: &ReportConfig,
+) -> Result<ReportModel> {
+    let sections = vec![
+        row![
+            history.position_id,
+            history.pool_address,
+            history.lower_bin_id,
+            history.upper_bin_id,
+            history.bin_step,
+        ],
+        row![
+            performance.as_of,
+            performance.active_bin_id,
+            performance.liquidity.to_string(),
+            performance.price_x,
+            performance.price_y,
+            performance.average_entry_price_x,
+            performance.average_entry_price_y,
+            performance.hold_amount_x,
+            performance.hold_amount_y,
+            performance.deposited_usd,
+            performance.withdrawn_usd,
+            performance.fees_claimed_usd,
+            performance.unclaimed_fees_usd,
+            performance.cost_basis_usd,
+            performance.realized_pnl_usd,
+            performance.unrealized_pnl_usd,
+            performance.current_value_usd,
+            performance.hold_value_usd,
+            performance.il_usd_value,
+            performance.il_percentage,
+            performance.net_pnl_usd,
+            performance.time_weighted_return,
+            performance.money_weighted_return,
+        ],
+    ];
+
+    let event_rows = history
+        .events
+        .iter()
+        .map(|event| {
+            row![
+                event.signature.as_str(),
+                event.timestamp,
+                serde_name(&event.kind),
+                event.amount_x,
+                event.amount_y,
+                event.liquidity.to_string(),
+                event.active_bin_id,
+                event.price_x,
+                event.price_y,
+            ]
+        })
+        .collect();
+    let cash_flow_rows = performance
+        .cash_flows
+        .iter()
+        .map(|flow| {
+            row![
+                flow.timestamp,
+                serde_name(&flow.kind),
+                flow.signature.as_str(),
+                flow.amount_x,
+                flow.amount_y,
+                flow.value_usd,
+            ]
+        })
+        .collect();
+    let bins = performance.bin_breakdown.as_ref().map(bin_rows);
+
+    ReportModel::new(
+        &POSITION_HISTORY_SCHEMA,
+        config,
+        sections,
+        vec![Some(event_rows), Some(cash_flow_rows), bins],
+    )
+}
+
+fn bin_rows(breakdown: &BinILBreakdown) -> Rows {
+    breakdown
+        .bins
+        .iter()
+        .map(|bin| {
+            row![
+                bin.bin_id,
+                bin.price,
+                bin.amount_x,
+                bin.amount_y,
+                bin.value_usd,
+                bin.hold_value_usd,
+                bin.il_usd_value,
+                bin.il_percentage,
+                bin.crossed,
+            ]
+        })
+        .collect()
+}
+
+/// Name a unit enum variant serializes to, so the JSON loads back into it
+fn serde_name<T: serde::Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+fn check_row(schema: &ReportSchema, group: &Group, values: &[FieldValue]) -> Result<()> {
+    if values.len() != group.columns.len() {
+        return Err(anyhow!(
+            "{}.{} has {} values for {} columns",
+            schema.report_type,
+            group.key,
+            values.len(),
+            group.columns.len()
+        ));
+    }
+    for (column, value) in group.columns.iter().zip(values) {
+        let matches = match value {
+            FieldValue::Null => column.nullable,
+            FieldValue::Text(_) => column.kind == Text,
+            FieldValue::Integer(_) => column.kind == Integer,
+            FieldValue::Decimal(_) => matches!(column.kind, Number | Usd | Ratio),
+            FieldValue::Timestamp(_) => column.kind == Timestamp,
+            FieldValue::Boolean(_) => column.kind == Boolean,
+        };
+        if !matches {
+            return Err(anyhow!(
+                "{}.{}.{} expects {:?}, got {:?}",
+                schema.report_type,
+                group.key,
+                column.key,
+                column.kind,
+                value
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn json_object(group: &Group, values: &[FieldValue]) -> Value {
+    Value::Object(
+        group
+            .columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| (column.key.to_string(), json_value(value)))
+            .collect(),
+    )
+}
+
+fn json_value(value: &FieldValue) -> Value {
+    match value {
+        FieldValue::Null => Value::Null,
+        FieldValue::Text(text) => json!(text),
+        FieldValue::Integer(integer) => json!(integer),
+        FieldValue::Decimal(decimal) => decimal
+            .to_f64()
+            .and_then(serde_json::Number::from_f64)
+            .map_or(Value::Null, Value::Number),
+        FieldValue::Timestamp(timestamp) => json!(format_timestamp(*timestamp)),
+        FieldValue::Boolean(boolean) => json!(boolean),
+    }
+}
+
+fn object_schema(group: &Group) -> Value {
+    let properties: Map<String, Value> = group
+        .columns
+        .iter()
+        .map(|column| {
+            let mut property = match column.kind {
+                Text => json!({ "type": "string" }),
+                Integer => json!({ "type": "integer" }),
+                Number | Usd | Ratio => json!({ "type": "number" }),
+                Timestamp => json!({ "type": "string", "format": "date-time" }),
+                Boolean => json!({ "type": "boolean" }),
+            };
+            if column.nullable {
+                let kind = property["type"].clone();
+                property["type"] = json!([kind, "null"]);
+            }
+            property["description"] = json!(match column.kind {
+                Usd => format!("{} (USD)", column.label),
+                Ratio => format!("{} (fraction, 0.05 = 5%)", column.label),
+                _ => column.label.to_string(),
+            });
+            (column.key.to_string(), property)
+        })
+        .collect();
+
+    json!({
+        "description": group.title,
+        "type": "object",
+        "required": group.columns.iter().map(|column| column.key).collect::<Vec<_>>(),
+        "properties": properties,
+        "additionalProperties": false
+    })
+}
+
+fn csv_value(value: &FieldValue) -> String {
+    match value {
+        FieldValue::Null => String::new(),
+        FieldValue::Text(text) => text.clone(),
+        FieldValue::Integer(integer) => integer.to_string(),
+        FieldValue::Decimal(decimal) => decimal.normalize().to_string(),
+        FieldValue::Timestamp(timestamp) => format_timestamp(*timestamp),
+        FieldValue::Boolean(boolean) => boolean.to_string(),
+    }
+}
+
+fn markdown_value(kind: ValueKind, value: &FieldValue) -> String {
+    escape_markdown(&display_value(kind, value))
+}
+
+fn html_cell(kind: ValueKind, value: &FieldValue) -> String {
+    let negative = matches!((kind, value), (Usd | Ratio, FieldValue::Decimal(decimal)) if decimal.is_sign_negative());
+    let class = match (kind, negative) {
+        (Text | Timestamp | Boolean, _) => "",
+        (_, true) => " class=\"num negative\"",
+        (_, false) => " class=\"num\"",
+    };
+    format!("<td{}>{}</td>", class, escape_html(&display_value(kind, value)))
+}
+
+/// Value as a person reads it: currency, percentages and UTC times
+fn display_value(kind: ValueKind, value: &FieldValue) -> String {
+    match (kind, value) {
+        (_, FieldValue::Null) => "—".to_string(),
+        (Usd, FieldValue::Decimal(amount)) if amount.is_sign_negative() => format!("-${:.2}", amount.abs()),
+        (Usd, FieldValue::Decimal(amount)) => format!("${:.2}", amount),
+        (Ratio, FieldValue::Decimal(fraction)) => format!("{:.2}%", fraction * Decimal::new(100, 0)),
+        (_, FieldValue::Decimal(decimal)) => decimal.round_dp(6).normalize().to_string(),
+        (_, FieldValue::Text(text)) => text.clone(),
+        (_, FieldValue::Integer(integer)) => integer.to_string(),
+        (_, FieldValue::Timestamp(timestamp)) => timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        (_, FieldValue::Boolean(boolean)) => if *boolean { "yes" } else { "no" }.to_string(),
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report_generator::tests::create_mock_analysis;
+    use rust_decimal_macros::dec;
+    use std::collections::HashSet;
+
+    fn config() -> ReportConfig {
+        ReportConfig {
+            title: "Model | Test".to_string(),
+            include_charts: false,
+            include_raw_data: true,
+            timestamp: DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn test_schema_keys_are_unique_and_published() {
+        for schema in SCHEMAS {
+            let groups: Vec<&Group> = schema.sections.iter().chain(schema.tables).collect();
+            let group_keys: HashSet<&str> = groups.iter().map(|group| group.key).collect();
+            assert_eq!(group_keys.len(), groups.len(), "{}", schema.report_type);
+            assert!(!group_keys.contains("report_info"));
+            for group in groups {
+                let keys: HashSet<&str> = group.columns.iter().map(|column| column.key).collect();
+                assert_eq!(keys.len(), group.columns.len(), "{}.{}", schema.report_type, group.key);
+            }
+            if schema.csv_table.is_none() {
+                // Section fields share one CSV row
+                let columns: Vec<&str> = schema.sections.iter().flat_map(|group| group.columns).map(|column| column.key).collect();
+                assert_eq!(columns.iter().collect::<HashSet<_>>().len(), columns.len());
+            }
+
+            let published = std::fs::read_to_string(
+                std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas").join(schema.file_name()),
+            )
+            .expect("schema file is published under schemas/");
+            let published: Value = serde_json::from_str(&published).unwrap();
+            assert_eq!(published, schema.json_schema(), "regenerate with `il_calc schema`");
+        }
+    }
+
+    #[test]
+    fn test_every_format_renders_the_same_fields() {
+        let mut analysis = create_mock_analysis();
+        analysis.performance_summary.vs_market_performance = None;
+        let model = position_report(&analysis, &analysis.il_result, &config()).unwrap();
+
+        let document = model.to_json();
+        assert_eq!(document["report_info"]["schema_version"], 1);
+        assert_eq!(document["impermanent_loss"]["il_percentage"], json!(-0.025));
+        assert_eq!(document["performance_metrics"]["vs_market_performance"], Value::Null);
+        assert_eq!(document["position_details"]["pool_address"], json!(analysis.position_info.pool_address.to_string()));
+        assert!(document.get("bins").is_none());
+
+        // The JSON carries exactly the schema's fields
+        let schema = POSITION_SCHEMA.json_schema();
+        for group in POSITION_SCHEMA.sections {
+            let keys: HashSet<&String> = document[group.key].as_object().unwrap().keys().collect();
+            let schema_keys: HashSet<&String> = schema["properties"][group.key]["properties"].as_object().unwrap().keys().collect();
+            assert_eq!(keys, schema_keys);
+        }
+
+        let csv = model.to_csv().unwrap();
+        let mut lines = csv.lines();
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(&header[..4], ["report_type", "schema_version", "generated_at", "pool_address"]);
+        assert_eq!(header.len(), 3 + POSITION_SCHEMA.sections.iter().map(|group| group.columns.len()).sum::<usize>());
+        let values: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(values.len(), header.len());
+        assert_eq!(values[header.iter().position(|key| *key == "il_percentage").unwrap()], "-0.025");
+        assert!(lines.next().is_none());
+
+        let markdown = model.to_markdown();
+        assert!(markdown.starts_with("# Model \\| Test\n"));
+        assert!(markdown.contains("| Impermanent loss | -2.50% |"));
+        assert!(markdown.contains("| Performance vs market | — |"));
+    }
+
+    #[test]
+    fn test_model_rejects_values_that_do_not_match_the_schema() {
+        let mut sections: Vec<Vec<FieldValue>> = POSITION_SCHEMA
+            .sections
+            .iter()
+            .map(|group| {
+                group
+                    .columns
+                    .iter()
+                    .map(|column| match column.kind {
+                        Text => FieldValue::from("x"),
+                        Integer => FieldValue::from(1),
+                        Number | Usd | Ratio => FieldValue::from(dec!(1)),
+                        Timestamp => FieldValue::from(Utc::now()),
+                        Boolean => FieldValue::from(true),
+                    })
+                    .collect()
+            })
+            .collect();
+        assert!(ReportModel::new(&POSITION_SCHEMA, &config(), sections.clone(), vec![None]).is_ok());
+
+        // pool_address is not nullable
+        sections[0][0] = FieldValue::Null;
+        assert!(ReportModel::new(&POSITION_SCHEMA, &config(), sections.clone(), vec![None]).is_err());
+        sections[0][0] = FieldValue::from(dec!(1));
+        assert!(ReportModel::new(&POSITION_SCHEMA, &config(), sections.clone(), vec![None]).is_err());
+        sections[0].pop();
+        assert!(ReportModel::new(&POSITION_SCHEMA, &config(), sections, vec![None]).is_err());
+    }
+}
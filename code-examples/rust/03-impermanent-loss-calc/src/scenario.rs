@@ -0,0 +1,516 @@
+//! Forward-looking IL scenarios and stress tests
+//!
+//! Evaluates a position's IL, fee income and net PnL at prices it hasn't seen yet:
+//! a grid of ±X% shocks, an explicit price path, or Monte Carlo paths under geometric
+//! Brownian motion. Token Y is held at its entry price and token X moves, so every
+//! scenario is a move in the pool's X/Y price. Fees accrue only while the active bin is
+//! inside the position's range, either at a flat daily rate on the entry value or on the
+//! liquidity the position holds in whichever bin is active.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::bin_model::{self, LiquidityDistribution, PositionBins};
+use crate::types::ILError;
+
+/// Percentiles reported for Monte Carlo outcomes
+pub const PERCENTILES: [u8; 5] = [5, 25, 50, 75, 95];
+
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// Position outcome at one future price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioPoint {
+    /// Move in token X's price from entry (e.g., -0.1 for -10%)
+    pub price_change: Decimal,
+    pub price_x: Decimal,
+    pub price_y: Decimal,
+    pub active_bin_id: i32,
+    pub in_range: bool,
+    pub value_usd: Decimal,
+    pub hold_value_usd: Decimal,
+    pub il_usd_value: Decimal,
+    pub il_percentage: Decimal,
+    /// Days the active bin spent inside the range, earning fees
+    pub days_in_range: Decimal,
+    pub fee_income_usd: Decimal,
+    /// Position value plus fees, less the entry value
+    pub net_pnl_usd: Decimal,
+    /// Fees less IL: positive when providing liquidity beat holding
+    pub net_vs_hold_usd: Decimal,
+}
+
+/// Monte Carlo simulation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloConfig {
+    pub paths: u32,
+    pub horizon_days: u32,
+    pub steps_per_day: u32,
+    /// Annualized volatility of token X's price
+    pub volatility: Decimal,
+    /// Annualized drift of token X's price
+    pub drift: Decimal,
+    pub seed: u64,
+}
+
+/// Outcome distribution at a given percentile. Each metric is its own percentile, so a
+/// band doesn't describe a single path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PercentileBand {
+    pub percentile: u8,
+    pub price_x: Decimal,
+    pub il_percentage: Decimal,
+    pub il_usd_value: Decimal,
+    pub fee_income_usd: Decimal,
+    pub net_pnl_usd: Decimal,
+    pub net_vs_hold_usd: Decimal,
+}
+
+/// Summary of Monte Carlo outcomes at the horizon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloResult {
+    pub paths: u32,
+    pub horizon_days: u32,
+    pub volatility: Decimal,
+    /// Share of paths on which the active bin left the range at least once
+    pub probability_exit_range: Decimal,
+    /// Share of paths that end outside the range
+    pub probability_out_of_range_at_horizon: Decimal,
+    /// Share of paths on which fees covered IL
+    pub probability_fees_cover_il: Decimal,
+    pub expected_days_in_range: Decimal,
+    pub expected_il_usd: Decimal,
+    pub expected_fee_income_usd: Decimal,
+    pub expected_net_pnl_usd: Decimal,
+    /// Days of expected fee income needed to offset the expected IL
+    pub breakeven_days: Option<Decimal>,
+    pub percentiles: Vec<PercentileBand>,
+}
+
+/// Scenario analysis written to disk by the `scenario` mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioReport {
+    pub pool_address: Pubkey,
+    pub generated_at: DateTime<Utc>,
+    pub entry_price_x: Decimal,
+    pub entry_price_y: Decimal,
+    pub bin_step: u16,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub entry_active_bin_id: i32,
+    pub distribution: LiquidityDistribution,
+    pub daily_fee_rate: Decimal,
+    pub entry_value_usd: Decimal,
+    pub shocks: Vec<ScenarioPoint>,
+    /// Outcomes along a supplied daily price path
+    pub path: Vec<ScenarioPoint>,
+    pub monte_carlo: Option<MonteCarloResult>,
+}
+
+/// Evaluates a position across future prices
+#[derive(Debug, Clone)]
+pub struct ScenarioEngine {
+    bins: PositionBins,
+    entry_price_x: Decimal,
+    entry_price_y: Decimal,
+    /// Fee income per day while each bin, from the lowest up, is the active bin
+    daily_fees: Vec<Decimal>,
+    lower_bin_id: i32,
+    upper_bin_id: i32,
+}
+
+impl ScenarioEngine {
+    /// Engine earning `daily_fee_rate` of the entry value per day while in range
+    pub fn new(
+        bins: PositionBins,
+        entry_prices: (Decimal, Decimal),
+        daily_fee_rate: Decimal,
+    ) -> Result<Self, ILError> {
+        let mut engine = Self::without_fees(bins, entry_prices, daily_fee_rate)?;
+        let daily_fees = engine.entry_value() * daily_fee_rate;
+        engine.daily_fees.iter_mut().for_each(|fees| *fees = daily_fees);
+        Ok(engine)
+    }
+
+    /// Engine earning `daily_fee_rate` per day on the entry value of whichever bin is
+    /// active, so concentrated liquidity earns more while the price stays near it
+    pub fn with_active_bin_fees(
+        bins: PositionBins,
+        entry_prices: (Decimal, Decimal),
+        daily_fee_rate: Decimal,
+    ) -> Result<Self, ILError> {
+        let mut engine = Self::without_fees(bins, entry_prices, daily_fee_rate)?;
+        for bin in &engine.bins.bins {
+            let index = (bin.bin_id - engine.lower_bin_id) as usize;
+            engine.daily_fees[index] += (bin.amount_x * engine.entry_price_x + bin.amount_y * engine.entry_price_y) * daily_fee_rate;
+        }
+        Ok(engine)
+    }
+
+    fn without_fees(
+        bins: PositionBins,
+        entry_prices: (Decimal, Decimal),
+        daily_fee_rate: Decimal,
+    ) -> Result<Self, ILError> {
+        let (entry_price_x, entry_price_y) = entry_prices;
+        if entry_price_x <= Decimal::ZERO || entry_price_y <= Decimal::ZERO {
+            return Err(ILError::PriceDataUnavailable("Entry prices must be positive".to_string()));
+        }
+        if daily_fee_rate < Decimal::ZERO {
+            return Err(ILError::CalculationError("Daily fee rate must not be negative".to_string()));
+        }
+
+        let (Some(lower_bin_id), Some(upper_bin_id)) = (
+            bins.bins.iter().map(|bin| bin.bin_id).min(),
+            bins.bins.iter().map(|bin| bin.bin_id).max(),
+        ) else {
+            return Err(ILError::InvalidPosition("Position has no bins".to_string()));
+        };
+
+        let daily_fees = vec![Decimal::ZERO; (upper_bin_id - lower_bin_id + 1) as usize];
+        Ok(Self { bins, entry_price_x, entry_price_y, daily_fees, lower_bin_id, upper_bin_id })
+    }
+
+    /// Value of the deposit at entry prices
+    pub fn entry_value(&self) -> Decimal {
+        self.bins.bins
+            .iter()
+            .map(|bin| bin.amount_x * self.entry_price_x + bin.amount_y * self.entry_price_y)
+            .sum()
+    }
+
+    /// Fee income for one day with `active_bin_id` active
+    pub fn daily_fee_income(&self, active_bin_id: i32) -> Decimal {
+        if self.in_range(active_bin_id) {
+            self.daily_fees[(active_bin_id - self.lower_bin_id) as usize]
+        } else {
+            Decimal::ZERO
+        }
+    }
+
+    /// Outcome at `price_x` after `days_in_range` days of fee income at that price
+    pub fn evaluate(&self, price_x: Decimal, days_in_range: Decimal) -> ScenarioPoint {
+        let fee_income_usd = self.daily_fee_income(self.active_bin_id(price_x)) * days_in_range;
+        self.evaluate_with_fees(price_x, days_in_range, fee_income_usd)
+    }
+
+    fn evaluate_with_fees(&self, price_x: Decimal, days_in_range: Decimal, fee_income_usd: Decimal) -> ScenarioPoint {
+        let price_y = self.entry_price_y;
+        let price_ratio = price_x / price_y;
+        let active_bin_id = self.active_bin_id(price_x);
+        let breakdown = self.bins.reprice(
+            active_bin_id,
+            bin_model::price_y_share(price_ratio, active_bin_id, self.bins.bin_step),
+            price_x,
+            price_y,
+        );
+
+        ScenarioPoint {
+            price_change: price_x / self.entry_price_x - Decimal::ONE,
+            price_x,
+            price_y,
+            active_bin_id,
+            in_range: self.in_range(active_bin_id),
+            value_usd: breakdown.value_usd,
+            hold_value_usd: breakdown.hold_value_usd,
+            il_usd_value: breakdown.il_usd_value,
+            il_percentage: breakdown.il_percentage,
+            days_in_range,
+            fee_income_usd,
+            net_pnl_usd: breakdown.value_usd + fee_income_usd - self.entry_value(),
+            net_vs_hold_usd: breakdown.il_usd_value + fee_income_usd,
+        }
+    }
+
+    /// Evaluate instant price shocks, each held for `horizon_days`. Shocks at or below
+    /// -100% are skipped.
+    pub fn stress_test(&self, shocks: &[Decimal], horizon_days: u32) -> Vec<ScenarioPoint> {
+        shocks
+            .iter()
+            .filter(|shock| **shock > -Decimal::ONE)
+            .map(|shock| {
+                let price_x = self.entry_price_x * (Decimal::ONE + shock);
+                let days_in_range = if self.in_range(self.active_bin_id(price_x)) {
+                    Decimal::from(horizon_days)
+                } else {
+                    Decimal::ZERO
+                };
+                self.evaluate(price_x, days_in_range)
+            })
+            .collect()
+    }
+
+    /// Walk a path of token X prices, `step_days` apart. Each point carries the fees
+    /// earned on the way there.
+    pub fn evaluate_path(&self, prices_x: &[Decimal], step_days: Decimal) -> Vec<ScenarioPoint> {
+        let mut days_in_range = Decimal::ZERO;
+        let mut fee_income_usd = Decimal::ZERO;
+        prices_x
+            .iter()
+            .enumerate()
+            .map(|(step, &price_x)| {
+                let active_bin_id = self.active_bin_id(price_x);
+                if step > 0 && self.in_range(active_bin_id) {
+                    days_in_range += step_days;
+                    fee_income_usd += self.daily_fee_income(active_bin_id) * step_days;
+                }
+                self.evaluate_with_fees(price_x, days_in_range, fee_income_usd)
+            })
+            .collect()
+    }
+
+    /// Simulate token X's price under geometric Brownian motion and summarize the
+    /// outcomes at the horizon
+    pub fn monte_carlo(&self, config: &MonteCarloConfig) -> Result<MonteCarloResult, ILError> {
+        if config.paths == 0 || config.horizon_days == 0 || config.steps_per_day == 0 {
+            return Err(ILError::CalculationError("Monte Carlo needs paths, horizon and steps".to_string()));
+        }
+        let volatility = config.volatility.to_f64().unwrap_or(0.0);
+        if volatility < 0.0 {
+            return Err(ILError::CalculationError("Volatility must not be negative".to_string()));
+        }
+
+        let drift = config.drift.to_f64().unwrap_or(0.0);
+        let steps = config.horizon_days * config.steps_per_day;
+        let dt = 1.0 / (DAYS_PER_YEAR * config.steps_per_day as f64);
+        let step_days = 1.0 / config.steps_per_day as f64;
+        let log_drift = (drift - 0.5 * volatility * volatility) * dt;
+        let log_diffusion = volatility * dt.sqrt();
+
+        let entry_price_x = self.entry_price_x.to_f64().unwrap_or(0.0);
+        let entry_price_y = self.entry_price_y.to_f64().unwrap_or(1.0);
+        let step_fees: Vec<f64> = self.daily_fees
+            .iter()
+            .map(|fees| fees.to_f64().unwrap_or(0.0) * step_days)
+            .collect();
+        let mut rng = fastrand::Rng::with_seed(config.seed);
+
+        let mut outcomes = Vec::with_capacity(config.paths as usize);
+        let mut exited_paths = 0u32;
+        for _ in 0..config.paths {
+            let mut price_x = entry_price_x;
+            let mut in_range_steps = 0u32;
+            let mut fee_income = 0.0;
+            let mut exited = false;
+
+            for _ in 0..steps {
+                price_x *= (log_drift + log_diffusion * standard_normal(&mut rng)).exp();
+                let active_bin_id = saros_dlmm_sdk::bin_math::price_to_bin_id(
+                    price_x / entry_price_y,
+                    self.bins.bin_step,
+                    0,
+                );
+                if self.in_range(active_bin_id) {
+                    in_range_steps += 1;
+                    fee_income += step_fees[(active_bin_id - self.lower_bin_id) as usize];
+                } else {
+                    exited = true;
+                }
+            }
+
+            if exited {
+                exited_paths += 1;
+            }
+            let days_in_range = Decimal::from_f64(in_range_steps as f64 * step_days).unwrap_or_default();
+            outcomes.push(self.evaluate_with_fees(
+                Decimal::from_f64(price_x).unwrap_or_default(),
+                days_in_range,
+                Decimal::from_f64(fee_income).unwrap_or_default(),
+            ));
+        }
+
+        let count = Decimal::from(config.paths);
+        let share = |matching: usize| Decimal::from(matching) / count;
+        let mean = |metric: fn(&ScenarioPoint) -> Decimal| outcomes.iter().map(metric).sum::<Decimal>() / count;
+
+        let expected_il_usd = mean(|point| point.il_usd_value);
+        let expected_fee_income_usd = mean(|point| point.fee_income_usd);
+        let expected_daily_fees = expected_fee_income_usd / Decimal::from(config.horizon_days);
+        let breakeven_days = if expected_daily_fees > Decimal::ZERO {
+            Some(expected_il_usd.min(Decimal::ZERO).abs() / expected_daily_fees)
+        } else {
+            None
+        };
+
+        let percentiles = PERCENTILES
+            .iter()
+            .map(|&percentile| PercentileBand {
+                percentile,
+                price_x: percentile_of(&outcomes, percentile, |point| point.price_x),
+                il_percentage: percentile_of(&outcomes, percentile, |point| point.il_percentage),
+                il_usd_value: percentile_of(&outcomes, percentile, |point| point.il_usd_value),
+                fee_income_usd: percentile_of(&outcomes, percentile, |point| point.fee_income_usd),
+                net_pnl_usd: percentile_of(&outcomes, percentile, |point| point.net_pnl_usd),
+                net_vs_hold_usd: percentile_of(&outcomes, percentile, |point| point.net_vs_hold_usd),
+            })
+            .collect();
+
+        Ok(MonteCarloResult {
+            paths: config.paths,
+            horizon_days: config.horizon_days,
+            volatility: config.volatility,
+            probability_exit_range: share(exited_paths as usize),
+            probability_out_of_range_at_horizon: share(outcomes.iter().filter(|point| !point.in_range).count()),
+            probability_fees_cover_il: share(outcomes.iter().filter(|point| point.net_vs_hold_usd >= Decimal::ZERO).count()),
+            expected_days_in_range: mean(|point| point.days_in_range),
+            expected_il_usd,
+            expected_fee_income_usd,
+            expected_net_pnl_usd: mean(|point| point.net_pnl_usd),
+            breakeven_days,
+            percentiles,
+        })
+    }
+
+    fn active_bin_id(&self, price_x: Decimal) -> i32 {
+        bin_model::price_to_bin_id(price_x / self.entry_price_y, self.bins.bin_step)
+    }
+
+    fn in_range(&self, active_bin_id: i32) -> bool {
+        (self.lower_bin_id..=self.upper_bin_id).contains(&active_bin_id)
+    }
+}
+
+/// Symmetric shocks from `-max_shock` to `+max_shock` in `steps` increments each way
+pub fn shock_grid(max_shock: Decimal, steps: u32) -> Vec<Decimal> {
+    if steps == 0 {
+        return vec![Decimal::ZERO];
+    }
+    let increment = max_shock.abs() / Decimal::from(steps);
+    (-(steps as i64)..=steps as i64)
+        .map(|step| increment * Decimal::from(step))
+        .collect()
+}
+
+/// Standard normal draw by the Box-Muller transform
+fn standard_normal(rng: &mut fastrand::Rng) -> f64 {
+    let u1 = 1.0 - rng.f64(); // (0, 1], keeps ln finite
+    let u2 = rng.f64();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Nearest-rank percentile of one metric
+fn percentile_of(outcomes: &[ScenarioPoint], percentile: u8, metric: fn(&ScenarioPoint) -> Decimal) -> Decimal {
+    let mut values: Vec<Decimal> = outcomes.iter().map(metric).collect();
+    if values.is_empty() {
+        return Decimal::ZERO;
+    }
+    values.sort();
+    let rank = (percentile as usize * (values.len() - 1) + 50) / 100;
+    values[rank.min(values.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn engine(daily_fee_rate: Decimal) -> ScenarioEngine {
+        let bin_step = 20;
+        let entry_active_bin_id = bin_model::price_to_bin_id(dec!(100), bin_step);
+        let bins = PositionBins::uniform(
+            entry_active_bin_id - 10,
+            entry_active_bin_id + 10,
+            entry_active_bin_id,
+            bin_step,
+            dec!(10),
+            dec!(1000),
+        ).unwrap();
+        ScenarioEngine::new(bins, (dec!(100), dec!(1)), daily_fee_rate).unwrap()
+    }
+
+    #[test]
+    fn test_shock_grid_is_symmetric() {
+        let grid = shock_grid(dec!(0.2), 2);
+        assert_eq!(grid, vec![dec!(-0.2), dec!(-0.1), dec!(0), dec!(0.1), dec!(0.2)]);
+    }
+
+    #[test]
+    fn test_stress_test_charges_il_both_ways_and_stops_fees_out_of_range() {
+        let engine = engine(dec!(0.001));
+        let points = engine.stress_test(&shock_grid(dec!(0.3), 3), 30);
+
+        let unchanged = &points[3];
+        assert!(unchanged.in_range);
+        assert!(unchanged.il_usd_value.abs() < dec!(0.5));
+        assert_eq!(unchanged.fee_income_usd, engine.daily_fee_income(unchanged.active_bin_id) * dec!(30));
+
+        // ±30% is well outside a ±10 bin range at 20 bps
+        for point in [&points[0], &points[6]] {
+            assert!(!point.in_range);
+            assert_eq!(point.fee_income_usd, Decimal::ZERO);
+            assert!(point.il_usd_value < Decimal::ZERO);
+        }
+        assert!(points[0].il_usd_value < points[1].il_usd_value);
+    }
+
+    #[test]
+    fn test_path_accrues_fees_only_while_in_range() {
+        let engine = engine(dec!(0.001));
+        let path = [dec!(100), dec!(101), dec!(150), dec!(100)];
+        let points = engine.evaluate_path(&path, Decimal::ONE);
+
+        let days: Vec<Decimal> = points.iter().map(|point| point.days_in_range).collect();
+        assert_eq!(days, vec![dec!(0), dec!(1), dec!(1), dec!(2)]);
+    }
+
+    #[test]
+    fn test_active_bin_fees_reward_concentration() {
+        let active = bin_model::price_to_bin_id(dec!(100), 20);
+        let position = |half_width| {
+            let bins = PositionBins::uniform(active - half_width, active + half_width, active, 20, dec!(10), dec!(1000)).unwrap();
+            ScenarioEngine::with_active_bin_fees(bins, (dec!(100), dec!(1)), dec!(0.01)).unwrap()
+        };
+
+        let (narrow, wide) = (position(2), position(20));
+        assert!(narrow.daily_fee_income(active) > wide.daily_fee_income(active) * dec!(5));
+        assert_eq!(narrow.daily_fee_income(active + 3), Decimal::ZERO);
+
+        let total: Decimal = (active - 2..=active + 2).map(|bin_id| narrow.daily_fee_income(bin_id)).sum();
+        assert!((total - narrow.entry_value() * dec!(0.01)).abs() < dec!(0.000001));
+    }
+
+    #[test]
+    fn test_monte_carlo_is_reproducible_and_widens_with_volatility() {
+        let engine = engine(dec!(0.002));
+        let config = MonteCarloConfig {
+            paths: 200,
+            horizon_days: 14,
+            steps_per_day: 1,
+            volatility: dec!(0.3),
+            drift: Decimal::ZERO,
+            seed: 7,
+        };
+
+        let calm = engine.monte_carlo(&config).unwrap();
+        let again = engine.monte_carlo(&config).unwrap();
+        assert_eq!(calm.expected_il_usd, again.expected_il_usd);
+        assert_eq!(calm.percentiles.len(), PERCENTILES.len());
+        assert!(calm.percentiles[0].price_x <= calm.percentiles[4].price_x);
+
+        let stormy = engine.monte_carlo(&MonteCarloConfig { volatility: dec!(1.5), ..config }).unwrap();
+        assert!(stormy.probability_exit_range > calm.probability_exit_range);
+        assert!(stormy.expected_il_usd < calm.expected_il_usd);
+        assert!(stormy.expected_days_in_range < calm.expected_days_in_range);
+    }
+
+    #[test]
+    fn test_zero_volatility_never_leaves_range() {
+        let engine = engine(dec!(0.001));
+        let config = MonteCarloConfig {
+            paths: 10,
+            horizon_days: 10,
+            steps_per_day: 2,
+            volatility: Decimal::ZERO,
+            drift: Decimal::ZERO,
+            seed: 1,
+        };
+
+        let result = engine.monte_carlo(&config).unwrap();
+        assert_eq!(result.probability_exit_range, Decimal::ZERO);
+        assert_eq!(result.expected_days_in_range, dec!(10));
+        assert_eq!(result.breakeven_days.map(|days| days < dec!(1)), Some(true));
+    }
+}
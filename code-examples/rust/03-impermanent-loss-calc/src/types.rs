@@ -2,18 +2,25 @@
 
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use saros_dlmm_sdk::{DLMMError, ErrorCategory};
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use std::collections::BTreeMap;
 
+use crate::bin_model::{BinILBreakdown, LiquidityDistribution};
+
 /// Analysis configuration for IL calculations
 #[derive(Debug, Clone)]
 pub struct AnalysisConfig {
     pub pool_address: Pubkey,
     pub position_id: Option<Pubkey>,
+    /// Every position given; `position_id` is the first
+    pub position_ids: Vec<Pubkey>,
     pub mode: MonitoringMode,
     pub interval_secs: u64,
     pub output_directory: String,
+    /// Also write report charts as standalone SVG files
+    pub export_charts: bool,
     pub enable_notifications: bool,
     pub max_price_deviation: Decimal,
     pub min_fee_threshold: Decimal,
@@ -21,6 +28,62 @@ pub struct AnalysisConfig {
     pub volatility_window: u32, // hours
 }
 
+/// Manually supplied position parameters for IL calculation
+#[derive(Debug, Clone)]
+pub struct ManualPosition {
+    pub initial_price_x: Decimal,
+    pub initial_price_y: Decimal,
+    pub initial_amount_x: Decimal,
+    pub initial_amount_y: Decimal,
+    /// Position bin range; without one the position is treated as full-range liquidity
+    pub bin_range: Option<(i32, i32)>,
+    /// Bin step override in basis points; defaults to the pool's
+    pub bin_step: Option<u16>,
+}
+
+/// Parameters for forward-looking scenario analysis
+#[derive(Debug, Clone)]
+pub struct ScenarioParams {
+    pub amount_x: Decimal,
+    pub amount_y: Decimal,
+    /// Position bin range; defaults to `range_bins` either side of the active bin
+    pub bin_range: Option<(i32, i32)>,
+    pub range_bins: i32,
+    pub bin_step: Option<u16>,
+    pub distribution: LiquidityDistribution,
+    /// Largest price shock to stress, as a fraction (e.g., 0.2 for ±20%)
+    pub max_shock: Decimal,
+    /// Daily token X prices to walk the position through
+    pub price_path: Vec<Decimal>,
+    pub horizon_days: u32,
+    pub paths: u32,
+    /// Annualized volatility override; defaults to the price monitor's estimate
+    pub volatility: Option<Decimal>,
+    /// Daily fee rate override; defaults to the pool's 24h fees over TVL
+    pub daily_fee_rate: Option<Decimal>,
+    pub seed: u64,
+}
+
+/// Parameters for the `optimize` subcommand
+#[derive(Debug, Clone)]
+pub struct OptimizeParams {
+    pub capital_usd: Decimal,
+    pub horizon_days: u32,
+    /// Largest acceptable 5th percentile loss against holding, as a fraction of capital
+    pub max_drawdown: Decimal,
+    /// Range half-widths to search, in bins either side of the active bin
+    pub half_widths: Vec<i32>,
+    pub paths: u32,
+    /// Annualized volatility override; defaults to the price monitor's estimate
+    pub volatility: Option<Decimal>,
+    /// Daily fee rate on active-bin liquidity; defaults to an estimate from pool fees and TVL
+    pub active_bin_daily_fee_rate: Option<Decimal>,
+    /// Bins the pool's TVL is assumed to be spread over when estimating fees
+    pub pool_width_bins: u32,
+    pub token_decimals: (u8, u8),
+    pub seed: u64,
+}
+
 /// Different monitoring modes for the IL calculator
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MonitoringMode {
@@ -30,6 +93,10 @@ pub enum MonitoringMode {
     RealTime,
     /// Historical data analysis
     Historical,
+    /// Forward-looking scenario and stress testing
+    Scenario,
+    /// Aggregate analysis across an owner's or a list of positions
+    Portfolio,
 }
 
 /// Comprehensive impermanent loss calculation result
@@ -57,6 +124,12 @@ pub struct ImpermanentLossResult {
     pub timestamp: DateTime<Utc>,
     /// Additional metadata
     pub metadata: ILMetadata,
+    /// Per-bin re-pricing of the position, when its bin range is known
+    #[serde(default)]
+    pub bin_breakdown: Option<BinILBreakdown>,
+    /// Fees claimed and unclaimed by the position up to `timestamp`, when its transactions are known
+    #[serde(default)]
+    pub fees_collected_usd: Option<Decimal>,
 }
 
 /// Additional metadata for IL calculations
@@ -246,6 +319,19 @@ pub enum ReportFormat {
     Json,
     Csv,
     Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// File extension of reports in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Html => "html",
+            ReportFormat::Markdown => "md",
+        }
+    }
 }
 
 impl std::fmt::Display for ReportFormat {
@@ -254,6 +340,7 @@ impl std::fmt::Display for ReportFormat {
             ReportFormat::Json => write!(f, "JSON"),
             ReportFormat::Csv => write!(f, "CSV"),
             ReportFormat::Html => write!(f, "HTML"),
+            ReportFormat::Markdown => write!(f, "Markdown"),
         }
     }
 }
@@ -304,13 +391,18 @@ pub struct PositionSnapshot {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ILNotificationEvent {
     pub event_type: ILEventType,
+    #[serde(with = "pubkey_string::option")]
     pub position_id: Option<Pubkey>,
+    #[serde(with = "pubkey_string")]
     pub pool_address: Pubkey,
     pub il_percentage: Decimal,
     pub threshold_crossed: Option<Decimal>,
     pub message: String,
     pub timestamp: DateTime<Utc>,
     pub severity: NotificationSeverity,
+    /// Name of the alert rule that fired the event
+    #[serde(default)]
+    pub rule: Option<String>,
 }
 
 /// Types of IL notification events
@@ -363,6 +455,42 @@ pub enum ILError {
     
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Sdk(#[from] DLMMError),
+}
+
+impl ILError {
+    /// Classification used to decide whether a failed calculation is worth retrying
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ILError::InvalidPosition(_) | ILError::CalculationError(_) => ErrorCategory::InvalidInput,
+            ILError::PriceDataUnavailable(_) | ILError::InsufficientData(_) | ILError::SerializationError(_) => {
+                ErrorCategory::Data
+            }
+            ILError::PoolNotFound(_) | ILError::PositionNotFound(_) => ErrorCategory::NotFound,
+            ILError::ReportError(_) => ErrorCategory::Unknown,
+            ILError::NetworkError(e) if e.status().is_some_and(|status| status.as_u16() == 429) => {
+                ErrorCategory::RateLimited
+            }
+            ILError::NetworkError(e) if e.is_timeout() => ErrorCategory::Timeout,
+            ILError::NetworkError(_) => ErrorCategory::Network,
+            ILError::Sdk(e) => e.category(),
+        }
+    }
+}
+
+/// Category of the first IL or SDK error in an `anyhow` chain
+pub fn il_error_category(error: &anyhow::Error) -> ErrorCategory {
+    error.chain()
+        .find_map(|cause| cause.downcast_ref::<ILError>())
+        .map_or_else(|| saros_dlmm_sdk::error_category(error), ILError::category)
+}
+
+/// Abbreviated address for terminal output and alert messages
+pub fn short_id(pubkey: &Pubkey) -> String {
+    let id = pubkey.to_string();
+    format!("{}…{}", &id[..4], &id[id.len() - 4..])
 }
 
 /// Result type for IL operations
@@ -375,8 +503,78 @@ pub struct AggregateStatistics {
     pub total_tvl: Decimal,
     pub average_il: Decimal,
     pub total_fees_earned: Decimal,
+    /// Pool with the best risk-adjusted return
+    #[serde(with = "pubkey_string::option")]
     pub best_performing_pool: Option<Pubkey>,
+    /// Pool with the worst risk-adjusted return
+    #[serde(with = "pubkey_string::option")]
     pub worst_performing_pool: Option<Pubkey>,
+    /// Correlation of daily returns between pools, keyed by pool address
     pub correlation_matrix: BTreeMap<String, BTreeMap<String, Decimal>>,
-    pub risk_adjusted_returns: BTreeMap<Pubkey, Decimal>,
-}
\ No newline at end of file
+    /// Annualized return over annualized volatility, keyed by pool address
+    pub risk_adjusted_returns: BTreeMap<String, Decimal>,
+    /// One-day 95% value at risk of the whole portfolio in USD, by historical simulation
+    pub portfolio_var_95: Decimal,
+    /// Sum of each pool's own VaR; the gap to `portfolio_var_95` is the diversification benefit
+    pub undiversified_var_95: Decimal,
+    /// Share of portfolio value held in each token, keyed by mint
+    pub token_exposure: BTreeMap<String, Decimal>,
+    /// Herfindahl index of token exposure: 1 when everything is in one token
+    pub exposure_concentration: Decimal,
+    /// Pools from best to worst risk-adjusted return
+    pub pool_rankings: Vec<PoolRanking>,
+}
+
+/// A pool's positions in a portfolio, ranked by risk-adjusted return
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolRanking {
+    pub rank: usize,
+    #[serde(with = "pubkey_string")]
+    pub pool_address: Pubkey,
+    pub positions: usize,
+    pub value_usd: Decimal,
+    pub il_usd_value: Decimal,
+    pub fees_earned: Decimal,
+    pub net_pnl: Decimal,
+    /// Value-weighted annualized return of the pool's positions
+    pub annualized_return: Decimal,
+    /// Annualized volatility of the positions' daily value changes
+    pub annualized_volatility: Decimal,
+    pub risk_adjusted_return: Decimal,
+}
+
+/// Serialize pubkeys as base58 strings so exported reports and histories can be edited by hand
+pub(crate) mod pubkey_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&pubkey.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Pubkey::from_str(&value).map_err(serde::de::Error::custom)
+    }
+
+    /// The same, for optional pubkeys
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+        use solana_sdk::pubkey::Pubkey;
+        use std::str::FromStr;
+
+        pub fn serialize<S: Serializer>(pubkey: &Option<Pubkey>, serializer: S) -> Result<S::Ok, S::Error> {
+            match pubkey {
+                Some(pubkey) => serializer.serialize_some(&pubkey.to_string()),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+            Option::<String>::deserialize(deserializer)?
+                .map(|value| Pubkey::from_str(&value).map_err(serde::de::Error::custom))
+                .transpose()
+        }
+    }
+}
@@ -11,8 +11,12 @@ use chrono::{DateTime, Duration, Utc};
 use log::{debug, error, info, warn};
 use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
-use saros_dlmm_sdk::{DLMMClient, DLMMError, DLMMResult, SwapParams, TransactionResult};
-use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use saros_dlmm_sdk::{
+    error_category, DLMMClient, DLMMError, DLMMResult, Ledger, LedgerEntry, LedgerEventKind, PriorityFeeConfig,
+    PriorityFeeEstimate, PriorityFeeEstimator, PriorityLevel, SharedSigner, SwapParams, TransactionErrorKind,
+    TransactionResult, TransactionSender,
+};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signature};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock, Semaphore};
@@ -37,8 +41,12 @@ pub struct ExecutionEngine {
     execution_semaphore: Arc<Semaphore>,
     /// Notification sender
     notification_sender: Option<mpsc::UnboundedSender<NotificationType>>,
-    /// Gas price optimizer
+    /// Priority fee optimizer
     gas_optimizer: GasOptimizer,
+    /// Sender and payer for on-chain execution (mock execution when unset)
+    transaction_sender: Option<(Arc<TransactionSender>, SharedSigner)>,
+    /// Accounting ledger of executed swaps and network fees
+    ledger: Arc<RwLock<Ledger>>,
 }
 
 /// Execution configuration
@@ -52,7 +60,7 @@ pub struct ExecutionConfig {
     pub max_retry_attempts: u32,
     /// Retry delay in milliseconds
     pub retry_delay_ms: u64,
-    /// Gas price strategy
+    /// Priority fee strategy
     pub gas_strategy: GasStrategy,
     /// Slippage protection
     pub enable_slippage_protection: bool,
@@ -64,18 +72,18 @@ pub struct ExecutionConfig {
     pub enable_smart_routing: bool,
 }
 
-/// Gas price strategy
+/// Priority fee strategy
 #[derive(Debug, Clone, PartialEq)]
 pub enum GasStrategy {
-    /// Use standard gas price
+    /// Median of recent priority fees on the order's pool and bin arrays
     Standard,
-    /// Use fast gas price (higher cost)
+    /// Upper percentile of recent priority fees (higher cost)
     Fast,
-    /// Use economic gas price (slower but cheaper)
+    /// Lower percentile of recent priority fees (slower but cheaper)
     Economic,
-    /// Dynamic gas price based on network conditions
+    /// Percentile chosen by signal urgency
     Dynamic,
-    /// Custom gas price
+    /// Fixed compute-unit price in micro-lamports
     Custom(u64),
 }
 
@@ -111,29 +119,11 @@ struct FailedExecution {
     pub original_signal: ExecutionSignal,
 }
 
-/// Gas price optimizer
-#[derive(Debug, Clone)]
+/// Priority fee optimizer, pricing from recent fees on the accounts an order touches
+#[derive(Clone)]
 struct GasOptimizer {
-    /// Recent gas prices
-    recent_gas_prices: Arc<RwLock<VecDeque<GasPrice>>>,
-    /// Network congestion indicator
-    network_congestion: Arc<RwLock<NetworkCongestion>>,
-}
-
-#[derive(Debug, Clone)]
-struct GasPrice {
-    pub timestamp: DateTime<Utc>,
-    pub standard: u64,
-    pub fast: u64,
-    pub economic: u64,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum NetworkCongestion {
-    Low,
-    Medium,
-    High,
-    Critical,
+    client: Arc<DLMMClient>,
+    estimator: Arc<PriorityFeeEstimator>,
 }
 
 /// Execution result with detailed metrics
@@ -168,94 +158,39 @@ impl Default for ExecutionConfig {
 }
 
 impl GasOptimizer {
-    fn new() -> Self {
-        Self {
-            recent_gas_prices: Arc::new(RwLock::new(VecDeque::new())),
-            network_congestion: Arc::new(RwLock::new(NetworkCongestion::Medium)),
-        }
+    fn new(client: Arc<DLMMClient>) -> Self {
+        let estimator = Arc::new(client.priority_fee_estimator(PriorityFeeConfig::default()));
+        Self { client, estimator }
     }
 
-    /// Update gas price data
-    async fn update_gas_prices(&self) -> Result<()> {
-        // In a real implementation, this would fetch from Solana RPC or gas tracking service
-        let mock_gas_price = GasPrice {
-            timestamp: Utc::now(),
-            standard: 5000,
-            fast: 10000,
-            economic: 2000,
-        };
-
-        let mut prices = self.recent_gas_prices.write().await;
-        prices.push_back(mock_gas_price);
-        
-        // Keep only recent prices (last hour)
-        let cutoff = Utc::now() - Duration::hours(1);
-        while let Some(front) = prices.front() {
-            if front.timestamp < cutoff {
-                prices.pop_front();
-            } else {
-                break;
-            }
-        }
-
-        Ok(())
-    }
-
-    /// Get optimal gas price based on strategy
-    async fn get_gas_price(&self, strategy: &GasStrategy, urgency: SignalUrgency) -> Result<u64> {
-        let prices = self.recent_gas_prices.read().await;
-        let latest = prices.back().ok_or_else(|| anyhow!("No gas price data available"))?;
-
-        let base_price = match strategy {
-            GasStrategy::Standard => latest.standard,
-            GasStrategy::Fast => latest.fast,
-            GasStrategy::Economic => latest.economic,
-            GasStrategy::Custom(price) => *price,
-            GasStrategy::Dynamic => {
-                // Dynamic pricing based on urgency and network conditions
-                let congestion = *self.network_congestion.read().await;
-                let base = match urgency {
-                    SignalUrgency::Critical => latest.fast,
-                    SignalUrgency::High => latest.standard,
-                    SignalUrgency::Medium => latest.standard,
-                    SignalUrgency::Low => latest.economic,
-                };
-
-                // Adjust for network congestion
-                let multiplier = match congestion {
-                    NetworkCongestion::Critical => 2.0,
-                    NetworkCongestion::High => 1.5,
-                    NetworkCongestion::Medium => 1.0,
-                    NetworkCongestion::Low => 0.8,
-                };
-
-                (base as f64 * multiplier) as u64
-            }
+    /// Priority fee for an order under the given strategy, at the default compute-unit limit
+    async fn get_priority_fee(
+        &self,
+        strategy: &GasStrategy,
+        urgency: &SignalUrgency,
+        order: &RangeOrder,
+    ) -> PriorityFeeEstimate {
+        let level = match strategy {
+            GasStrategy::Custom(price) => return PriorityFeeEstimate::fixed(*price),
+            GasStrategy::Standard => PriorityLevel::Standard,
+            GasStrategy::Fast => PriorityLevel::Fast,
+            GasStrategy::Economic => PriorityLevel::Economic,
+            GasStrategy::Dynamic => match urgency {
+                SignalUrgency::Critical | SignalUrgency::High => PriorityLevel::Fast,
+                SignalUrgency::Medium => PriorityLevel::Standard,
+                SignalUrgency::Low => PriorityLevel::Economic,
+            },
         };
 
-        Ok(base_price)
-    }
+        let accounts = self.client.priority_fee_accounts(&order.pool_address, &[order.bin_id]);
+        let estimate = self.estimator.estimate(&accounts, level).await;
 
-    /// Update network congestion based on recent execution times
-    async fn update_network_congestion(&self, recent_execution_times: &[u64]) {
-        if recent_execution_times.is_empty() {
-            return;
-        }
+        debug!(
+            "Priority fee for order {} ({}): {} micro-lamports/CU from {} samples",
+            order.id, level, estimate.compute_unit_price, estimate.samples
+        );
 
-        let avg_time = recent_execution_times.iter().sum::<u64>() / recent_execution_times.len() as u64;
-        
-        let congestion = if avg_time > 30000 {  // > 30 seconds
-            NetworkCongestion::Critical
-        } else if avg_time > 15000 {  // > 15 seconds
-            NetworkCongestion::High
-        } else if avg_time > 5000 {   // > 5 seconds
-            NetworkCongestion::Medium
-        } else {
-            NetworkCongestion::Low
-        };
-
-        let mut current_congestion = self.network_congestion.write().await;
-        *current_congestion = congestion;
+        estimate
     }
 }
 
@@ -268,6 +203,7 @@ impl ExecutionEngine {
         notification_sender: Option<mpsc::UnboundedSender<NotificationType>>,
     ) -> Self {
         Self {
+            gas_optimizer: GasOptimizer::new(client.clone()),
             client,
             bin_calculator,
             config: config.clone(),
@@ -276,10 +212,17 @@ impl ExecutionEngine {
             failed_executions: Arc::new(RwLock::new(HashMap::new())),
             execution_semaphore: Arc::new(Semaphore::new(config.max_concurrent_executions)),
             notification_sender,
-            gas_optimizer: GasOptimizer::new(),
+            transaction_sender: None,
+            ledger: Arc::new(RwLock::new(Ledger::new())),
         }
     }
 
+    /// Execute orders on-chain through a shared transaction sender, signing with `payer`
+    pub fn with_transaction_sender(mut self, sender: Arc<TransactionSender>, payer: SharedSigner) -> Self {
+        self.transaction_sender = Some((sender, payer));
+        self
+    }
+
     /// Start the execution engine
     pub async fn start(&self) -> Result<()> {
         info!("Starting execution engine with {} max concurrent executions", 
@@ -301,14 +244,6 @@ impl ExecutionEngine {
             }
         });
 
-        // Start gas price updater
-        let engine_clone = self.clone();
-        tokio::spawn(async move {
-            if let Err(e) = engine_clone.gas_price_update_loop().await {
-                error!("Gas price update loop error: {}", e);
-            }
-        });
-
         Ok(())
     }
 
@@ -318,10 +253,12 @@ impl ExecutionEngine {
         let order_id = order.id.clone();
         let signal_urgency = signal.urgency.clone();
         
-        let gas_price = self.gas_optimizer
-            .get_gas_price(&self.config.gas_strategy, signal.urgency.clone())
-            .await
-            .ok();
+        let gas_price = Some(
+            self.gas_optimizer
+                .get_priority_fee(&self.config.gas_strategy, &signal.urgency, &order)
+                .await
+                .compute_unit_price,
+        );
 
         let pending = PendingExecution {
             signal,
@@ -358,6 +295,11 @@ impl ExecutionEngine {
         self.execute_order_internal(signal, order, 0).await
     }
 
+    /// Accounting ledger of every executed order
+    pub async fn get_ledger(&self) -> Ledger {
+        self.ledger.read().await.clone()
+    }
+
     /// Get execution statistics
     pub async fn get_execution_stats(&self) -> ExecutionStats {
         let history = self.execution_history.read().await;
@@ -406,8 +348,6 @@ impl ExecutionEngine {
                 error!("Failed to process execution queue: {}", e);
             }
 
-            // Update network congestion based on recent executions
-            self.update_congestion_metrics().await;
         }
     }
 
@@ -482,25 +422,33 @@ impl ExecutionEngine {
             });
         }
 
-        // Get optimal gas price
-        let gas_price = self.gas_optimizer
-            .get_gas_price(&self.config.gas_strategy, signal.urgency.clone())
-            .await?;
+        // Price the priority fee from recent fees on the order's pool and bin array
+        let priority_fee = self.gas_optimizer
+            .get_priority_fee(&self.config.gas_strategy, &signal.urgency, &order)
+            .await;
 
         // Prepare swap parameters
-        let swap_params = self.prepare_swap_params(&order, gas_price).await?;
+        let swap_params = self.prepare_swap_params(&order, priority_fee.compute_unit_price).await?;
 
-        // Execute with timeout
+        // Execute with timeout. The swap may already have been sent when the deadline hits,
+        // so a timeout is an unknown outcome: `should_retry` won't re-queue it.
         let execution_result = timeout(
             TokioDuration::from_secs(self.config.execution_timeout_secs),
             self.execute_swap(swap_params, &order, &signal),
         )
-        .await;
+        .await
+        .unwrap_or_else(|_| {
+            Err(DLMMError::Transaction {
+                kind: TransactionErrorKind::Timeout,
+                message: format!("execution timed out after {}s", self.config.execution_timeout_secs),
+            }
+            .into())
+        });
 
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         match execution_result {
-            Ok(Ok(tx_result)) => {
+            Ok(tx_result) => {
                 // Successful execution
                 let result = ExecutionResult {
                     order_id: order.id,
@@ -535,6 +483,11 @@ impl ExecutionEngine {
                     success: true,
                 };
 
+                match self.ledger_entries(&order, &execution).await {
+                    Ok(entries) => self.ledger.write().await.extend(entries),
+                    Err(e) => warn!("Could not value order {} for the ledger: {}", order.id, e),
+                }
+
                 {
                     let mut history = self.execution_history.write().await;
                     history.push_back(completed);
@@ -560,13 +513,13 @@ impl ExecutionEngine {
 
                 Ok(result)
             }
-            Ok(Err(e)) => {
+            Err(e) => {
                 // Failed execution
-                let error_msg = e.to_string();
-                warn!("Failed to execute order {}: {}", order.id, error_msg);
+                let error_msg = format!("{:#}", e);
+                warn!("Failed to execute order {} ({}): {}", order.id, error_category(&e), error_msg);
 
-                // Handle retry
-                if retry_count < self.config.max_retry_attempts {
+                // Handle retry; terminal failures such as slippage or insufficient funds fail straight away
+                if self.should_retry(&e, retry_count) {
                     self.schedule_retry(signal, order.clone(), retry_count + 1, &error_msg).await?;
                     
                     // Return intermediate result for retry case
@@ -619,56 +572,6 @@ impl ExecutionEngine {
                         let _ = sender.send(notification);
                     }
 
-                    Ok(result)
-                }
-            }
-            Err(timeout_err) => {
-                // Execution timeout
-                let error_msg = "Execution timeout".to_string();
-
-                warn!("Failed to execute order {}: {}", order.id, error_msg);
-
-                // Handle retry
-                if retry_count < self.config.max_retry_attempts {
-                    self.schedule_retry(signal, order.clone(), retry_count + 1, &error_msg).await?;
-                    
-                    // Return intermediate result for retry case
-                    Ok(ExecutionResult {
-                        order_id: order.id,
-                        success: false,
-                        transaction_signature: None,
-                        executed_amount: Decimal::ZERO,
-                        execution_price: Decimal::ZERO,
-                        slippage_bps: 0,
-                        gas_fee: 0,
-                        execution_time_ms: execution_time,
-                        error: Some(format!("Timeout - retry scheduled: {}", error_msg)),
-                        mev_protection_triggered: false,
-                    })
-                } else {
-                    // Max retries reached, record as permanently failed
-                    let result = ExecutionResult {
-                        order_id: order.id,
-                        success: false,
-                        transaction_signature: None,
-                        executed_amount: Decimal::ZERO,
-                        execution_price: Decimal::ZERO,
-                        slippage_bps: 0,
-                        gas_fee: 0,
-                        execution_time_ms: execution_time,
-                        error: Some(error_msg.clone()),
-                        mev_protection_triggered: false,
-                    };
-
-                    // Send failure notification
-                    if let Some(sender) = &self.notification_sender {
-                        let notification = NotificationType::OrderFailed {
-                            order_id: order.id,
-                            error: error_msg,
-                        };
-                        let _ = sender.send(notification);
-                    }
-
                     Ok(result)
                 }
             }
@@ -691,30 +594,63 @@ impl ExecutionEngine {
 
         // Check slippage protection
         if self.config.enable_slippage_protection && signal.expected_slippage > Decimal::from_str("0.05").unwrap() {
-            return Err(anyhow!("Expected slippage {} exceeds protection threshold", signal.expected_slippage));
+            return Err(anyhow::Error::new(DLMMError::SlippageExceeded)
+                .context(format!("Expected slippage {} exceeds protection threshold", signal.expected_slippage)));
         }
 
         // Check minimum liquidity
         if signal.available_liquidity < Decimal::from(1000) {
-            return Err(anyhow!("Insufficient liquidity for execution: {}", signal.available_liquidity));
+            return Err(anyhow::Error::new(DLMMError::InsufficientLiquidity)
+                .context(format!("Insufficient liquidity for execution: {}", signal.available_liquidity)));
         }
 
         Ok(())
     }
 
     /// Prepare swap parameters for execution
-    async fn prepare_swap_params(&self, order: &RangeOrder, gas_price: u64) -> Result<SwapParams> {
+    async fn prepare_swap_params(&self, order: &RangeOrder, compute_unit_price: u64) -> Result<SwapParams> {
         // This would prepare the actual swap parameters for the DLMM SDK
         // For now, return a mock structure
         Ok(SwapParams {
             pool_address: order.pool_address,
             amount_in: order.amount,
             minimum_amount_out: order.amount * (Decimal::ONE - Decimal::from_str("0.005").unwrap()), // 0.5% slippage
-            gas_price: Some(gas_price),
+            gas_price: Some(compute_unit_price),
             slippage_bps: Some(50), // 0.5% in basis points
         })
     }
 
+    /// Ledger entries for an executed order, valued at current prices: the pool token sold,
+    /// the pool token bought and the network fee
+    async fn ledger_entries(&self, order: &RangeOrder, execution: &OrderExecution) -> DLMMResult<Vec<LedgerEntry>> {
+        let pool = self.client.get_pool(order.pool_address).await?;
+        let quote_price = self.client.get_token_price_usd(&pool.token_y).await?;
+        let sol_price = self.client.get_sol_price_usd().await?;
+
+        // Sell-side orders dispose of the base token (X) for the quote token (Y)
+        let sign = match order.order_type {
+            OrderType::LimitSell | OrderType::TakeProfit | OrderType::StopLoss => Decimal::NEGATIVE_ONE,
+            _ => Decimal::ONE,
+        };
+        let base_amount = execution.executed_amount;
+        let quote_amount = execution.executed_amount * execution.execution_price;
+        let fee_sol = Decimal::from(execution.gas_fee) / Decimal::from(LAMPORTS_PER_SOL);
+
+        let entries = [
+            LedgerEntry::new(LedgerEventKind::Swap, execution.executed_at, pool.token_x.to_string(),
+                             sign * base_amount, execution.execution_price * quote_price),
+            LedgerEntry::new(LedgerEventKind::Swap, execution.executed_at, pool.token_y.to_string(),
+                             -sign * quote_amount, quote_price),
+            LedgerEntry::new(LedgerEventKind::GasSpend, execution.executed_at, "SOL", -fee_sol, sol_price),
+        ];
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| !entry.amount.is_zero())
+            .map(|entry| entry.with_signature(&execution.signature).with_source(order.id.to_string()))
+            .collect())
+    }
+
     /// Execute the actual swap
     async fn execute_swap(
         &self,
@@ -722,10 +658,37 @@ impl ExecutionEngine {
         order: &RangeOrder,
         signal: &ExecutionSignal,
     ) -> Result<TransactionResult> {
-        // In a real implementation, this would call the DLMM SDK
-        // For now, return a mock successful transaction
-        
         debug!("Executing swap for order {} at bin {}", order.id, order.bin_id);
+
+        let priority_fee = PriorityFeeEstimate::fixed(params.gas_price.unwrap_or_default());
+
+        if let Some((sender, payer)) = &self.transaction_sender {
+            let transaction = self.client.swap_transaction(&params, &payer.pubkey()).await?;
+            // Size the compute-unit limit by simulation before paying the priority fee on it
+            let (transaction, priority_fee) = self.gas_optimizer.estimator
+                .prepare(&transaction, &payer.pubkey(), priority_fee)
+                .await?;
+            debug!(
+                "Order {} compute budget: {} CU at {} micro-lamports/CU",
+                order.id, priority_fee.compute_unit_limit, priority_fee.compute_unit_price
+            );
+
+            // Swaps that land but fail on-chain go through the normal failure/retry path
+            let outcome = sender
+                .send_transaction(&transaction, &[payer.as_ref()])
+                .await?
+                .into_result()?;
+
+            debug!("Swap for order {} landed in slot {}", order.id, outcome.slot);
+
+            return Ok(TransactionResult {
+                signature: outcome.signature,
+                gas_used: outcome.fee_lamports.unwrap_or_default(),
+                success: outcome.is_success(),
+            });
+        }
+
+        // Without a sender, return a mock successful transaction
         
         // Simulate execution time based on network conditions
         let execution_delay = match signal.urgency {
@@ -737,14 +700,20 @@ impl ExecutionEngine {
         
         sleep(TokioDuration::from_millis(execution_delay)).await;
         
-        // Mock successful result
+        // Mock successful result paying the base fee plus the priority fee
         Ok(TransactionResult {
             signature: solana_sdk::signature::Signature::new_unique(),
-            gas_used: params.gas_price.unwrap_or(5000),
+            gas_used: 5000 + priority_fee.priority_fee_lamports(),
             success: true,
         })
     }
 
+    /// Whether a failed execution should be retried: only transient failures (RPC outages,
+    /// rate limits, expired blockhashes, locked accounts) within the attempt limit
+    fn should_retry(&self, error: &anyhow::Error, retry_count: u32) -> bool {
+        retry_count < self.config.max_retry_attempts && error_category(error).is_retryable()
+    }
+
     /// Schedule retry for failed execution
     async fn schedule_retry(
         &self,
@@ -807,34 +776,6 @@ impl ExecutionEngine {
         Ok(())
     }
 
-    /// Gas price update loop
-    async fn gas_price_update_loop(&self) -> Result<()> {
-        let mut interval = tokio::time::interval(TokioDuration::from_secs(30));
-
-        loop {
-            interval.tick().await;
-            
-            if let Err(e) = self.gas_optimizer.update_gas_prices().await {
-                warn!("Failed to update gas prices: {}", e);
-            }
-        }
-    }
-
-    /// Update network congestion metrics
-    async fn update_congestion_metrics(&self) {
-        let history = self.execution_history.read().await;
-        let recent_times: Vec<u64> = history
-            .iter()
-            .rev()
-            .take(50)
-            .map(|e| e.execution_time_ms)
-            .collect();
-
-        if !recent_times.is_empty() {
-            self.gas_optimizer.update_network_congestion(&recent_times).await;
-        }
-    }
-
     /// Cancel pending execution
     pub async fn cancel_execution(&self, order_id: Uuid) -> Result<bool> {
         let mut queue = self.execution_queue.write().await;
@@ -876,6 +817,8 @@ impl Clone for ExecutionEngine {
             execution_semaphore: Arc::clone(&self.execution_semaphore),
             notification_sender: self.notification_sender.clone(),
             gas_optimizer: self.gas_optimizer.clone(),
+            transaction_sender: self.transaction_sender.clone(),
+            ledger: Arc::clone(&self.ledger),
         }
     }
 }
@@ -923,16 +866,103 @@ mod tests {
 
     #[tokio::test]
     async fn test_gas_optimizer() {
-        let optimizer = GasOptimizer::new();
-        
-        // Update with mock data
-        optimizer.update_gas_prices().await.unwrap();
-        
-        // Test gas price retrieval
-        let standard_price = optimizer.get_gas_price(&GasStrategy::Standard, SignalUrgency::Medium).await.unwrap();
-        let fast_price = optimizer.get_gas_price(&GasStrategy::Fast, SignalUrgency::Critical).await.unwrap();
-        
-        assert!(fast_price >= standard_price);
+        let client = Arc::new(saros_dlmm_sdk::DLMMClient::new("mock://test").unwrap());
+        let optimizer = GasOptimizer::new(client);
+        let order = RangeOrder {
+            id: Uuid::new_v4(),
+            pool_address: Pubkey::new_unique(),
+            order_type: OrderType::LimitBuy,
+            bin_id: 95,
+            amount: dec!(100),
+            target_price: dec!(95),
+            status: OrderStatus::Pending,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            filled_amount: Decimal::ZERO,
+            avg_fill_price: None,
+            position_id: None,
+            expires_at: None,
+            max_slippage_bps: 100,
+            strategy_id: None,
+        };
+
+        // Without recent fee samples every level falls back to the same price
+        let standard = optimizer.get_priority_fee(&GasStrategy::Standard, &SignalUrgency::Medium, &order).await;
+        let fast = optimizer.get_priority_fee(&GasStrategy::Fast, &SignalUrgency::Critical, &order).await;
+        assert!(fast.compute_unit_price >= standard.compute_unit_price);
+        assert_eq!(standard.samples, 0);
+
+        let custom = optimizer.get_priority_fee(&GasStrategy::Custom(42_000), &SignalUrgency::Low, &order).await;
+        assert_eq!(custom.compute_unit_price, 42_000);
+        assert_eq!(custom.priority_fee_lamports(), 8_400);
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_transient_failures() {
+        let client = Arc::new(saros_dlmm_sdk::DLMMClient::new("mock://test").unwrap());
+        let bin_calculator = Arc::new(BinCalculator::new(20, dec!(100)).unwrap());
+        let engine = ExecutionEngine::new(client, bin_calculator, ExecutionConfig::default(), None);
+
+        let transient = anyhow::Error::new(DLMMError::rpc_timeout("sendTransaction timed out"));
+        assert!(engine.should_retry(&transient, 0));
+        assert!(!engine.should_retry(&transient, engine.config.max_retry_attempts));
+
+        // A timed-out swap may have executed already
+        let unconfirmed = anyhow::Error::new(DLMMError::Transaction {
+            kind: TransactionErrorKind::Timeout,
+            message: "execution timed out".to_string(),
+        });
+        assert!(!engine.should_retry(&unconfirmed, 0));
+
+        let slippage = anyhow::Error::new(DLMMError::SlippageExceeded).context("swap failed");
+        assert!(!engine.should_retry(&slippage, 0));
+        assert!(!engine.should_retry(&anyhow!("unclassified failure"), 0));
+    }
+
+    #[tokio::test]
+    async fn test_ledger_entries_for_sell_order() {
+        let client = Arc::new(saros_dlmm_sdk::DLMMClient::new("mock://test").unwrap());
+        let bin_calculator = Arc::new(BinCalculator::new(20, dec!(100)).unwrap());
+        let engine = ExecutionEngine::new(client, bin_calculator, ExecutionConfig::default(), None);
+
+        let order = RangeOrder {
+            id: Uuid::new_v4(),
+            pool_address: Pubkey::new_unique(),
+            order_type: OrderType::TakeProfit,
+            bin_id: 105,
+            amount: dec!(2),
+            target_price: dec!(95),
+            status: OrderStatus::Pending,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            filled_amount: Decimal::ZERO,
+            avg_fill_price: None,
+            position_id: None,
+            expires_at: None,
+            max_slippage_bps: 100,
+            strategy_id: None,
+        };
+        let execution = OrderExecution {
+            order_id: order.id,
+            signature: "sig".to_string(),
+            executed_amount: dec!(2),
+            execution_price: dec!(95),
+            executed_at: Utc::now(),
+            gas_fee: 10_000,
+            slippage_bps: 0,
+        };
+
+        let entries = engine.ledger_entries(&order, &execution).await.unwrap();
+        assert_eq!(entries.len(), 3);
+
+        // The base token is sold for the quote token (priced at $1 by the mock oracle)
+        assert_eq!(entries[0].amount, dec!(-2));
+        assert_eq!(entries[0].usd_value, dec!(190));
+        assert_eq!(entries[1].amount, dec!(190));
+        assert_eq!(entries[1].usd_value, dec!(190));
+        assert_eq!(entries[2].kind, LedgerEventKind::GasSpend);
+        assert_eq!(entries[2].amount, dec!(-0.00001));
+        assert!(entries.iter().all(|entry| entry.signature == "sig" && entry.source == order.id.to_string()));
     }
 
     #[tokio::test]
@@ -14,14 +14,21 @@ use config::{Config, File as ConfigFile};
 use log::{error, info, warn};
 use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
+use saros_dlmm_sdk::wallet::{self, Keystore, DEFAULT_KEYSTORE_ITERATIONS};
+use saros_dlmm_sdk::{
+    CostBasisMethod, RpcConnection, RpcConnectionConfig, RpcEndpoint, SendConfig, SignerSource,
+    WalletSecrets,
+};
 use serde::{Deserialize, Serialize};
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::signal;
 use uuid::Uuid;
+use zeroize::Zeroize;
 
 mod bin_calculations;
 mod execution_engine;
@@ -234,6 +241,12 @@ enum MonitorAction {
         /// Enable websocket monitoring
         #[arg(long)]
         websocket: bool,
+        /// Export the accounting ledger of executed orders to this directory on stop
+        #[arg(long)]
+        ledger_dir: Option<PathBuf>,
+        /// Lot matching for realised gains (fifo/lifo)
+        #[arg(long, default_value = "fifo")]
+        cost_basis: CostBasisMethod,
     },
     /// Show market data
     Market {
@@ -274,6 +287,18 @@ enum ConfigAction {
     },
     /// Validate configuration
     Validate,
+    /// Encrypt a keypair file into a password-protected keystore
+    EncryptWallet {
+        /// Keypair file to encrypt
+        #[arg(long, default_value = "~/.config/solana/id.json")]
+        keypair: String,
+        /// Keystore output path
+        #[arg(long)]
+        output: String,
+        /// PBKDF2 iterations
+        #[arg(long, default_value_t = DEFAULT_KEYSTORE_ITERATIONS)]
+        iterations: u32,
+    },
 }
 
 /// Application configuration
@@ -298,6 +323,25 @@ struct RpcConfig {
     pub url: String,
     pub timeout_secs: u64,
     pub max_retries: u32,
+    /// Weighted endpoint pool with health-based failover; replaces `url` when set
+    #[serde(default)]
+    pub endpoints: Vec<RpcEndpoint>,
+}
+
+impl RpcConfig {
+    fn connection_config(&self) -> RpcConnectionConfig {
+        let endpoints = if self.endpoints.is_empty() {
+            vec![RpcEndpoint::new(self.url.clone(), 1)]
+        } else {
+            self.endpoints.clone()
+        };
+
+        RpcConnectionConfig {
+            timeout: Duration::from_secs(self.timeout_secs),
+            max_retries: self.max_retries,
+            ..RpcConnectionConfig::with_endpoints(endpoints)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -353,6 +397,7 @@ impl Default for AppConfig {
                 url: "https://api.mainnet-beta.solana.com".to_string(),
                 timeout_secs: 30,
                 max_retries: 3,
+                endpoints: Vec::new(),
             },
             trading: TradingConfig {
                 wallet_path: "~/.config/solana/id.json".to_string(),
@@ -403,12 +448,36 @@ struct App {
 }
 
 impl App {
-    /// Initialize the application
-    async fn new(config_path: &PathBuf) -> Result<Self> {
+    /// Initialize the application, loading the wallet signer with `secrets`
+    async fn new(config_path: &PathBuf, dry_run: bool, secrets: &WalletSecrets) -> Result<Self> {
         let config = load_config(config_path)?;
         
+        // Load the wallet signer; orders are only executed on-chain when one is available
+        let signer = if dry_run {
+            None
+        } else {
+            match SignerSource::parse(&config.trading.wallet_path).and_then(|source| source.load(secrets)) {
+                Ok(signer) => {
+                    info!("Wallet loaded: {}", signer.pubkey());
+                    Some(signer)
+                }
+                Err(e) => {
+                    warn!("Wallet not loaded ({}), orders will use simulated execution", e);
+                    None
+                }
+            }
+        };
+        
         // Initialize DLMM client
-        let client = Arc::new(saros_dlmm_sdk::DLMMClient::new(&config.rpc.url)?);
+        let connection = Arc::new(RpcConnection::new(config.rpc.connection_config()));
+        if connection.endpoint_count() > 1 {
+            connection.spawn_health_checks();
+        }
+        let mut client = saros_dlmm_sdk::DLMMClient::new_with_rpc(connection);
+        if let Some(signer) = &signer {
+            client.set_wallet(signer.clone());
+        }
+        let client = Arc::new(client);
         
         // Initialize bin calculator
         let base_price = Decimal::from_str(&config.trading.base_price)?;
@@ -475,12 +544,17 @@ impl App {
             enable_smart_routing: true,
         };
         
-        let execution_engine = Arc::new(ExecutionEngine::new(
+        let transaction_sender = Arc::new(client.transaction_sender(SendConfig::default()));
+        let mut execution_engine = ExecutionEngine::new(
             client,
             bin_calculator,
             execution_config,
             None, // TODO: Add notification sender
-        ));
+        );
+        if let Some(signer) = signer {
+            execution_engine = execution_engine.with_transaction_sender(transaction_sender, signer);
+        }
+        let execution_engine = Arc::new(execution_engine);
         
         // Start monitoring and execution
         monitor.start_monitoring().await?;
@@ -509,8 +583,7 @@ impl App {
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     let cli = Cli::parse();
     
     // Initialize logging
@@ -523,13 +596,24 @@ async fn main() -> Result<()> {
         warn!("Running in DRY RUN mode - no actual transactions will be executed");
     }
     
+    // Take wallet secrets out of the environment while the process is still single-threaded
+    let source = load_config(&cli.config)
+        .ok()
+        .and_then(|config| SignerSource::parse(&config.trading.wallet_path).ok());
+    let secrets = WalletSecrets::take_from_env(source.as_ref());
+    
+    tokio::runtime::Runtime::new()?.block_on(run(cli, secrets))
+}
+
+async fn run(cli: Cli, secrets: WalletSecrets) -> Result<()> {
     match cli.command {
         Commands::Config(config_cmd) => {
             handle_config_command(config_cmd, &cli.config).await?;
         }
         _ => {
             // Initialize app for other commands
-            let app = App::new(&cli.config).await?;
+            let app = App::new(&cli.config, cli.dry_run, &secrets).await?;
+            drop(secrets);
             
             match cli.command {
                 Commands::Order(order_cmd) => {
@@ -576,6 +660,26 @@ async fn handle_config_command(cmd: ConfigCommands, config_path: &PathBuf) -> Re
                 }
             }
         }
+        ConfigAction::EncryptWallet { keypair, output, iterations } => {
+            let keypair = wallet::read_keypair_file(&wallet::expand_path(&keypair))?;
+            let output = wallet::expand_path(&output);
+            
+            let mut password = rpassword::prompt_password("New keystore password: ")?;
+            let mut confirmation = rpassword::prompt_password("Confirm password: ")?;
+            let matches = password == confirmation;
+            confirmation.zeroize();
+            if !matches || password.is_empty() {
+                password.zeroize();
+                return Err(anyhow!("Passwords are empty or do not match"));
+            }
+            
+            let keystore = Keystore::encrypt(&keypair, &password, iterations);
+            password.zeroize();
+            keystore?.save(&output)?;
+            
+            println!("Keystore for {} written to: {}", keypair.pubkey(), output.display());
+            println!("Set trading.wallet_path = \"keystore:{}\" to use it", output.display());
+        }
     }
     Ok(())
 }
@@ -870,13 +974,23 @@ async fn handle_strategy_command(cmd: StrategyCommands, app: &App) -> Result<()>
 /// Handle monitor commands
 async fn handle_monitor_command(cmd: MonitorCommands, app: &App) -> Result<()> {
     match cmd.action {
-        MonitorAction::Start { interval, websocket } => {
+        MonitorAction::Start { interval, websocket, ledger_dir, cost_basis } => {
             println!("Starting monitoring (interval: {}ms, websocket: {})", interval, websocket);
             println!("Press Ctrl+C to stop...");
             
             // Wait for interrupt signal
             signal::ctrl_c().await?;
             println!("\nStopping monitor...");
+            
+            if let Some(dir) = ledger_dir {
+                let export = app.execution_engine.get_ledger().await.export_csv(&dir, cost_basis)?;
+                println!("Exported {} ledger events ({} realised lots, {} open)",
+                         export.events, export.realized_lots, export.open_lots);
+                println!("Realised gain: ${}", export.realized_gain_usd.round_dp(2));
+                for file in &export.files {
+                    println!("  {}", file.display());
+                }
+            }
         }
         MonitorAction::Market { pool, range } => {
             let pool_pubkey = Pubkey::from_str(&pool)?;
@@ -1,7 +1,9 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use rust_decimal::Decimal;
+use saros_dlmm_sdk::PriceSourceConfig;
 use solana_sdk::pubkey::Pubkey;
+use std::path::PathBuf;
 use std::str::FromStr;
 use tracing::{info, warn, error};
 
@@ -23,6 +25,15 @@ use types::*;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Replay recorded USD prices from a CSV (timestamp,mint,price) or JSON file
+    #[arg(long, global = true)]
+    price_file: Option<PathBuf>,
+    /// Read USD prices from an HTTP endpoint serving GET /price/{mint}
+    #[arg(long, global = true)]
+    price_url: Option<String>,
+    /// Ignore price quotes older than this many seconds and take the median of the sources
+    #[arg(long, global = true)]
+    max_price_age: Option<u64>,
 }
 
 #[derive(Subcommand)]
@@ -55,6 +66,9 @@ enum Commands {
         amount: String,
         #[arg(long, default_value = "false")]
         simulate: bool,
+        /// Fee payer used for the simulated transaction (required with --simulate)
+        #[arg(long, required_if_eq("simulate", "true"))]
+        payer: Option<String>,
     },
     /// Analyze pool graph connectivity
     Analyze {
@@ -85,11 +99,18 @@ async fn main() -> Result<()> {
 
     info!("Starting Saros Multi-Hop Router");
 
+    let price_source = PriceSourceConfig {
+        price_file: cli.price_file.clone(),
+        price_url: cli.price_url.clone(),
+        max_age_secs: cli.max_price_age,
+    }
+    .build()?;
+
     // Initialize components
-    let pool_graph = PoolGraph::new().await?;
+    let pool_graph = PoolGraph::new(price_source).await?;
     let route_finder = RouteFinder::new(pool_graph.clone()).await?;
     let arbitrage_detector = ArbitrageDetector::new(pool_graph.clone()).await?;
-    let route_executor = RouteExecutor::new().await?;
+    let route_executor = RouteExecutor::new(pool_graph.clone()).await?;
 
     match cli.command {
         Commands::Route {
@@ -118,8 +139,9 @@ async fn main() -> Result<()> {
             route_id,
             amount,
             simulate,
+            payer,
         } => {
-            handle_execute_command(&route_executor, route_id, amount, simulate).await?;
+            handle_execute_command(&route_executor, route_id, amount, simulate, payer).await?;
         }
         Commands::Analyze { token, export_graph } => {
             handle_analyze_command(&pool_graph, token, export_graph).await?;
@@ -181,7 +203,7 @@ async fn handle_route_command(
             }
         }
         Err(e) => {
-            error!("Failed to find route: {}", e);
+            error!("Failed to find route ({}): {}", routing_error_category(&e), e);
             return Err(e);
         }
     }
@@ -232,12 +254,15 @@ async fn handle_execute_command(
     route_id: String,
     amount: String,
     simulate: bool,
+    payer: Option<String>,
 ) -> Result<()> {
     let amount_decimal = Decimal::from_str(&amount)?;
 
     if simulate {
         info!("Simulating execution of route: {}", route_id);
-        let simulation = route_executor.simulate_route_execution(&route_id, amount_decimal).await?;
+        let payer = payer.ok_or_else(|| anyhow::anyhow!("--payer is required to simulate a route"))?;
+        let payer = Pubkey::from_str(&payer)?;
+        let simulation = route_executor.simulate_route_execution(&route_id, amount_decimal, payer).await?;
         
         println!("\n=== SIMULATION RESULTS ===");
         println!("Route: {}", route_id);
@@ -246,6 +271,19 @@ async fn handle_execute_command(
         println!("Price Impact: {:.4}%", simulation.total_price_impact * Decimal::from(100));
         println!("Gas Cost: {} SOL", simulation.estimated_gas);
         println!("Success Probability: {:.1}%", simulation.success_probability * Decimal::from(100));
+        if let Some(units) = simulation.compute_units_consumed {
+            println!("Compute Units: {}", units);
+        }
+        if let Some(error) = &simulation.error {
+            println!("Error: {}", error);
+        }
+        
+        if !simulation.logs.is_empty() {
+            println!("\nProgram Logs:");
+            for log in &simulation.logs {
+                println!("  {}", log);
+            }
+        }
         
         if !simulation.warnings.is_empty() {
             println!("\nWarnings:");
@@ -335,7 +373,7 @@ async fn handle_monitor_command(
                 }
             }
             Err(e) => {
-                warn!("Arbitrage scan failed: {}", e);
+                warn!("Arbitrage scan failed ({}): {}", routing_error_category(&e), e);
             }
         }
 
@@ -10,7 +10,7 @@ use std::sync::Arc;
 use tracing::{info, warn, debug};
 
 use crate::types::*;
-use saros_dlmm_sdk::SarosClient;
+use saros_dlmm_sdk::{SarosClient, SharedPriceSource};
 
 /// Pool connectivity graph manager for efficient route finding
 pub struct PoolGraph {
@@ -32,12 +32,15 @@ pub struct PoolGraph {
     /// Saros client for data fetching
     client: Arc<SarosClient>,
     
+    /// USD prices for token valuations, overriding the token list's prices
+    price_source: Option<SharedPriceSource>,
+    
     /// Last graph update timestamp
     last_update: Arc<tokio::sync::RwLock<u64>>,
 }
 
 impl PoolGraph {
-    pub async fn new() -> Result<Arc<Self>> {
+    pub async fn new(price_source: Option<SharedPriceSource>) -> Result<Arc<Self>> {
         info!("Initializing pool connectivity graph");
         
         let client = Arc::new(SarosClient::new_mock()?);
@@ -50,6 +53,7 @@ impl PoolGraph {
             pool_cache: Arc::new(DashMap::new()),
             token_cache: Arc::new(DashMap::new()),
             client,
+            price_source,
             last_update: Arc::new(tokio::sync::RwLock::new(0)),
         });
         
@@ -75,6 +79,12 @@ impl PoolGraph {
         
         debug!("Found {} pools and {} tokens", pools.len(), tokens.len());
         
+        // Value tokens before taking the graph lock
+        let mut prices_usd = HashMap::new();
+        for token in &tokens {
+            prices_usd.insert(token.mint, self.token_price_usd(token.mint, token.price_usd).await);
+        }
+        
         // Clear existing data
         {
             let mut graph = self.graph.write().await;
@@ -95,7 +105,7 @@ impl PoolGraph {
                     address: token.mint,
                     symbol: token.symbol.clone(),
                     decimals: token.decimals,
-                    price_usd: token.price_usd.unwrap_or_default(),
+                    price_usd: prices_usd[&token.mint],
                     market_cap: Decimal::ZERO,
                     pools: Vec::new(),
                 };
@@ -565,6 +575,17 @@ impl PoolGraph {
         }
     }
     
+    /// USD price from the price source, falling back to the token list's price
+    async fn token_price_usd(&self, mint: Pubkey, listed_price: Option<Decimal>) -> Decimal {
+        if let Some(price_source) = &self.price_source {
+            match price_source.get_price(&mint).await {
+                Ok(quote) => return quote.price,
+                Err(e) => debug!("No {} price for {}, using listed price: {}", price_source.name(), mint, e),
+            }
+        }
+        listed_price.unwrap_or_default()
+    }
+    
     async fn update_token_pool_associations(&self) -> Result<()> {
         let mut token_pools: HashMap<Pubkey, Vec<Pubkey>> = HashMap::new();
         
@@ -1,8 +1,9 @@
 use anyhow::Result;
 use rust_decimal::Decimal;
 use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
-    signature::{Keypair, Signature, Signer},
+    signature::{Signature, Signer},
     transaction::Transaction,
 };
 use std::sync::Arc;
@@ -10,7 +11,10 @@ use tracing::{debug, info, warn};
 
 use crate::pool_graph::PoolGraph;
 use crate::types::*;
-use saros_dlmm_sdk::{SarosClient, TransactionBuilder};
+use saros_dlmm_sdk::{SarosClient, SendConfig, TransactionBuilder, TransactionSender};
+
+/// Base fee charged per transaction signature (lamports)
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
 
 /// Simplified multi-hop route execution
 pub struct RouteExecutor {
@@ -22,18 +26,22 @@ pub struct RouteExecutor {
     
     /// Transaction builder
     transaction_builder: Arc<TransactionBuilder>,
+    
+    /// Shared sender handling blockhash refresh and confirmation
+    transaction_sender: Arc<TransactionSender>,
 }
 
 impl RouteExecutor {
-    pub async fn new() -> Result<Self> {
+    pub async fn new(pool_graph: Arc<PoolGraph>) -> Result<Self> {
         let client = Arc::new(SarosClient::new_mock()?);
-        let pool_graph = PoolGraph::new().await?;
         let transaction_builder = Arc::new(TransactionBuilder::new());
+        let transaction_sender = Arc::new(client.transaction_sender(SendConfig::default()));
         
         Ok(Self {
             client,
             pool_graph,
             transaction_builder,
+            transaction_sender,
         })
     }
     
@@ -41,7 +49,7 @@ impl RouteExecutor {
     pub async fn execute_route(
         &self,
         route: &RouteResponse,
-        user_keypair: &Keypair,
+        signer: &(dyn Signer + Sync),
         _amount: Decimal,
     ) -> Result<String> {
         info!("Executing route: {}", route.route_id);
@@ -51,28 +59,32 @@ impl RouteExecutor {
         
         // 2. Build transaction
         let transaction = self.transaction_builder.build_transaction(
-            user_keypair.pubkey(),
+            signer.pubkey(),
             &[], // Mock route data
         )?;
         
-        // 3. Simulate transaction first
-        let simulation_success = self.client.simulate_transaction(&transaction).await?;
-        if !simulation_success {
-            return Err(anyhow::anyhow!("Transaction simulation failed"));
-        }
+        // 3. Simulate first and size the compute budget, failing before any fee is paid
+        let (transaction, simulation) = self.client
+            .budget_compute_units(&transaction, &signer.pubkey())
+            .await?;
+        debug!("Route simulation used {:?} compute units", simulation.compute_units_consumed);
         
         // 4. Execute transaction
-        let signature = self.client.send_transaction(&transaction).await?;
+        let outcome = self.transaction_sender
+            .send_transaction(&transaction, &[signer])
+            .await?
+            .into_result()?;
         
-        info!("Route executed successfully: {}", signature);
-        Ok(signature)
+        info!("Route executed successfully: {} (slot {}, {} attempt(s))",
+              outcome.signature, outcome.slot, outcome.attempts);
+        Ok(outcome.signature.to_string())
     }
     
     /// Execute arbitrage opportunity
     pub async fn execute_arbitrage(
         &self,
         opportunity: &ArbitrageOpportunity,
-        keypair: &Keypair,
+        signer: &(dyn Signer + Sync),
     ) -> Result<String> {
         info!("Executing arbitrage opportunity: {}", opportunity.id);
         
@@ -84,37 +96,63 @@ impl RouteExecutor {
         
         // 2. Build priority transaction
         let transaction = self.transaction_builder.build_priority_transaction(
-            keypair.pubkey(),
+            signer.pubkey(),
             &[], // Mock route data
             rust_decimal_macros::dec!(0.01), // Priority fee
         )?;
         
-        // 3. Submit transaction
-        let signature = self.client.send_transaction(&transaction).await?;
+        // 3. Simulate and size the compute budget, dropping a failing cycle before paying the priority fee
+        let (transaction, simulation) = self.client
+            .budget_compute_units(&transaction, &signer.pubkey())
+            .await?;
+        debug!("Arbitrage simulation used {:?} compute units", simulation.compute_units_consumed);
         
-        info!("Arbitrage executed successfully: {}", signature);
-        Ok(signature)
+        // 4. Submit transaction
+        let outcome = self.transaction_sender
+            .send_transaction(&transaction, &[signer])
+            .await?
+            .into_result()?;
+        
+        info!("Arbitrage executed successfully: {} (slot {})", outcome.signature, outcome.slot);
+        Ok(outcome.signature.to_string())
     }
     
-    /// Simulate route execution without actually executing
+    /// Simulate route execution on-chain without sending the transaction
     pub async fn simulate_route_execution(
         &self,
         route_id: &str,
         amount: Decimal,
+        payer: Pubkey,
     ) -> Result<RouteExecutionSimulation> {
         info!("Simulating route execution: {}", route_id);
         
-        // Mock simulation result
+        let transaction = self.transaction_builder.build_transaction(
+            payer,
+            &[], // Mock route data
+        )?;
+        let simulation = self.client.simulate_transaction(&transaction).await?;
+        
+        let signatures = u64::from(transaction.message.header.num_required_signatures).max(1);
+        let estimated_gas = Decimal::from(signatures * LAMPORTS_PER_SIGNATURE) / Decimal::from(LAMPORTS_PER_SOL);
+        
+        let mut warnings = Vec::new();
+        if let Some(error) = &simulation.error_message {
+            warn!("Route {} would fail: {}", route_id, error);
+            warnings.push(format!("Simulation failed: {}", error));
+            warnings.extend(simulation.logs.iter().filter(|log| log.contains("Error")).cloned());
+        }
+        
+        // Quote figures are still mocked; success and costs come from the simulation
         Ok(RouteExecutionSimulation {
             route_id: route_id.to_string(),
             expected_output: amount * rust_decimal_macros::dec!(0.99), // 1% slippage
             total_price_impact: rust_decimal_macros::dec!(0.005), // 0.5% impact
-            estimated_gas: rust_decimal_macros::dec!(0.001), // 0.001 SOL
-            success_probability: rust_decimal_macros::dec!(0.95), // 95% success
-            warnings: vec![
-                "High slippage detected".to_string(),
-                "Low liquidity in intermediate pools".to_string(),
-            ],
+            estimated_gas,
+            success_probability: if simulation.is_success() { Decimal::ONE } else { Decimal::ZERO },
+            compute_units_consumed: simulation.compute_units_consumed,
+            logs: simulation.logs,
+            error: simulation.error_message,
+            warnings,
             execution_steps: vec![
                 ExecutionStep {
                     step_number: 1,
@@ -1,5 +1,6 @@
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
+use saros_dlmm_sdk::{error::rpc_error_kind, DLMMError, ErrorCategory};
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
@@ -106,6 +107,12 @@ pub struct RouteExecutionSimulation {
     pub total_price_impact: Decimal,
     pub estimated_gas: Decimal,
     pub success_probability: Decimal,
+    /// Compute units consumed by the simulated transaction
+    pub compute_units_consumed: Option<u64>,
+    /// Program logs from the simulation
+    pub logs: Vec<String>,
+    /// Error the transaction would fail with
+    pub error: Option<String>,
     pub warnings: Vec<String>,
     pub execution_steps: Vec<ExecutionStep>,
 }
@@ -249,6 +256,39 @@ pub enum RoutingError {
     
     #[error("Calculation error: {message}")]
     CalculationError { message: String },
+    
+    #[error(transparent)]
+    Sdk(#[from] DLMMError),
+}
+
+impl RoutingError {
+    /// Classification used to decide whether a failed route or execution is worth retrying
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            RoutingError::NoRouteFound => ErrorCategory::NotFound,
+            RoutingError::InsufficientLiquidity { .. } => ErrorCategory::InsufficientLiquidity,
+            RoutingError::PriceImpactTooHigh { .. } => ErrorCategory::Slippage,
+            RoutingError::InvalidTokenPair { .. } => ErrorCategory::InvalidInput,
+            RoutingError::GraphConstructionFailed { .. }
+            | RoutingError::CacheOperationFailed { .. }
+            | RoutingError::SimulationFailed { .. }
+            | RoutingError::CalculationError { .. } => ErrorCategory::Unknown,
+            RoutingError::NetworkError { source } if source.status().is_some_and(|status| status.as_u16() == 429) => {
+                ErrorCategory::RateLimited
+            }
+            RoutingError::NetworkError { source } if source.is_timeout() => ErrorCategory::Timeout,
+            RoutingError::NetworkError { .. } => ErrorCategory::Network,
+            RoutingError::SolanaRpcError { source } => rpc_error_kind(source).category(),
+            RoutingError::Sdk(e) => e.category(),
+        }
+    }
+}
+
+/// Category of the first routing or SDK error in an `anyhow` chain
+pub fn routing_error_category(error: &anyhow::Error) -> ErrorCategory {
+    error.chain()
+        .find_map(|cause| cause.downcast_ref::<RoutingError>())
+        .map_or_else(|| saros_dlmm_sdk::error_category(error), RoutingError::category)
 }
 
 // Constants
@@ -1,5 +1,13 @@
 //! Bin math utilities for DLMM calculations
 
+/// Number of bins stored in each bin array account
+pub const BINS_PER_ARRAY: i32 = 256;
+
+/// Index of the bin array account holding `bin_id`
+pub fn bin_array_index(bin_id: i32) -> i32 {
+    bin_id.div_euclid(BINS_PER_ARRAY)
+}
+
 /// Convert price to bin ID
 pub fn price_to_bin_id(price: f64, bin_step: u16, decimals_diff: i8) -> i32 {
     let price_adjusted = price * 10_f64.powi(decimals_diff as i32);
@@ -38,6 +46,20 @@ pub fn get_bin_composition(bin_id: i32, active_bin_id: i32) -> (f64, f64) {
     }
 }
 
+/// Share of a position's value held in X and Y when every bin in the range holds the same value
+pub fn get_range_composition(lower_bin: i32, upper_bin: i32, active_bin_id: i32) -> (f64, f64) {
+    if lower_bin > upper_bin {
+        return (0.0, 0.0);
+    }
+
+    let num_bins = (upper_bin - lower_bin + 1) as f64;
+    let (total_x, total_y) = (lower_bin..=upper_bin)
+        .map(|bin_id| get_bin_composition(bin_id, active_bin_id))
+        .fold((0.0, 0.0), |(acc_x, acc_y), (x, y)| (acc_x + x, acc_y + y));
+
+    (total_x / num_bins, total_y / num_bins)
+}
+
 /// Calculate liquidity for uniform distribution
 pub fn uniform_liquidity_distribution(
     total_liquidity: u128,
@@ -85,6 +107,16 @@ pub fn normal_liquidity_distribution(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bin_array_index() {
+        assert_eq!(bin_array_index(0), 0);
+        assert_eq!(bin_array_index(255), 0);
+        assert_eq!(bin_array_index(256), 1);
+        assert_eq!(bin_array_index(-1), -1);
+        assert_eq!(bin_array_index(-256), -1);
+        assert_eq!(bin_array_index(-257), -2);
+    }
+
     #[test]
     fn test_price_bin_conversion() {
         let price = 110.5;
@@ -115,6 +147,25 @@ mod tests {
         assert_eq!(y_active, 0.5);
     }
     
+    #[test]
+    fn test_range_composition() {
+        // Range entirely above the active bin holds only token X
+        assert_eq!(get_range_composition(101, 110, 100), (1.0, 0.0));
+
+        // Range entirely below the active bin holds only token Y
+        assert_eq!(get_range_composition(90, 99, 100), (0.0, 1.0));
+
+        // Symmetric range around the active bin splits evenly
+        let (x, y) = get_range_composition(95, 105, 100);
+        assert!((x - 0.5).abs() < 1e-9);
+        assert!((y - 0.5).abs() < 1e-9);
+
+        // Skewed range: 2 bins below, active bin, 7 bins above
+        let (x, y) = get_range_composition(98, 107, 100);
+        assert!((x - 0.75).abs() < 1e-9);
+        assert!((y - 0.25).abs() < 1e-9);
+    }
+
     #[test]
     fn test_uniform_distribution() {
         let total_liquidity = 1000;
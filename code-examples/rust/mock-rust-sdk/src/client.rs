@@ -1,12 +1,19 @@
 //! Main DLMM client implementation
 
-use crate::{types::*, error::DLMMError};
+use crate::{
+    types::*,
+    bin_math,
+    connection::RpcConnection,
+    error::DLMMError,
+    priority_fee::{PriorityFeeConfig, PriorityFeeEstimator},
+    simulation::{self, SimulationRpc, TransactionSimulation, DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT},
+    transaction_sender::{SendConfig, TransactionSender},
+    wallet::SharedSigner,
+};
 use anyhow::Result;
 use rust_decimal::{prelude::*, Decimal};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
     transaction::Transaction,
 };
 use std::sync::Arc;
@@ -14,9 +21,9 @@ use tokio::time::{sleep, Duration};
 
 /// Main DLMM client
 pub struct DLMMClient {
-    rpc_client: Arc<RpcClient>,
+    rpc_client: Arc<RpcConnection>,
     program_id: Pubkey,
-    wallet: Option<Keypair>,
+    wallet: Option<SharedSigner>,
 }
 
 impl DLMMClient {
@@ -24,10 +31,8 @@ impl DLMMClient {
     pub fn new(rpc_url: &str) -> Result<Self, DLMMError> {
         log::info!("Initializing DLMM client with RPC: {}", rpc_url);
         
-        let rpc_client = RpcClient::new(rpc_url.to_string());
-        
         Ok(Self {
-            rpc_client: Arc::new(rpc_client),
+            rpc_client: Arc::new(RpcConnection::from_url(rpc_url)),
             program_id: Pubkey::new_unique(), // Mock program ID
             wallet: None,
         })
@@ -39,17 +44,56 @@ impl DLMMClient {
     }
     
     /// Create client with wallet
-    pub fn with_wallet(rpc_url: &str, wallet: Keypair) -> Result<Self, DLMMError> {
+    pub fn with_wallet(rpc_url: &str, wallet: SharedSigner) -> Result<Self, DLMMError> {
         let mut client = Self::new(rpc_url)?;
         client.wallet = Some(wallet);
         Ok(client)
     }
     
     /// Set wallet after initialization
-    pub fn set_wallet(&mut self, wallet: Keypair) {
+    pub fn set_wallet(&mut self, wallet: SharedSigner) {
         self.wallet = Some(wallet);
     }
     
+    /// Create a transaction sender over this client's RPC connection
+    pub fn transaction_sender(&self, config: SendConfig) -> TransactionSender {
+        TransactionSender::new(self.rpc_client.clone(), config)
+    }
+    
+    /// Create a priority fee estimator over this client's RPC connection
+    pub fn priority_fee_estimator(&self, config: PriorityFeeConfig) -> PriorityFeeEstimator {
+        PriorityFeeEstimator::new(self.rpc_client.clone(), config)
+    }
+    
+    /// Address of the bin array account with the given index
+    pub fn bin_array_address(&self, pool_address: &Pubkey, index: i32) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"bin_array", pool_address.as_ref(), &index.to_le_bytes()],
+            &self.program_id,
+        ).0
+    }
+    
+    /// Accounts write-locked when trading or providing liquidity in `bin_ids`:
+    /// the pool and the bin arrays holding those bins
+    pub fn priority_fee_accounts(&self, pool_address: &Pubkey, bin_ids: &[i32]) -> Vec<Pubkey> {
+        let mut indexes: Vec<i32> = bin_ids.iter().map(|&bin_id| bin_math::bin_array_index(bin_id)).collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+        
+        std::iter::once(*pool_address)
+            .chain(indexes.into_iter().map(|index| self.bin_array_address(pool_address, index)))
+            .collect()
+    }
+    
+    /// Build an unsigned swap transaction for the given user
+    pub async fn swap_transaction(&self, params: &SwapParams, user: &Pubkey) -> Result<Transaction, DLMMError> {
+        log::info!("Building swap transaction for {} in pool {}: amount_in={}", user, params.pool_address, params.amount_in);
+        
+        sleep(Duration::from_millis(50)).await;
+        
+        Ok(Transaction::default())
+    }
+    
     /// Execute swap
     pub async fn swap(&self, params: SwapParams) -> Result<SwapResult, DLMMError> {
         log::info!("Executing swap: amount_in={}, min_out={}", params.amount_in, params.minimum_amount_out);
@@ -150,8 +194,8 @@ impl DLMMClient {
         // Generate mock pool data
         Ok(DLMMPoolInfo {
             address: pool_address,
-            token_x: Pubkey::new_unique(),
-            token_y: Pubkey::new_unique(),
+            token_x: crate::mock_token_mint("SOL").unwrap_or_default(),
+            token_y: crate::mock_token_mint("USDC").unwrap_or_default(),
             active_bin_id: 100,
             bin_step: 20, // 0.2%
             liquidity: 1_000_000_000_000,
@@ -203,6 +247,36 @@ impl DLMMClient {
         })
     }
     
+    /// Get a position's transactions, oldest first
+    pub async fn get_position_history(&self, position_id: Pubkey) -> Result<Vec<PositionTransaction>, DLMMError> {
+        log::info!("Fetching transaction history for position: {}", position_id);
+        
+        sleep(Duration::from_millis(50)).await;
+        
+        // Mock history whose net liquidity matches `get_position`
+        let now = chrono::Utc::now();
+        let history = [
+            (30, PositionTransactionKind::Create, 4_000_000_000, 400_000_000, 40_000_000_000, 100),
+            (20, PositionTransactionKind::AddLiquidity, 2_000_000_000, 150_000_000, 22_500_000_000, 102),
+            (10, PositionTransactionKind::RemoveLiquidity, 900_000_000, 140_000_000, 12_500_000_000, 98),
+            (5, PositionTransactionKind::ClaimFees, 50_000_000, 5_000_000, 0, 101),
+        ];
+        
+        Ok(history
+            .into_iter()
+            .enumerate()
+            .map(|(i, (days_ago, kind, amount_x, amount_y, liquidity, active_bin_id))| PositionTransaction {
+                signature: format!("mock_position_tx_{}_{}", position_id, i),
+                timestamp: now - chrono::Duration::days(days_ago),
+                kind,
+                amount_x,
+                amount_y,
+                liquidity,
+                active_bin_id,
+            })
+            .collect())
+    }
+    
     /// Get all positions for wallet
     pub async fn get_user_positions(&self, owner: Pubkey) -> Result<Vec<Position>, DLMMError> {
         log::info!("Fetching positions for owner: {}", owner);
@@ -339,8 +413,8 @@ impl DLMMClient {
         self.get_bin(pool_address, 100).await // Mock active bin at ID 100
     }
 
-    /// Create a new client with existing RPC client (for auto-compound system)
-    pub fn new_with_rpc(rpc_client: Arc<RpcClient>) -> Self {
+    /// Create a new client over an existing shared RPC connection
+    pub fn new_with_rpc(rpc_client: Arc<RpcConnection>) -> Self {
         Self {
             rpc_client,
             program_id: Pubkey::new_unique(),
@@ -361,6 +435,9 @@ impl DLMMClient {
             token_b_amount: 500.0,
             lp_token_amount: 1500.0,
             pending_rewards: rand::random::<f64>() * 10.0 + 1.0, // 1-11 tokens
+            lower_bin_id: 95,
+            upper_bin_id: 105,
+            reward_mint: crate::mock_token_mint("RAY").unwrap_or_default(), // Rewards paid in a third token
         })
     }
 
@@ -372,8 +449,8 @@ impl DLMMClient {
 
         Ok(PoolInfo {
             address: *pool_address,
-            token_a_mint: Pubkey::new_unique(),
-            token_b_mint: Pubkey::new_unique(),
+            token_a_mint: crate::mock_token_mint("SOL").unwrap_or_default(),
+            token_b_mint: crate::mock_token_mint("USDC").unwrap_or_default(),
             token_a_symbol: "SOL".to_string(),
             token_b_symbol: "USDC".to_string(),
             tvl: 10_000_000.0,
@@ -405,6 +482,81 @@ impl DLMMClient {
         Ok(transaction)
     }
 
+    /// Add liquidity with explicit X/Y amounts into an existing position's bin range
+    pub async fn add_liquidity_to_position_tx(
+        &self,
+        position: &UserPosition,
+        amount_x: f64,
+        amount_y: f64,
+        max_slippage: f64,
+    ) -> Result<Transaction, DLMMError> {
+        log::info!("Creating add liquidity transaction: pool={}, user={}, x={}, y={}, bins=[{}, {}], slippage={}",
+                   position.pool_address, position.user_pubkey, amount_x, amount_y,
+                   position.lower_bin_id, position.upper_bin_id, max_slippage);
+
+        if position.lower_bin_id > position.upper_bin_id {
+            return Err(DLMMError::InvalidBinRange);
+        }
+
+        sleep(Duration::from_millis(50)).await;
+
+        let transaction = Transaction::default();
+        Ok(transaction)
+    }
+
+    /// USD price of a token from the mock price oracle; mints it doesn't quote are an error
+    pub async fn get_token_price_usd(&self, mint: &Pubkey) -> Result<Decimal, DLMMError> {
+        let symbol = crate::mock_token_symbol(mint).ok_or(DLMMError::OracleUnavailable)?;
+        let price = crate::get_mock_price(symbol).await;
+        Decimal::from_f64(price).ok_or(DLMMError::OracleUnavailable)
+    }
+    
+    /// USD price of SOL, for valuing network fees
+    pub async fn get_sol_price_usd(&self) -> Result<Decimal, DLMMError> {
+        let price = crate::get_mock_price("SOL").await;
+        Decimal::from_f64(price).ok_or(DLMMError::OracleUnavailable)
+    }
+    
+    /// Quote a swap between two mints (for reward token conversion)
+    pub async fn get_swap_quote(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount_in: f64) -> Result<SwapQuote, DLMMError> {
+        log::info!("Getting swap quote: {} -> {}, amount_in={}", input_mint, output_mint, amount_in);
+
+        if amount_in <= 0.0 {
+            return Err(DLMMError::InvalidParams("Swap amount must be positive".to_string()));
+        }
+
+        sleep(Duration::from_millis(25)).await;
+
+        let fee = amount_in * 0.003; // 0.3% pool fee
+        let price_impact = 0.5; // 0.5% price impact
+        let amount_out = (amount_in - fee) * (1.0 - price_impact / 100.0);
+
+        Ok(SwapQuote {
+            pool_address: Pubkey::new_unique(),
+            input_mint: *input_mint,
+            output_mint: *output_mint,
+            amount_in,
+            amount_out,
+            fee,
+            price_impact,
+        })
+    }
+
+    /// Swap transaction (for reward token conversion)
+    pub async fn swap_tx(&self, quote: &SwapQuote, user_pubkey: &Pubkey, minimum_amount_out: f64) -> Result<Transaction, DLMMError> {
+        log::info!("Creating swap transaction: pool={}, user={}, amount_in={}, min_out={}",
+                   quote.pool_address, user_pubkey, quote.amount_in, minimum_amount_out);
+
+        if quote.amount_out < minimum_amount_out {
+            return Err(DLMMError::SlippageExceeded);
+        }
+
+        sleep(Duration::from_millis(40)).await;
+
+        let transaction = Transaction::default();
+        Ok(transaction)
+    }
+
     /// Stake tokens transaction (for staking reinvestment)
     pub async fn stake_tokens(&self, pool_address: &Pubkey, user_pubkey: &Pubkey, amount: f64) -> Result<Transaction, DLMMError> {
         log::info!("Creating stake transaction: pool={}, user={}, amount={}", pool_address, user_pubkey, amount);
@@ -446,6 +598,50 @@ impl DLMMClient {
         Ok(transaction)
     }
 
+    /// Remove all liquidity transaction (for emergency withdrawal of LP positions)
+    pub async fn remove_all_liquidity_tx(&self, pool_address: &Pubkey, user_pubkey: &Pubkey) -> Result<Transaction, DLMMError> {
+        log::info!("Creating remove all liquidity transaction for pool: {}, user: {}", pool_address, user_pubkey);
+
+        sleep(Duration::from_millis(50)).await;
+
+        let transaction = Transaction::default();
+        Ok(transaction)
+    }
+
+    /// Unstake all tokens transaction (for emergency withdrawal of staking positions)
+    pub async fn unstake_all_tx(&self, pool_address: &Pubkey, user_pubkey: &Pubkey) -> Result<Transaction, DLMMError> {
+        log::info!("Creating unstake all transaction for pool: {}, user: {}", pool_address, user_pubkey);
+
+        sleep(Duration::from_millis(40)).await;
+
+        let transaction = Transaction::default();
+        Ok(transaction)
+    }
+
+    /// Withdraw from farm transaction (for emergency withdrawal of farming positions)
+    pub async fn withdraw_farm_tx(&self, pool_address: &Pubkey, user_pubkey: &Pubkey) -> Result<Transaction, DLMMError> {
+        log::info!("Creating farm withdraw transaction for pool: {}, user: {}", pool_address, user_pubkey);
+
+        sleep(Duration::from_millis(45)).await;
+
+        let transaction = Transaction::default();
+        Ok(transaction)
+    }
+
+    /// Transfer full token balances for the given mints to another wallet
+    pub async fn transfer_tokens_tx(&self, user_pubkey: &Pubkey, destination: &Pubkey, mints: &[Pubkey]) -> Result<Transaction, DLMMError> {
+        log::info!("Creating token transfer transaction: {} -> {} ({} mints)", user_pubkey, destination, mints.len());
+
+        if mints.is_empty() {
+            return Err(DLMMError::InvalidParams("No token mints to transfer".to_string()));
+        }
+
+        sleep(Duration::from_millis(30)).await;
+
+        let transaction = Transaction::default();
+        Ok(transaction)
+    }
+
     // Additional methods for multi-hop routing compatibility
     
     /// Get all pools (mock implementation for routing)
@@ -528,14 +724,47 @@ impl DLMMClient {
         Ok(mock_tokens)
     }
     
-    /// Simulate transaction execution
-    pub async fn simulate_transaction(&self, _transaction: &Transaction) -> Result<bool, DLMMError> {
-        log::info!("Simulating transaction execution");
+    /// Simulate transaction execution, reporting compute units, logs and any error
+    pub async fn simulate_transaction(&self, transaction: &Transaction) -> Result<TransactionSimulation, DLMMError> {
+        self.simulate_transaction_with_balances(transaction, &[]).await
+    }
+    
+    /// Simulate transaction execution, also returning the post-simulation balances of `accounts`
+    pub async fn simulate_transaction_with_balances(
+        &self,
+        transaction: &Transaction,
+        accounts: &[Pubkey],
+    ) -> Result<TransactionSimulation, DLMMError> {
+        log::info!("Simulating transaction with {} instruction(s)", transaction.message.instructions.len());
+        
+        // Transactions built by this mock SDK carry no instructions and simulate as no-ops
+        if transaction.message.instructions.is_empty() {
+            sleep(Duration::from_millis(50)).await;
+            return Ok(TransactionSimulation::default());
+        }
         
-        sleep(Duration::from_millis(50)).await;
+        let simulation = self.rpc_client.simulate_transaction(transaction, accounts).await?;
         
-        // Mock simulation - return success
-        Ok(true)
+        match &simulation.error_message {
+            Some(error) => log::warn!("Simulation failed: {}", error),
+            None => log::info!("Simulation succeeded using {:?} compute units", simulation.compute_units_consumed),
+        }
+        
+        Ok(simulation)
+    }
+    
+    /// Simulate a transaction and set its compute-unit limit to the measured usage plus a
+    /// safety margin, failing before any fee is paid if the transaction would fail
+    pub async fn budget_compute_units(
+        &self,
+        transaction: &Transaction,
+        payer: &Pubkey,
+    ) -> Result<(Transaction, TransactionSimulation), DLMMError> {
+        if transaction.message.instructions.is_empty() {
+            return Ok((transaction.clone(), self.simulate_transaction(transaction).await?));
+        }
+        
+        simulation::budget_compute_units(self.rpc_client.as_ref(), transaction, payer, DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT).await
     }
     
     /// Send transaction to network
@@ -0,0 +1,575 @@
+//! Shared non-blocking RPC connection
+//!
+//! Wraps the non-blocking Solana RPC client with per-request timeouts, retries on
+//! transient failures and per-method request metrics, so callers running inside
+//! tokio tasks never block a worker thread on network I/O. A connection can span
+//! several weighted endpoints, failing over between them based on their health.
+
+use crate::{
+    error::{program_error, rpc_error_kind, DLMMError, RpcErrorKind},
+    priority_fee::PriorityFeeRpc,
+    simulation::{SimulatedAccount, SimulationRpc, TransactionSimulation},
+    rpc_pool::{EndpointHealth, EndpointPool, PoolSettings, RpcEndpoint},
+    transaction_sender::{classify_client_error, classify_transaction_error, TransactionCost, TransactionRpc},
+    DLMMResult,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    client_error::ClientError,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{
+        RpcSendTransactionConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+        RpcTransactionConfig,
+    },
+    rpc_response::RpcResult,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use solana_transaction_status::{TransactionStatus, UiTransactionEncoding};
+use std::{collections::HashMap, future::Future, sync::Arc, sync::Mutex};
+use tokio::{
+    task::JoinHandle,
+    time::{sleep, timeout, Duration, Instant},
+};
+
+/// Connection settings for one or more RPC endpoints
+#[derive(Debug, Clone)]
+pub struct RpcConnectionConfig {
+    /// Endpoints to spread requests over; the first one is the primary
+    pub endpoints: Vec<RpcEndpoint>,
+    /// Time limit for each individual request attempt
+    pub timeout: Duration,
+    /// Retries after the first attempt for transient failures
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each further retry
+    pub retry_base_delay: Duration,
+    pub commitment: CommitmentConfig,
+    /// Endpoints further than this many slots behind the others are skipped
+    pub max_slot_lag: u64,
+    /// Backoff after a rate-limited (HTTP 429) response, doubled on repeated strikes
+    pub rate_limit_backoff: Duration,
+    pub max_rate_limit_backoff: Duration,
+    /// How often the background health check polls each endpoint's slot
+    pub health_check_interval: Duration,
+}
+
+impl RpcConnectionConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_endpoints(vec![RpcEndpoint::new(url, 1)])
+    }
+
+    pub fn with_endpoints(endpoints: Vec<RpcEndpoint>) -> Self {
+        Self {
+            endpoints,
+            timeout: Duration::from_secs(30),
+            max_retries: 3,
+            retry_base_delay: Duration::from_millis(250),
+            commitment: CommitmentConfig::confirmed(),
+            max_slot_lag: 150,
+            rate_limit_backoff: Duration::from_secs(1),
+            max_rate_limit_backoff: Duration::from_secs(30),
+            health_check_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Request counters for one RPC method
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RpcMethodMetrics {
+    pub method: String,
+    pub requests: u64,
+    pub failures: u64,
+    pub retries: u64,
+    pub timeouts: u64,
+    pub total_latency_ms: u64,
+}
+
+impl RpcMethodMetrics {
+    pub fn average_latency_ms(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.total_latency_ms as f64 / self.requests as f64
+        }
+    }
+}
+
+/// Non-blocking RPC connection with failover, timeouts, retries and request metrics
+pub struct RpcConnection {
+    clients: Vec<Arc<RpcClient>>,
+    pool: EndpointPool,
+    config: RpcConnectionConfig,
+    metrics: Mutex<HashMap<&'static str, RpcMethodMetrics>>,
+}
+
+impl RpcConnection {
+    /// Create a connection over the configured endpoints.
+    ///
+    /// Panics if `config.endpoints` is empty.
+    pub fn new(config: RpcConnectionConfig) -> Self {
+        assert!(!config.endpoints.is_empty(), "RpcConnectionConfig needs at least one endpoint");
+
+        let clients = config
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                Arc::new(RpcClient::new_with_timeout_and_commitment(
+                    endpoint.url.clone(),
+                    config.timeout,
+                    config.commitment,
+                ))
+            })
+            .collect();
+
+        let pool = EndpointPool::new(config.endpoints.clone(), PoolSettings {
+            max_slot_lag: config.max_slot_lag,
+            latency_reference: Duration::from_millis(250),
+            rate_limit_backoff: config.rate_limit_backoff,
+            max_rate_limit_backoff: config.max_rate_limit_backoff,
+        });
+
+        Self {
+            clients,
+            pool,
+            config,
+            metrics: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Connect with default timeouts and retries
+    pub fn from_url(url: &str) -> Self {
+        Self::new(RpcConnectionConfig::new(url))
+    }
+
+    /// URL of the primary endpoint
+    pub fn url(&self) -> &str {
+        &self.config.endpoints[0].url
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.config.commitment
+    }
+
+    /// Client for the healthiest endpoint, for calls that need no timeout, retry or metrics handling
+    pub fn client(&self) -> &RpcClient {
+        &self.clients[self.pool.best()]
+    }
+
+    /// Highest context slot seen on any endpoint. Reads pinned to this slot never
+    /// observe older state than an earlier read did, whichever endpoint serves them.
+    pub fn min_context_slot(&self) -> Option<u64> {
+        self.pool.highest_slot()
+    }
+
+    /// Run an RPC call with the per-request timeout, failing over between endpoints
+    /// and retrying transient failures
+    pub async fn call<T, F, Fut>(&self, method: &'static str, request: F) -> DLMMResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        self.call_endpoint(method, request).await.map(|(value, _)| value)
+    }
+
+    /// Run a call that returns a context slot, pinned to the highest slot seen so far.
+    ///
+    /// The request closure receives the minimum context slot to pass in the method's
+    /// config; endpoints that haven't reached it reject the request and the call fails
+    /// over to another endpoint.
+    pub async fn call_with_context<T, F, Fut>(&self, method: &'static str, request: F) -> DLMMResult<T>
+    where
+        F: Fn(Arc<RpcClient>, Option<u64>) -> Fut,
+        Fut: Future<Output = RpcResult<T>>,
+    {
+        let min_context_slot = self.min_context_slot();
+        let (response, index) = self
+            .call_endpoint(method, |client| request(client, min_context_slot))
+            .await?;
+
+        self.pool.observe_slot(index, response.context.slot);
+        Ok(response.value)
+    }
+
+    async fn call_endpoint<T, F, Fut>(&self, method: &'static str, request: F) -> DLMMResult<(T, usize)>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let order = self.pool.order();
+        // Every endpoint gets at least one try before giving up
+        let max_attempts = (self.config.max_retries as usize + 1).max(order.len());
+        let mut attempt = 0;
+
+        loop {
+            let index = order[attempt % order.len()];
+            let started = Instant::now();
+            let result = timeout(self.config.timeout, request(self.clients[index].clone())).await;
+            let latency = started.elapsed();
+
+            let error = match result {
+                Ok(Ok(value)) => {
+                    self.pool.record_success(index, latency);
+                    self.record(method, latency, |metrics| metrics.requests += 1);
+                    return Ok((value, index));
+                }
+                Ok(Err(e)) => {
+                    self.record(method, latency, |metrics| {
+                        metrics.requests += 1;
+                        metrics.failures += 1;
+                    });
+
+                    match rpc_error_kind(&e) {
+                        RpcErrorKind::Rejected => {
+                            // The endpoint answered; it's the request that was rejected
+                            self.pool.record_success(index, latency);
+                            return Err(classify_client_error(e));
+                        }
+                        RpcErrorKind::RateLimited => {
+                            let backoff = self.pool.record_rate_limited(index, latency);
+                            log::warn!("RPC endpoint {} rate limited, backing off for {:?}", self.pool.url(index), backoff);
+                        }
+                        RpcErrorKind::NodeBehind | RpcErrorKind::Transport | RpcErrorKind::Timeout => {
+                            self.pool.record_failure(index, latency)
+                        }
+                    }
+                    classify_client_error(e)
+                }
+                Err(_) => {
+                    self.pool.record_failure(index, latency);
+                    self.record(method, latency, |metrics| {
+                        metrics.requests += 1;
+                        metrics.failures += 1;
+                        metrics.timeouts += 1;
+                    });
+                    DLMMError::rpc_timeout(format!("{} timed out after {:?}", method, self.config.timeout))
+                }
+            };
+
+            attempt += 1;
+            if attempt >= max_attempts {
+                return Err(error);
+            }
+
+            self.record(method, Duration::ZERO, |metrics| metrics.retries += 1);
+
+            // Fail over straight away while untried endpoints remain, back off once they've all failed
+            if attempt < order.len() {
+                log::warn!("RPC {} failed on {}, failing over: {}", method, self.pool.url(index), error);
+            } else {
+                let retry = (attempt - order.len()) as u32;
+                let delay = self.config.retry_base_delay * 2u32.pow(retry);
+                log::warn!("RPC {} failed, retrying in {:?}: {}", method, delay, error);
+                sleep(delay).await;
+            }
+        }
+    }
+
+    /// Poll every endpoint's slot to refresh latency and slot lag
+    pub async fn refresh_health(&self) {
+        let checks = self.clients.iter().enumerate().map(|(index, client)| async move {
+            let started = Instant::now();
+            let result = timeout(self.config.timeout, client.get_slot_with_commitment(self.config.commitment)).await;
+            let latency = started.elapsed();
+
+            match result {
+                Ok(Ok(slot)) => {
+                    self.pool.record_success(index, latency);
+                    self.pool.observe_slot(index, slot);
+                }
+                Ok(Err(e)) if rpc_error_kind(&e) == RpcErrorKind::RateLimited => {
+                    self.pool.record_rate_limited(index, latency);
+                }
+                _ => self.pool.record_failure(index, latency),
+            }
+        });
+
+        futures::future::join_all(checks).await;
+    }
+
+    /// Refresh endpoint health in the background every `health_check_interval`.
+    /// Only worthwhile when the connection spans more than one endpoint.
+    pub fn spawn_health_checks(self: &Arc<Self>) -> JoinHandle<()> {
+        let connection = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(connection.config.health_check_interval);
+            loop {
+                interval.tick().await;
+                connection.refresh_health().await;
+            }
+        })
+    }
+
+    /// Current health of each endpoint, in configuration order
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.pool.health()
+    }
+
+    pub fn endpoint_count(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Per-method request metrics, busiest methods first
+    pub fn metrics(&self) -> Vec<RpcMethodMetrics> {
+        let metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        let mut snapshot: Vec<RpcMethodMetrics> = metrics.values().cloned().collect();
+        snapshot.sort_by_key(|metrics| std::cmp::Reverse(metrics.requests));
+        snapshot
+    }
+
+    fn record(&self, method: &'static str, latency: Duration, update: impl FnOnce(&mut RpcMethodMetrics)) {
+        let mut metrics = self.metrics.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = metrics.entry(method).or_insert_with(|| RpcMethodMetrics {
+            method: method.to_string(),
+            ..Default::default()
+        });
+        entry.total_latency_ms += latency.as_millis() as u64;
+        update(entry);
+    }
+}
+
+#[async_trait]
+impl TransactionRpc for RpcConnection {
+    async fn get_latest_blockhash(&self, commitment: CommitmentConfig) -> DLMMResult<(Hash, u64)> {
+        self.call("getLatestBlockhash", |client| async move {
+            client.get_latest_blockhash_with_commitment(commitment).await
+        }).await
+    }
+
+    async fn get_block_height(&self, commitment: CommitmentConfig) -> DLMMResult<u64> {
+        self.call("getBlockHeight", |client| async move {
+            client.get_block_height_with_commitment(commitment).await
+        }).await
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction, skip_preflight: bool) -> DLMMResult<Signature> {
+        let config = RpcSendTransactionConfig {
+            skip_preflight,
+            max_retries: Some(0), // Rebroadcasting is handled by the sender
+            ..Default::default()
+        };
+
+        self.call("sendTransaction", |client| {
+            let transaction = transaction.clone();
+            async move { client.send_transaction_with_config(&transaction, config).await }
+        }).await
+    }
+
+    async fn get_signature_statuses(&self, signatures: &[Signature]) -> DLMMResult<Vec<Option<TransactionStatus>>> {
+        self.call_with_context("getSignatureStatuses", |client, _| {
+            let signatures = signatures.to_vec();
+            async move { client.get_signature_statuses(&signatures).await }
+        }).await
+    }
+
+    async fn get_transaction_cost(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+    ) -> DLMMResult<Option<TransactionCost>> {
+        let signature = *signature;
+        let transaction = self.call("getTransaction", |client| async move {
+            client.get_transaction_with_config(&signature, RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(commitment),
+                max_supported_transaction_version: Some(0),
+            }).await
+        }).await?;
+
+        Ok(transaction.transaction.meta.map(|meta| TransactionCost {
+            fee_lamports: meta.fee,
+            compute_units_consumed: meta.compute_units_consumed.into(),
+        }))
+    }
+}
+
+#[async_trait]
+impl PriorityFeeRpc for RpcConnection {
+    async fn get_recent_prioritization_fees(&self, accounts: &[Pubkey]) -> DLMMResult<Vec<u64>> {
+        let fees = self.call("getRecentPrioritizationFees", |client| {
+            let accounts = accounts.to_vec();
+            async move { client.get_recent_prioritization_fees(&accounts).await }
+        }).await?;
+
+        Ok(fees.into_iter().map(|fee| fee.prioritization_fee).collect())
+    }
+}
+
+/// SPL Token and Token-2022 program ids
+const TOKEN_PROGRAM_IDS: [&str; 2] = [
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+];
+
+/// Length of an SPL token account; the amount is stored at bytes 64..72
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+#[async_trait]
+impl SimulationRpc for RpcConnection {
+    async fn simulate_transaction(&self, transaction: &Transaction, accounts: &[Pubkey]) -> DLMMResult<TransactionSimulation> {
+        let commitment = self.config.commitment;
+        let addresses: Vec<String> = accounts.iter().map(|account| account.to_string()).collect();
+
+        let result = self.call_with_context("simulateTransaction", |client, min_context_slot| {
+            let transaction = transaction.clone();
+            let accounts = (!addresses.is_empty()).then(|| RpcSimulateTransactionAccountsConfig {
+                encoding: None, // base64
+                addresses: addresses.clone(),
+            });
+            async move {
+                client.simulate_transaction_with_config(&transaction, RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    replace_recent_blockhash: true,
+                    commitment: Some(commitment),
+                    accounts,
+                    min_context_slot,
+                    ..Default::default()
+                }).await
+            }
+        }).await?;
+
+        let post_balances = accounts
+            .iter()
+            .zip(result.accounts.unwrap_or_default())
+            .filter_map(|(address, account)| {
+                let account = account?;
+                let token_amount = if TOKEN_PROGRAM_IDS.contains(&account.owner.as_str()) {
+                    account.data.decode()
+                        .filter(|data| data.len() >= TOKEN_ACCOUNT_LEN)
+                        .map(|data| u64::from_le_bytes(data[64..72].try_into().unwrap()))
+                } else {
+                    None
+                };
+
+                Some(SimulatedAccount {
+                    address: *address,
+                    lamports: account.lamports,
+                    token_amount,
+                })
+            })
+            .collect();
+
+        Ok(TransactionSimulation {
+            error: result.err.as_ref().map(classify_transaction_error),
+            program_error: result.err.as_ref().and_then(program_error),
+            error_message: result.err.map(|e| e.to_string()),
+            compute_units_consumed: result.units_consumed,
+            logs: result.logs.unwrap_or_default(),
+            post_balances,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::{
+        client_error::ClientErrorKind,
+        rpc_response::{Response, RpcResponseContext},
+    };
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn connection(max_retries: u32) -> RpcConnection {
+        RpcConnection::new(RpcConnectionConfig {
+            timeout: Duration::from_millis(20),
+            max_retries,
+            retry_base_delay: Duration::from_millis(1),
+            ..RpcConnectionConfig::new("http://127.0.0.1:1")
+        })
+    }
+
+    fn io_error() -> ClientError {
+        ClientError::from(std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset"))
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures() {
+        let connection = connection(3);
+        let calls = AtomicU32::new(0);
+
+        let value = connection.call("getSlot", |_| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move { if call < 2 { Err(io_error()) } else { Ok(7u64) } }
+        }).await.unwrap();
+
+        assert_eq!(value, 7);
+        let metrics = &connection.metrics()[0];
+        assert_eq!(metrics.requests, 3);
+        assert_eq!(metrics.failures, 2);
+        assert_eq!(metrics.retries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_times_out_and_gives_up() {
+        let connection = connection(1);
+
+        let result: DLMMResult<u64> = connection.call("getSlot", |_| async {
+            sleep(Duration::from_secs(1)).await;
+            Ok(0)
+        }).await;
+
+        assert!(matches!(result, Err(DLMMError::Rpc { kind: RpcErrorKind::Timeout, .. })));
+        let metrics = &connection.metrics()[0];
+        assert_eq!(metrics.timeouts, 2);
+        assert_eq!(metrics.retries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_rpc_errors() {
+        let connection = connection(3);
+        let calls = AtomicU32::new(0);
+
+        let result: DLMMResult<u64> = connection.call("getSlot", |_| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(ClientError::from(ClientErrorKind::Custom("invalid params".to_string()))) }
+        }).await;
+
+        assert!(matches!(result, Err(DLMMError::Rpc { kind: RpcErrorKind::Rejected, .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_to_next_endpoint() {
+        let connection = RpcConnection::new(RpcConnectionConfig {
+            max_retries: 0,
+            ..RpcConnectionConfig::with_endpoints(vec![
+                RpcEndpoint::new("http://127.0.0.1:1", 5),
+                RpcEndpoint::new("http://127.0.0.1:2", 1),
+            ])
+        });
+
+        let url = connection.call("getSlot", |client| async move {
+            let url = client.url();
+            if url.ends_with(":1") { Err(io_error()) } else { Ok(url) }
+        }).await.unwrap();
+
+        assert_eq!(url, "http://127.0.0.1:2");
+        let health = connection.endpoint_health();
+        assert_eq!(health[0].failures, 1);
+        assert_eq!(health[1].requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pins_reads_to_highest_context_slot() {
+        let connection = connection(0);
+        let response = |slot| Response {
+            context: RpcResponseContext { slot, api_version: None },
+            value: (),
+        };
+
+        assert_eq!(connection.min_context_slot(), None);
+
+        connection.call_with_context("getBalance", |_, min_slot| async move {
+            assert_eq!(min_slot, None);
+            Ok(response(120))
+        }).await.unwrap();
+
+        connection.call_with_context("getBalance", |_, min_slot| async move {
+            assert_eq!(min_slot, Some(120));
+            Ok(response(125))
+        }).await.unwrap();
+
+        assert_eq!(connection.min_context_slot(), Some(125));
+    }
+}
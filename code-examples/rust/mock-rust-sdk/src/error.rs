@@ -1,66 +1,390 @@
 //! Error types for the Saros DLMM SDK
+//!
+//! Every error carries a [`ErrorCategory`] so callers can tell failures worth retrying
+//! (rate limits, lagging nodes, expired blockhashes) from terminal ones (slippage,
+//! insufficient balance, rejected instructions) without matching on messages.
 
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_custom_error::{JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY},
+    rpc_request::RpcError,
+};
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use std::fmt;
 use thiserror::Error;
 
+use crate::transaction_sender::TransactionErrorKind;
+
+/// Broad classification of an error, used to decide whether to retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ErrorCategory {
+    /// Transport failure reaching the RPC node
+    Network,
+    /// The RPC node rejected the request with HTTP 429
+    RateLimited,
+    /// The RPC node is behind the requested slot or unhealthy
+    NodeBehind,
+    /// The blockhash expired before the transaction landed
+    BlockhashExpired,
+    /// A request timed out
+    Timeout,
+    /// A transaction was sent but it is unknown whether it landed; resending could
+    /// execute it twice
+    OutcomeUnknown,
+    /// A writable account was locked by another transaction
+    AccountInUse,
+    Slippage,
+    InsufficientFunds,
+    InsufficientLiquidity,
+    InvalidInput,
+    /// A pool, position, farm or account doesn't exist
+    NotFound,
+    /// A program instruction failed for a reason not covered above
+    Program,
+    Signing,
+    Configuration,
+    /// Malformed or unreadable data
+    Data,
+    Unknown,
+}
+
+impl ErrorCategory {
+    /// Whether the same operation may succeed if attempted again
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCategory::Network
+                | ErrorCategory::RateLimited
+                | ErrorCategory::NodeBehind
+                | ErrorCategory::BlockhashExpired
+                | ErrorCategory::Timeout
+                | ErrorCategory::AccountInUse
+        )
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorCategory::Network => "network",
+            ErrorCategory::RateLimited => "rate limited",
+            ErrorCategory::NodeBehind => "node behind",
+            ErrorCategory::BlockhashExpired => "blockhash expired",
+            ErrorCategory::Timeout => "timeout",
+            ErrorCategory::OutcomeUnknown => "outcome unknown",
+            ErrorCategory::AccountInUse => "account in use",
+            ErrorCategory::Slippage => "slippage",
+            ErrorCategory::InsufficientFunds => "insufficient funds",
+            ErrorCategory::InsufficientLiquidity => "insufficient liquidity",
+            ErrorCategory::InvalidInput => "invalid input",
+            ErrorCategory::NotFound => "not found",
+            ErrorCategory::Program => "program",
+            ErrorCategory::Signing => "signing",
+            ErrorCategory::Configuration => "configuration",
+            ErrorCategory::Data => "data",
+            ErrorCategory::Unknown => "unknown",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Why an RPC request failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RpcErrorKind {
+    /// HTTP 429
+    RateLimited,
+    /// The node is behind the requested context slot or reports itself unhealthy
+    NodeBehind,
+    /// Connection, I/O or server-side failure
+    Transport,
+    Timeout,
+    /// The node answered and rejected the request; another attempt won't fix it
+    Rejected,
+}
+
+impl RpcErrorKind {
+    pub fn category(self) -> ErrorCategory {
+        match self {
+            RpcErrorKind::RateLimited => ErrorCategory::RateLimited,
+            RpcErrorKind::NodeBehind => ErrorCategory::NodeBehind,
+            RpcErrorKind::Transport => ErrorCategory::Network,
+            RpcErrorKind::Timeout => ErrorCategory::Timeout,
+            RpcErrorKind::Rejected => ErrorCategory::Unknown,
+        }
+    }
+}
+
+/// Classify an RPC client error. Transport failures, rate limiting and lagging nodes
+/// are worth another attempt; errors the node answered with are not.
+pub fn rpc_error_kind(error: &ClientError) -> RpcErrorKind {
+    match error.kind() {
+        ClientErrorKind::Io(_) => RpcErrorKind::Transport,
+        ClientErrorKind::Reqwest(e) => match e.status() {
+            Some(status) if status.as_u16() == 429 => RpcErrorKind::RateLimited,
+            Some(status) if status.is_server_error() => RpcErrorKind::Transport,
+            _ if e.is_timeout() => RpcErrorKind::Timeout,
+            _ if e.is_connect() => RpcErrorKind::Transport,
+            _ => RpcErrorKind::Rejected,
+        },
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if *code == JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED
+                || *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY =>
+        {
+            RpcErrorKind::NodeBehind
+        }
+        _ => RpcErrorKind::Rejected,
+    }
+}
+
+/// Custom error codes returned by the DLMM program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProgramErrorCode {
+    InvalidBinId,
+    SlippageExceeded,
+    InsufficientLiquidity,
+    InvalidPosition,
+    BinArrayNotFound,
+    ZeroLiquidity,
+    InvalidBinStep,
+    MathOverflow,
+    PositionNotEmpty,
+    InvalidRewardIndex,
+    /// A code this SDK doesn't know, e.g. from another program in the transaction
+    Custom(u32),
+}
+
+impl ProgramErrorCode {
+    /// Program error codes start after the 6000 reserved by Anchor
+    const OFFSET: u32 = 6000;
+
+    const KNOWN: [ProgramErrorCode; 10] = [
+        ProgramErrorCode::InvalidBinId,
+        ProgramErrorCode::SlippageExceeded,
+        ProgramErrorCode::InsufficientLiquidity,
+        ProgramErrorCode::InvalidPosition,
+        ProgramErrorCode::BinArrayNotFound,
+        ProgramErrorCode::ZeroLiquidity,
+        ProgramErrorCode::InvalidBinStep,
+        ProgramErrorCode::MathOverflow,
+        ProgramErrorCode::PositionNotEmpty,
+        ProgramErrorCode::InvalidRewardIndex,
+    ];
+
+    pub fn from_code(code: u32) -> Self {
+        code.checked_sub(Self::OFFSET)
+            .and_then(|index| Self::KNOWN.get(index as usize).copied())
+            .unwrap_or(ProgramErrorCode::Custom(code))
+    }
+
+    pub fn code(self) -> u32 {
+        match self {
+            ProgramErrorCode::Custom(code) => code,
+            known => Self::OFFSET + Self::KNOWN.iter().position(|k| *k == known).unwrap_or_default() as u32,
+        }
+    }
+
+    pub fn category(self) -> ErrorCategory {
+        match self {
+            ProgramErrorCode::SlippageExceeded => ErrorCategory::Slippage,
+            ProgramErrorCode::InsufficientLiquidity | ProgramErrorCode::ZeroLiquidity => {
+                ErrorCategory::InsufficientLiquidity
+            }
+            ProgramErrorCode::InvalidBinId
+            | ProgramErrorCode::InvalidBinStep
+            | ProgramErrorCode::InvalidRewardIndex
+            | ProgramErrorCode::PositionNotEmpty => ErrorCategory::InvalidInput,
+            ProgramErrorCode::InvalidPosition | ProgramErrorCode::BinArrayNotFound => ErrorCategory::NotFound,
+            ProgramErrorCode::MathOverflow | ProgramErrorCode::Custom(_) => ErrorCategory::Program,
+        }
+    }
+}
+
+impl fmt::Display for ProgramErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramErrorCode::Custom(code) => write!(f, "custom program error {:#x}", code),
+            known => write!(f, "{:?} ({:#x})", known, known.code()),
+        }
+    }
+}
+
+/// Failing instruction index and program error code of a custom instruction error
+pub fn program_error(error: &TransactionError) -> Option<(u8, ProgramErrorCode)> {
+    match error {
+        TransactionError::InstructionError(instruction, InstructionError::Custom(code)) => {
+            Some((*instruction, ProgramErrorCode::from_code(*code)))
+        }
+        _ => None,
+    }
+}
+
 /// SDK errors
 #[derive(Debug, Error)]
 pub enum DLMMError {
-    #[error("RPC error: {0}")]
-    RpcError(String),
-    
+    #[error("RPC error ({kind:?}): {message}")]
+    Rpc {
+        kind: RpcErrorKind,
+        message: String,
+        #[source]
+        source: Option<Box<ClientError>>,
+    },
+
     #[error("Invalid parameters: {0}")]
     InvalidParams(String),
-    
+
     #[error("Pool not found")]
     PoolNotFound,
-    
+
     #[error("Insufficient liquidity")]
     InsufficientLiquidity,
-    
+
     #[error("Slippage exceeded")]
     SlippageExceeded,
-    
+
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
-    
+
+    #[error("Transaction error ({kind:?}): {message}")]
+    Transaction {
+        kind: TransactionErrorKind,
+        message: String,
+    },
+
+    #[error("Instruction {instruction} failed: {code}")]
+    Program {
+        instruction: u8,
+        code: ProgramErrorCode,
+        message: String,
+    },
+
     #[error("Wallet not configured")]
     WalletNotConfigured,
-    
-    #[error("Network error: {0}")]
-    NetworkError(String),
-    
+
+    #[error("Signer error: {0}")]
+    SignerError(String),
+
     #[error("Deserialization error: {0}")]
-    DeserializationError(String),
-    
+    DeserializationError(#[from] serde_json::Error),
+
     #[error("Position not found")]
     PositionNotFound,
-    
+
     #[error("Invalid bin range")]
     InvalidBinRange,
-    
+
     #[error("Insufficient balance")]
     InsufficientBalance,
-    
+
     #[error("Price oracle unavailable")]
     OracleUnavailable,
-    
+
+    #[error("Price unavailable: {0}")]
+    PriceUnavailable(String),
+
+    #[error("Price feed error: {0}")]
+    PriceFeed(String),
+
     #[error("Farm not found")]
     FarmNotFound,
-    
+
+    #[error("Export error: {0}")]
+    ExportError(#[source] Box<dyn std::error::Error + Send + Sync>),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
-/// Convert from solana_client errors
-impl From<solana_client::client_error::ClientError> for DLMMError {
-    fn from(err: solana_client::client_error::ClientError) -> Self {
-        DLMMError::RpcError(err.to_string())
+impl DLMMError {
+    /// Timeout of an RPC request that never answered
+    pub fn rpc_timeout(message: impl Into<String>) -> Self {
+        DLMMError::Rpc {
+            kind: RpcErrorKind::Timeout,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            DLMMError::Rpc { kind, .. } => kind.category(),
+            DLMMError::InvalidParams(_) | DLMMError::InvalidBinRange => ErrorCategory::InvalidInput,
+            DLMMError::PoolNotFound
+            | DLMMError::PositionNotFound
+            | DLMMError::FarmNotFound
+            | DLMMError::PriceUnavailable(_) => ErrorCategory::NotFound,
+            DLMMError::InsufficientLiquidity => ErrorCategory::InsufficientLiquidity,
+            DLMMError::SlippageExceeded => ErrorCategory::Slippage,
+            DLMMError::InsufficientBalance => ErrorCategory::InsufficientFunds,
+            DLMMError::Transaction { kind, .. } => kind.category(),
+            DLMMError::Program { code, .. } => code.category(),
+            DLMMError::WalletNotConfigured => ErrorCategory::Configuration,
+            DLMMError::SignerError(_) => ErrorCategory::Signing,
+            DLMMError::DeserializationError(_) | DLMMError::ExportError(_) => ErrorCategory::Data,
+            DLMMError::OracleUnavailable | DLMMError::PriceFeed(_) => ErrorCategory::Network,
+            DLMMError::TransactionFailed(_) | DLMMError::Unknown(_) => ErrorCategory::Unknown,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        self.category().is_retryable()
     }
 }
 
-/// Convert from serde_json errors
-impl From<serde_json::Error> for DLMMError {
-    fn from(err: serde_json::Error) -> Self {
-        DLMMError::DeserializationError(err.to_string())
+/// Convert from solana_client errors, keeping the client error as the source
+impl From<ClientError> for DLMMError {
+    fn from(err: ClientError) -> Self {
+        DLMMError::Rpc {
+            kind: rpc_error_kind(&err),
+            message: err.to_string(),
+            source: Some(Box::new(err)),
+        }
+    }
+}
+
+/// Category of the first SDK error in an `anyhow` chain, for callers that wrap SDK errors
+pub fn error_category(error: &anyhow::Error) -> ErrorCategory {
+    error.chain()
+        .find_map(|cause| cause.downcast_ref::<DLMMError>())
+        .map_or(ErrorCategory::Unknown, DLMMError::category)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_error_codes_round_trip() {
+        assert_eq!(ProgramErrorCode::from_code(6001), ProgramErrorCode::SlippageExceeded);
+        assert_eq!(ProgramErrorCode::SlippageExceeded.code(), 6001);
+        assert_eq!(ProgramErrorCode::from_code(6009).code(), 6009);
+        assert_eq!(ProgramErrorCode::from_code(6010), ProgramErrorCode::Custom(6010));
+        assert_eq!(ProgramErrorCode::from_code(1), ProgramErrorCode::Custom(1));
+
+        let error = TransactionError::InstructionError(1, InstructionError::Custom(0x1771));
+        assert_eq!(program_error(&error), Some((1, ProgramErrorCode::SlippageExceeded)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_categories_separate_retryable_from_terminal() {
+        assert!(DLMMError::rpc_timeout("getSlot timed out").is_retryable());
+        assert!(DLMMError::from(ClientError::from(std::io::Error::other("reset"))).is_retryable());
+        assert!(!DLMMError::SlippageExceeded.is_retryable());
+        assert!(!DLMMError::InsufficientBalance.is_retryable());
+
+        let program = DLMMError::Program {
+            instruction: 0,
+            code: ProgramErrorCode::InsufficientLiquidity,
+            message: String::new(),
+        };
+        assert_eq!(program.category(), ErrorCategory::InsufficientLiquidity);
+        assert!(!program.is_retryable());
+    }
+
+    #[test]
+    fn test_error_category_walks_anyhow_chain() {
+        let error = anyhow::Error::new(DLMMError::rpc_timeout("slow")).context("harvest failed");
+        assert_eq!(error_category(&error), ErrorCategory::Timeout);
+        assert_eq!(error_category(&anyhow::anyhow!("plain")), ErrorCategory::Unknown);
+    }
+}
@@ -0,0 +1,673 @@
+//! Accounting ledger of realised events
+//!
+//! Bots record every swap, harvest, reinvestment, withdrawal, fee claim and gas spend as a
+//! normalised [`LedgerEntry`] carrying the token, signed amount, USD value at the
+//! time and fees. The ledger matches disposals against acquisition lots (FIFO or
+//! LIFO) and exports events, realised gains and open lots as CSV for reconciliation.
+//! A [`LedgerJournal`] appends entries to a JSON lines file as they are recorded, so
+//! the ledger survives crashes and restarts.
+
+use crate::{error::DLMMError, types::{SwapQuote, SwapResult}, DLMMResult};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Decimal places kept for USD values in exports
+const USD_DECIMALS: u32 = 6;
+
+/// Holding period after which a disposal counts as long-term
+const LONG_TERM_DAYS: i64 = 365;
+
+/// Kind of realised event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerEventKind {
+    Swap,
+    Harvest,
+    Reinvestment,
+    /// Tokens taken back out of a position when its liquidity is removed
+    Withdrawal,
+    FeeClaim,
+    GasSpend,
+}
+
+impl fmt::Display for LedgerEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LedgerEventKind::Swap => "swap",
+            LedgerEventKind::Harvest => "harvest",
+            LedgerEventKind::Reinvestment => "reinvestment",
+            LedgerEventKind::Withdrawal => "withdrawal",
+            LedgerEventKind::FeeClaim => "fee_claim",
+            LedgerEventKind::GasSpend => "gas_spend",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Lot selection when matching disposals against acquisitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostBasisMethod {
+    /// Oldest lots are disposed of first
+    #[default]
+    Fifo,
+    /// Newest lots are disposed of first
+    Lifo,
+}
+
+impl fmt::Display for CostBasisMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CostBasisMethod::Fifo => write!(f, "fifo"),
+            CostBasisMethod::Lifo => write!(f, "lifo"),
+        }
+    }
+}
+
+impl FromStr for CostBasisMethod {
+    type Err = DLMMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Ok(CostBasisMethod::Fifo),
+            "lifo" => Ok(CostBasisMethod::Lifo),
+            _ => Err(DLMMError::InvalidParams(format!("Unknown cost basis method: {} (expected fifo or lifo)", s))),
+        }
+    }
+}
+
+/// A token and its USD price at the time of an event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerToken {
+    /// Symbol or mint address
+    pub token: String,
+    pub decimals: u8,
+    pub price_usd: Decimal,
+}
+
+impl LedgerToken {
+    /// Convert a raw on-chain amount into token units
+    pub fn ui_amount(&self, raw_amount: u64) -> Decimal {
+        Decimal::from_i128_with_scale(raw_amount as i128, self.decimals as u32)
+    }
+}
+
+/// A single realised event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub timestamp: DateTime<Utc>,
+    pub kind: LedgerEventKind,
+    /// Symbol or mint address
+    pub token: String,
+    /// Token amount: positive when acquired, negative when disposed of
+    pub amount: Decimal,
+    /// USD value of the amount at the time of the event
+    pub usd_value: Decimal,
+    /// Fees attributed to the event (USD); added to the cost of acquisitions and
+    /// deducted from the proceeds of disposals
+    pub fee_usd: Decimal,
+    pub signature: String,
+    /// Wallet, pool or order the event belongs to
+    pub source: String,
+}
+
+impl LedgerEntry {
+    /// Create an entry valuing `amount` at `price_usd`
+    pub fn new(
+        kind: LedgerEventKind,
+        timestamp: DateTime<Utc>,
+        token: impl Into<String>,
+        amount: Decimal,
+        price_usd: Decimal,
+    ) -> Self {
+        Self {
+            timestamp,
+            kind,
+            token: token.into(),
+            amount,
+            usd_value: amount.abs() * price_usd,
+            fee_usd: Decimal::ZERO,
+            signature: String::new(),
+            source: String::new(),
+        }
+    }
+
+    pub fn with_fee_usd(mut self, fee_usd: Decimal) -> Self {
+        self.fee_usd = fee_usd;
+        self
+    }
+
+    pub fn with_signature(mut self, signature: impl Into<String>) -> Self {
+        self.signature = signature.into();
+        self
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    pub fn is_acquisition(&self) -> bool {
+        self.amount > Decimal::ZERO
+    }
+
+    pub fn is_disposal(&self) -> bool {
+        self.amount < Decimal::ZERO
+    }
+}
+
+/// Ledger entries for a swap: the input disposed of, carrying the pool fee, and the output acquired
+pub fn swap_entries(
+    swap: &SwapResult,
+    timestamp: DateTime<Utc>,
+    input: &LedgerToken,
+    output: &LedgerToken,
+) -> [LedgerEntry; 2] {
+    let fee_usd = input.ui_amount(swap.fee) * input.price_usd;
+
+    [
+        LedgerEntry::new(LedgerEventKind::Swap, timestamp, &input.token, -input.ui_amount(swap.amount_in), input.price_usd)
+            .with_fee_usd(fee_usd)
+            .with_signature(&swap.signature),
+        LedgerEntry::new(LedgerEventKind::Swap, timestamp, &output.token, output.ui_amount(swap.amount_out), output.price_usd)
+            .with_signature(&swap.signature),
+    ]
+}
+
+/// Ledger entries for a swap executed at `quote`, whose amounts are already in token units
+pub fn quote_swap_entries(
+    quote: &SwapQuote,
+    signature: &str,
+    timestamp: DateTime<Utc>,
+    input_price_usd: Decimal,
+    output_price_usd: Decimal,
+) -> [LedgerEntry; 2] {
+    let amount = |value: f64| Decimal::from_f64(value).unwrap_or_default();
+
+    [
+        LedgerEntry::new(LedgerEventKind::Swap, timestamp, quote.input_mint.to_string(), -amount(quote.amount_in), input_price_usd)
+            .with_fee_usd(amount(quote.fee) * input_price_usd)
+            .with_signature(signature),
+        LedgerEntry::new(LedgerEventKind::Swap, timestamp, quote.output_mint.to_string(), amount(quote.amount_out), output_price_usd)
+            .with_signature(signature),
+    ]
+}
+
+/// Part of an acquisition not yet disposed of
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenLot {
+    pub token: String,
+    pub amount: Decimal,
+    pub acquired_at: DateTime<Utc>,
+    pub cost_basis_usd: Decimal,
+    pub signature: String,
+}
+
+/// A disposal, or the part of one, matched against a single acquisition lot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RealizedLot {
+    pub token: String,
+    pub amount: Decimal,
+    /// `None` when nothing recorded in the ledger covers the disposal; its cost basis is zero
+    pub acquired_at: Option<DateTime<Utc>>,
+    pub disposed_at: DateTime<Utc>,
+    pub kind: LedgerEventKind,
+    pub proceeds_usd: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub gain_usd: Decimal,
+    pub signature: String,
+    pub source: String,
+}
+
+impl RealizedLot {
+    /// Whether the lot was held for more than a year; `None` when its acquisition is unknown
+    pub fn is_long_term(&self) -> Option<bool> {
+        self.acquired_at
+            .map(|acquired_at| self.disposed_at - acquired_at > Duration::days(LONG_TERM_DAYS))
+    }
+}
+
+/// Disposals matched against acquisition lots
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostBasisReport {
+    pub method: CostBasisMethod,
+    pub realized: Vec<RealizedLot>,
+    pub open_lots: Vec<OpenLot>,
+}
+
+impl CostBasisReport {
+    pub fn total_proceeds_usd(&self) -> Decimal {
+        self.realized.iter().map(|lot| lot.proceeds_usd).sum()
+    }
+
+    pub fn total_cost_basis_usd(&self) -> Decimal {
+        self.realized.iter().map(|lot| lot.cost_basis_usd).sum()
+    }
+
+    pub fn total_gain_usd(&self) -> Decimal {
+        self.realized.iter().map(|lot| lot.gain_usd).sum()
+    }
+}
+
+/// Summary of a CSV export
+#[derive(Debug, Clone)]
+pub struct LedgerExport {
+    pub events: usize,
+    pub realized_lots: usize,
+    pub open_lots: usize,
+    pub realized_gain_usd: Decimal,
+    pub files: Vec<PathBuf>,
+}
+
+/// Normalised record of realised events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: LedgerEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = LedgerEntry>) {
+        self.entries.extend(entries);
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Match every disposal against earlier acquisitions of the same token, in timestamp order
+    pub fn cost_basis(&self, method: CostBasisMethod) -> CostBasisReport {
+        let mut entries: Vec<&LedgerEntry> = self.entries.iter().collect();
+        entries.sort_by_key(|entry| entry.timestamp);
+
+        let mut lots: HashMap<&str, VecDeque<OpenLot>> = HashMap::new();
+        let mut realized = Vec::new();
+
+        for entry in entries {
+            if entry.is_acquisition() {
+                lots.entry(&entry.token).or_default().push_back(OpenLot {
+                    token: entry.token.clone(),
+                    amount: entry.amount,
+                    acquired_at: entry.timestamp,
+                    cost_basis_usd: entry.usd_value + entry.fee_usd,
+                    signature: entry.signature.clone(),
+                });
+                continue;
+            }
+            if !entry.is_disposal() {
+                continue;
+            }
+
+            let disposed = -entry.amount;
+            let proceeds = entry.usd_value - entry.fee_usd;
+            let queue = lots.entry(&entry.token).or_default();
+            let mut remaining = disposed;
+
+            while remaining > Decimal::ZERO {
+                let lot = match method {
+                    CostBasisMethod::Fifo => queue.front_mut(),
+                    CostBasisMethod::Lifo => queue.back_mut(),
+                };
+                let Some(lot) = lot else { break };
+
+                let taken = remaining.min(lot.amount);
+                let cost = lot.cost_basis_usd * taken / lot.amount;
+                let acquired_at = lot.acquired_at;
+                lot.amount -= taken;
+                lot.cost_basis_usd -= cost;
+
+                if lot.amount.is_zero() {
+                    match method {
+                        CostBasisMethod::Fifo => queue.pop_front(),
+                        CostBasisMethod::Lifo => queue.pop_back(),
+                    };
+                }
+
+                realized.push(realized_lot(entry, taken, Some(acquired_at), proceeds * taken / disposed, cost));
+                remaining -= taken;
+            }
+
+            if remaining > Decimal::ZERO {
+                log::warn!(
+                    "No acquisition covers {} {} disposed of in {}; using a zero cost basis",
+                    remaining, entry.token, entry.signature
+                );
+                realized.push(realized_lot(entry, remaining, None, proceeds * remaining / disposed, Decimal::ZERO));
+            }
+        }
+
+        let mut open_lots: Vec<OpenLot> = lots.into_values().flatten().collect();
+        open_lots.sort_by(|a, b| a.acquired_at.cmp(&b.acquired_at).then_with(|| a.token.cmp(&b.token)));
+
+        CostBasisReport { method, realized, open_lots }
+    }
+
+    /// Write every event as CSV
+    pub fn write_events_csv<W: std::io::Write>(&self, writer: W) -> DLMMResult<()> {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(["timestamp", "kind", "token", "amount", "usd_value", "fee_usd", "signature", "source"])
+            .map_err(export_error)?;
+
+        for entry in &self.entries {
+            csv.write_record([
+                entry.timestamp.to_rfc3339(),
+                entry.kind.to_string(),
+                entry.token.clone(),
+                entry.amount.normalize().to_string(),
+                usd(entry.usd_value),
+                usd(entry.fee_usd),
+                entry.signature.clone(),
+                entry.source.clone(),
+            ]).map_err(export_error)?;
+        }
+
+        csv.flush().map_err(export_error)
+    }
+
+    /// Write realised gains, one row per disposal and matched lot (Form 8949 layout)
+    pub fn write_realized_csv<W: std::io::Write>(&self, writer: W, method: CostBasisMethod) -> DLMMResult<()> {
+        write_realized(&self.cost_basis(method), writer)
+    }
+
+    /// Write the lots still held after all disposals
+    pub fn write_open_lots_csv<W: std::io::Write>(&self, writer: W, method: CostBasisMethod) -> DLMMResult<()> {
+        write_open_lots(&self.cost_basis(method), writer)
+    }
+
+    /// Export events, realised gains and open lots as CSV files in `dir`
+    pub fn export_csv(&self, dir: &Path, method: CostBasisMethod) -> DLMMResult<LedgerExport> {
+        std::fs::create_dir_all(dir).map_err(export_error)?;
+        let report = self.cost_basis(method);
+
+        let events_path = dir.join("ledger_events.csv");
+        let realized_path = dir.join(format!("realized_gains_{}.csv", method));
+        let open_lots_path = dir.join(format!("open_lots_{}.csv", method));
+
+        self.write_events_csv(File::create(&events_path).map_err(export_error)?)?;
+        write_realized(&report, File::create(&realized_path).map_err(export_error)?)?;
+        write_open_lots(&report, File::create(&open_lots_path).map_err(export_error)?)?;
+
+        log::info!("Exported {} ledger events to {}", self.entries.len(), dir.display());
+
+        Ok(LedgerExport {
+            events: self.entries.len(),
+            realized_lots: report.realized.len(),
+            open_lots: report.open_lots.len(),
+            realized_gain_usd: report.total_gain_usd(),
+            files: vec![events_path, realized_path, open_lots_path],
+        })
+    }
+}
+
+/// Append-only JSON lines file holding every ledger entry recorded so far
+#[derive(Debug, Clone)]
+pub struct LedgerJournal {
+    path: PathBuf,
+}
+
+impl LedgerJournal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append entries, one JSON object per line
+    pub fn append(&self, entries: &[LedgerEntry]) -> DLMMResult<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(export_error)?;
+        }
+
+        let mut lines = String::new();
+        for entry in entries {
+            lines.push_str(&serde_json::to_string(entry).map_err(export_error)?);
+            lines.push('\n');
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).map_err(export_error)?;
+        file.write_all(lines.as_bytes()).map_err(export_error)?;
+        file.flush().map_err(export_error)
+    }
+
+    /// Read every entry back into a ledger; a missing file is an empty ledger
+    pub fn load(&self) -> DLMMResult<Ledger> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Ledger::new()),
+            Err(e) => return Err(export_error(e)),
+        };
+
+        let mut ledger = Ledger::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(export_error)?;
+            if !line.trim().is_empty() {
+                ledger.record(serde_json::from_str(&line).map_err(export_error)?);
+            }
+        }
+        Ok(ledger)
+    }
+}
+
+fn realized_lot(
+    entry: &LedgerEntry,
+    amount: Decimal,
+    acquired_at: Option<DateTime<Utc>>,
+    proceeds_usd: Decimal,
+    cost_basis_usd: Decimal,
+) -> RealizedLot {
+    RealizedLot {
+        token: entry.token.clone(),
+        amount,
+        acquired_at,
+        disposed_at: entry.timestamp,
+        kind: entry.kind,
+        proceeds_usd,
+        cost_basis_usd,
+        gain_usd: proceeds_usd - cost_basis_usd,
+        signature: entry.signature.clone(),
+        source: entry.source.clone(),
+    }
+}
+
+fn write_realized<W: std::io::Write>(report: &CostBasisReport, writer: W) -> DLMMResult<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record([
+        "description", "token", "amount", "date_acquired", "date_disposed", "proceeds_usd",
+        "cost_basis_usd", "gain_usd", "term", "kind", "signature", "source",
+    ]).map_err(export_error)?;
+
+    for lot in &report.realized {
+        let term = match lot.is_long_term() {
+            Some(true) => "long",
+            Some(false) => "short",
+            None => "unknown",
+        };
+
+        csv.write_record([
+            format!("{} {}", lot.amount.normalize(), lot.token),
+            lot.token.clone(),
+            lot.amount.normalize().to_string(),
+            lot.acquired_at.map(|date| date.to_rfc3339()).unwrap_or_default(),
+            lot.disposed_at.to_rfc3339(),
+            usd(lot.proceeds_usd),
+            usd(lot.cost_basis_usd),
+            usd(lot.gain_usd),
+            term.to_string(),
+            lot.kind.to_string(),
+            lot.signature.clone(),
+            lot.source.clone(),
+        ]).map_err(export_error)?;
+    }
+
+    csv.flush().map_err(export_error)
+}
+
+fn write_open_lots<W: std::io::Write>(report: &CostBasisReport, writer: W) -> DLMMResult<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    csv.write_record(["token", "amount", "date_acquired", "cost_basis_usd", "signature"])
+        .map_err(export_error)?;
+
+    for lot in &report.open_lots {
+        csv.write_record([
+            lot.token.clone(),
+            lot.amount.normalize().to_string(),
+            lot.acquired_at.to_rfc3339(),
+            usd(lot.cost_basis_usd),
+            lot.signature.clone(),
+        ]).map_err(export_error)?;
+    }
+
+    csv.flush().map_err(export_error)
+}
+
+fn usd(value: Decimal) -> String {
+    value.round_dp(USD_DECIMALS).normalize().to_string()
+}
+
+fn export_error(error: impl std::error::Error + Send + Sync + 'static) -> DLMMError {
+    DLMMError::ExportError(Box::new(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn day(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+    }
+
+    fn ledger() -> Ledger {
+        let mut ledger = Ledger::new();
+        ledger.record(LedgerEntry::new(LedgerEventKind::Harvest, day(1), "SAROS", dec!(10), dec!(1)).with_signature("a"));
+        ledger.record(LedgerEntry::new(LedgerEventKind::Harvest, day(2), "SAROS", dec!(10), dec!(2)).with_signature("b"));
+        ledger.record(
+            LedgerEntry::new(LedgerEventKind::Reinvestment, day(3), "SAROS", dec!(-15), dec!(3))
+                .with_fee_usd(dec!(1.5))
+                .with_signature("c"),
+        );
+        ledger
+    }
+
+    #[test]
+    fn test_fifo_and_lifo_lots() {
+        let ledger = ledger();
+
+        // FIFO: all of lot a ($10) and half of lot b ($10) against $43.50 of proceeds
+        let fifo = ledger.cost_basis(CostBasisMethod::Fifo);
+        assert_eq!(fifo.realized.len(), 2);
+        assert_eq!(fifo.realized[0].acquired_at, Some(day(1)));
+        assert_eq!(fifo.realized[0].proceeds_usd, dec!(29));
+        assert_eq!(fifo.total_cost_basis_usd(), dec!(20));
+        assert_eq!(fifo.total_gain_usd(), dec!(23.5));
+        assert_eq!(fifo.open_lots.len(), 1);
+        assert_eq!(fifo.open_lots[0].amount, dec!(5));
+        assert_eq!(fifo.open_lots[0].cost_basis_usd, dec!(10));
+
+        // LIFO: all of lot b ($20) and half of lot a ($5)
+        let lifo = ledger.cost_basis(CostBasisMethod::Lifo);
+        assert_eq!(lifo.realized[0].acquired_at, Some(day(2)));
+        assert_eq!(lifo.total_cost_basis_usd(), dec!(25));
+        assert_eq!(lifo.total_gain_usd(), dec!(18.5));
+        assert_eq!(lifo.open_lots[0].acquired_at, day(1));
+        assert_eq!(lifo.open_lots[0].cost_basis_usd, dec!(5));
+    }
+
+    #[test]
+    fn test_uncovered_disposal_has_zero_basis() {
+        let mut ledger = Ledger::new();
+        ledger.record(LedgerEntry::new(LedgerEventKind::GasSpend, day(1), "SOL", dec!(-0.00001), dec!(100)));
+
+        let report = ledger.cost_basis(CostBasisMethod::Fifo);
+        assert_eq!(report.realized.len(), 1);
+        assert_eq!(report.realized[0].acquired_at, None);
+        assert_eq!(report.realized[0].is_long_term(), None);
+        assert_eq!(report.realized[0].gain_usd, dec!(0.001));
+    }
+
+    #[test]
+    fn test_swap_entries() {
+        let swap = SwapResult {
+            signature: "sig".to_string(),
+            amount_in: 2_000_000_000,
+            amount_out: 219_000_000,
+            fee: 6_000_000,
+            price_impact: 0.1,
+        };
+        let sol = LedgerToken { token: "SOL".to_string(), decimals: 9, price_usd: dec!(110) };
+        let usdc = LedgerToken { token: "USDC".to_string(), decimals: 6, price_usd: dec!(1) };
+
+        let [sold, bought] = swap_entries(&swap, day(1), &sol, &usdc);
+        assert_eq!(sold.amount, dec!(-2));
+        assert_eq!(sold.usd_value, dec!(220));
+        assert_eq!(sold.fee_usd, dec!(0.66));
+        assert_eq!(bought.amount, dec!(219));
+        assert_eq!(bought.usd_value, dec!(219));
+    }
+
+    #[test]
+    fn test_journal_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ledger_journal_{}", std::process::id()));
+        let journal = LedgerJournal::new(dir.join("ledger_events.jsonl"));
+        assert!(journal.load().unwrap().is_empty());
+
+        let entries = ledger().entries().to_vec();
+        journal.append(&entries[..2]).unwrap();
+        journal.append(&entries[2..]).unwrap();
+        assert_eq!(journal.load().unwrap().entries(), entries.as_slice());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_csv_exports() {
+        let ledger = ledger();
+
+        let mut events = Vec::new();
+        ledger.write_events_csv(&mut events).unwrap();
+        let events = String::from_utf8(events).unwrap();
+        assert_eq!(events.lines().count(), 4);
+        assert!(events.starts_with("timestamp,kind,token,amount,usd_value,fee_usd,signature,source\n"));
+        assert!(events.contains("reinvestment,SAROS,-15,45,1.5,c,"));
+
+        let mut realized = Vec::new();
+        ledger.write_realized_csv(&mut realized, CostBasisMethod::Fifo).unwrap();
+        let realized = String::from_utf8(realized).unwrap();
+        assert!(realized.lines().nth(1).unwrap().starts_with("10 SAROS,SAROS,10,2024-01-01T00:00:00+00:00,"));
+        assert!(realized.contains(",29,10,19,short,reinvestment,c,"));
+    }
+}
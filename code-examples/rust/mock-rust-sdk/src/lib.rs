@@ -8,13 +8,40 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 pub mod client;
+pub mod connection;
 pub mod types;
 pub mod bin_math;
 pub mod error;
+pub mod ledger;
+pub mod price_source;
+pub mod priority_fee;
+pub mod rpc_pool;
+pub mod simulation;
+pub mod transaction_sender;
+pub mod wallet;
 
 pub use client::DLMMClient;
+pub use connection::{RpcConnection, RpcConnectionConfig, RpcMethodMetrics};
+pub use rpc_pool::{EndpointHealth, RpcEndpoint};
+pub use simulation::{SimulatedAccount, SimulationRpc, TransactionSimulation};
 pub use types::*;
-pub use error::DLMMError;
+pub use error::{error_category, DLMMError, ErrorCategory, ProgramErrorCode, RpcErrorKind};
+pub use ledger::{
+    CostBasisMethod, CostBasisReport, Ledger, LedgerEntry, LedgerEventKind, LedgerExport, LedgerJournal, LedgerToken,
+    OpenLot, RealizedLot,
+};
+pub use price_source::{
+    serve_prices, ActiveBinPriceSource, CompositePriceSource, FallbackPriceSource, HttpPriceSource,
+    PoolPriceRoute, PriceQuote, PriceSource, PriceSourceConfig, ReplayPriceSource, SharedPriceSource,
+    StaticPriceSource,
+};
+pub use priority_fee::{
+    PriorityFeeConfig, PriorityFeeEstimate, PriorityFeeEstimator, PriorityFeeLevels, PriorityFeeRpc, PriorityLevel,
+};
+pub use transaction_sender::{
+    SendConfig, TransactionErrorKind, TransactionOutcome, TransactionRpc, TransactionSender,
+};
+pub use wallet::{ExternalSigner, Keystore, SharedSigner, SignerSource, WalletSecrets};
 
 // Type aliases for multi-hop routing compatibility
 pub type SarosClient = DLMMClient;
@@ -67,6 +94,26 @@ static MOCK_PRICES: once_cell::sync::Lazy<Arc<RwLock<HashMap<String, f64>>>> =
         Arc::new(RwLock::new(prices))
     });
 
+/// Mainnet mints of the tokens the mock price oracle quotes, by symbol
+static MOCK_TOKEN_MINTS: [(&str, solana_sdk::pubkey::Pubkey); 6] = [
+    ("SOL", solana_sdk::pubkey!("So11111111111111111111111111111111111111112")),
+    ("USDC", solana_sdk::pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")),
+    ("ETH", solana_sdk::pubkey!("7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs")),
+    ("BTC", solana_sdk::pubkey!("3NZ9JMVBmGAqocybic2c7LQCJScmgsAZ6vQqTDzcqmJh")),
+    ("RAY", solana_sdk::pubkey!("4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R")),
+    ("SRM", solana_sdk::pubkey!("SRMuApVNdxXokk5GT7XD5cUUgXMBCoAz2LHeuAoKWRt")),
+];
+
+/// Mint of a token the mock price oracle quotes
+pub fn mock_token_mint(symbol: &str) -> Option<solana_sdk::pubkey::Pubkey> {
+    MOCK_TOKEN_MINTS.iter().find(|(known, _)| *known == symbol).map(|(_, mint)| *mint)
+}
+
+/// Symbol the mock price oracle quotes a mint under, if it is a known token
+pub fn mock_token_symbol(mint: &solana_sdk::pubkey::Pubkey) -> Option<&'static str> {
+    MOCK_TOKEN_MINTS.iter().find(|(_, known)| known == mint).map(|(symbol, _)| *symbol)
+}
+
 /// Get mock price for a token
 pub async fn get_mock_price(symbol: &str) -> f64 {
     let prices = MOCK_PRICES.read().await;
@@ -98,4 +145,12 @@ mod tests {
         let updated_price = get_mock_price("SOL").await;
         assert_eq!(updated_price, 120.0);
     }
+
+    #[test]
+    fn test_mock_token_mints() {
+        let usdc = mock_token_mint("USDC").unwrap();
+        assert_eq!(usdc.to_string(), "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+        assert_eq!(mock_token_symbol(&usdc), Some("USDC"));
+        assert_eq!(mock_token_symbol(&solana_sdk::pubkey::Pubkey::new_unique()), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,624 @@
+//! Pluggable USD price sources
+//!
+//! Anything that values tokens in USD takes a [`PriceSource`] instead of calling a price
+//! API directly. Prices can come from a pool's active bin, a recorded CSV/JSON file
+//! replayed offline, an HTTP endpoint (a local stand-in is included), or a median over
+//! several of these with staleness checks, and [`FallbackPriceSource`] tries sources in
+//! order. With replay and static sources the examples run without network access.
+
+use crate::{bin_math, client::DLMMClient, error::DLMMError, DLMMResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, path::{Path, PathBuf}, str::FromStr, sync::Arc, sync::Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// USD price of a token at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceQuote {
+    pub mint: Pubkey,
+    pub price: Decimal,
+    pub timestamp: DateTime<Utc>,
+    /// Source that produced the quote
+    pub source: String,
+}
+
+/// Provider of USD token prices
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Name used in quotes and logs
+    fn name(&self) -> &str;
+
+    /// Latest USD price of `mint`
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote>;
+
+    /// Recorded prices of `mint` since `since`, oldest first. Sources without history
+    /// return none.
+    async fn get_history(&self, _mint: &Pubkey, _since: DateTime<Utc>) -> DLMMResult<Vec<PriceQuote>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Price source shared between components
+pub type SharedPriceSource = Arc<dyn PriceSource>;
+
+/// Fixed prices, e.g. for stablecoins anchoring an [`ActiveBinPriceSource`]
+#[derive(Debug, Clone, Default)]
+pub struct StaticPriceSource {
+    prices: HashMap<Pubkey, Decimal>,
+}
+
+impl StaticPriceSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_price(mut self, mint: Pubkey, price: Decimal) -> Self {
+        self.prices.insert(mint, price);
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for StaticPriceSource {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        let price = self.prices
+            .get(mint)
+            .ok_or_else(|| DLMMError::PriceUnavailable(format!("no static price for {}", mint)))?;
+        Ok(quote(*mint, *price, Utc::now(), self.name()))
+    }
+}
+
+/// Pool whose active bin prices its two tokens against each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolPriceRoute {
+    pub pool: Pubkey,
+    pub token_x: Pubkey,
+    pub token_y: Pubkey,
+    /// Token X decimals minus token Y decimals
+    pub decimals_diff: i8,
+}
+
+/// Prices derived from DLMM pools' active bins. A token priced by a pool is valued in
+/// the pool's other token, whose USD price comes from the `quote` source.
+pub struct ActiveBinPriceSource {
+    client: Arc<DLMMClient>,
+    quote: SharedPriceSource,
+    routes: Vec<PoolPriceRoute>,
+}
+
+impl ActiveBinPriceSource {
+    pub fn new(client: Arc<DLMMClient>, quote: SharedPriceSource) -> Self {
+        Self { client, quote, routes: Vec::new() }
+    }
+
+    pub fn with_pool(mut self, route: PoolPriceRoute) -> Self {
+        self.routes.push(route);
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for ActiveBinPriceSource {
+    fn name(&self) -> &str {
+        "active-bin"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        let route = self.routes
+            .iter()
+            .find(|route| route.token_x == *mint || route.token_y == *mint)
+            .ok_or_else(|| DLMMError::PriceUnavailable(format!("no pool prices {}", mint)))?;
+
+        let pool = self.client.get_pool(route.pool).await?;
+        let bin_price = Decimal::from_f64(bin_math::bin_id_to_price(pool.active_bin_id, pool.bin_step, route.decimals_diff))
+            .filter(|price| *price > Decimal::ZERO)
+            .ok_or_else(|| DLMMError::PriceUnavailable(format!("active bin {} has no price", pool.active_bin_id)))?;
+
+        // The active bin prices X in Y
+        let price = if route.token_x == *mint {
+            self.quote.get_price(&route.token_y).await?.price * bin_price
+        } else {
+            self.quote.get_price(&route.token_x).await?.price / bin_price
+        };
+
+        Ok(quote(*mint, price, Utc::now(), self.name()))
+    }
+}
+
+/// Recorded row of a replay file
+#[derive(Debug, Deserialize)]
+struct ReplayRecord {
+    timestamp: DateTime<Utc>,
+    mint: String,
+    price: Decimal,
+}
+
+/// Replays recorded prices from a CSV or JSON file. The latest price is the last one
+/// at or before the replay clock, which defaults to the end of the recording.
+#[derive(Debug, Default)]
+pub struct ReplayPriceSource {
+    quotes: HashMap<Pubkey, Vec<PriceQuote>>,
+    clock: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl ReplayPriceSource {
+    pub fn from_quotes(quotes: impl IntoIterator<Item = PriceQuote>) -> Self {
+        let mut by_mint: HashMap<Pubkey, Vec<PriceQuote>> = HashMap::new();
+        for quote in quotes {
+            by_mint.entry(quote.mint).or_default().push(quote);
+        }
+        for quotes in by_mint.values_mut() {
+            quotes.sort_by_key(|quote| quote.timestamp);
+        }
+
+        Self { quotes: by_mint, clock: Mutex::new(None) }
+    }
+
+    /// Load `timestamp,mint,price` rows, with RFC 3339 timestamps
+    pub fn from_csv(path: impl AsRef<Path>) -> DLMMResult<Self> {
+        let path = path.as_ref();
+        let mut reader = csv::Reader::from_path(path).map_err(|e| replay_error(path, e))?;
+        let records = reader
+            .deserialize::<ReplayRecord>()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| replay_error(path, e))?;
+        Self::from_records(records)
+    }
+
+    /// Load an array of `{"timestamp", "mint", "price"}` objects
+    pub fn from_json(path: impl AsRef<Path>) -> DLMMResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| replay_error(path, e))?;
+        Self::from_records(serde_json::from_str(&contents)?)
+    }
+
+    /// Load a file by extension: `.json` as JSON, anything else as CSV
+    pub fn from_file(path: impl AsRef<Path>) -> DLMMResult<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Self::from_json(path),
+            _ => Self::from_csv(path),
+        }
+    }
+
+    /// Replay prices as of `time`
+    pub fn set_clock(&self, time: DateTime<Utc>) {
+        *self.clock.lock().unwrap() = Some(time);
+    }
+
+    fn from_records(records: Vec<ReplayRecord>) -> DLMMResult<Self> {
+        let quotes = records
+            .into_iter()
+            .map(|record| {
+                let mint = Pubkey::from_str(&record.mint)
+                    .map_err(|e| DLMMError::InvalidParams(format!("invalid mint {}: {}", record.mint, e)))?;
+                Ok(quote(mint, record.price, record.timestamp, "replay"))
+            })
+            .collect::<DLMMResult<Vec<_>>>()?;
+        Ok(Self::from_quotes(quotes))
+    }
+
+    fn recorded_until(&self, mint: &Pubkey) -> &[PriceQuote] {
+        let quotes = self.quotes.get(mint).map_or(&[][..], Vec::as_slice);
+        match *self.clock.lock().unwrap() {
+            Some(clock) => &quotes[..quotes.partition_point(|quote| quote.timestamp <= clock)],
+            None => quotes,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for ReplayPriceSource {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        self.recorded_until(mint)
+            .last()
+            .cloned()
+            .ok_or_else(|| DLMMError::PriceUnavailable(format!("no recorded price for {}", mint)))
+    }
+
+    async fn get_history(&self, mint: &Pubkey, since: DateTime<Utc>) -> DLMMResult<Vec<PriceQuote>> {
+        Ok(self.recorded_until(mint)
+            .iter()
+            .filter(|quote| quote.timestamp >= since)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Body of a price endpoint response
+#[derive(Debug, Serialize, Deserialize)]
+struct HttpPriceResponse {
+    price: Decimal,
+    #[serde(default)]
+    timestamp: Option<DateTime<Utc>>,
+}
+
+/// Prices from an HTTP endpoint answering `GET {base_url}/price/{mint}` with
+/// `{"price": ..., "timestamp": ...}`, such as [`serve_prices`] on a local port
+pub struct HttpPriceSource {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl HttpPriceSource {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_timeout(base_url, std::time::Duration::from_secs(5))
+    }
+
+    pub fn with_timeout(base_url: impl Into<String>, timeout: std::time::Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        Self { base_url: base_url.into().trim_end_matches('/').to_string(), http }
+    }
+}
+
+#[async_trait]
+impl PriceSource for HttpPriceSource {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        let url = format!("{}/price/{}", self.base_url, mint);
+        let response = self.http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| DLMMError::PriceFeed(format!("{}: {}", url, e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(DLMMError::PriceUnavailable(format!("{} has no price for {}", self.base_url, mint)));
+        }
+        let body: HttpPriceResponse = response
+            .error_for_status()
+            .map_err(|e| DLMMError::PriceFeed(format!("{}: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| DLMMError::PriceFeed(format!("{}: {}", url, e)))?;
+
+        Ok(quote(*mint, body.price, body.timestamp.unwrap_or_else(Utc::now), self.name()))
+    }
+}
+
+/// Serve `source` over HTTP in the format [`HttpPriceSource`] reads, standing in for a
+/// price API in offline environments. Runs until the listener fails.
+pub async fn serve_prices(listener: TcpListener, source: SharedPriceSource) -> DLMMResult<()> {
+    loop {
+        let (mut stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| DLMMError::PriceFeed(format!("stand-in accept failed: {}", e)))?;
+        let source = source.clone();
+
+        tokio::spawn(async move {
+            let mut request = vec![0u8; 4096];
+            let Ok(read) = stream.read(&mut request).await else { return };
+            let request = String::from_utf8_lossy(&request[..read]);
+
+            let mint = request
+                .split_whitespace()
+                .nth(1)
+                .and_then(|path| path.strip_prefix("/price/"))
+                .and_then(|mint| Pubkey::from_str(mint).ok());
+            let quote = match mint {
+                Some(mint) => source.get_price(&mint).await.ok(),
+                None => None,
+            };
+
+            let response = match quote {
+                Some(quote) => {
+                    let body = serde_json::to_string(&HttpPriceResponse {
+                        price: quote.price,
+                        timestamp: Some(quote.timestamp),
+                    })
+                    .unwrap_or_default();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                }
+                None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// Median of several sources, ignoring sources that fail or whose quotes are stale
+pub struct CompositePriceSource {
+    sources: Vec<SharedPriceSource>,
+    min_sources: usize,
+    max_age: Option<Duration>,
+}
+
+impl CompositePriceSource {
+    pub fn new(sources: Vec<SharedPriceSource>) -> Self {
+        Self { sources, min_sources: 1, max_age: None }
+    }
+
+    /// Require at least `min_sources` fresh quotes
+    pub fn with_min_sources(mut self, min_sources: usize) -> Self {
+        self.min_sources = min_sources.max(1);
+        self
+    }
+
+    /// Ignore quotes older than `max_age`
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+#[async_trait]
+impl PriceSource for CompositePriceSource {
+    fn name(&self) -> &str {
+        "median"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        let results = futures::future::join_all(self.sources.iter().map(|source| source.get_price(mint))).await;
+
+        let now = Utc::now();
+        let mut quotes: Vec<PriceQuote> = results
+            .into_iter()
+            .zip(&self.sources)
+            .filter_map(|(result, source)| match result {
+                Ok(quote) => Some(quote),
+                Err(e) => {
+                    log::debug!("Price source {} failed for {}: {}", source.name(), mint, e);
+                    None
+                }
+            })
+            .filter(|quote| match self.max_age {
+                Some(max_age) if now - quote.timestamp > max_age => {
+                    log::debug!("Ignoring stale {} price for {} from {}", quote.source, mint, quote.timestamp);
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+
+        if quotes.len() < self.min_sources {
+            return Err(DLMMError::PriceUnavailable(format!(
+                "{} fresh price(s) for {}, need {}",
+                quotes.len(),
+                mint,
+                self.min_sources
+            )));
+        }
+
+        quotes.sort_by_key(|quote| quote.price);
+        let middle = quotes.len() / 2;
+        let price = if quotes.len().is_multiple_of(2) {
+            (quotes[middle - 1].price + quotes[middle].price) / Decimal::TWO
+        } else {
+            quotes[middle].price
+        };
+        let timestamp = quotes.iter().map(|quote| quote.timestamp).min().unwrap_or(now);
+        let sources: Vec<&str> = quotes.iter().map(|quote| quote.source.as_str()).collect();
+
+        Ok(quote(*mint, price, timestamp, &format!("median({})", sources.join(","))))
+    }
+
+    /// History from the first source that has any
+    async fn get_history(&self, mint: &Pubkey, since: DateTime<Utc>) -> DLMMResult<Vec<PriceQuote>> {
+        first_history(&self.sources, mint, since).await
+    }
+}
+
+/// First source that prices a token, e.g. a live API with a replay file behind it
+pub struct FallbackPriceSource {
+    sources: Vec<SharedPriceSource>,
+}
+
+impl FallbackPriceSource {
+    pub fn new(sources: Vec<SharedPriceSource>) -> Self {
+        Self { sources }
+    }
+}
+
+#[async_trait]
+impl PriceSource for FallbackPriceSource {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    async fn get_price(&self, mint: &Pubkey) -> DLMMResult<PriceQuote> {
+        let mut last_error = None;
+        for source in &self.sources {
+            match source.get_price(mint).await {
+                Ok(quote) => return Ok(quote),
+                Err(e) => {
+                    log::debug!("Price source {} failed for {}: {}", source.name(), mint, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| DLMMError::PriceUnavailable(format!("no price source for {}", mint))))
+    }
+
+    async fn get_history(&self, mint: &Pubkey, since: DateTime<Utc>) -> DLMMResult<Vec<PriceQuote>> {
+        first_history(&self.sources, mint, since).await
+    }
+}
+
+/// Price source options shared by the example CLIs
+#[derive(Debug, Clone, Default)]
+pub struct PriceSourceConfig {
+    /// Recorded CSV or JSON prices to replay
+    pub price_file: Option<PathBuf>,
+    /// Base URL of an HTTP price endpoint
+    pub price_url: Option<String>,
+    /// Ignore quotes older than this when combining sources
+    pub max_age_secs: Option<u64>,
+}
+
+impl PriceSourceConfig {
+    /// Source for the configured options: `None` when nothing is configured, the source
+    /// itself when one is, and their median when several are
+    pub fn build(&self) -> DLMMResult<Option<SharedPriceSource>> {
+        let mut sources: Vec<SharedPriceSource> = Vec::new();
+        if let Some(path) = &self.price_file {
+            sources.push(Arc::new(ReplayPriceSource::from_file(path)?));
+        }
+        if let Some(url) = &self.price_url {
+            sources.push(Arc::new(HttpPriceSource::new(url.clone())));
+        }
+
+        if sources.len() <= 1 && self.max_age_secs.is_none() {
+            return Ok(sources.pop());
+        }
+        if sources.is_empty() {
+            return Err(DLMMError::InvalidParams("price age limit set without a price source".to_string()));
+        }
+
+        let mut composite = CompositePriceSource::new(sources);
+        if let Some(max_age_secs) = self.max_age_secs {
+            composite = composite.with_max_age(Duration::seconds(max_age_secs as i64));
+        }
+        Ok(Some(Arc::new(composite)))
+    }
+}
+
+async fn first_history(
+    sources: &[SharedPriceSource],
+    mint: &Pubkey,
+    since: DateTime<Utc>,
+) -> DLMMResult<Vec<PriceQuote>> {
+    for source in sources {
+        if let Ok(history) = source.get_history(mint, since).await {
+            if !history.is_empty() {
+                return Ok(history);
+            }
+        }
+    }
+    Ok(Vec::new())
+}
+
+fn quote(mint: Pubkey, price: Decimal, timestamp: DateTime<Utc>, source: &str) -> PriceQuote {
+    PriceQuote { mint, price, timestamp, source: source.to_string() }
+}
+
+fn replay_error(path: &Path, error: impl std::fmt::Display) -> DLMMError {
+    DLMMError::InvalidParams(format!("cannot read price file {}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2024-01-01T{:02}:00:00Z", hour)).unwrap().with_timezone(&Utc)
+    }
+
+    #[tokio::test]
+    async fn test_replay_follows_clock_and_reads_files() {
+        let mint = Pubkey::new_unique();
+        let path = std::env::temp_dir().join(format!("replay-{}.csv", mint));
+        std::fs::write(
+            &path,
+            format!("timestamp,mint,price\n{},{},100\n{},{},104.5\n", at(1).to_rfc3339(), mint, at(2).to_rfc3339(), mint),
+        )
+        .unwrap();
+
+        let replay = ReplayPriceSource::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.get_price(&mint).await.unwrap().price, dec!(104.5));
+        replay.set_clock(at(1));
+        assert_eq!(replay.get_price(&mint).await.unwrap().price, dec!(100));
+        assert_eq!(replay.get_history(&mint, at(0)).await.unwrap().len(), 1);
+
+        replay.set_clock(at(0));
+        assert!(matches!(replay.get_price(&mint).await, Err(DLMMError::PriceUnavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_composite_takes_median_of_fresh_quotes() {
+        let mint = Pubkey::new_unique();
+        let stale = ReplayPriceSource::from_quotes([quote(mint, dec!(1000), at(1), "replay")]);
+        let sources: Vec<SharedPriceSource> = vec![
+            Arc::new(StaticPriceSource::new().with_price(mint, dec!(10))),
+            Arc::new(StaticPriceSource::new().with_price(mint, dec!(12))),
+            Arc::new(StaticPriceSource::new().with_price(mint, dec!(11))),
+            Arc::new(StaticPriceSource::new()),
+            Arc::new(stale),
+        ];
+
+        let fresh = CompositePriceSource::new(sources.clone()).with_max_age(Duration::minutes(5));
+        assert_eq!(fresh.get_price(&mint).await.unwrap().price, dec!(11));
+
+        // Without a staleness limit the recorded outlier joins and the median moves
+        let any_age = CompositePriceSource::new(sources.clone());
+        assert_eq!(any_age.get_price(&mint).await.unwrap().price, dec!(11.5));
+
+        let strict = CompositePriceSource::new(sources).with_max_age(Duration::minutes(5)).with_min_sources(4);
+        assert!(matches!(strict.get_price(&mint).await, Err(DLMMError::PriceUnavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_uses_first_source_with_a_price() {
+        let mint = Pubkey::new_unique();
+        let fallback = FallbackPriceSource::new(vec![
+            Arc::new(StaticPriceSource::new()),
+            Arc::new(StaticPriceSource::new().with_price(mint, dec!(3))),
+            Arc::new(StaticPriceSource::new().with_price(mint, dec!(4))),
+        ]);
+
+        assert_eq!(fallback.get_price(&mint).await.unwrap().price, dec!(3));
+        assert!(matches!(
+            fallback.get_price(&Pubkey::new_unique()).await,
+            Err(DLMMError::PriceUnavailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_active_bin_prices_token_in_quote_counterpart() {
+        let (token_x, token_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let client = Arc::new(DLMMClient::new("http://localhost:8899").unwrap());
+        let usdc = Arc::new(StaticPriceSource::new().with_price(token_y, dec!(1)));
+        let source = ActiveBinPriceSource::new(client, usdc).with_pool(PoolPriceRoute {
+            pool: Pubkey::new_unique(),
+            token_x,
+            token_y,
+            decimals_diff: 0,
+        });
+
+        // The mock pool's active bin is 100 at a 20 bps step
+        let bin_price = Decimal::from_f64(bin_math::bin_id_to_price(100, 20, 0)).unwrap();
+        assert_eq!(source.get_price(&token_x).await.unwrap().price, bin_price);
+        assert!(matches!(source.get_price(&Pubkey::new_unique()).await, Err(DLMMError::PriceUnavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_http_source_reads_local_stand_in() {
+        let mint = Pubkey::new_unique();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let replay: SharedPriceSource = Arc::new(ReplayPriceSource::from_quotes([quote(mint, dec!(42.25), at(3), "replay")]));
+        tokio::spawn(serve_prices(listener, replay));
+
+        let http = HttpPriceSource::new(format!("http://{}", address));
+        let quote = http.get_price(&mint).await.unwrap();
+        assert_eq!(quote.price, dec!(42.25));
+        assert_eq!(quote.timestamp, at(3));
+        assert!(matches!(http.get_price(&Pubkey::new_unique()).await, Err(DLMMError::PriceUnavailable(_))));
+    }
+}
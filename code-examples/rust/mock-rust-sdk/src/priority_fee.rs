@@ -0,0 +1,472 @@
+//! Priority fee estimation
+//!
+//! Samples the prioritization fees recently paid by transactions that write-locked
+//! the accounts a transaction will touch (pool, bin arrays), turns them into
+//! percentile based fee levels and sizes the compute-unit limit from a simulation.
+
+use crate::{
+    error::DLMMError,
+    simulation::{
+        with_compute_budget, SimulationRpc, DEFAULT_COMPUTE_UNIT_LIMIT, DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT,
+        MAX_COMPUTE_UNIT_LIMIT,
+    },
+    DLMMResult,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc, sync::Mutex};
+use tokio::time::{Duration, Instant};
+
+/// `getRecentPrioritizationFees` accepts at most this many accounts
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// How aggressively to bid for inclusion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PriorityLevel {
+    /// Cheaper, may take several slots to land
+    Economic,
+    Standard,
+    /// Outbids most recent transactions on the same accounts
+    Fast,
+}
+
+impl PriorityLevel {
+    pub const ALL: [PriorityLevel; 3] = [PriorityLevel::Economic, PriorityLevel::Standard, PriorityLevel::Fast];
+}
+
+impl fmt::Display for PriorityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriorityLevel::Economic => write!(f, "economic"),
+            PriorityLevel::Standard => write!(f, "standard"),
+            PriorityLevel::Fast => write!(f, "fast"),
+        }
+    }
+}
+
+impl FromStr for PriorityLevel {
+    type Err = DLMMError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "economic" => Ok(PriorityLevel::Economic),
+            "standard" => Ok(PriorityLevel::Standard),
+            "fast" => Ok(PriorityLevel::Fast),
+            other => Err(DLMMError::InvalidParams(format!("Unknown priority level: {}", other))),
+        }
+    }
+}
+
+/// Priority fee estimator settings
+#[derive(Debug, Clone)]
+pub struct PriorityFeeConfig {
+    /// Percentile of recent fees (0-100) used for each level
+    pub economic_percentile: u8,
+    pub standard_percentile: u8,
+    pub fast_percentile: u8,
+    /// Lowest compute-unit price ever bid (micro-lamports)
+    pub min_compute_unit_price: u64,
+    /// Compute-unit price used when no recent fees could be sampled (micro-lamports)
+    pub fallback_compute_unit_price: u64,
+    /// Highest total priority fee paid per transaction (lamports)
+    pub max_fee_lamports: Option<u64>,
+    /// Headroom added on top of the simulated compute units (%)
+    pub compute_unit_margin_percent: u64,
+    /// How long sampled fees are reused for the same accounts
+    pub cache_ttl: Duration,
+}
+
+impl Default for PriorityFeeConfig {
+    fn default() -> Self {
+        Self {
+            economic_percentile: 25,
+            standard_percentile: 50,
+            fast_percentile: 75,
+            min_compute_unit_price: 1_000,
+            fallback_compute_unit_price: 10_000,
+            max_fee_lamports: None,
+            compute_unit_margin_percent: DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT,
+            cache_ttl: Duration::from_secs(10),
+        }
+    }
+}
+
+impl PriorityFeeConfig {
+    pub fn percentile(&self, level: PriorityLevel) -> u8 {
+        match level {
+            PriorityLevel::Economic => self.economic_percentile,
+            PriorityLevel::Standard => self.standard_percentile,
+            PriorityLevel::Fast => self.fast_percentile,
+        }
+    }
+}
+
+/// Compute-unit prices (micro-lamports) for each priority level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorityFeeLevels {
+    pub economic: u64,
+    pub standard: u64,
+    pub fast: u64,
+    /// Number of recent slots the levels were computed from (0 when falling back)
+    pub samples: usize,
+}
+
+impl PriorityFeeLevels {
+    pub fn get(&self, level: PriorityLevel) -> u64 {
+        match level {
+            PriorityLevel::Economic => self.economic,
+            PriorityLevel::Standard => self.standard,
+            PriorityLevel::Fast => self.fast,
+        }
+    }
+}
+
+/// Compute budget for a single transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorityFeeEstimate {
+    /// Price per compute unit (micro-lamports)
+    pub compute_unit_price: u64,
+    pub compute_unit_limit: u32,
+    /// Number of recent slots the price was derived from
+    pub samples: usize,
+}
+
+impl PriorityFeeEstimate {
+    /// Estimate with a fixed compute-unit price and the default limit
+    pub fn fixed(compute_unit_price: u64) -> Self {
+        Self {
+            compute_unit_price,
+            compute_unit_limit: DEFAULT_COMPUTE_UNIT_LIMIT,
+            samples: 0,
+        }
+    }
+
+    /// Total priority fee paid on top of the base fee (lamports)
+    pub fn priority_fee_lamports(&self) -> u64 {
+        let micro_lamports = self.compute_unit_price as u128 * self.compute_unit_limit as u128;
+        micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT) as u64
+    }
+
+    /// Lower the compute-unit price so the total priority fee stays within `max_fee_lamports`
+    pub fn capped(self, max_fee_lamports: u64) -> Self {
+        if self.priority_fee_lamports() <= max_fee_lamports || self.compute_unit_limit == 0 {
+            return self;
+        }
+
+        let max_price = max_fee_lamports as u128 * MICRO_LAMPORTS_PER_LAMPORT / self.compute_unit_limit as u128;
+        Self {
+            compute_unit_price: max_price as u64,
+            ..self
+        }
+    }
+
+    /// Compute budget instructions setting this limit and price
+    pub fn instructions(&self) -> [Instruction; 2] {
+        [
+            ComputeBudgetInstruction::set_compute_unit_limit(self.compute_unit_limit),
+            ComputeBudgetInstruction::set_compute_unit_price(self.compute_unit_price),
+        ]
+    }
+}
+
+/// RPC calls needed to estimate priority fees and compute usage
+#[async_trait]
+pub trait PriorityFeeRpc: SimulationRpc {
+    /// Per-slot prioritization fees (micro-lamports per CU) of recent transactions
+    /// that write-locked any of `accounts`
+    async fn get_recent_prioritization_fees(&self, accounts: &[Pubkey]) -> DLMMResult<Vec<u64>>;
+}
+
+/// Nearest-rank percentile of ascending `sorted` values
+fn percentile(sorted: &[u64], percentile: u8) -> u64 {
+    let rank = (percentile.min(100) as usize * sorted.len()).div_ceil(100);
+    sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
+
+/// Accounts a message write-locks, other than the fee payer
+pub fn writable_accounts(message: &Message) -> Vec<Pubkey> {
+    message
+        .account_keys
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(|(index, _)| message.is_writable(*index))
+        .map(|(_, key)| *key)
+        .collect()
+}
+
+/// Estimates priority fees from recent fees on the accounts a transaction locks and
+/// sets its compute-unit limit from a simulation
+pub struct PriorityFeeEstimator {
+    rpc: Arc<dyn PriorityFeeRpc>,
+    config: PriorityFeeConfig,
+    cache: Mutex<HashMap<Vec<Pubkey>, (Instant, PriorityFeeLevels)>>,
+}
+
+impl PriorityFeeEstimator {
+    pub fn new(rpc: Arc<dyn PriorityFeeRpc>, config: PriorityFeeConfig) -> Self {
+        Self {
+            rpc,
+            config,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &PriorityFeeConfig {
+        &self.config
+    }
+
+    /// Fee levels for transactions write-locking `accounts`.
+    ///
+    /// Falls back to `fallback_compute_unit_price` for every level when the RPC call
+    /// fails or no recent fees were paid on these accounts.
+    pub async fn fee_levels(&self, accounts: &[Pubkey]) -> PriorityFeeLevels {
+        let mut key = accounts.to_vec();
+        key.sort();
+        key.dedup();
+        key.truncate(MAX_FEE_ACCOUNTS);
+
+        if let Some((sampled_at, levels)) = self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            if sampled_at.elapsed() < self.config.cache_ttl {
+                return *levels;
+            }
+        }
+
+        let levels = match self.rpc.get_recent_prioritization_fees(&key).await {
+            Ok(mut fees) if !fees.is_empty() => {
+                fees.sort_unstable();
+                let price = |level| percentile(&fees, self.config.percentile(level)).max(self.config.min_compute_unit_price);
+                PriorityFeeLevels {
+                    economic: price(PriorityLevel::Economic),
+                    standard: price(PriorityLevel::Standard),
+                    fast: price(PriorityLevel::Fast),
+                    samples: fees.len(),
+                }
+            }
+            result => {
+                if let Err(e) = result {
+                    log::warn!("Could not sample recent prioritization fees, using fallback: {}", e);
+                }
+                let fallback = self.config.fallback_compute_unit_price.max(self.config.min_compute_unit_price);
+                PriorityFeeLevels {
+                    economic: fallback,
+                    standard: fallback,
+                    fast: fallback,
+                    samples: 0,
+                }
+            }
+        };
+
+        self.cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, (Instant::now(), levels));
+
+        levels
+    }
+
+    /// Compute budget at `level` for a transaction write-locking `accounts`, at the
+    /// default compute-unit limit and before applying `max_fee_lamports`
+    pub async fn estimate(&self, accounts: &[Pubkey], level: PriorityLevel) -> PriorityFeeEstimate {
+        let levels = self.fee_levels(accounts).await;
+        PriorityFeeEstimate {
+            compute_unit_price: levels.get(level),
+            compute_unit_limit: DEFAULT_COMPUTE_UNIT_LIMIT,
+            samples: levels.samples,
+        }
+    }
+
+    /// Size the compute-unit limit by simulation, cap the fee and prepend the compute
+    /// budget to `transaction`, returning the unsigned transaction and the final budget.
+    ///
+    /// RPC failures fall back to the estimate's limit; a simulation that fails on-chain
+    /// is returned as an error since sending the transaction would fail the same way.
+    pub async fn prepare(
+        &self,
+        transaction: &Transaction,
+        payer: &Pubkey,
+        estimate: PriorityFeeEstimate,
+    ) -> DLMMResult<(Transaction, PriorityFeeEstimate)> {
+        // Simulate at the maximum limit so the measurement isn't cut short
+        let probe = with_compute_budget(transaction, payer, &PriorityFeeEstimate {
+            compute_unit_limit: MAX_COMPUTE_UNIT_LIMIT,
+            ..estimate
+        }.instructions());
+
+        let compute_unit_limit = match self.rpc.simulate_transaction(&probe, &[]).await {
+            Ok(simulation) => simulation
+                .into_result()?
+                .compute_unit_limit(self.config.compute_unit_margin_percent)
+                .unwrap_or(estimate.compute_unit_limit),
+            Err(e) => {
+                log::warn!("Could not simulate compute units, using {}: {}", estimate.compute_unit_limit, e);
+                estimate.compute_unit_limit
+            }
+        };
+
+        let estimate = self.apply_cap(PriorityFeeEstimate {
+            compute_unit_limit,
+            ..estimate
+        });
+
+        Ok((with_compute_budget(transaction, payer, &estimate.instructions()), estimate))
+    }
+
+    /// Lower the estimate's price to keep within `max_fee_lamports`, if set
+    pub fn apply_cap(&self, estimate: PriorityFeeEstimate) -> PriorityFeeEstimate {
+        match self.config.max_fee_lamports {
+            Some(max_fee) if estimate.priority_fee_lamports() > max_fee => {
+                let capped = estimate.capped(max_fee);
+                log::warn!(
+                    "Capping priority fee at {} lamports ({} micro-lamports/CU, estimated {})",
+                    max_fee, capped.compute_unit_price, estimate.compute_unit_price
+                );
+                capped
+            }
+            _ => estimate,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{simulation::TransactionSimulation, transaction_sender::TransactionErrorKind};
+    use solana_sdk::{compute_budget, instruction::AccountMeta, signature::Keypair, signer::Signer};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct MockRpc {
+        fees: Vec<u64>,
+        units: Option<u64>,
+        simulation_fails: bool,
+        fee_calls: AtomicU32,
+    }
+
+    impl MockRpc {
+        fn new(fees: Vec<u64>, units: Option<u64>) -> Self {
+            Self {
+                fees,
+                units,
+                simulation_fails: false,
+                fee_calls: AtomicU32::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PriorityFeeRpc for MockRpc {
+        async fn get_recent_prioritization_fees(&self, _accounts: &[Pubkey]) -> DLMMResult<Vec<u64>> {
+            self.fee_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.fees.clone())
+        }
+    }
+
+    #[async_trait]
+    impl SimulationRpc for MockRpc {
+        async fn simulate_transaction(&self, _transaction: &Transaction, _accounts: &[Pubkey]) -> DLMMResult<TransactionSimulation> {
+            Ok(TransactionSimulation {
+                error: self.simulation_fails.then_some(TransactionErrorKind::ProgramError),
+                error_message: self.simulation_fails.then(|| "custom program error: 0x1".to_string()),
+                compute_units_consumed: self.units,
+                ..Default::default()
+            })
+        }
+    }
+
+    fn config() -> PriorityFeeConfig {
+        PriorityFeeConfig {
+            min_compute_unit_price: 0,
+            ..Default::default()
+        }
+    }
+
+    fn swap_transaction(payer: &Pubkey, pool: Pubkey) -> Transaction {
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(*payer, true), AccountMeta::new(pool, false)],
+        );
+        Transaction::new_unsigned(Message::new(&[instruction], Some(payer)))
+    }
+
+    #[tokio::test]
+    async fn test_levels_follow_percentiles() {
+        let fees = (1..=100).rev().map(|fee| fee * 100).collect();
+        let rpc = Arc::new(MockRpc::new(fees, None));
+        let estimator = PriorityFeeEstimator::new(rpc.clone(), config());
+        let pool = Pubkey::new_unique();
+
+        let levels = estimator.fee_levels(&[pool]).await;
+        assert_eq!((levels.economic, levels.standard, levels.fast), (2_500, 5_000, 7_500));
+        assert_eq!(levels.samples, 100);
+
+        // Cached for the same accounts regardless of order or duplicates
+        estimator.fee_levels(&[pool, pool]).await;
+        assert_eq!(rpc.fee_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_without_samples() {
+        let estimator = PriorityFeeEstimator::new(Arc::new(MockRpc::new(Vec::new(), None)), config());
+
+        let estimate = estimator.estimate(&[Pubkey::new_unique()], PriorityLevel::Fast).await;
+        assert_eq!(estimate.compute_unit_price, 10_000);
+        assert_eq!(estimate.compute_unit_limit, DEFAULT_COMPUTE_UNIT_LIMIT);
+        assert_eq!(estimate.samples, 0);
+        assert_eq!(estimate.priority_fee_lamports(), 2_000);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_sets_limit_from_simulation_and_caps_fee() {
+        let rpc = Arc::new(MockRpc::new(vec![50_000], Some(100_000)));
+        let estimator = PriorityFeeEstimator::new(rpc, PriorityFeeConfig {
+            max_fee_lamports: Some(3_300),
+            ..config()
+        });
+        let payer = Keypair::new();
+        let pool = Pubkey::new_unique();
+        let transaction = swap_transaction(&payer.pubkey(), pool);
+        assert_eq!(writable_accounts(&transaction.message), vec![pool]);
+
+        let estimate = estimator.estimate(&writable_accounts(&transaction.message), PriorityLevel::Standard).await;
+        let (prepared, estimate) = estimator.prepare(&transaction, &payer.pubkey(), estimate).await.unwrap();
+
+        assert_eq!(estimate.compute_unit_limit, 110_000);
+        assert_eq!(estimate.compute_unit_price, 30_000);
+        assert_eq!(estimate.priority_fee_lamports(), 3_300);
+
+        // Budget instructions come first and preparing again doesn't stack them
+        let message = &prepared.message;
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(message.account_keys[0], payer.pubkey());
+        assert_eq!(message.account_keys[message.instructions[0].program_id_index as usize], compute_budget::id());
+        let (again, _) = estimator.prepare(&prepared, &payer.pubkey(), estimate).await.unwrap();
+        assert_eq!(again.message.instructions.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_surfaces_failed_simulation() {
+        let rpc = MockRpc {
+            simulation_fails: true,
+            ..MockRpc::new(vec![1_000], None)
+        };
+        let estimator = PriorityFeeEstimator::new(Arc::new(rpc), config());
+        let payer = Keypair::new();
+        let transaction = swap_transaction(&payer.pubkey(), Pubkey::new_unique());
+
+        let error = estimator
+            .prepare(&transaction, &payer.pubkey(), PriorityFeeEstimate::fixed(1_000))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DLMMError::Transaction { kind: TransactionErrorKind::ProgramError, .. }));
+    }
+}
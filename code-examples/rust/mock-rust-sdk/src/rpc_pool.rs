@@ -0,0 +1,424 @@
+//! Weighted RPC endpoint pool
+//!
+//! Tracks the health of each configured endpoint (latency, error rate and slot lag)
+//! and decides the order endpoints are tried in for every request. Traffic follows
+//! the configured weights scaled by health, while rate-limited or lagging endpoints
+//! are only used as a last resort until they recover.
+
+use crate::{error::DLMMError, DLMMResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Smoothing factor for latency and error rate moving averages
+const HEALTH_EWMA_ALPHA: f64 = 0.2;
+/// Lowest health score, so a struggling endpoint still gets occasional traffic to recover
+const MIN_HEALTH_SCORE: f64 = 0.01;
+
+/// A single RPC endpoint and its share of traffic relative to the others
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcEndpoint {
+    pub url: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl RpcEndpoint {
+    pub fn new(url: impl Into<String>, weight: u32) -> Self {
+        Self {
+            url: url.into(),
+            weight: weight.max(1),
+        }
+    }
+
+    /// Parse an endpoint written as `url` or `url|weight`
+    pub fn parse(spec: &str) -> DLMMResult<Self> {
+        let (url, weight) = match spec.trim().rsplit_once('|') {
+            Some((url, weight)) => {
+                let weight = weight.trim().parse::<u32>().map_err(|_| {
+                    DLMMError::InvalidParams(format!("Invalid weight in RPC endpoint '{}'", spec))
+                })?;
+                (url.trim(), weight)
+            }
+            None => (spec.trim(), 1),
+        };
+
+        if url.is_empty() {
+            return Err(DLMMError::InvalidParams(format!("Empty URL in RPC endpoint '{}'", spec)));
+        }
+
+        Ok(Self::new(url, weight))
+    }
+
+    /// Parse a comma-separated endpoint list, e.g. `https://a.example|3,https://b.example`
+    pub fn parse_list(specs: &str) -> DLMMResult<Vec<Self>> {
+        let endpoints = specs
+            .split(',')
+            .filter(|spec| !spec.trim().is_empty())
+            .map(Self::parse)
+            .collect::<DLMMResult<Vec<_>>>()?;
+
+        if endpoints.is_empty() {
+            return Err(DLMMError::InvalidParams("No RPC endpoints configured".to_string()));
+        }
+
+        Ok(endpoints)
+    }
+}
+
+/// Health snapshot for one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub weight: u32,
+    pub requests: u64,
+    pub failures: u64,
+    pub rate_limited: u64,
+    /// Moving average of request latency
+    pub latency_ms: Option<f64>,
+    /// Moving average of the failure rate, between 0 and 1
+    pub error_rate: f64,
+    pub last_slot: Option<u64>,
+    /// Slots behind the highest slot seen on any endpoint
+    pub slot_lag: u64,
+    pub score: f64,
+    /// False while the endpoint is backing off or lagging too far behind
+    pub available: bool,
+}
+
+/// Thresholds used to score and exclude endpoints
+#[derive(Debug, Clone)]
+pub(crate) struct PoolSettings {
+    pub max_slot_lag: u64,
+    /// Latency at which the latency component of the score halves
+    pub latency_reference: Duration,
+    pub rate_limit_backoff: Duration,
+    pub max_rate_limit_backoff: Duration,
+}
+
+#[derive(Debug)]
+struct EndpointState {
+    endpoint: RpcEndpoint,
+    requests: u64,
+    failures: u64,
+    rate_limited: u64,
+    latency_ms: Option<f64>,
+    error_rate: f64,
+    last_slot: Option<u64>,
+    rate_limit_strikes: u32,
+    backoff_until: Option<Instant>,
+    /// Running weight for smooth weighted round-robin
+    current_weight: f64,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    endpoints: Vec<EndpointState>,
+    highest_slot: u64,
+}
+
+/// Health tracking and endpoint selection for a set of RPC endpoints
+#[derive(Debug)]
+pub(crate) struct EndpointPool {
+    settings: PoolSettings,
+    state: Mutex<PoolState>,
+}
+
+impl EndpointPool {
+    pub fn new(endpoints: Vec<RpcEndpoint>, settings: PoolSettings) -> Self {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|endpoint| EndpointState {
+                endpoint,
+                requests: 0,
+                failures: 0,
+                rate_limited: 0,
+                latency_ms: None,
+                error_rate: 0.0,
+                last_slot: None,
+                rate_limit_strikes: 0,
+                backoff_until: None,
+                current_weight: 0.0,
+            })
+            .collect();
+
+        Self {
+            settings,
+            state: Mutex::new(PoolState { endpoints, highest_slot: 0 }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().endpoints.len()
+    }
+
+    /// Order in which to try endpoints for the next request.
+    ///
+    /// The first entry is chosen by weighted round-robin over available endpoints, the
+    /// remaining available endpoints follow by health score, and endpoints that are
+    /// backing off or lagging come last, soonest-recovering first.
+    pub fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut state = self.lock();
+        let highest_slot = state.highest_slot;
+
+        let scores: Vec<(usize, f64, bool)> = state
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                (index, self.score(endpoint, highest_slot), self.is_available(endpoint, highest_slot, now))
+            })
+            .collect();
+
+        let mut available: Vec<(usize, f64)> = scores
+            .iter()
+            .filter(|(_, _, available)| *available)
+            .map(|(index, score, _)| (*index, *score))
+            .collect();
+
+        let mut order = Vec::with_capacity(scores.len());
+
+        if !available.is_empty() {
+            let mut total = 0.0;
+            let mut selected = available[0].0;
+            let mut selected_weight = f64::MIN;
+
+            for (index, score) in &available {
+                let endpoint = &mut state.endpoints[*index];
+                let effective = endpoint.endpoint.weight as f64 * score;
+                endpoint.current_weight += effective;
+                total += effective;
+
+                if endpoint.current_weight > selected_weight {
+                    selected = *index;
+                    selected_weight = endpoint.current_weight;
+                }
+            }
+            state.endpoints[selected].current_weight -= total;
+
+            order.push(selected);
+            available.retain(|(index, _)| *index != selected);
+            available.sort_by(|a, b| b.1.total_cmp(&a.1));
+            order.extend(available.iter().map(|(index, _)| *index));
+        }
+
+        let mut unavailable: Vec<usize> = scores
+            .iter()
+            .filter(|(_, _, available)| !*available)
+            .map(|(index, _, _)| *index)
+            .collect();
+        unavailable.sort_by_key(|index| state.endpoints[*index].backoff_until);
+        order.extend(unavailable);
+
+        order
+    }
+
+    /// Healthiest available endpoint, without advancing the round-robin
+    pub fn best(&self) -> usize {
+        let now = Instant::now();
+        let state = self.lock();
+
+        state
+            .endpoints
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                let a_key = (self.is_available(a, state.highest_slot, now), self.score(a, state.highest_slot));
+                let b_key = (self.is_available(b, state.highest_slot, now), self.score(b, state.highest_slot));
+                a_key.0.cmp(&b_key.0).then(a_key.1.total_cmp(&b_key.1))
+            })
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    pub fn record_success(&self, index: usize, latency: Duration) {
+        let mut state = self.lock();
+        let endpoint = &mut state.endpoints[index];
+        endpoint.requests += 1;
+        endpoint.latency_ms = Some(ewma(endpoint.latency_ms, latency.as_secs_f64() * 1000.0));
+        endpoint.error_rate = ewma(Some(endpoint.error_rate), 0.0);
+        endpoint.rate_limit_strikes = 0;
+        endpoint.backoff_until = None;
+    }
+
+    pub fn record_failure(&self, index: usize, latency: Duration) {
+        let mut state = self.lock();
+        let endpoint = &mut state.endpoints[index];
+        endpoint.requests += 1;
+        endpoint.failures += 1;
+        endpoint.latency_ms = Some(ewma(endpoint.latency_ms, latency.as_secs_f64() * 1000.0));
+        endpoint.error_rate = ewma(Some(endpoint.error_rate), 1.0);
+    }
+
+    /// Record an HTTP 429 and back the endpoint off, doubling the delay on each strike.
+    /// Returns how long the endpoint will be skipped for.
+    pub fn record_rate_limited(&self, index: usize, latency: Duration) -> Duration {
+        self.record_failure(index, latency);
+
+        let mut state = self.lock();
+        let endpoint = &mut state.endpoints[index];
+        endpoint.rate_limited += 1;
+        endpoint.rate_limit_strikes += 1;
+
+        let backoff = self
+            .settings
+            .rate_limit_backoff
+            .saturating_mul(2u32.saturating_pow(endpoint.rate_limit_strikes - 1))
+            .min(self.settings.max_rate_limit_backoff);
+        endpoint.backoff_until = Some(Instant::now() + backoff);
+        backoff
+    }
+
+    /// Record the slot an endpoint has reached, raising the pool's highest slot
+    pub fn observe_slot(&self, index: usize, slot: u64) {
+        let mut state = self.lock();
+        let endpoint = &mut state.endpoints[index];
+        endpoint.last_slot = Some(endpoint.last_slot.map_or(slot, |last| last.max(slot)));
+        state.highest_slot = state.highest_slot.max(slot);
+    }
+
+    /// Highest slot seen from any endpoint, used to pin reads to a minimum context slot
+    pub fn highest_slot(&self) -> Option<u64> {
+        let slot = self.lock().highest_slot;
+        (slot > 0).then_some(slot)
+    }
+
+    pub fn url(&self, index: usize) -> String {
+        self.lock().endpoints[index].endpoint.url.clone()
+    }
+
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let now = Instant::now();
+        let state = self.lock();
+
+        state
+            .endpoints
+            .iter()
+            .map(|endpoint| EndpointHealth {
+                url: endpoint.endpoint.url.clone(),
+                weight: endpoint.endpoint.weight,
+                requests: endpoint.requests,
+                failures: endpoint.failures,
+                rate_limited: endpoint.rate_limited,
+                latency_ms: endpoint.latency_ms,
+                error_rate: endpoint.error_rate,
+                last_slot: endpoint.last_slot,
+                slot_lag: slot_lag(endpoint, state.highest_slot),
+                score: self.score(endpoint, state.highest_slot),
+                available: self.is_available(endpoint, state.highest_slot, now),
+            })
+            .collect()
+    }
+
+    fn score(&self, endpoint: &EndpointState, highest_slot: u64) -> f64 {
+        let reference_ms = self.settings.latency_reference.as_secs_f64() * 1000.0;
+        let latency = endpoint
+            .latency_ms
+            .map_or(1.0, |latency| reference_ms / (reference_ms + latency));
+        let reliability = 1.0 - endpoint.error_rate;
+        let freshness = 1.0 / (1.0 + slot_lag(endpoint, highest_slot) as f64 / self.settings.max_slot_lag.max(1) as f64);
+
+        (latency * reliability * freshness).max(MIN_HEALTH_SCORE)
+    }
+
+    fn is_available(&self, endpoint: &EndpointState, highest_slot: u64, now: Instant) -> bool {
+        let backing_off = endpoint.backoff_until.is_some_and(|until| until > now);
+        !backing_off && slot_lag(endpoint, highest_slot) <= self.settings.max_slot_lag
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn slot_lag(endpoint: &EndpointState, highest_slot: u64) -> u64 {
+    endpoint.last_slot.map_or(0, |slot| highest_slot.saturating_sub(slot))
+}
+
+fn ewma(previous: Option<f64>, sample: f64) -> f64 {
+    match previous {
+        Some(previous) => previous + HEALTH_EWMA_ALPHA * (sample - previous),
+        None => sample,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(endpoints: Vec<RpcEndpoint>) -> EndpointPool {
+        EndpointPool::new(endpoints, PoolSettings {
+            max_slot_lag: 50,
+            latency_reference: Duration::from_millis(250),
+            rate_limit_backoff: Duration::from_secs(1),
+            max_rate_limit_backoff: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn test_parse_endpoints() {
+        let endpoints = RpcEndpoint::parse_list("https://a.example|3, https://b.example").unwrap();
+        assert_eq!(endpoints, vec![
+            RpcEndpoint::new("https://a.example", 3),
+            RpcEndpoint::new("https://b.example", 1),
+        ]);
+
+        assert!(RpcEndpoint::parse("https://a.example|heavy").is_err());
+        assert!(RpcEndpoint::parse_list(" , ").is_err());
+    }
+
+    #[test]
+    fn test_traffic_follows_weights() {
+        let pool = pool(vec![RpcEndpoint::new("a", 3), RpcEndpoint::new("b", 1)]);
+
+        let first_choices: Vec<usize> = (0..8).map(|_| pool.order()[0]).collect();
+        assert_eq!(first_choices.iter().filter(|index| **index == 0).count(), 6);
+        assert_eq!(first_choices.iter().filter(|index| **index == 1).count(), 2);
+    }
+
+    #[test]
+    fn test_rate_limited_endpoint_is_tried_last() {
+        let pool = pool(vec![RpcEndpoint::new("a", 5), RpcEndpoint::new("b", 1)]);
+
+        assert_eq!(pool.record_rate_limited(0, Duration::ZERO), Duration::from_secs(1));
+        assert_eq!(pool.record_rate_limited(0, Duration::ZERO), Duration::from_secs(2));
+        assert_eq!(pool.order(), vec![1, 0]);
+        assert!(!pool.health()[0].available);
+
+        pool.record_success(0, Duration::from_millis(10));
+        assert!(pool.health()[0].available);
+    }
+
+    #[test]
+    fn test_lagging_endpoint_is_excluded() {
+        let pool = pool(vec![RpcEndpoint::new("a", 5), RpcEndpoint::new("b", 1)]);
+
+        pool.observe_slot(0, 1_000);
+        pool.observe_slot(1, 1_100);
+
+        assert_eq!(pool.highest_slot(), Some(1_100));
+        assert_eq!(pool.health()[0].slot_lag, 100);
+        assert_eq!(pool.order(), vec![1, 0]);
+        assert_eq!(pool.best(), 1);
+    }
+
+    #[test]
+    fn test_failures_lower_score() {
+        let pool = pool(vec![RpcEndpoint::new("a", 1), RpcEndpoint::new("b", 1)]);
+
+        pool.record_success(0, Duration::from_millis(100));
+        pool.record_success(1, Duration::from_millis(100));
+        for _ in 0..3 {
+            pool.record_failure(1, Duration::from_millis(100));
+        }
+
+        let health = pool.health();
+        assert!(health[1].score < health[0].score);
+        assert_eq!(pool.best(), 0);
+    }
+}
@@ -0,0 +1,267 @@
+//! Transaction simulation and compute budgeting
+//!
+//! Simulates transactions before they are sent, reporting consumed compute units,
+//! program logs, the returned error and post-simulation balances, and sizes the
+//! compute-unit limit from the measured usage so failing transactions are caught
+//! before any fee is paid.
+
+use crate::{
+    error::{DLMMError, ProgramErrorCode},
+    transaction_sender::TransactionErrorKind,
+    DLMMResult,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+/// Compute units a transaction may use when it doesn't request a limit
+pub const DEFAULT_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// Highest compute-unit limit a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Default headroom added on top of the simulated compute units (%)
+pub const DEFAULT_COMPUTE_UNIT_MARGIN_PERCENT: u64 = 10;
+
+/// Program logs kept in error messages
+const ERROR_LOG_LINES: usize = 5;
+
+/// State of an account after the simulated transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedAccount {
+    pub address: Pubkey,
+    pub lamports: u64,
+    /// Token amount, for SPL token accounts
+    pub token_amount: Option<u64>,
+}
+
+/// Result of simulating a transaction
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionSimulation {
+    /// Classification of the error the transaction would fail with
+    pub error: Option<TransactionErrorKind>,
+    pub error_message: Option<String>,
+    /// Failing instruction and program error code, for custom program errors
+    #[serde(default)]
+    pub program_error: Option<(u8, ProgramErrorCode)>,
+    pub compute_units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    /// Post-simulation state of the requested accounts that exist
+    pub post_balances: Vec<SimulatedAccount>,
+}
+
+impl TransactionSimulation {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Compute-unit limit covering the simulated usage plus `margin_percent`
+    pub fn compute_unit_limit(&self, margin_percent: u64) -> Option<u32> {
+        self.compute_units_consumed
+            .map(|units| compute_unit_limit(units, margin_percent))
+    }
+
+    /// Post-simulation state of `address`, if it was requested and exists
+    pub fn post_balance(&self, address: &Pubkey) -> Option<&SimulatedAccount> {
+        self.post_balances.iter().find(|account| account.address == *address)
+    }
+
+    /// Convert a failed simulation into an error carrying the last program logs
+    pub fn into_result(self) -> DLMMResult<Self> {
+        match self.error {
+            None => Ok(self),
+            Some(kind) => {
+                let tail = &self.logs[self.logs.len().saturating_sub(ERROR_LOG_LINES)..];
+                let mut message = format!("Simulation failed: {}", self.error_message.unwrap_or_default());
+                if !tail.is_empty() {
+                    message.push_str(&format!(" (logs: {})", tail.join(" | ")));
+                }
+                Err(match self.program_error {
+                    Some((instruction, code)) => DLMMError::Program { instruction, code, message },
+                    None => DLMMError::Transaction { kind, message },
+                })
+            }
+        }
+    }
+}
+
+/// RPC call needed to simulate transactions
+#[async_trait]
+pub trait SimulationRpc: Send + Sync {
+    /// Simulate `transaction` without signature verification, returning the post-simulation
+    /// state of `accounts`. A transaction that would fail is reported in the result, not as an error.
+    async fn simulate_transaction(&self, transaction: &Transaction, accounts: &[Pubkey]) -> DLMMResult<TransactionSimulation>;
+}
+
+/// Compute-unit limit covering `units_consumed` plus `margin_percent`, within the protocol maximum
+pub fn compute_unit_limit(units_consumed: u64, margin_percent: u64) -> u32 {
+    let with_margin = (units_consumed * (100 + margin_percent)).div_ceil(100);
+    with_margin.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+/// Rebuild a transaction's instructions for `payer` behind the given compute budget
+/// instructions, dropping any compute budget instructions it already had
+pub fn with_compute_budget(transaction: &Transaction, payer: &Pubkey, budget: &[Instruction]) -> Transaction {
+    let message = &transaction.message;
+    let mut instructions = budget.to_vec();
+
+    for compiled in &message.instructions {
+        let program_id = message.account_keys[compiled.program_id_index as usize];
+        if program_id == compute_budget::id() {
+            continue;
+        }
+
+        let accounts = compiled
+            .accounts
+            .iter()
+            .map(|&index| {
+                let index = index as usize;
+                AccountMeta {
+                    pubkey: message.account_keys[index],
+                    is_signer: message.is_signer(index),
+                    is_writable: message.is_writable(index),
+                }
+            })
+            .collect();
+
+        instructions.push(Instruction {
+            program_id,
+            accounts,
+            data: compiled.data.clone(),
+        });
+    }
+
+    Transaction::new_unsigned(Message::new(&instructions, Some(payer)))
+}
+
+/// Simulate `transaction` at the maximum compute-unit limit and set its limit to the
+/// measured usage plus `margin_percent`.
+///
+/// Returns an error, before any fee is paid, if the transaction would fail. Any compute
+/// unit price already on the transaction is dropped; use the priority fee estimator to
+/// set both. When the node doesn't report compute units the transaction is returned as is.
+pub async fn budget_compute_units(
+    rpc: &dyn SimulationRpc,
+    transaction: &Transaction,
+    payer: &Pubkey,
+    margin_percent: u64,
+) -> DLMMResult<(Transaction, TransactionSimulation)> {
+    let probe = with_compute_budget(transaction, payer, &[
+        ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+    ]);
+    let simulation = rpc.simulate_transaction(&probe, &[]).await?.into_result()?;
+
+    let transaction = match simulation.compute_unit_limit(margin_percent) {
+        Some(limit) => {
+            log::debug!(
+                "Simulation used {} CU, setting limit to {}",
+                simulation.compute_units_consumed.unwrap_or_default(), limit
+            );
+            with_compute_budget(transaction, payer, &[ComputeBudgetInstruction::set_compute_unit_limit(limit)])
+        }
+        None => transaction.clone(),
+    };
+
+    Ok((transaction, simulation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{signature::Keypair, signer::Signer};
+    use std::sync::Mutex;
+
+    struct MockRpc {
+        simulation: TransactionSimulation,
+        simulated: Mutex<Vec<Transaction>>,
+    }
+
+    #[async_trait]
+    impl SimulationRpc for MockRpc {
+        async fn simulate_transaction(&self, transaction: &Transaction, _accounts: &[Pubkey]) -> DLMMResult<TransactionSimulation> {
+            self.simulated.lock().unwrap().push(transaction.clone());
+            Ok(self.simulation.clone())
+        }
+    }
+
+    fn transaction(payer: &Pubkey) -> Transaction {
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(*payer, true), AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        Transaction::new_unsigned(Message::new(&[instruction], Some(payer)))
+    }
+
+    #[test]
+    fn test_compute_unit_limit_margin() {
+        assert_eq!(compute_unit_limit(100_000, 10), 110_000);
+        assert_eq!(compute_unit_limit(1, 10), 2);
+        assert_eq!(compute_unit_limit(1_380_000, 10), MAX_COMPUTE_UNIT_LIMIT);
+    }
+
+    #[tokio::test]
+    async fn test_budget_sets_limit_from_simulation() {
+        let rpc = MockRpc {
+            simulation: TransactionSimulation {
+                compute_units_consumed: Some(42_000),
+                ..Default::default()
+            },
+            simulated: Mutex::new(Vec::new()),
+        };
+        let payer = Keypair::new();
+
+        let (budgeted, simulation) = budget_compute_units(&rpc, &transaction(&payer.pubkey()), &payer.pubkey(), 10)
+            .await
+            .unwrap();
+
+        assert_eq!(simulation.compute_unit_limit(10), Some(46_200));
+        let message = &budgeted.message;
+        assert_eq!(message.instructions.len(), 2);
+        assert_eq!(message.account_keys[message.instructions[0].program_id_index as usize], compute_budget::id());
+        assert_eq!(message.instructions[0].data, ComputeBudgetInstruction::set_compute_unit_limit(46_200).data);
+
+        // The probe ran at the maximum limit
+        let probe = &rpc.simulated.lock().unwrap()[0];
+        assert_eq!(probe.message.instructions[0].data, ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT).data);
+    }
+
+    #[tokio::test]
+    async fn test_budget_fails_fast_with_logs() {
+        let rpc = MockRpc {
+            simulation: TransactionSimulation {
+                error: Some(TransactionErrorKind::ProgramError),
+                error_message: Some("Error processing Instruction 1: custom program error: 0x1771".to_string()),
+                program_error: Some((1, ProgramErrorCode::SlippageExceeded)),
+                compute_units_consumed: Some(12_000),
+                logs: vec![
+                    "Program log: Instruction: Swap".to_string(),
+                    "Program log: Error: SlippageExceeded".to_string(),
+                ],
+                post_balances: Vec::new(),
+            },
+            simulated: Mutex::new(Vec::new()),
+        };
+        let payer = Keypair::new();
+
+        let error = budget_compute_units(&rpc, &transaction(&payer.pubkey()), &payer.pubkey(), 10)
+            .await
+            .unwrap_err();
+
+        match error {
+            DLMMError::Program { instruction, code, message } => {
+                assert_eq!(instruction, 1);
+                assert_eq!(code, ProgramErrorCode::SlippageExceeded);
+                assert!(message.contains("0x1771"));
+                assert!(message.contains("SlippageExceeded"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+}
@@ -0,0 +1,572 @@
+//! Transaction lifecycle management
+//!
+//! Sends transactions with a fresh blockhash, polls their status up to a target
+//! commitment and rebuilds them with a new blockhash when the previous one expires.
+
+use crate::{
+    error::{program_error, DLMMError, ErrorCategory, ProgramErrorCode},
+    DLMMResult,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::ClientError;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    message::Message,
+    signature::Signature,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::TransactionStatus;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+/// Classification of why a transaction failed to land or failed on-chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionErrorKind {
+    /// The blockhash expired before the transaction landed
+    BlockhashExpired,
+    /// The fee payer can't cover fees or rent
+    InsufficientFunds,
+    /// A program instruction returned an error
+    ProgramError,
+    /// A writable account was locked by another transaction
+    AccountInUse,
+    /// The same signature was already processed
+    AlreadyProcessed,
+    /// The transaction couldn't be signed
+    Signing,
+    /// Transport or RPC node failure
+    Network,
+    /// The transaction wasn't confirmed in time and may still land
+    Timeout,
+    Other,
+}
+
+impl TransactionErrorKind {
+    pub fn category(self) -> ErrorCategory {
+        match self {
+            TransactionErrorKind::BlockhashExpired => ErrorCategory::BlockhashExpired,
+            TransactionErrorKind::InsufficientFunds => ErrorCategory::InsufficientFunds,
+            TransactionErrorKind::ProgramError => ErrorCategory::Program,
+            TransactionErrorKind::AccountInUse => ErrorCategory::AccountInUse,
+            TransactionErrorKind::Signing => ErrorCategory::Signing,
+            TransactionErrorKind::Network => ErrorCategory::Network,
+            TransactionErrorKind::Timeout => ErrorCategory::OutcomeUnknown,
+            TransactionErrorKind::AlreadyProcessed | TransactionErrorKind::Other => ErrorCategory::Unknown,
+        }
+    }
+
+    /// Whether rebuilding and resending the transaction may succeed
+    pub fn is_retryable(self) -> bool {
+        self.category().is_retryable()
+    }
+}
+
+/// Classify an on-chain or preflight transaction error
+pub fn classify_transaction_error(error: &TransactionError) -> TransactionErrorKind {
+    match error {
+        TransactionError::BlockhashNotFound => TransactionErrorKind::BlockhashExpired,
+        TransactionError::InsufficientFundsForFee
+        | TransactionError::InsufficientFundsForRent { .. } => TransactionErrorKind::InsufficientFunds,
+        TransactionError::InstructionError(_, _) => TransactionErrorKind::ProgramError,
+        TransactionError::AccountInUse => TransactionErrorKind::AccountInUse,
+        TransactionError::AlreadyProcessed => TransactionErrorKind::AlreadyProcessed,
+        _ => TransactionErrorKind::Other,
+    }
+}
+
+/// Convert a failed on-chain or preflight transaction error, mapping program error codes
+pub fn transaction_error(error: &TransactionError) -> DLMMError {
+    match program_error(error) {
+        Some((instruction, code)) => DLMMError::Program {
+            instruction,
+            code,
+            message: error.to_string(),
+        },
+        None => DLMMError::Transaction {
+            kind: classify_transaction_error(error),
+            message: error.to_string(),
+        },
+    }
+}
+
+/// Convert an RPC client error, keeping the transaction error classification when there is one
+pub fn classify_client_error(error: ClientError) -> DLMMError {
+    match error.get_transaction_error() {
+        Some(transaction_err) => transaction_error(&transaction_err),
+        None => error.into(),
+    }
+}
+
+fn error_kind(error: &DLMMError) -> TransactionErrorKind {
+    match error {
+        DLMMError::Transaction { kind, .. } => *kind,
+        DLMMError::Program { .. } => TransactionErrorKind::ProgramError,
+        error if error.is_retryable() => TransactionErrorKind::Network,
+        _ => TransactionErrorKind::Other,
+    }
+}
+
+/// Fee and compute usage of a landed transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionCost {
+    pub fee_lamports: u64,
+    pub compute_units_consumed: Option<u64>,
+}
+
+/// RPC calls needed to drive a transaction through its lifecycle
+#[async_trait]
+pub trait TransactionRpc: Send + Sync {
+    /// Latest blockhash and the last block height at which it is valid
+    async fn get_latest_blockhash(&self, commitment: CommitmentConfig) -> DLMMResult<(Hash, u64)>;
+
+    async fn get_block_height(&self, commitment: CommitmentConfig) -> DLMMResult<u64>;
+
+    async fn send_transaction(&self, transaction: &Transaction, skip_preflight: bool) -> DLMMResult<Signature>;
+
+    async fn get_signature_statuses(&self, signatures: &[Signature]) -> DLMMResult<Vec<Option<TransactionStatus>>>;
+
+    async fn get_transaction_cost(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+    ) -> DLMMResult<Option<TransactionCost>>;
+}
+
+/// Transaction sender configuration
+#[derive(Debug, Clone)]
+pub struct SendConfig {
+    /// Commitment a transaction must reach to be considered landed
+    pub commitment: CommitmentConfig,
+    /// Delay between status polls (the transaction is rebroadcast on each poll)
+    pub poll_interval: Duration,
+    /// Overall time limit across all rebuilds
+    pub confirm_timeout: Duration,
+    /// How many times to rebuild with a new blockhash after expiry
+    pub max_rebuilds: u32,
+    pub skip_preflight: bool,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            poll_interval: Duration::from_millis(500),
+            confirm_timeout: Duration::from_secs(90),
+            max_rebuilds: 3,
+            skip_preflight: false,
+        }
+    }
+}
+
+/// Result of a transaction that landed, successfully or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionOutcome {
+    pub signature: Signature,
+    pub slot: u64,
+    pub fee_lamports: Option<u64>,
+    pub compute_units_consumed: Option<u64>,
+    /// Number of distinct signed versions sent before one landed
+    pub attempts: u32,
+    /// Classification of the on-chain error, if the transaction failed
+    pub error: Option<TransactionErrorKind>,
+    pub error_message: Option<String>,
+    /// Failing instruction and program error code, for custom program errors
+    #[serde(default)]
+    pub program_error: Option<(u8, ProgramErrorCode)>,
+}
+
+impl TransactionOutcome {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Fee paid in SOL, if known
+    pub fn fee_sol(&self) -> Option<f64> {
+        self.fee_lamports.map(|lamports| lamports as f64 / 1_000_000_000.0)
+    }
+
+    /// Convert a landed-but-failed outcome into an error
+    pub fn into_result(self) -> DLMMResult<Self> {
+        let Some(kind) = self.error else { return Ok(self) };
+        let message = format!("{} failed on-chain: {}", self.signature, self.error_message.unwrap_or_default());
+
+        Err(match self.program_error {
+            Some((instruction, code)) => DLMMError::Program { instruction, code, message },
+            None => DLMMError::Transaction { kind, message },
+        })
+    }
+}
+
+fn sign(message: &Message, signers: &[&(dyn Signer + Sync)], blockhash: Hash) -> DLMMResult<Transaction> {
+    let signers: Vec<&dyn Signer> = signers.iter().map(|signer| *signer as &dyn Signer).collect();
+
+    let mut transaction = Transaction::new_unsigned(message.clone());
+    transaction.try_sign(&signers, blockhash).map_err(|e| DLMMError::Transaction {
+        kind: TransactionErrorKind::Signing,
+        message: e.to_string(),
+    })?;
+
+    Ok(transaction)
+}
+
+/// Shared transaction sender: signs with a fresh blockhash, rebroadcasts until the
+/// transaction lands and rebuilds it when the blockhash expires
+pub struct TransactionSender {
+    rpc: Arc<dyn TransactionRpc>,
+    config: SendConfig,
+}
+
+impl TransactionSender {
+    pub fn new(rpc: Arc<dyn TransactionRpc>, config: SendConfig) -> Self {
+        Self { rpc, config }
+    }
+
+    /// Re-sign a prebuilt transaction's message with a fresh blockhash and send it
+    pub async fn send_transaction(
+        &self,
+        transaction: &Transaction,
+        signers: &[&(dyn Signer + Sync)],
+    ) -> DLMMResult<TransactionOutcome> {
+        self.send_and_confirm(&transaction.message, signers).await
+    }
+
+    /// Sign, send and confirm a message, rebuilding it whenever its blockhash expires
+    pub async fn send_and_confirm(
+        &self,
+        message: &Message,
+        signers: &[&(dyn Signer + Sync)],
+    ) -> DLMMResult<TransactionOutcome> {
+        let started = Instant::now();
+        let commitment = self.config.commitment;
+        let mut signatures: Vec<Signature> = Vec::new();
+
+        for rebuild in 0..=self.config.max_rebuilds {
+            let (blockhash, last_valid_block_height) = self.rpc.get_latest_blockhash(commitment).await?;
+
+            let transaction = sign(message, signers, blockhash)?;
+
+            // An unchanged blockhash yields the same signature, which is already tracked
+            let signature = transaction.signatures[0];
+            if !signatures.contains(&signature) {
+                signatures.push(signature);
+            }
+
+            log::info!("Sending transaction {} (build {}/{})", signature, rebuild + 1, self.config.max_rebuilds + 1);
+
+            loop {
+                match self.rpc.send_transaction(&transaction, self.config.skip_preflight).await {
+                    Ok(_) => {}
+                    Err(e) => match error_kind(&e) {
+                        TransactionErrorKind::AlreadyProcessed => {}
+                        TransactionErrorKind::BlockhashExpired => break,
+                        kind if kind.is_retryable() => log::warn!("Send of {} failed, will retry: {}", signature, e),
+                        _ => return Err(e),
+                    },
+                }
+
+                let statuses = self.rpc.get_signature_statuses(&signatures).await?;
+                let mut processed = false;
+                for (signature, status) in signatures.iter().zip(statuses) {
+                    let Some(status) = status else { continue };
+                    processed = true;
+                    if status.satisfies_commitment(commitment) {
+                        return Ok(self.landed(*signature, status, signatures.len() as u32).await);
+                    }
+                }
+
+                if started.elapsed() >= self.config.confirm_timeout {
+                    return self.await_expiry(&signatures, last_valid_block_height).await;
+                }
+
+                // A processed transaction can still confirm, so only rebuild when nothing has landed
+                if !processed && self.rpc.get_block_height(commitment).await? > last_valid_block_height {
+                    log::warn!("Blockhash expired for {}, rebuilding", signature);
+                    break;
+                }
+
+                sleep(self.config.poll_interval).await;
+            }
+        }
+
+        Err(DLMMError::Transaction {
+            kind: TransactionErrorKind::BlockhashExpired,
+            message: format!("Transaction not confirmed after {} rebuilds", self.config.max_rebuilds),
+        })
+    }
+
+    /// After the confirm timeout, keep polling the signatures sent so far without
+    /// rebroadcasting until the latest blockhash expires. A transaction that still hasn't
+    /// landed by then never will, so resending is safe; anything else is an unknown outcome.
+    async fn await_expiry(
+        &self,
+        signatures: &[Signature],
+        last_valid_block_height: u64,
+    ) -> DLMMResult<TransactionOutcome> {
+        let commitment = self.config.commitment;
+        let unknown = |reason: String| DLMMError::Transaction {
+            kind: TransactionErrorKind::Timeout,
+            message: format!(
+                "{} not confirmed within {:?} and may still land: {}",
+                signatures[signatures.len() - 1], self.config.confirm_timeout, reason
+            ),
+        };
+
+        loop {
+            let statuses = self.rpc.get_signature_statuses(signatures).await.map_err(|e| unknown(e.to_string()))?;
+            let mut processed = false;
+            for (signature, status) in signatures.iter().zip(statuses) {
+                let Some(status) = status else { continue };
+                processed = true;
+                if status.satisfies_commitment(commitment) {
+                    return Ok(self.landed(*signature, status, signatures.len() as u32).await);
+                }
+            }
+
+            let block_height = self.rpc.get_block_height(commitment).await.map_err(|e| unknown(e.to_string()))?;
+            if block_height > last_valid_block_height {
+                return Err(if processed {
+                    unknown("processed but not confirmed".to_string())
+                } else {
+                    DLMMError::Transaction {
+                        kind: TransactionErrorKind::BlockhashExpired,
+                        message: format!(
+                            "{} not confirmed within {:?}; blockhash expired without it landing",
+                            signatures[signatures.len() - 1], self.config.confirm_timeout
+                        ),
+                    }
+                });
+            }
+
+            sleep(self.config.poll_interval).await;
+        }
+    }
+
+    async fn landed(&self, signature: Signature, status: TransactionStatus, attempts: u32) -> TransactionOutcome {
+        // Cost lookup is best effort; the transaction has landed either way
+        let cost = match self.rpc.get_transaction_cost(&signature, self.config.commitment).await {
+            Ok(cost) => cost,
+            Err(e) => {
+                log::warn!("Could not fetch cost for {}: {}", signature, e);
+                None
+            }
+        };
+
+        log::info!("Transaction {} landed in slot {}", signature, status.slot);
+
+        TransactionOutcome {
+            signature,
+            slot: status.slot,
+            fee_lamports: cost.map(|cost| cost.fee_lamports),
+            compute_units_consumed: cost.and_then(|cost| cost.compute_units_consumed),
+            attempts,
+            error: status.err.as_ref().map(classify_transaction_error),
+            program_error: status.err.as_ref().and_then(program_error),
+            error_message: status.err.map(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction, InstructionError},
+        pubkey::Pubkey,
+        signature::Keypair,
+    };
+    use solana_transaction_status::TransactionConfirmationStatus;
+    use std::sync::Mutex;
+
+    /// Mock RPC where the `land_build`-th distinct signature lands
+    struct MockRpc {
+        land_build: usize,
+        land_error: Option<TransactionError>,
+        /// Status polls before the landed signature shows up
+        land_after_polls: usize,
+        land_status: TransactionConfirmationStatus,
+        polls: Mutex<usize>,
+        blocks_per_blockhash: u64,
+        block_height: Mutex<u64>,
+        sent: Mutex<Vec<Signature>>,
+    }
+
+    impl MockRpc {
+        fn new(land_build: usize) -> Self {
+            Self {
+                land_build,
+                land_error: None,
+                land_after_polls: 0,
+                land_status: TransactionConfirmationStatus::Finalized,
+                polls: Mutex::new(0),
+                blocks_per_blockhash: 150,
+                block_height: Mutex::new(0),
+                sent: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl TransactionRpc for MockRpc {
+        async fn get_latest_blockhash(&self, _commitment: CommitmentConfig) -> DLMMResult<(Hash, u64)> {
+            let height = *self.block_height.lock().unwrap();
+            Ok((Hash::new_unique(), height + self.blocks_per_blockhash))
+        }
+
+        async fn get_block_height(&self, _commitment: CommitmentConfig) -> DLMMResult<u64> {
+            let mut height = self.block_height.lock().unwrap();
+            *height += 100;
+            Ok(*height)
+        }
+
+        async fn send_transaction(&self, transaction: &Transaction, _skip_preflight: bool) -> DLMMResult<Signature> {
+            let signature = transaction.signatures[0];
+            let mut sent = self.sent.lock().unwrap();
+            if !sent.contains(&signature) {
+                sent.push(signature);
+            }
+            Ok(signature)
+        }
+
+        async fn get_signature_statuses(&self, signatures: &[Signature]) -> DLMMResult<Vec<Option<TransactionStatus>>> {
+            let sent = self.sent.lock().unwrap();
+            let mut polls = self.polls.lock().unwrap();
+            *polls += 1;
+            let visible = *polls > self.land_after_polls;
+            Ok(signatures
+                .iter()
+                .map(|signature| {
+                    (visible && sent.iter().position(|sent| sent == signature) == Some(self.land_build)).then(|| TransactionStatus {
+                        slot: 42,
+                        confirmations: None,
+                        status: self.land_error.clone().map_or(Ok(()), Err),
+                        err: self.land_error.clone(),
+                        confirmation_status: Some(self.land_status.clone()),
+                    })
+                })
+                .collect())
+        }
+
+        async fn get_transaction_cost(
+            &self,
+            _signature: &Signature,
+            _commitment: CommitmentConfig,
+        ) -> DLMMResult<Option<TransactionCost>> {
+            Ok(Some(TransactionCost {
+                fee_lamports: 5_000,
+                compute_units_consumed: Some(12_345),
+            }))
+        }
+    }
+
+    fn config(max_rebuilds: u32) -> SendConfig {
+        SendConfig {
+            poll_interval: Duration::from_millis(1),
+            max_rebuilds,
+            ..Default::default()
+        }
+    }
+
+    fn message(payer: &Keypair) -> Message {
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(payer.pubkey(), true)],
+        );
+        Message::new(&[instruction], Some(&payer.pubkey()))
+    }
+
+    #[tokio::test]
+    async fn test_lands_first_build() {
+        let payer = Keypair::new();
+        let sender = TransactionSender::new(Arc::new(MockRpc::new(0)), config(3));
+
+        let outcome = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(outcome.slot, 42);
+        assert_eq!(outcome.attempts, 1);
+        assert_eq!(outcome.fee_lamports, Some(5_000));
+        assert_eq!(outcome.compute_units_consumed, Some(12_345));
+    }
+
+    #[tokio::test]
+    async fn test_rebuilds_after_blockhash_expiry() {
+        let payer = Keypair::new();
+        let sender = TransactionSender::new(Arc::new(MockRpc::new(1)), config(3));
+
+        let outcome = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap();
+        assert_eq!(outcome.attempts, 2);
+        assert!(outcome.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_rebuilds() {
+        let payer = Keypair::new();
+        let sender = TransactionSender::new(Arc::new(MockRpc::new(usize::MAX)), config(1));
+
+        let error = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap_err();
+        assert!(matches!(error, DLMMError::Transaction { kind: TransactionErrorKind::BlockhashExpired, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_keeps_polling_after_timeout_until_blockhash_expires() {
+        let payer = Keypair::new();
+        let timed_out = SendConfig { confirm_timeout: Duration::ZERO, ..config(3) };
+
+        // Lands after the confirm timeout but before its blockhash expires
+        let rpc = MockRpc { land_after_polls: 1, ..MockRpc::new(0) };
+        let sender = TransactionSender::new(Arc::new(rpc), timed_out.clone());
+        let outcome = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap();
+        assert!(outcome.is_success());
+        assert_eq!(outcome.attempts, 1);
+
+        // Never lands: once the blockhash expires it is safe to send again
+        let sender = TransactionSender::new(Arc::new(MockRpc::new(usize::MAX)), timed_out.clone());
+        let error = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap_err();
+        assert!(matches!(error, DLMMError::Transaction { kind: TransactionErrorKind::BlockhashExpired, .. }));
+        assert!(error.is_retryable());
+
+        // Processed but never confirmed: the outcome is unknown and must not be retried
+        let rpc = MockRpc { land_status: TransactionConfirmationStatus::Processed, ..MockRpc::new(0) };
+        let sender = TransactionSender::new(Arc::new(rpc), timed_out);
+        let error = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap_err();
+        assert!(matches!(error, DLMMError::Transaction { kind: TransactionErrorKind::Timeout, .. }));
+        assert_eq!(error.category(), ErrorCategory::OutcomeUnknown);
+        assert!(!error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_classifies_on_chain_failure() {
+        let payer = Keypair::new();
+        let rpc = MockRpc {
+            land_error: Some(TransactionError::InsufficientFundsForFee),
+            ..MockRpc::new(0)
+        };
+        let sender = TransactionSender::new(Arc::new(rpc), config(3));
+
+        let outcome = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap();
+        assert_eq!(outcome.error, Some(TransactionErrorKind::InsufficientFunds));
+        assert!(!outcome.error.unwrap().is_retryable());
+        assert!(outcome.into_result().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_maps_program_error_codes() {
+        let payer = Keypair::new();
+        let rpc = MockRpc {
+            land_error: Some(TransactionError::InstructionError(1, InstructionError::Custom(6001))),
+            ..MockRpc::new(0)
+        };
+        let sender = TransactionSender::new(Arc::new(rpc), config(3));
+
+        let outcome = sender.send_and_confirm(&message(&payer), &[&payer]).await.unwrap();
+        assert_eq!(outcome.program_error, Some((1, ProgramErrorCode::SlippageExceeded)));
+
+        let error = outcome.into_result().unwrap_err();
+        assert!(matches!(error, DLMMError::Program { instruction: 1, code: ProgramErrorCode::SlippageExceeded, .. }));
+        assert_eq!(error.category(), ErrorCategory::Slippage);
+        assert!(!error.is_retryable());
+    }
+}
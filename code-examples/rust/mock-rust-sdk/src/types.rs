@@ -24,6 +24,7 @@ pub struct SwapParams {
     pub pool_address: Pubkey,
     pub amount_in: Decimal,
     pub minimum_amount_out: Decimal,
+    /// Compute-unit price for the priority fee (micro-lamports)
     pub gas_price: Option<u64>,
     pub slippage_bps: Option<u16>,
 }
@@ -91,6 +92,32 @@ pub struct Position {
     pub value_usd: f64,
 }
 
+/// Kind of position transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionTransactionKind {
+    Create,
+    AddLiquidity,
+    RemoveLiquidity,
+    ClaimFees,
+}
+
+/// Transaction that changed a position, as decoded from its logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionTransaction {
+    pub signature: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub kind: PositionTransactionKind,
+    /// Raw token X deposited, withdrawn or claimed
+    pub amount_x: u64,
+    /// Raw token Y deposited, withdrawn or claimed
+    pub amount_y: u64,
+    /// Liquidity added or removed; zero for fee claims
+    pub liquidity: u128,
+    /// Pool active bin when the transaction executed
+    pub active_bin_id: i32,
+}
+
 /// Bin information
 #[derive(Debug, Clone)]
 pub struct BinInfo {
@@ -219,6 +246,21 @@ pub struct UserPosition {
     pub token_b_amount: f64,
     pub lp_token_amount: f64,
     pub pending_rewards: f64,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub reward_mint: Pubkey,
+}
+
+/// Swap quote between two mints for auto-compound reward conversion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapQuote {
+    pub pool_address: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: f64,
+    pub amount_out: f64,
+    pub fee: f64,          // Denominated in the input token
+    pub price_impact: f64, // Percentage
 }
 
 /// Pool info for auto-compound system (different from regular PoolInfo)
@@ -0,0 +1,604 @@
+//! Wallet signer sources
+//!
+//! Loads the signer used for transactions from a keypair file, a password-encrypted
+//! keystore, an environment secret or an external signing process, and hands it out
+//! as a `Signer` trait object so callers never hold raw key material themselves.
+//! Secrets held in the environment are taken out of it once, at startup, as
+//! [`WalletSecrets`].
+
+use crate::{error::DLMMError, DLMMResult};
+use aes_gcm_siv::{
+    aead::{Aead, NewAead, Payload},
+    Aes256GcmSiv, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::Hmac;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use solana_sdk::{
+    bs58,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    signer::SignerError,
+};
+use std::{
+    collections::HashMap,
+    env, fmt, fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use zeroize::Zeroize;
+
+/// Signer shared between tasks
+pub type SharedSigner = Arc<dyn Signer + Send + Sync>;
+
+/// Environment variable holding the keystore password; prompted for when unset
+pub const KEYSTORE_PASSWORD_ENV: &str = "WALLET_KEYSTORE_PASSWORD";
+/// PBKDF2 rounds for new keystores
+pub const DEFAULT_KEYSTORE_ITERATIONS: u32 = 600_000;
+const KEYSTORE_VERSION: u32 = 1;
+/// Longest an external signer may take to answer a request
+const EXTERNAL_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where the wallet signer comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerSource {
+    /// Solana CLI keypair file (JSON byte array)
+    KeypairFile(PathBuf),
+    /// Password-encrypted keystore written by [`Keystore::save`]
+    Keystore(PathBuf),
+    /// base58 or JSON byte-array secret in an environment variable
+    Env(String),
+    /// External signing program, run once per request
+    Command { program: String, args: Vec<String> },
+    /// External signer listening on a Unix socket
+    Socket(PathBuf),
+}
+
+impl SignerSource {
+    /// Parse a signer spec:
+    ///
+    /// - `file:<path>` or a bare path: Solana CLI keypair file
+    /// - `keystore:<path>`: encrypted keystore, password from `WALLET_KEYSTORE_PASSWORD` or a prompt
+    /// - `env:<VAR>`: base58 or JSON byte-array secret in an environment variable
+    /// - `exec:<program> [args...]`: external signing program
+    /// - `socket:<path>`: external signer on a Unix socket
+    pub fn parse(spec: &str) -> DLMMResult<Self> {
+        let spec = spec.trim();
+        let (scheme, value) = spec.split_once(':').unwrap_or(("file", spec));
+        let value = value.trim();
+
+        if value.is_empty() {
+            return Err(DLMMError::InvalidParams(format!("Empty signer spec '{}'", spec)));
+        }
+
+        match scheme {
+            "file" => Ok(Self::KeypairFile(expand_path(value))),
+            "keystore" => Ok(Self::Keystore(expand_path(value))),
+            "env" => Ok(Self::Env(value.to_string())),
+            "socket" => Ok(Self::Socket(expand_path(value))),
+            "exec" => {
+                let mut parts = value.split_whitespace().map(str::to_string);
+                let program = parts.next().unwrap_or_default();
+                Ok(Self::Command { program, args: parts.collect() })
+            }
+            // Windows-style paths such as C:\keys\id.json
+            _ if scheme.len() == 1 => Ok(Self::KeypairFile(PathBuf::from(spec))),
+            _ => Err(DLMMError::InvalidParams(format!("Unknown signer source '{}'", scheme))),
+        }
+    }
+
+    /// Load the signer, reading environment secrets from `secrets`. Keystores prompt for a
+    /// password on the terminal when `secrets` holds none.
+    pub fn load(&self, secrets: &WalletSecrets) -> DLMMResult<SharedSigner> {
+        Ok(match self {
+            Self::KeypairFile(path) => Arc::new(read_keypair_file(path)?),
+            Self::Keystore(path) => {
+                let mut password = keystore_password(path, secrets)?;
+                let keypair = Keystore::load(path).and_then(|keystore| keystore.decrypt(&password));
+                password.zeroize();
+                Arc::new(keypair?)
+            }
+            Self::Env(var) => Arc::new(keypair_from_secret(secrets.get(var).ok_or(DLMMError::WalletNotConfigured)?)?),
+            Self::Command { program, args } => Arc::new(ExternalSigner::connect(ExternalTransport::Command {
+                program: program.clone(),
+                args: args.clone(),
+            })?),
+            Self::Socket(path) => Arc::new(ExternalSigner::connect(ExternalTransport::Socket(path.clone()))?),
+        })
+    }
+}
+
+impl FromStr for SignerSource {
+    type Err = DLMMError;
+
+    fn from_str(spec: &str) -> DLMMResult<Self> {
+        Self::parse(spec)
+    }
+}
+
+impl fmt::Display for SignerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeypairFile(path) => write!(f, "keypair file {}", path.display()),
+            Self::Keystore(path) => write!(f, "keystore {}", path.display()),
+            Self::Env(var) => write!(f, "environment variable {}", var),
+            Self::Command { program, .. } => write!(f, "external signer `{}`", program),
+            Self::Socket(path) => write!(f, "signer socket {}", path.display()),
+        }
+    }
+}
+
+/// Expand a leading `~` to the user's home directory
+pub fn expand_path(path: &str) -> PathBuf {
+    let home = || env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(PathBuf::from);
+
+    match path.strip_prefix('~') {
+        Some("") => home().unwrap_or_else(|| PathBuf::from(path)),
+        Some(rest) if rest.starts_with('/') || rest.starts_with('\\') => match home() {
+            Some(home) => home.join(&rest[1..]),
+            None => PathBuf::from(path),
+        },
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Parse a base58 or JSON byte-array secret key, zeroising intermediate copies
+pub fn keypair_from_secret(secret: &str) -> DLMMResult<Keypair> {
+    let secret = secret.trim();
+    let mut bytes = if secret.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(secret)
+            .map_err(|_| DLMMError::SignerError("Invalid JSON keypair".to_string()))?
+    } else {
+        bs58::decode(secret)
+            .into_vec()
+            .map_err(|_| DLMMError::SignerError("Invalid base58 keypair".to_string()))?
+    };
+
+    let keypair = Keypair::from_bytes(&bytes)
+        .map_err(|_| DLMMError::SignerError(format!("Expected a 64-byte keypair, got {} bytes", bytes.len())));
+    bytes.zeroize();
+    keypair
+}
+
+/// Read a Solana CLI keypair file
+pub fn read_keypair_file(path: &Path) -> DLMMResult<Keypair> {
+    let mut contents = fs::read_to_string(path)
+        .map_err(|e| DLMMError::SignerError(format!("Cannot read keypair file {}: {}", path.display(), e)))?;
+    let keypair = keypair_from_secret(&contents);
+    contents.zeroize();
+    keypair
+}
+
+/// Secrets taken out of the process environment at startup: the keystore password and
+/// the variables named by `env:` signer sources. Values are zeroised on drop.
+#[derive(Default)]
+pub struct WalletSecrets {
+    values: HashMap<String, String>,
+}
+
+impl WalletSecrets {
+    /// Remove the keystore password and the secrets `sources` read from the environment.
+    /// Call this before starting any threads (including an async runtime), since changing
+    /// the environment while other threads read it is not thread-safe.
+    pub fn take_from_env<'a>(sources: impl IntoIterator<Item = &'a SignerSource>) -> Self {
+        let vars: Vec<&str> = sources
+            .into_iter()
+            .filter_map(|source| match source {
+                SignerSource::Env(var) => Some(var.as_str()),
+                _ => None,
+            })
+            .chain([KEYSTORE_PASSWORD_ENV])
+            .collect();
+
+        let mut values = HashMap::new();
+        for var in vars {
+            if let Ok(value) = env::var(var) {
+                env::remove_var(var);
+                values.insert(var.to_string(), value);
+            }
+        }
+        Self { values }
+    }
+
+    pub fn get(&self, var: &str) -> Option<&str> {
+        self.values.get(var).map(String::as_str)
+    }
+}
+
+impl Drop for WalletSecrets {
+    fn drop(&mut self) {
+        self.values.values_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+fn keystore_password(path: &Path, secrets: &WalletSecrets) -> DLMMResult<String> {
+    if let Some(password) = secrets.get(KEYSTORE_PASSWORD_ENV) {
+        return Ok(password.to_string());
+    }
+
+    rpassword::prompt_password(format!("Password for keystore {}: ", path.display()))
+        .map_err(|e| DLMMError::SignerError(format!("Cannot read keystore password: {}", e)))
+}
+
+/// Password-encrypted keypair. PBKDF2-HMAC-SHA256 derives an AES-256-GCM-SIV key
+/// that seals the secret key, with the public key bound as associated data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub pubkey: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    pub fn encrypt(keypair: &Keypair, password: &str, iterations: u32) -> DLMMResult<Self> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let pubkey = keypair.pubkey();
+        let mut secret = keypair.to_bytes();
+        let cipher = keystore_cipher(password, &salt, iterations);
+        let ciphertext = cipher.encrypt(&Nonce::from(nonce), Payload {
+            msg: &secret,
+            aad: pubkey.as_ref(),
+        });
+        secret.zeroize();
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            pubkey: pubkey.to_string(),
+            iterations,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(
+                ciphertext.map_err(|_| DLMMError::SignerError("Keystore encryption failed".to_string()))?,
+            ),
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> DLMMResult<Keypair> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(DLMMError::SignerError(format!("Unsupported keystore version {}", self.version)));
+        }
+
+        let decode = |field: &str| {
+            BASE64
+                .decode(field)
+                .map_err(|_| DLMMError::SignerError("Corrupt keystore".to_string()))
+        };
+        let pubkey = Pubkey::from_str(&self.pubkey)
+            .map_err(|_| DLMMError::SignerError("Corrupt keystore".to_string()))?;
+        let nonce: [u8; 12] = decode(&self.nonce)?
+            .try_into()
+            .map_err(|_| DLMMError::SignerError("Corrupt keystore".to_string()))?;
+
+        let cipher = keystore_cipher(password, &decode(&self.salt)?, self.iterations);
+        let mut secret = cipher
+            .decrypt(&Nonce::from(nonce), Payload {
+                msg: &decode(&self.ciphertext)?,
+                aad: pubkey.as_ref(),
+            })
+            .map_err(|_| DLMMError::SignerError("Wrong keystore password".to_string()))?;
+
+        let keypair = Keypair::from_bytes(&secret)
+            .map_err(|_| DLMMError::SignerError("Corrupt keystore".to_string()));
+        secret.zeroize();
+        keypair
+    }
+
+    pub fn load(path: &Path) -> DLMMResult<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| DLMMError::SignerError(format!("Cannot read keystore {}: {}", path.display(), e)))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Write the keystore, readable by the owner only on Unix
+    pub fn save(&self, path: &Path) -> DLMMResult<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options
+            .open(path)
+            .map_err(|e| DLMMError::SignerError(format!("Cannot write keystore {}: {}", path.display(), e)))?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())
+            .map_err(|e| DLMMError::SignerError(format!("Cannot write keystore {}: {}", path.display(), e)))
+    }
+}
+
+fn keystore_cipher(password: &str, salt: &[u8], iterations: u32) -> Aes256GcmSiv {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, iterations, &mut key);
+    let cipher = Aes256GcmSiv::new_from_slice(&key).expect("AES-256 key is 32 bytes");
+    key.zeroize();
+    cipher
+}
+
+/// How requests reach an external signer
+#[derive(Debug, Clone)]
+pub enum ExternalTransport {
+    Command { program: String, args: Vec<String> },
+    Socket(PathBuf),
+}
+
+/// One-line JSON request sent to an external signer
+#[derive(Debug, Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum SignerRequest {
+    GetPubkey,
+    SignMessage { pubkey: String, message: String },
+}
+
+/// One-line JSON reply from an external signer
+#[derive(Debug, Default, Deserialize)]
+struct SignerResponse {
+    pubkey: Option<String>,
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+/// Signer that delegates to another process, so keys can stay in an HSM, a hardware
+/// wallet bridge or a separate service.
+///
+/// Each request is a single line of JSON, `{"method":"get_pubkey"}` or
+/// `{"method":"sign_message","pubkey":"<base58>","message":"<base64>"}`, answered with
+/// `{"pubkey":"<base58>"}`, `{"signature":"<base58>"}` or `{"error":"..."}`.
+/// Signatures are verified before they are used.
+#[derive(Debug)]
+pub struct ExternalSigner {
+    transport: ExternalTransport,
+    pubkey: Pubkey,
+}
+
+impl ExternalSigner {
+    /// Ask the external signer for its public key
+    pub fn connect(transport: ExternalTransport) -> DLMMResult<Self> {
+        let response = exchange(&transport, &SignerRequest::GetPubkey)
+            .map_err(|e| DLMMError::SignerError(e.to_string()))?;
+        let pubkey = response
+            .pubkey
+            .and_then(|pubkey| Pubkey::from_str(&pubkey).ok())
+            .ok_or_else(|| DLMMError::SignerError("External signer returned no valid pubkey".to_string()))?;
+
+        Ok(Self { transport, pubkey })
+    }
+}
+
+impl Signer for ExternalSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let response = exchange(&self.transport, &SignerRequest::SignMessage {
+            pubkey: self.pubkey.to_string(),
+            message: BASE64.encode(message),
+        })?;
+
+        let signature = response
+            .signature
+            .and_then(|signature| Signature::from_str(&signature).ok())
+            .ok_or_else(|| SignerError::Protocol("External signer returned no valid signature".to_string()))?;
+
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(SignerError::Protocol("External signer returned a signature that does not verify".to_string()));
+        }
+        Ok(signature)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+fn exchange(transport: &ExternalTransport, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+    let mut line = serde_json::to_string(request).map_err(|e| SignerError::Protocol(e.to_string()))?;
+    line.push('\n');
+
+    // `Signer` is synchronous, so move off the async workers while waiting on the signer
+    let reply = blocking_io(|| match transport {
+        ExternalTransport::Command { program, args } => run_signer_command(program, args, &line),
+        ExternalTransport::Socket(path) => call_signer_socket(path, &line),
+    })?;
+
+    let response: SignerResponse = serde_json::from_str(reply.trim())
+        .map_err(|e| SignerError::Protocol(format!("Invalid external signer reply: {}", e)))?;
+    match response.error {
+        Some(error) => Err(SignerError::Custom(error)),
+        None => Ok(response),
+    }
+}
+
+/// Run blocking I/O without stalling the other tasks of a multi-threaded Tokio runtime
+fn blocking_io<T>(f: impl FnOnce() -> T) -> T {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => tokio::task::block_in_place(f),
+        _ => f(),
+    }
+}
+
+fn run_signer_command(program: &str, args: &[String], request: &str) -> Result<String, SignerError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| SignerError::Connection(format!("Cannot start {}: {}", program, e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(request.as_bytes())
+            .map_err(|e| SignerError::Connection(e.to_string()))?;
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait().map_err(|e| SignerError::Connection(e.to_string()))? {
+            Some(status) => break status,
+            None if started.elapsed() > EXTERNAL_SIGNER_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(SignerError::Connection(format!(
+                    "{} did not answer within {}s", program, EXTERNAL_SIGNER_TIMEOUT.as_secs()
+                )));
+            }
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    if !status.success() {
+        return Err(SignerError::Connection(format!("{} exited with {}", program, status)));
+    }
+
+    let mut stdout = String::new();
+    if let Some(mut pipe) = child.stdout.take() {
+        pipe.read_to_string(&mut stdout).map_err(|e| SignerError::Connection(e.to_string()))?;
+    }
+    Ok(stdout.lines().next().unwrap_or_default().to_string())
+}
+
+#[cfg(unix)]
+fn call_signer_socket(path: &Path, request: &str) -> Result<String, SignerError> {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixStream;
+
+    let connection_error = |e: std::io::Error| SignerError::Connection(format!("{}: {}", path.display(), e));
+    let mut stream = UnixStream::connect(path).map_err(connection_error)?;
+    stream.set_read_timeout(Some(EXTERNAL_SIGNER_TIMEOUT)).map_err(connection_error)?;
+    stream.set_write_timeout(Some(EXTERNAL_SIGNER_TIMEOUT)).map_err(connection_error)?;
+    stream.write_all(request.as_bytes()).map_err(connection_error)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).map_err(connection_error)?;
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+fn call_signer_socket(path: &Path, _request: &str) -> Result<String, SignerError> {
+    Err(SignerError::Connection(format!("Unix sockets are not supported on this platform ({})", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("saros-wallet-{}-{}", name, Pubkey::new_unique()))
+    }
+
+    #[test]
+    fn test_parse_signer_specs() {
+        let home = PathBuf::from(env::var("HOME").unwrap());
+
+        assert_eq!(
+            SignerSource::parse("~/.config/solana/id.json").unwrap(),
+            SignerSource::KeypairFile(home.join(".config/solana/id.json"))
+        );
+        assert_eq!(
+            SignerSource::parse("keystore:~/wallet.json").unwrap(),
+            SignerSource::Keystore(home.join("wallet.json"))
+        );
+        assert_eq!(SignerSource::parse("env:MY_KEY").unwrap(), SignerSource::Env("MY_KEY".to_string()));
+        assert_eq!(
+            SignerSource::parse("exec:/usr/bin/signer --account 2").unwrap(),
+            SignerSource::Command {
+                program: "/usr/bin/signer".to_string(),
+                args: vec!["--account".to_string(), "2".to_string()],
+            }
+        );
+        assert!(SignerSource::parse("ledger:usb").is_err());
+        assert!(SignerSource::parse("env:").is_err());
+    }
+
+    #[test]
+    fn test_keypair_file_and_env_sources() {
+        let keypair = Keypair::new();
+
+        let path = temp_path("id");
+        fs::write(&path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+        let signer = SignerSource::KeypairFile(path.clone()).load(&WalletSecrets::default()).unwrap();
+        assert_eq!(signer.pubkey(), keypair.pubkey());
+        fs::remove_file(path).unwrap();
+
+        let var = format!("SAROS_TEST_KEY_{}", std::process::id());
+        env::set_var(&var, keypair.to_base58_string());
+        let source = SignerSource::Env(var.clone());
+        let secrets = WalletSecrets::take_from_env([&source]);
+        assert!(env::var(&var).is_err());
+
+        // The secret stays available to every signer that needs it
+        for _ in 0..2 {
+            assert_eq!(source.load(&secrets).unwrap().pubkey(), keypair.pubkey());
+        }
+        assert!(SignerSource::Env("SAROS_TEST_MISSING".to_string()).load(&secrets).is_err());
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let keypair = Keypair::new();
+        let keystore = Keystore::encrypt(&keypair, "correct horse", 1_000).unwrap();
+
+        let path = temp_path("keystore");
+        keystore.save(&path).unwrap();
+        let loaded = Keystore::load(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded.decrypt("correct horse").unwrap().pubkey(), keypair.pubkey());
+        assert!(loaded.decrypt("wrong password").is_err());
+
+        let mut swapped = loaded;
+        swapped.pubkey = Pubkey::new_unique().to_string();
+        assert!(swapped.decrypt("correct horse").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_external_signer() {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::net::UnixListener;
+
+        let keypair = Keypair::new();
+        let path = temp_path("signer.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server_keypair = keypair.insecure_clone();
+        let server = std::thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut request = String::new();
+                BufReader::new(&stream).read_line(&mut request).unwrap();
+                let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+
+                let reply = match request["method"].as_str().unwrap() {
+                    "get_pubkey" => serde_json::json!({ "pubkey": server_keypair.pubkey().to_string() }),
+                    _ => {
+                        let message = BASE64.decode(request["message"].as_str().unwrap()).unwrap();
+                        serde_json::json!({ "signature": server_keypair.sign_message(&message).to_string() })
+                    }
+                };
+                writeln!(stream, "{}", reply).unwrap();
+            }
+        });
+
+        let signer = SignerSource::Socket(path.clone()).load(&WalletSecrets::default()).unwrap();
+        assert_eq!(signer.pubkey(), keypair.pubkey());
+        let signature = signer.sign_message(b"compound");
+        assert!(signature.verify(keypair.pubkey().as_ref(), b"compound"));
+
+        server.join().unwrap();
+        fs::remove_file(path).unwrap();
+    }
+}